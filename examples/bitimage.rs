@@ -1,4 +1,4 @@
-use custom_printer::{BitImageMode, CustomPrinter, CutType, FeedUnit};
+use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter, CutType, FeedUnit};
 
 fn main() {
     // Replace /dev/null with actual device node when the printer is connected
@@ -0,0 +1,74 @@
+/*
+ * C ABI of the custom-printer crate, built with the ffi feature:
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Functions returning int return CUSTOM_PRINTER_OK on success or a negative error code.
+ */
+
+#ifndef CUSTOM_PRINTER_H
+#define CUSTOM_PRINTER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define CUSTOM_PRINTER_OK 0
+#define CUSTOM_PRINTER_INVALID_ARGUMENT -1
+#define CUSTOM_PRINTER_DEVICE_OPEN -2
+#define CUSTOM_PRINTER_IO -3
+#define CUSTOM_PRINTER_TIMEOUT -4
+#define CUSTOM_PRINTER_UNSUPPORTED_IMAGE -5
+#define CUSTOM_PRINTER_REFUSED -6
+#define CUSTOM_PRINTER_PRINTER -7
+
+/* Modes of bit images */
+#define CUSTOM_PRINTER_DOTS8_SINGLE_DENSITY 0
+#define CUSTOM_PRINTER_DOTS8_DOUBLE_DENSITY 1
+#define CUSTOM_PRINTER_DOTS24_SINGLE_DENSITY 2
+#define CUSTOM_PRINTER_DOTS24_DOUBLE_DENSITY 3
+
+typedef struct CustomPrinter CustomPrinter;
+
+/* Each field is 0 or 1 */
+typedef struct CustomPrinterStatus {
+    uint8_t paper_end;
+    uint8_t paper_near_end;
+    uint8_t ticket_present;
+    uint8_t cover_open;
+    uint8_t head_overheated;
+    uint8_t cutter_error;
+    uint8_t idle;
+} CustomPrinterStatus;
+
+/* Open the printer connected to the device node dev, or return NULL */
+CustomPrinter *custom_printer_new(const char *dev);
+/* Create a printer without any device */
+CustomPrinter *custom_printer_detached(void);
+/* Free the printer, discarding the pending commands */
+void custom_printer_free(CustomPrinter *printer);
+
+/* Append text as it is, in the code page of the printer */
+int custom_printer_text(CustomPrinter *printer, const char *text);
+/* Print and feed the paper by lines, or by one line if lines is 0 */
+int custom_printer_print(CustomPrinter *printer, uint8_t lines);
+/* Append the bit image of a 1 bpp bitmap of len bytes */
+int custom_printer_bitmap(CustomPrinter *printer, size_t width, size_t height, const uint8_t *bitmap,
+                          size_t len, int mode);
+/* Append the bit image from an image file, only with the image feature */
+int custom_printer_image(CustomPrinter *printer, const char *path, int mode);
+/* Feed the paper to the cut position and cut it, partially if partial isn't 0 */
+int custom_printer_cut(CustomPrinter *printer, int partial);
+/* Send the pending commands to the printer */
+int custom_printer_run(CustomPrinter *printer);
+/* Query the status of the printer */
+int custom_printer_status(CustomPrinter *printer, CustomPrinterStatus *status);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CUSTOM_PRINTER_H */
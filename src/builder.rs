@@ -0,0 +1,1035 @@
+//! Construction of the commands, shared by the printer and the standalone job builder.
+
+use alloc::{format, string::ToString, vec, vec::Vec};
+#[cfg(feature = "image")]
+use std::path::Path;
+
+use crate::{
+    validate, BarcodeSystem, BitImageMode, Command, CutType, Density, DrawerPin, Error, FeedUnit,
+    HriFont, HriPosition, Justification, Length, LimitPolicy, MarkPosition, Model,
+    NearEndSensitivity, PageDirection, PaperSaving, PrinterConfig, Profile, QrErrorCorrection,
+    Speed,
+};
+
+/// Number of bytes of the bit image converted from a bitmap of `width` x `height` in `mode`, i.e. the size of
+/// the output needed by [`convert_bitmap_to_bitimage_into()`].
+///
+/// The height is rounded up to the banks of 8 or 24 lines of the mode.
+pub fn bitimage_len(width: usize, height: usize, mode: BitImageMode) -> usize {
+    let bank = bank_lines(mode);
+    height.div_ceil(bank) * (bank / 8) * width
+}
+
+/// Number of lines in a bank of a bit image in `mode`.
+pub(crate) fn bank_lines(mode: BitImageMode) -> usize {
+    match mode {
+        BitImageMode::Dots8SingleDensity | BitImageMode::Dots8DoubleDensity => 8,
+        BitImageMode::Dots24SingleDensity | BitImageMode::Dots24DoubleDensity => 24,
+    }
+}
+
+/// Convert a 1 bpp `bitmap` of `width` x `height` to the banks of a bit image in `mode`, as printed by
+/// [`CommandBuilder::bitmap()`].
+///
+/// The bitmap is stored line by line, `width / 8` bytes a line rounded up, with the most significant bit as the
+/// leftmost dot. Missing bytes at the end of the bitmap are blank.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{convert_bitmap_to_bitimage, BitImageMode};
+/// // a vertical line in the leftmost column
+/// let bitimage = convert_bitmap_to_bitimage(8, 8, &[0x80; 8], BitImageMode::Dots8SingleDensity);
+/// assert_eq!(bitimage, [0xFF, 0, 0, 0, 0, 0, 0, 0]);
+/// ```
+pub fn convert_bitmap_to_bitimage(
+    width: usize,
+    height: usize,
+    bitmap: &[u8],
+    mode: BitImageMode,
+) -> Vec<u8> {
+    let mut bitimage = vec![0; bitimage_len(width, height, mode)];
+    convert_into(width, height, bitmap, mode, &mut bitimage);
+    bitimage
+}
+
+/// Convert a 1 bpp `bitmap` of `width` x `height` to the banks of a bit image in `mode` into `out` without
+/// allocating, and return the number of bytes written, see [`convert_bitmap_to_bitimage()`].
+///
+/// # Errors
+///
+/// Returns [`Error::BufferTooSmall`] if `out` is shorter than [`bitimage_len()`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{convert_bitmap_to_bitimage_into, BitImageMode};
+/// let mut out = [0; 64];
+/// let len = convert_bitmap_to_bitimage_into(8, 8, &[0x80; 8], BitImageMode::Dots8SingleDensity, &mut out)
+///     .unwrap();
+/// assert_eq!(&out[..len], [0xFF, 0, 0, 0, 0, 0, 0, 0]);
+/// ```
+pub fn convert_bitmap_to_bitimage_into(
+    width: usize,
+    height: usize,
+    bitmap: &[u8],
+    mode: BitImageMode,
+    out: &mut [u8],
+) -> Result<usize, Error> {
+    let size = bitimage_len(width, height, mode);
+    let Some(out) = out.get_mut(..size) else {
+        return Err(Error::BufferTooSmall { required: size });
+    };
+    out.fill(0);
+    convert_into(width, height, bitmap, mode, out);
+    Ok(size)
+}
+
+/// Set the dots of `bitmap` in the zeroed `bitimage` of [`bitimage_len()`] bytes.
+fn convert_into(
+    width: usize,
+    height: usize,
+    bitmap: &[u8],
+    mode: BitImageMode,
+    bitimage: &mut [u8],
+) {
+    // number of bytes in a line, padded to whole bytes
+    let step = width.div_ceil(8);
+
+    convert_dots_into(width, height, mode, bitimage, |x, y| {
+        let src = y * step + x / 8;
+        src < bitmap.len() && bitmap[src] & (0x80 >> (x % 8)) != 0
+    });
+}
+
+/// Set the dots at `(x, y)` for which `dot` is true in the zeroed `bitimage` of [`bitimage_len()`] bytes.
+///
+/// With the `rayon` feature, the banks are thresholded and packed in parallel, as each of them is written to its
+/// own bytes.
+pub(crate) fn convert_dots_into(
+    width: usize,
+    height: usize,
+    mode: BitImageMode,
+    bitimage: &mut [u8],
+    dot: impl Fn(usize, usize) -> bool + Sync,
+) {
+    // number of lines in a bank
+    let bank = bank_lines(mode);
+    // number of banks in bit image (might have padding lines in the last bank)
+    let banks = height.div_ceil(bank);
+    // number of bytes in a bank
+    let len = width * (bank / 8);
+    if len == 0 {
+        return;
+    }
+
+    let pack = |(i, out): (usize, &mut [u8])| {
+        for j in 0..width {
+            for k in 0..bank {
+                let y = i * bank + k;
+                if y < height && dot(j, y) {
+                    out[j * (bank / 8) + k / 8] |= 0x80 >> (k % 8);
+                }
+            }
+        }
+    };
+    let bitimage = &mut bitimage[..banks * len];
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        bitimage
+            .par_chunks_exact_mut(len)
+            .enumerate()
+            .for_each(pack);
+    }
+    #[cfg(not(feature = "rayon"))]
+    bitimage.chunks_exact_mut(len).enumerate().for_each(pack);
+}
+
+/// Check the size of an image of `width` x `height` for [`CommandBuilder::bit_image()`], neither empty nor wider
+/// than the width of a bit image.
+pub(crate) fn check_image_size(width: usize, height: usize) -> Result<(), Error> {
+    if width == 0 || height == 0 {
+        return Err(Error::UnsupportedImage(format!(
+            "image of {width} x {height} dots is empty"
+        )));
+    }
+    if width > u16::MAX as usize {
+        return Err(Error::UnsupportedImage(format!(
+            "width {width} is wider than {}",
+            u16::MAX
+        )));
+    }
+
+    Ok(())
+}
+
+/// Check the size of a bitmap of `width` x `height` for [`CommandBuilder::bitmap()`], see
+/// [`check_image_size()`].
+pub(crate) fn check_bitmap_size(width: usize, height: usize, len: usize) -> Result<(), Error> {
+    check_image_size(width, height)?;
+    if len < width.div_ceil(8) * height {
+        return Err(Error::UnsupportedImage(format!(
+            "bitmap of {len} bytes is shorter than {height} lines"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Constructing functions appending [`Command`]s, implemented by [`CustomPrinter`](crate::CustomPrinter) for running them
+/// on the device and by [`JobBuilder`](crate::JobBuilder) for building a [`Job`](crate::Job) without any device.
+///
+/// Only [`push()`](CommandBuilder::push()) and [`model()`](CommandBuilder::model()) need to be implemented.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{CommandBuilder, CutType, FeedUnit};
+/// fn receipt<B: CommandBuilder>(builder: &mut B) -> &mut B {
+///     builder
+///         .print_and_feed_paper(FeedUnit::Lines, 3)
+///         .cut_paper(CutType::TotalCut)
+/// }
+/// ```
+pub trait CommandBuilder {
+    /// Append `command` to the constructed commands.
+    fn push(&mut self, command: Command) -> &mut Self;
+
+    /// The [`Model`] of the printer the commands are constructed for, if known.
+    fn model(&self) -> Option<Model>;
+
+    /// The [`Profile`] of the printer the commands are constructed for, if known, consulted for its physical
+    /// characteristics and the commands it supports.
+    ///
+    /// The default implementation returns the profile of the [`model()`](CommandBuilder::model()).
+    fn profile(&self) -> Option<Profile> {
+        self.model().map(Profile::for_model)
+    }
+
+    /// Append `command` if it's supported by the [`profile()`](CommandBuilder::profile()), or return
+    /// [`Error::Unsupported`] without appending it, e.g. for commands of optional firmware features.
+    ///
+    /// The command is appended without checking if the profile isn't known.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Command, CommandBuilder, Error, JobBuilder, Model};
+    /// let mut builder = JobBuilder::new();
+    /// builder.set_model(Some(Model::Tg2480h));
+    /// assert!(matches!(
+    ///     builder.try_push(Command::EjectTicket),
+    ///     Err(Error::Unsupported { command: "eject_ticket", .. })
+    /// ));
+    /// ```
+    fn try_push(&mut self, command: Command) -> Result<&mut Self, Error> {
+        if let Some(profile) = self.profile().filter(|profile| !profile.supports(&command)) {
+            return Err(Error::Unsupported {
+                command: command.kind(),
+                printer: profile.name,
+            });
+        }
+
+        Ok(self.push(command))
+    }
+
+    /// The [`PrinterConfig`] of the printer the commands are constructed for.
+    ///
+    /// The default implementation returns the configuration of the [`profile()`](CommandBuilder::profile()), or
+    /// the default configuration if the profile isn't known.
+    fn config(&self) -> PrinterConfig {
+        PrinterConfig::of(self.profile().as_ref())
+    }
+
+    /// A zeroed buffer of `len` bytes for the data of a command, e.g. of a bit image.
+    ///
+    /// The default implementation allocates a new buffer, while [`CustomPrinter`](crate::CustomPrinter) reuses
+    /// the buffers of the commands it has run.
+    fn scratch_buffer(&mut self, len: usize) -> Vec<u8> {
+        vec![0; len]
+    }
+
+    /// Append the commands of the default density, speed, code page and margins of the
+    /// [`config()`](CommandBuilder::config()), e.g. after [`initialize()`](CommandBuilder::initialize()) which resets them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Command, CommandBuilder, Density, JobBuilder, PrinterConfig};
+    /// let mut builder = JobBuilder::new();
+    /// builder.set_config(Some(PrinterConfig {
+    ///     density: Some(Density::Plus25),
+    ///     ..Default::default()
+    /// }));
+    /// let job = builder.initialize().apply_config().build();
+    /// assert_eq!(job.commands(), &[Command::Initialize, Command::Density(Density::Plus25)]);
+    /// ```
+    fn apply_config(&mut self) -> &mut Self {
+        self.config().apply(self);
+
+        self
+    }
+
+    /// Construct commands by `f` on the builder taken by value and return it, for an owned chaining style,
+    /// e.g. to build a printer or a job in a single expression and store it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CutType, Job, JobBuilder};
+    /// struct Kiosk {
+    ///     footer: Job,
+    /// }
+    ///
+    /// let kiosk = Kiosk {
+    ///     footer: JobBuilder::new()
+    ///         .with(|b| b.print().cut_paper(CutType::TotalCut))
+    ///         .build(),
+    /// };
+    /// assert_eq!(kiosk.footer.commands().len(), 2);
+    /// ```
+    fn with(mut self, f: impl FnOnce(&mut Self) -> &mut Self) -> Self
+    where
+        Self: Sized,
+    {
+        f(&mut self);
+
+        self
+    }
+
+    /// Construct commands by the fallible `f` on the builder taken by value, returning it if `f` succeeds,
+    /// see [`with()`](CommandBuilder::with()).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, CutType, Error, JobBuilder};
+    /// # fn main() -> Result<(), Error> {
+    /// let logo = JobBuilder::new()
+    ///     .try_with(|b| {
+    ///         Ok(b
+    ///             .bitmap(8, 1, &[0xFF], BitImageMode::Dots8SingleDensity)?
+    ///             .print()
+    ///             .cut_paper(CutType::TotalCut))
+    ///     })?
+    ///     .build();
+    /// assert_eq!(logo.commands().len(), 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn try_with(
+        mut self,
+        f: impl FnOnce(&mut Self) -> Result<&mut Self, Error>,
+    ) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        f(&mut self)?;
+
+        Ok(self)
+    }
+
+    /// Append a command for initializing the printer, resetting the fonts, modes and line spacing to their defaults,
+    /// e.g. at the start of a job to guarantee a known state after another application used the printer.
+    ///
+    /// **NOTE:** The data in the print buffer is cleared as well.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, CutType};
+    /// # let mut builder = JobBuilder::new();
+    /// builder.initialize().print().cut_paper(CutType::TotalCut);
+    /// ```
+    fn initialize(&mut self) -> &mut Self {
+        self.push(Command::Initialize)
+    }
+
+    /// Append commands for printing a bit image from `path` in `mode`. See [`BitImageMode`] for supported modes.
+    ///
+    /// Any format supported by the `image` feature can be decoded, and the pixels which aren't black
+    /// are left blank.
+    ///
+    /// **NOTE:** Because opening and reading the image file may fail, so the return Self is wrapped in a [`Result`]
+    /// and needs to be unwrapped before concatenating with other constructing functions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, JobBuilder};
+    /// # let mut builder = JobBuilder::new();
+    /// builder
+    ///     .bit_image(
+    ///         "tests/data/Thermal_Test_Image.png",
+    ///         BitImageMode::Dots24DoubleDensity
+    ///     )
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "image")]
+    fn bit_image(
+        &mut self,
+        path: impl AsRef<Path>,
+        mode: BitImageMode,
+    ) -> Result<&mut Self, Error> {
+        // Open image and convert to grayscale
+        let img = image::open(path)?.grayscale();
+
+        let width = img.width() as usize;
+        let height = img.height() as usize;
+        let pixels = img.as_bytes();
+        check_image_size(width, height)?;
+
+        // convert the black pixels of 8bpp grayscaled image to the dots of the bit image directly,
+        // without any intermediate 1 bpp bitmap
+        let mut bitimage = self.scratch_buffer(bitimage_len(width, height, mode));
+        convert_dots_into(width, height, mode, &mut bitimage, |x, y| {
+            pixels[y * width + x] == 0x00
+        });
+
+        Ok(self.push(Command::BitImage {
+            mode,
+            width: width as u16,
+            data: bitimage,
+        }))
+    }
+
+    /// Append commands for printing a 1 bpp `bitmap` of `width` x `height` in `mode`, e.g. a logo rendered
+    /// in advance, without decoding any image file.
+    ///
+    /// Each line of the bitmap is `width` / 8 bytes rounded up with the most significant bit on the left, and set
+    /// bits are printed.
+    ///
+    /// [`Error::UnsupportedImage`] is returned if `width` or `height` is 0, `width` is over 65535, or `bitmap` is
+    /// shorter than `height` lines.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, JobBuilder};
+    /// // a black square of 8 x 8 dots
+    /// let job = JobBuilder::new()
+    ///     .bitmap(8, 8, &[0xFF; 8], BitImageMode::Dots8SingleDensity)
+    ///     .unwrap()
+    ///     .print()
+    ///     .build();
+    /// ```
+    fn bitmap(
+        &mut self,
+        width: usize,
+        height: usize,
+        bitmap: &[u8],
+        mode: BitImageMode,
+    ) -> Result<&mut Self, Error> {
+        check_bitmap_size(width, height, bitmap.len())?;
+
+        let mut bitimage = self.scratch_buffer(bitimage_len(width, height, mode));
+        convert_into(width, height, bitmap, mode, &mut bitimage);
+
+        Ok(self.push(Command::BitImage {
+            mode,
+            width: width as u16,
+            data: bitimage,
+        }))
+    }
+
+    /// Append a command for printing `data` as a barcode of `system`, printed at once like
+    /// [`print()`](CommandBuilder::print()).
+    ///
+    /// [`Error::InvalidParameter`] is returned if `data` can't be encoded by `system`, e.g. letters in EAN-13, and
+    /// [`Error::Unsupported`] if the [`profile()`](CommandBuilder::profile()) doesn't print barcodes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BarcodeSystem, CommandBuilder, JobBuilder};
+    /// # let mut builder = JobBuilder::new();
+    /// builder.barcode(BarcodeSystem::Ean13, b"400638133393").unwrap();
+    /// assert!(builder.barcode(BarcodeSystem::Ean13, b"ABC").is_err());
+    /// ```
+    fn barcode(&mut self, system: BarcodeSystem, data: &[u8]) -> Result<&mut Self, Error> {
+        if let Some(reason) = system.invalid_data(data) {
+            return Err(Error::InvalidParameter(reason));
+        }
+
+        self.try_push(Command::Barcode {
+            system,
+            data: data.to_vec(),
+        })
+    }
+
+    /// Append a command for printing the following barcodes with narrow modules of `module_width` dots, from 1 to 6,
+    /// and their human readable interpretation at `hri` in `hri_font`, until the printer is initialized.
+    ///
+    /// [`Error::InvalidParameter`] is returned if `module_width` isn't 1 to 6, and [`Error::Unsupported`] if the
+    /// [`profile()`](CommandBuilder::profile()) doesn't print barcodes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BarcodeSystem, CommandBuilder, JobBuilder, HriFont, HriPosition};
+    /// # let mut builder = JobBuilder::new();
+    /// builder
+    ///     .barcode_style(2, HriPosition::Below, HriFont::B)
+    ///     .unwrap()
+    ///     .barcode(BarcodeSystem::Ean13, b"400638133393")
+    ///     .unwrap();
+    /// assert!(builder.barcode_style(0, HriPosition::None, HriFont::A).is_err());
+    /// ```
+    fn barcode_style(
+        &mut self,
+        module_width: u8,
+        hri: HriPosition,
+        hri_font: HriFont,
+    ) -> Result<&mut Self, Error> {
+        if !(1..=6).contains(&module_width) {
+            return Err(Error::InvalidParameter(format!(
+                "barcode module width {module_width} isn't 1 to 6 dots"
+            )));
+        }
+
+        self.try_push(Command::BarcodeStyle {
+            module_width,
+            hri,
+            hri_font,
+        })
+    }
+
+    /// Append a command for printing `data` as a QR code of model 2 made of squares of `module_size` dots, from 1
+    /// to 16, restoring the damaged code as much as `error_correction`, printed at once like
+    /// [`print()`](CommandBuilder::print()) and justified by [`justification()`](CommandBuilder::justification()).
+    ///
+    /// [`Error::InvalidParameter`] is returned if `data` isn't 1 to 7089 bytes long or `module_size` isn't 1 to 16,
+    /// and [`Error::Unsupported`] if the [`profile()`](CommandBuilder::profile()) doesn't print 2D codes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, Justification, QrErrorCorrection};
+    /// # let mut builder = JobBuilder::new();
+    /// builder
+    ///     .justification(Justification::Center)
+    ///     .qr_code(b"https://example.com", 6, QrErrorCorrection::Medium)
+    ///     .unwrap()
+    ///     .justification(Justification::Left);
+    /// assert!(builder.qr_code(b"", 6, QrErrorCorrection::Medium).is_err());
+    /// ```
+    fn qr_code(
+        &mut self,
+        data: &[u8],
+        module_size: u8,
+        error_correction: QrErrorCorrection,
+    ) -> Result<&mut Self, Error> {
+        if let Some(reason) = validate::invalid_qr_code(data, module_size) {
+            return Err(Error::InvalidParameter(reason));
+        }
+
+        self.try_push(Command::QrCode {
+            data: data.to_vec(),
+            module_size,
+            error_correction,
+        })
+    }
+
+    /// Append a command for entering page mode, where the following data is composed in the print area
+    /// set by [`page_area()`](CommandBuilder::page_area()) and printed all at once by
+    /// [`print_page()`](CommandBuilder::print_page()) or [`print_page_and_exit()`](CommandBuilder::print_page_and_exit()).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, JobBuilder, PageDirection};
+    /// # let mut builder = JobBuilder::new();
+    /// builder
+    ///     .enter_page_mode()
+    ///     .page_area(0, 0, 384, 800)
+    ///     .page_direction(PageDirection::BottomToTop)
+    ///     .position(100, 50)
+    ///     .bitmap(24, 24, &[0xFF; 72], BitImageMode::Dots24DoubleDensity)
+    ///     .unwrap()
+    ///     .print_page_and_exit();
+    /// ```
+    fn enter_page_mode(&mut self) -> &mut Self {
+        self.push(Command::EnterPageMode)
+    }
+
+    /// Append a command for returning to standard mode from page mode, discarding the data composed in the page.
+    fn exit_page_mode(&mut self) -> &mut Self {
+        self.push(Command::ExitPageMode)
+    }
+
+    /// Append a command for printing the data composed in page mode, staying in page mode.
+    fn print_page(&mut self) -> &mut Self {
+        self.push(Command::PrintPage)
+    }
+
+    /// Append a command for printing the data composed in page mode and returning to standard mode.
+    fn print_page_and_exit(&mut self) -> &mut Self {
+        self.push(Command::PrintPageAndExit)
+    }
+
+    /// Append a command for cancelling the data composed in the current print area in page mode.
+    fn cancel_page_data(&mut self) -> &mut Self {
+        self.push(Command::CancelPageData)
+    }
+
+    /// Append a command for setting the print area in page mode, with the origin at (`x`, `y`) and
+    /// the size of `width` x `height`, in horizontal and vertical motion units.
+    fn page_area(&mut self, x: u16, y: u16, width: u16, height: u16) -> &mut Self {
+        self.push(Command::PageArea {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// Append a command for selecting the print direction and starting position in page mode.
+    fn page_direction(&mut self, direction: PageDirection) -> &mut Self {
+        self.push(Command::PageDirection(direction))
+    }
+
+    /// Append commands for moving the print position to (`x`, `y`) relative to the starting position
+    /// of the print direction, in horizontal and vertical motion units.
+    ///
+    /// **NOTE:** The vertical position is only valid in page mode.
+    fn position(&mut self, x: u16, y: u16) -> &mut Self {
+        self.push(Command::Position { x, y })
+    }
+
+    /// Append a command for cutting the paper totally ([`CutType::TotalCut`]) or partially ([`CutType::PartialCut`]).
+    ///
+    /// Every cutter cuts totally, so the cut is appended without checking the profile, while partial cuts are
+    /// refused by [`CustomPrinter::run()`](crate::CustomPrinter::run()) if the profile lacks them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, CutType};
+    /// # let mut builder = JobBuilder::new();
+    /// builder.cut_paper(CutType::TotalCut);
+    /// ```
+    fn cut_paper(&mut self, cut_type: CutType) -> &mut Self {
+        self.push(Command::CutPaper(cut_type))
+    }
+
+    /// Append a command for feeding the paper to the cut position plus `amount` of vertical motion units,
+    /// then cutting the paper totally ([`CutType::TotalCut`]) or partially ([`CutType::PartialCut`]).
+    ///
+    /// Unlike [`cut_paper()`](CommandBuilder::cut_paper()), the last printed line is fed past the cutter
+    /// before cutting, so it's never sliced in half.
+    ///
+    /// `amount` is limited by [`Profile::max_cut_feed`], see [`PrinterConfig::limits`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, CutType};
+    /// # let mut builder = JobBuilder::new();
+    /// builder.feed_and_cut_paper(CutType::TotalCut, 0);
+    /// ```
+    fn feed_and_cut_paper(&mut self, cut_type: CutType, amount: u8) -> &mut Self {
+        let command = limit(self, Command::FeedAndCutPaper { cut_type, amount });
+        self.push(command)
+    }
+
+    /// Append a command for printing and line feeding.
+    ///
+    /// Either [`print()`](CommandBuilder::print()) or [`print_and_feed_paper()`](CommandBuilder::print_and_feed_paper()) should be appended
+    /// before calling [`run()`](crate::CustomPrinter::run()) to do actual printing, otherwise [`Error::UnprintedData`] is returned.
+    fn print(&mut self) -> &mut Self {
+        self.push(Command::Print)
+    }
+
+    /// Append a command for printing and feeding the paper by `amount` of `unit`.
+    ///
+    /// With [`FeedUnit::Inches`], the paper is fed by `amount` / `y` inches, where `y` is the vertical
+    /// motion unit set by [`motion_units()`](CommandBuilder::motion_units()).
+    ///
+    /// Either [`print()`](CommandBuilder::print()) or [`print_and_feed_paper()`](CommandBuilder::print_and_feed_paper()) should be appended
+    /// before calling [`run()`](crate::CustomPrinter::run()) to do actual printing, otherwise [`Error::UnprintedData`] is returned.
+    fn print_and_feed_paper(&mut self, unit: FeedUnit, amount: u8) -> &mut Self {
+        self.push(Command::PrintAndFeed { unit, amount })
+    }
+
+    /// Append commands for printing and feeding the last printed line exactly to the cutter of the
+    /// [`Model`] returned by [`model()`](CommandBuilder::model()), so the ticket has a consistent and
+    /// minimal bottom margin across models.
+    ///
+    /// The distance is fed in vertical motion units, assuming each of them is one dot line, which is
+    /// the default unless changed by [`motion_units()`](CommandBuilder::motion_units()).
+    ///
+    /// [`Error::InvalidParameter`] is returned if the model isn't set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, CutType, Model};
+    /// # let mut builder = JobBuilder::new();
+    /// builder
+    ///     .set_model(Some(Model::Tg2480h))
+    ///     .feed_to_cut()
+    ///     .unwrap()
+    ///     .cut_paper(CutType::TotalCut);
+    /// ```
+    fn feed_to_cut(&mut self) -> Result<&mut Self, Error> {
+        let profile = self
+            .profile()
+            .ok_or_else(|| Error::InvalidParameter("model isn't set".to_string()))?;
+
+        feed_dots(self, profile.head_to_cutter_dots());
+
+        Ok(self)
+    }
+
+    /// Append commands for printing and feeding the paper by `length`, converted to dot lines at the resolution
+    /// of the [`config()`](CommandBuilder::config()).
+    ///
+    /// Like [`feed_to_cut()`](CommandBuilder::feed_to_cut()), each vertical motion unit is assumed to be one
+    /// dot line, and a zero length appends nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, CutType, Length};
+    /// # let mut builder = JobBuilder::new();
+    /// builder.feed(Length::mm(10.0)).cut_paper(CutType::TotalCut);
+    /// ```
+    fn feed(&mut self, length: Length) -> &mut Self {
+        let dots = length.to_dots(self.config().dpi);
+        feed_dots(self, dots);
+
+        self
+    }
+
+    /// Append a command for printing and feeding the paper backwards by `amount` of `unit`,
+    /// e.g. to reduce the top margin of the next ticket after a cut.
+    ///
+    /// The amount is limited by the printer, see [`Profile::max_reverse_feed`] and [`PrinterConfig::limits`].
+    ///
+    /// [`Error::Unsupported`] is returned if the [`profile()`](CommandBuilder::profile()) doesn't feed backwards,
+    /// see [`Profile::reverse_feed`].
+    fn print_and_reverse_feed_paper(
+        &mut self,
+        unit: FeedUnit,
+        amount: u8,
+    ) -> Result<&mut Self, Error> {
+        let command = limit(self, Command::PrintAndReverseFeed { unit, amount });
+        self.try_push(command)
+    }
+
+    /// Append a command for searching for the next black mark or label gap and feeding the paper
+    /// to the print start position of the next ticket or label.
+    ///
+    /// **NOTE:** Only valid for printers loaded with ticket stock or labels.
+    ///
+    /// [`Error::Unsupported`] is returned if the [`profile()`](CommandBuilder::profile()) has no black mark sensor,
+    /// see [`Profile::black_mark`].
+    fn feed_to_mark(&mut self) -> Result<&mut Self, Error> {
+        self.try_push(Command::FeedToMark)
+    }
+
+    /// Append a command for adjusting `position` by `offset` of vertical motion units from the black mark
+    /// or label gap, forwards if `offset` is positive and backwards if negative.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, MarkPosition};
+    /// # let mut builder = JobBuilder::new();
+    /// builder
+    ///     .mark_offset(MarkPosition::PrintStart, 24)
+    ///     .mark_offset(MarkPosition::Cut, -8)
+    ///     .feed_to_mark()
+    ///     .unwrap();
+    /// ```
+    fn mark_offset(&mut self, position: MarkPosition, offset: i16) -> &mut Self {
+        self.push(Command::MarkOffset { position, offset })
+    }
+
+    /// Append a command for setting the horizontal and vertical motion units to 1 / `x` and 1 / `y` inch.
+    ///
+    /// The motion units determine the physical distances of [`FeedUnit::Inches`], e.g. after setting
+    /// `y` to 203, [`print_and_feed_paper(FeedUnit::Inches, 203)`](CommandBuilder::print_and_feed_paper())
+    /// feeds the paper by exactly 1 inch. A value of 0 restores the default motion unit of the printer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, FeedUnit};
+    /// # let mut builder = JobBuilder::new();
+    /// // feed the paper by 0.5 inch
+    /// builder
+    ///     .motion_units(200, 200)
+    ///     .print_and_feed_paper(FeedUnit::Inches, 100);
+    /// ```
+    fn motion_units(&mut self, x: u8, y: u8) -> &mut Self {
+        self.push(Command::MotionUnits { x, y })
+    }
+
+    /// Append a command for configuring the paper-saving settings, see [`PaperSaving`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, PaperSaving};
+    /// # let mut builder = JobBuilder::new();
+    /// builder.paper_saving(&PaperSaving {
+    ///     top_margin: true,
+    ///     blank_lines: true,
+    ///     ..Default::default()
+    /// });
+    /// ```
+    fn paper_saving(&mut self, saving: &PaperSaving) -> &mut Self {
+        self.push(Command::PaperSaving(*saving))
+    }
+
+    /// Append a command for selecting speed / quality mode.
+    fn speed(&mut self, speed: &Speed) -> &mut Self {
+        self.push(Command::Speed(*speed))
+    }
+
+    /// Append a command for setting printing density.
+    fn density(&mut self, density: &Density) -> &mut Self {
+        self.push(Command::Density(*density))
+    }
+
+    /// Append a command for selecting the character code table `page` of the printer, e.g. for text in a
+    /// language other than English.
+    fn code_page(&mut self, page: u8) -> &mut Self {
+        self.push(Command::CodePage(page))
+    }
+
+    /// Append a command for printing the following text in bold or not, until the printer is initialized.
+    fn bold(&mut self, enabled: bool) -> &mut Self {
+        self.push(Command::Bold(enabled))
+    }
+
+    /// Append a command for underlining the following text or not, until the printer is initialized.
+    fn underline(&mut self, enabled: bool) -> &mut Self {
+        self.push(Command::Underline(enabled))
+    }
+
+    /// Append a command for enlarging the following text `width` and `height` times, from 1 (the normal size) to 8,
+    /// until the printer is initialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder};
+    /// # let mut builder = JobBuilder::new();
+    /// builder
+    ///     .character_size(2, 2)
+    ///     .raw(b"TOTAL 4.30")
+    ///     .print()
+    ///     .character_size(1, 1);
+    /// ```
+    fn character_size(&mut self, width: u8, height: u8) -> &mut Self {
+        self.push(Command::CharacterSize { width, height })
+    }
+
+    /// Append a command for justifying the following lines in the printable area, including the bit images and
+    /// the barcodes, until the printer is initialized.
+    ///
+    /// It only applies to the lines started after it, and is ignored in page mode.
+    fn justification(&mut self, justification: Justification) -> &mut Self {
+        self.push(Command::Justification(justification))
+    }
+
+    /// Append commands for setting the printable area in standard mode to `width` from the `left` margin, in
+    /// horizontal motion units, e.g. to keep the margins of [`PrinterConfig::margin`] on every line.
+    ///
+    /// The area is kept until the printer is initialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder};
+    /// # let mut builder = JobBuilder::new();
+    /// // 4 mm margins on 80 mm paper at 203 dpi
+    /// builder.print_area(32, 512);
+    /// ```
+    fn print_area(&mut self, left: u16, width: u16) -> &mut Self {
+        self.push(Command::PrintArea { left, width })
+    }
+
+    /// Append commands for keeping the left and right `margin` on every line in standard mode, converted to dots at
+    /// the resolution of the [`config()`](CommandBuilder::config()), see [`print_area()`](CommandBuilder::print_area()).
+    ///
+    /// [`Error::InvalidParameter`] is returned if the margins leave nothing of the printed line.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, Length};
+    /// # let mut builder = JobBuilder::new();
+    /// builder.margins(Length::mm(3.0)).unwrap();
+    /// ```
+    fn margins(&mut self, margin: Length) -> Result<&mut Self, Error> {
+        let config = self.config();
+        let margin = margin.to_dots(config.dpi);
+        let width = config
+            .dots_per_line
+            .checked_sub(margin.saturating_mul(2))
+            .filter(|width| *width > 0)
+            .ok_or_else(|| {
+                Error::InvalidParameter(format!(
+                    "margins of {margin} dots leave nothing of {} dots",
+                    config.dots_per_line
+                ))
+            })?;
+
+        Ok(self.print_area(margin, width))
+    }
+
+    /// Append a command for generating a pulse on `pin` of the drawer kick-out connector to open the cash drawer.
+    ///
+    /// The pulse is on for `on_ms` and off for `off_ms` milliseconds, in steps of 2 ms up to 510 ms.
+    /// If `off_ms` is less than `on_ms`, the off time is the same as the on time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, DrawerPin};
+    /// # let mut builder = JobBuilder::new();
+    /// builder.open_drawer(DrawerPin::Pin2, 100, 200);
+    /// ```
+    fn open_drawer(&mut self, pin: DrawerPin, on_ms: u16, off_ms: u16) -> &mut Self {
+        self.push(Command::OpenDrawer { pin, on_ms, off_ms })
+    }
+
+    /// Append a command for sounding the buzzer `count` times, each lasting `duration_ms` milliseconds.
+    ///
+    /// The count is limited to 9 and the duration is in steps of 100 ms up to 900 ms.
+    ///
+    /// [`Error::Unsupported`] is returned if the [`profile()`](CommandBuilder::profile()) has no buzzer, see
+    /// [`Profile::buzzer`], e.g. on anything but TL60 and TL80.
+    fn beep(&mut self, count: u8, duration_ms: u16) -> Result<&mut Self, Error> {
+        self.try_push(Command::Beep { count, duration_ms })
+    }
+
+    /// Append a command for enabling or disabling the feed button on the panel of the printer,
+    /// e.g. to prevent paper waste or receipt tampering in unattended kiosks.
+    fn feed_button(&mut self, enabled: bool) -> &mut Self {
+        self.push(Command::FeedButton(enabled))
+    }
+
+    /// Append a command for entering the sleep mode to reduce power consumption between tickets.
+    ///
+    /// The printer doesn't process any further commands until it's woken up by [`wake_up()`](crate::CustomPrinter::wake_up()).
+    fn sleep(&mut self) -> &mut Self {
+        self.push(Command::Sleep)
+    }
+
+    /// Append a command for entering the sleep mode automatically after being idle for `minutes`,
+    /// or disabling the automatic sleep if `minutes` is `None`.
+    fn sleep_timeout(&mut self, minutes: Option<u8>) -> &mut Self {
+        self.push(Command::SleepTimeout(minutes))
+    }
+
+    /// Append a command for setting the length of the paper loop formed in the presenter to `length_mm` millimeters,
+    /// or disabling the loop if `length_mm` is 0.
+    ///
+    /// [`Error::Unsupported`] is returned if the [`profile()`](CommandBuilder::profile()) has no presenter, e.g. on
+    /// anything but VKP80.
+    fn presenter_loop(&mut self, length_mm: u8) -> Result<&mut Self, Error> {
+        self.try_push(Command::PresenterLoop(length_mm))
+    }
+
+    /// Append a command for presenting `length_mm` millimeters of the printed ticket at the presenter output.
+    ///
+    /// [`Error::Unsupported`] is returned if the [`profile()`](CommandBuilder::profile()) has no presenter, e.g. on
+    /// anything but VKP80.
+    fn present_ticket(&mut self, length_mm: u8) -> Result<&mut Self, Error> {
+        self.try_push(Command::PresentTicket(length_mm))
+    }
+
+    /// Append a command for ejecting the printed ticket out of the presenter.
+    ///
+    /// [`Error::Unsupported`] is returned if the [`profile()`](CommandBuilder::profile()) has no presenter, e.g. on
+    /// anything but VKP80.
+    fn eject_ticket(&mut self) -> Result<&mut Self, Error> {
+        self.try_push(Command::EjectTicket)
+    }
+
+    /// Append a command for retracting the presented ticket into the printer.
+    ///
+    /// [`Error::Unsupported`] is returned if the [`profile()`](CommandBuilder::profile()) has no presenter, e.g. on
+    /// anything but VKP80.
+    fn retract_ticket(&mut self) -> Result<&mut Self, Error> {
+        self.try_push(Command::RetractTicket)
+    }
+
+    /// Append a command for retracting the presented ticket automatically if it isn't taken within
+    /// `timeout_s` seconds, or disabling the automatic retraction if `timeout_s` is `None`.
+    ///
+    /// [`Error::Unsupported`] is returned if the [`profile()`](CommandBuilder::profile()) has no presenter, e.g. on
+    /// anything but VKP80.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder, CutType, Error, Model};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut builder = JobBuilder::new();
+    /// builder
+    ///     .set_model(Some(Model::Vkp80))
+    ///     .presenter_loop(0)?
+    ///     .retract_on_timeout(Some(30))?
+    ///     .print()
+    ///     .cut_paper(CutType::TotalCut)
+    ///     .present_ticket(50)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn retract_on_timeout(&mut self, timeout_s: Option<u8>) -> Result<&mut Self, Error> {
+        self.try_push(Command::RetractOnTimeout(timeout_s))
+    }
+
+    /// Append a command for enabling or disabling stopping printing when the paper near-end is detected.
+    ///
+    /// Disable stopping to keep printing to the true end of the roll, e.g. in unattended kiosks.
+    fn stop_on_near_end(&mut self, enabled: bool) -> &mut Self {
+        self.push(Command::StopOnNearEnd(enabled))
+    }
+
+    /// Append a command for setting the sensitivity of the paper near-end sensor.
+    fn near_end_sensitivity(&mut self, sensitivity: NearEndSensitivity) -> &mut Self {
+        self.push(Command::NearEndSensitivity(sensitivity))
+    }
+
+    /// Append `bytes` to be sent to the printer as they are, e.g. vendor-specific commands not wrapped by this crate.
+    ///
+    /// **NOTE:** The bytes aren't validated, so an incomplete command may garble the following ones.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, JobBuilder};
+    /// # let mut builder = JobBuilder::new();
+    /// // select the international character set
+    /// builder.raw(&[0x1B, 0x52, 0x02]).print();
+    /// ```
+    fn raw(&mut self, bytes: &[u8]) -> &mut Self {
+        self.push(Command::Raw(bytes.to_vec()))
+    }
+}
+
+/// Append commands to `builder` for printing and feeding the paper by `dots` lines, 255 at most per command.
+fn feed_dots<B: CommandBuilder + ?Sized>(builder: &mut B, mut dots: u16) {
+    while dots > 0 {
+        let amount = dots.min(u8::MAX as u16);
+        builder.print_and_feed_paper(FeedUnit::Inches, amount as u8);
+        dots -= amount;
+    }
+}
+
+/// `command` clamped to the limits of the profile of `builder` if its [`PrinterConfig::limits`] are
+/// [`LimitPolicy::Clamp`], or as it is.
+fn limit<B: CommandBuilder + ?Sized>(builder: &B, command: Command) -> Command {
+    match builder.profile() {
+        Some(profile) if builder.config().limits == LimitPolicy::Clamp => profile.clamp(command),
+        _ => command,
+    }
+}
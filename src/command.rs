@@ -0,0 +1,1019 @@
+//! Typed commands constructed by [`CustomPrinter`](crate::CustomPrinter) and their encoding to bytes.
+
+use alloc::vec::Vec;
+
+use crate::Error;
+#[cfg(feature = "std")]
+use crate::Profile;
+use crate::{
+    BarcodeSystem, BitImageMode, CutType, Density, DrawerPin, FeedUnit, HriFont, HriPosition,
+    Justification, MarkPosition, NearEndSensitivity, PageDirection, PaperSaving, QrErrorCorrection,
+    Speed,
+};
+
+#[cfg(feature = "std")]
+mod frame;
+#[cfg(feature = "json")]
+pub(crate) mod json;
+
+// List of supported commands
+const INITIALIZE: &[u8] = &[0x1B, 0x40];
+// Printing commands
+const PRINT: &[u8] = &[0x0A];
+const PRINT_FEED_INCHES: &[u8] = &[0x1B, 0x4A];
+const PRINT_FEED_LINES: &[u8] = &[0x1B, 0x64];
+const PRINT_REVERSE_FEED_INCHES: &[u8] = &[0x1B, 0x4B];
+const PRINT_REVERSE_FEED_LINES: &[u8] = &[0x1B, 0x65];
+const MOTION_UNITS: &[u8] = &[0x1D, 0x50];
+const PAPER_SAVING: &[u8] = &[0x1D, 0xF7];
+const SPEED_QUALITY: &[u8] = &[0x1B, 0x78];
+const DENSITY: &[u8] = &[0x1D, 0x7C];
+const CODE_PAGE: &[u8] = &[0x1B, 0x74];
+const BOLD: &[u8] = &[0x1B, 0x45];
+const UNDERLINE: &[u8] = &[0x1B, 0x2D];
+const CHARACTER_SIZE: &[u8] = &[0x1D, 0x21];
+const JUSTIFICATION: &[u8] = &[0x1B, 0x61];
+const BARCODE: &[u8] = &[0x1D, 0x6B];
+const BARCODE_WIDTH: &[u8] = &[0x1D, 0x77];
+const HRI_POSITION: &[u8] = &[0x1D, 0x48];
+const HRI_FONT: &[u8] = &[0x1D, 0x66];
+pub(crate) const QR_MODEL_2: &[u8] = &[0x1D, 0x28, 0x6B, 0x04, 0x00, 0x31, 0x41, 0x32, 0x00];
+pub(crate) const QR_MODULE_SIZE: &[u8] = &[0x1D, 0x28, 0x6B, 0x03, 0x00, 0x31, 0x43];
+pub(crate) const QR_ERROR_CORRECTION: &[u8] = &[0x1D, 0x28, 0x6B, 0x03, 0x00, 0x31, 0x45];
+pub(crate) const QR_STORE: &[u8] = &[0x1D, 0x28, 0x6B];
+pub(crate) const QR_PRINT: &[u8] = &[0x1D, 0x28, 0x6B, 0x03, 0x00, 0x31, 0x51, 0x30];
+const LEFT_MARGIN: &[u8] = &[0x1D, 0x4C];
+const PRINT_AREA_WIDTH: &[u8] = &[0x1D, 0x57];
+// Black mark / label commands
+const FEED_TO_MARK: &[u8] = &[0x1D, 0x0C];
+const MARK_OFFSET: &[u8] = &[0x1D, 0x28, 0x46, 0x04, 0x00];
+// Peripheral commands
+const DRAWER_PULSE: &[u8] = &[0x1B, 0x70];
+const BUZZER: &[u8] = &[0x1B, 0x42];
+const PANEL_BUTTONS: &[u8] = &[0x1B, 0x63, 0x35];
+// Power commands
+const SLEEP: &[u8] = &[0x1B, 0x8E, 0x00];
+const SLEEP_TIMEOUT: &[u8] = &[0x1B, 0x8E, 0x01];
+// Presenter commands
+const PRESENTER: &[u8] = &[0x1D, 0x65];
+// Paper sensor commands
+const NEAR_END_STOP: &[u8] = &[0x1B, 0x63, 0x34];
+const NEAR_END_SENSITIVITY: &[u8] = &[0x1D, 0xE6];
+// Page mode commands
+const PAGE_MODE: &[u8] = &[0x1B, 0x4C];
+const STANDARD_MODE: &[u8] = &[0x1B, 0x53];
+const PRINT_PAGE: &[u8] = &[0x1B, 0x0C];
+const PRINT_PAGE_AND_EXIT: &[u8] = &[0x0C];
+const PAGE_AREA: &[u8] = &[0x1B, 0x57];
+const PAGE_DIRECTION: &[u8] = &[0x1B, 0x54];
+const ABSOLUTE_X: &[u8] = &[0x1B, 0x24];
+const ABSOLUTE_Y: &[u8] = &[0x1D, 0x24];
+const CANCEL: &[u8] = &[0x18];
+// Bit-image commands
+const BIT_IMAGE: &[u8] = &[0x1B, 0x2A];
+const RASTER_IMAGE: &[u8] = &[0x1D, 0x76, 0x30];
+// Mechanism control commands
+const TOTAL_CUT: &[u8] = &[0x1B, 0x69];
+const PARTIAL_CUT: &[u8] = &[0x1B, 0x6D];
+const FEED_AND_CUT: &[u8] = &[0x1D, 0x56];
+
+/// Largest number of bytes of the data of a QR code, the capacity of version 40 in digits.
+pub(crate) const QR_MAX_BYTES: usize = 7089;
+
+/// Largest number of lines of a raster image, a multiple of the banks of 24 lines below the 2303 lines of
+/// the ESC/POS firmwares, so taller bit images are split in several raster images.
+pub(crate) const RASTER_MAX_LINES: usize = 2280;
+
+// Functions of PRESENTER command
+const PRESENTER_LOOP: u8 = 0x01;
+const PRESENTER_RETRACT: u8 = 0x02;
+const PRESENTER_PRESENT: u8 = 0x03;
+const PRESENTER_EJECT: u8 = 0x05;
+const PRESENTER_RETRACT_TIMEOUT: u8 = 0x12;
+
+/// A command constructed by [`CustomPrinter`](crate::CustomPrinter), encoded to bytes only when it's run.
+///
+/// Each variant corresponds to one of the constructing functions of [`CustomPrinter`](crate::CustomPrinter)
+/// and holds its parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum Command {
+    /// Initialize the printer, see [`CommandBuilder::initialize()`](crate::CommandBuilder::initialize())
+    Initialize,
+    /// Print and line feed, see [`CommandBuilder::print()`](crate::CommandBuilder::print())
+    Print,
+    /// Print and feed the paper, see [`CommandBuilder::print_and_feed_paper()`](crate::CommandBuilder::print_and_feed_paper())
+    PrintAndFeed {
+        /// Feed unit
+        unit: FeedUnit,
+        /// Amount of `unit`
+        amount: u8,
+    },
+    /// Print and feed the paper backwards, see
+    /// [`CommandBuilder::print_and_reverse_feed_paper()`](crate::CommandBuilder::print_and_reverse_feed_paper())
+    PrintAndReverseFeed {
+        /// Feed unit
+        unit: FeedUnit,
+        /// Amount of `unit`
+        amount: u8,
+    },
+    /// Set the motion units, see [`CommandBuilder::motion_units()`](crate::CommandBuilder::motion_units())
+    MotionUnits {
+        /// Horizontal motion unit in 1 / `x` inch
+        x: u8,
+        /// Vertical motion unit in 1 / `y` inch
+        y: u8,
+    },
+    /// Configure the paper-saving settings, see [`CommandBuilder::paper_saving()`](crate::CommandBuilder::paper_saving())
+    PaperSaving(PaperSaving),
+    /// Select speed / quality mode, see [`CommandBuilder::speed()`](crate::CommandBuilder::speed())
+    Speed(Speed),
+    /// Set printing density, see [`CommandBuilder::density()`](crate::CommandBuilder::density())
+    Density(Density),
+    /// Select the character code table, see [`CommandBuilder::code_page()`](crate::CommandBuilder::code_page())
+    CodePage(u8),
+    /// Print the following text in bold or not, see [`CommandBuilder::bold()`](crate::CommandBuilder::bold())
+    Bold(bool),
+    /// Underline the following text or not, see [`CommandBuilder::underline()`](crate::CommandBuilder::underline())
+    Underline(bool),
+    /// Enlarge the following text, see [`CommandBuilder::character_size()`](crate::CommandBuilder::character_size())
+    CharacterSize {
+        /// Width multiplier, 1 to 8
+        width: u8,
+        /// Height multiplier, 1 to 8
+        height: u8,
+    },
+    /// Justify the following lines, see [`CommandBuilder::justification()`](crate::CommandBuilder::justification())
+    Justification(Justification),
+    /// Set the printable area in standard mode, see [`CommandBuilder::print_area()`](crate::CommandBuilder::print_area())
+    PrintArea {
+        /// Left margin
+        left: u16,
+        /// Width of the printable area
+        width: u16,
+    },
+    /// Feed to the black mark or label gap, see [`CommandBuilder::feed_to_mark()`](crate::CommandBuilder::feed_to_mark())
+    FeedToMark,
+    /// Adjust a position from the black mark or label gap, see [`CommandBuilder::mark_offset()`](crate::CommandBuilder::mark_offset())
+    MarkOffset {
+        /// Position to adjust
+        position: MarkPosition,
+        /// Offset in vertical motion units
+        offset: i16,
+    },
+    /// Open the cash drawer, see [`CommandBuilder::open_drawer()`](crate::CommandBuilder::open_drawer())
+    OpenDrawer {
+        /// Connector pin
+        pin: DrawerPin,
+        /// On time in milliseconds
+        on_ms: u16,
+        /// Off time in milliseconds
+        off_ms: u16,
+    },
+    /// Sound the buzzer, see [`CommandBuilder::beep()`](crate::CommandBuilder::beep())
+    Beep {
+        /// Number of beeps
+        count: u8,
+        /// Duration of each beep in milliseconds
+        duration_ms: u16,
+    },
+    /// Enable or disable the feed button, see [`CommandBuilder::feed_button()`](crate::CommandBuilder::feed_button())
+    FeedButton(bool),
+    /// Enter the sleep mode, see [`CommandBuilder::sleep()`](crate::CommandBuilder::sleep())
+    Sleep,
+    /// Set the automatic sleep timeout in minutes, see [`CommandBuilder::sleep_timeout()`](crate::CommandBuilder::sleep_timeout())
+    SleepTimeout(Option<u8>),
+    /// Set the presenter loop length in millimeters, see [`CommandBuilder::presenter_loop()`](crate::CommandBuilder::presenter_loop())
+    PresenterLoop(u8),
+    /// Present the ticket by a length in millimeters, see [`CommandBuilder::present_ticket()`](crate::CommandBuilder::present_ticket())
+    PresentTicket(u8),
+    /// Eject the ticket, see [`CommandBuilder::eject_ticket()`](crate::CommandBuilder::eject_ticket())
+    EjectTicket,
+    /// Retract the ticket, see [`CommandBuilder::retract_ticket()`](crate::CommandBuilder::retract_ticket())
+    RetractTicket,
+    /// Set the automatic retraction timeout in seconds, see
+    /// [`CommandBuilder::retract_on_timeout()`](crate::CommandBuilder::retract_on_timeout())
+    RetractOnTimeout(Option<u8>),
+    /// Enable or disable stopping on paper near-end, see
+    /// [`CommandBuilder::stop_on_near_end()`](crate::CommandBuilder::stop_on_near_end())
+    StopOnNearEnd(bool),
+    /// Set the near-end sensor sensitivity, see
+    /// [`CommandBuilder::near_end_sensitivity()`](crate::CommandBuilder::near_end_sensitivity())
+    NearEndSensitivity(NearEndSensitivity),
+    /// Enter page mode, see [`CommandBuilder::enter_page_mode()`](crate::CommandBuilder::enter_page_mode())
+    EnterPageMode,
+    /// Return to standard mode, see [`CommandBuilder::exit_page_mode()`](crate::CommandBuilder::exit_page_mode())
+    ExitPageMode,
+    /// Print the page, see [`CommandBuilder::print_page()`](crate::CommandBuilder::print_page())
+    PrintPage,
+    /// Print the page and return to standard mode, see
+    /// [`CommandBuilder::print_page_and_exit()`](crate::CommandBuilder::print_page_and_exit())
+    PrintPageAndExit,
+    /// Cancel the data in page mode, see [`CommandBuilder::cancel_page_data()`](crate::CommandBuilder::cancel_page_data())
+    CancelPageData,
+    /// Set the print area in page mode, see [`CommandBuilder::page_area()`](crate::CommandBuilder::page_area())
+    PageArea {
+        /// Horizontal origin
+        x: u16,
+        /// Vertical origin
+        y: u16,
+        /// Width
+        width: u16,
+        /// Height
+        height: u16,
+    },
+    /// Select the print direction in page mode, see [`CommandBuilder::page_direction()`](crate::CommandBuilder::page_direction())
+    PageDirection(PageDirection),
+    /// Move the print position, see [`CommandBuilder::position()`](crate::CommandBuilder::position())
+    Position {
+        /// Horizontal position
+        x: u16,
+        /// Vertical position
+        y: u16,
+    },
+    /// Print a bit image, see [`CommandBuilder::bit_image()`](crate::CommandBuilder::bit_image())
+    BitImage {
+        /// Mode of the bit image
+        mode: BitImageMode,
+        /// Width in dots
+        width: u16,
+        /// Bit image data, bank after bank
+        data: Vec<u8>,
+    },
+    /// Print a barcode, see [`CommandBuilder::barcode()`](crate::CommandBuilder::barcode())
+    Barcode {
+        /// Barcode system
+        system: BarcodeSystem,
+        /// Data of 1 to 255 bytes, the bytes beyond 255 being left out
+        data: Vec<u8>,
+    },
+    /// Style the following barcodes, see [`CommandBuilder::barcode_style()`](crate::CommandBuilder::barcode_style())
+    BarcodeStyle {
+        /// Width of the narrow modules in dots, 1 to 6
+        module_width: u8,
+        /// Position of the human readable interpretation
+        hri: HriPosition,
+        /// Font of the human readable interpretation
+        hri_font: HriFont,
+    },
+    /// Print a QR code of model 2, see [`CommandBuilder::qr_code()`](crate::CommandBuilder::qr_code())
+    QrCode {
+        /// Data of 1 to 7089 bytes, the bytes beyond 7089 being left out
+        data: Vec<u8>,
+        /// Size of a module, i.e. a square of the code, in dots, 1 to 16
+        module_size: u8,
+        /// Error correction level
+        error_correction: QrErrorCorrection,
+    },
+    /// Cut the paper, see [`CommandBuilder::cut_paper()`](crate::CommandBuilder::cut_paper())
+    CutPaper(CutType),
+    /// Feed the paper to the cut position and cut it, see
+    /// [`CommandBuilder::feed_and_cut_paper()`](crate::CommandBuilder::feed_and_cut_paper())
+    FeedAndCutPaper {
+        /// Cut type
+        cut_type: CutType,
+        /// Amount of vertical motion units to feed beyond the cut position
+        amount: u8,
+    },
+    /// Raw bytes sent as they are, see [`CommandBuilder::raw()`](crate::CommandBuilder::raw())
+    Raw(Vec<u8>),
+}
+
+impl Command {
+    /// Name of the kind of the command in snake case, e.g. `"print_and_feed"` for [`Command::PrintAndFeed`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CutType, JobBuilder};
+    /// let job = JobBuilder::new().print().cut_paper(CutType::TotalCut).build();
+    /// let kinds: Vec<_> = job.commands().iter().map(|command| command.kind()).collect();
+    /// assert_eq!(kinds, ["print", "cut_paper"]);
+    /// ```
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Initialize => "initialize",
+            Self::Print => "print",
+            Self::PrintAndFeed { .. } => "print_and_feed",
+            Self::PrintAndReverseFeed { .. } => "print_and_reverse_feed",
+            Self::MotionUnits { .. } => "motion_units",
+            Self::PaperSaving(_) => "paper_saving",
+            Self::Speed(_) => "speed",
+            Self::Density(_) => "density",
+            Self::CodePage(_) => "code_page",
+            Self::Bold(_) => "bold",
+            Self::Underline(_) => "underline",
+            Self::CharacterSize { .. } => "character_size",
+            Self::Justification(_) => "justification",
+            Self::PrintArea { .. } => "print_area",
+            Self::FeedToMark => "feed_to_mark",
+            Self::MarkOffset { .. } => "mark_offset",
+            Self::OpenDrawer { .. } => "open_drawer",
+            Self::Beep { .. } => "beep",
+            Self::FeedButton(_) => "feed_button",
+            Self::Sleep => "sleep",
+            Self::SleepTimeout(_) => "sleep_timeout",
+            Self::PresenterLoop(_) => "presenter_loop",
+            Self::PresentTicket(_) => "present_ticket",
+            Self::EjectTicket => "eject_ticket",
+            Self::RetractTicket => "retract_ticket",
+            Self::RetractOnTimeout(_) => "retract_on_timeout",
+            Self::StopOnNearEnd(_) => "stop_on_near_end",
+            Self::NearEndSensitivity(_) => "near_end_sensitivity",
+            Self::EnterPageMode => "enter_page_mode",
+            Self::ExitPageMode => "exit_page_mode",
+            Self::PrintPage => "print_page",
+            Self::PrintPageAndExit => "print_page_and_exit",
+            Self::CancelPageData => "cancel_page_data",
+            Self::PageArea { .. } => "page_area",
+            Self::PageDirection(_) => "page_direction",
+            Self::Position { .. } => "position",
+            Self::BitImage { .. } => "bit_image",
+            Self::Barcode { .. } => "barcode",
+            Self::BarcodeStyle { .. } => "barcode_style",
+            Self::QrCode { .. } => "qr_code",
+            Self::CutPaper(_) => "cut_paper",
+            Self::FeedAndCutPaper { .. } => "feed_and_cut_paper",
+            Self::Raw(_) => "raw",
+        }
+    }
+
+    /// Length of the data carried by the command, i.e. the bytes of [`Command::BitImage`], [`Command::Barcode`],
+    /// [`Command::QrCode`] and [`Command::Raw`], or 0 for the other commands whose parameters are all in their
+    /// fields.
+    pub fn payload_len(&self) -> usize {
+        match self {
+            Self::BitImage { data, .. }
+            | Self::Barcode { data, .. }
+            | Self::QrCode { data, .. } => data.len(),
+            Self::Raw(bytes) => bytes.len(),
+            _ => 0,
+        }
+    }
+
+    /// Append the bytes of the command to `out`.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Initialize => out.extend_from_slice(INITIALIZE),
+            Self::Print => out.extend_from_slice(PRINT),
+            Self::PrintAndFeed { unit, amount } => {
+                out.extend_from_slice(match unit {
+                    FeedUnit::Inches => PRINT_FEED_INCHES,
+                    FeedUnit::Lines => PRINT_FEED_LINES,
+                });
+                out.push(*amount);
+            }
+            Self::PrintAndReverseFeed { unit, amount } => {
+                out.extend_from_slice(match unit {
+                    FeedUnit::Inches => PRINT_REVERSE_FEED_INCHES,
+                    FeedUnit::Lines => PRINT_REVERSE_FEED_LINES,
+                });
+                out.push(*amount);
+            }
+            Self::MotionUnits { x, y } => {
+                out.extend_from_slice(MOTION_UNITS);
+                out.extend_from_slice(&[*x, *y]);
+            }
+            Self::PaperSaving(saving) => {
+                out.extend_from_slice(PAPER_SAVING);
+                out.push(saving.bits());
+            }
+            Self::Speed(speed) => {
+                out.extend_from_slice(SPEED_QUALITY);
+                out.push(match speed {
+                    Speed::High => 0,
+                    Speed::Normal => 1,
+                    Speed::Low => 2,
+                });
+            }
+            Self::Density(density) => {
+                out.extend_from_slice(DENSITY);
+                out.push(match density {
+                    Density::Minus50 => 0,
+                    Density::Minus25 => 1,
+                    Density::Zero => 2,
+                    Density::Plus25 => 3,
+                    Density::Plus50 => 4,
+                });
+            }
+            Self::CodePage(page) => {
+                out.extend_from_slice(CODE_PAGE);
+                out.push(*page);
+            }
+            Self::Bold(enabled) => {
+                out.extend_from_slice(BOLD);
+                out.push(*enabled as u8);
+            }
+            Self::Underline(enabled) => {
+                out.extend_from_slice(UNDERLINE);
+                out.push(*enabled as u8);
+            }
+            Self::CharacterSize { width, height } => {
+                let scale = |n: &u8| n.clamp(&1, &8) - 1;
+                out.extend_from_slice(CHARACTER_SIZE);
+                out.push(scale(width) << 4 | scale(height));
+            }
+            Self::Justification(justification) => {
+                out.extend_from_slice(JUSTIFICATION);
+                out.push(match justification {
+                    Justification::Left => 0,
+                    Justification::Center => 1,
+                    Justification::Right => 2,
+                });
+            }
+            Self::PrintArea { left, width } => {
+                out.extend_from_slice(LEFT_MARGIN);
+                out.extend_from_slice(&left.to_le_bytes());
+                out.extend_from_slice(PRINT_AREA_WIDTH);
+                out.extend_from_slice(&width.to_le_bytes());
+            }
+            Self::FeedToMark => out.extend_from_slice(FEED_TO_MARK),
+            Self::MarkOffset { position, offset } => {
+                let amount = offset.unsigned_abs();
+
+                out.extend_from_slice(MARK_OFFSET);
+                out.extend_from_slice(&[
+                    match position {
+                        MarkPosition::PrintStart => 1,
+                        MarkPosition::Cut => 2,
+                    },
+                    if *offset < 0 { 1 } else { 0 },
+                    (amount % 256) as u8,
+                    (amount / 256) as u8,
+                ]);
+            }
+            Self::OpenDrawer { pin, on_ms, off_ms } => {
+                let to_units = |ms: u16| (ms / 2).min(u8::MAX as u16) as u8;
+
+                out.extend_from_slice(DRAWER_PULSE);
+                out.extend_from_slice(&[
+                    match pin {
+                        DrawerPin::Pin2 => 0,
+                        DrawerPin::Pin5 => 1,
+                    },
+                    to_units(*on_ms),
+                    to_units(*off_ms),
+                ]);
+            }
+            Self::Beep { count, duration_ms } => {
+                out.extend_from_slice(BUZZER);
+                out.extend_from_slice(&[(*count).min(9), (duration_ms / 100).min(9) as u8]);
+            }
+            Self::FeedButton(enabled) => {
+                out.extend_from_slice(PANEL_BUTTONS);
+                out.push(if *enabled { 0x00 } else { 0x01 });
+            }
+            Self::Sleep => out.extend_from_slice(SLEEP),
+            Self::SleepTimeout(minutes) => {
+                out.extend_from_slice(SLEEP_TIMEOUT);
+                out.push(minutes.unwrap_or(0));
+            }
+            Self::PresenterLoop(length_mm) => {
+                Self::encode_presenter(out, PRESENTER_LOOP, &[*length_mm])
+            }
+            Self::PresentTicket(length_mm) => {
+                Self::encode_presenter(out, PRESENTER_PRESENT, &[*length_mm])
+            }
+            Self::EjectTicket => Self::encode_presenter(out, PRESENTER_EJECT, &[]),
+            Self::RetractTicket => Self::encode_presenter(out, PRESENTER_RETRACT, &[]),
+            Self::RetractOnTimeout(timeout_s) => {
+                Self::encode_presenter(out, PRESENTER_RETRACT_TIMEOUT, &[timeout_s.unwrap_or(0)])
+            }
+            Self::StopOnNearEnd(enabled) => {
+                out.extend_from_slice(NEAR_END_STOP);
+                out.push(if *enabled { 0x03 } else { 0x00 });
+            }
+            Self::NearEndSensitivity(sensitivity) => {
+                out.extend_from_slice(NEAR_END_SENSITIVITY);
+                out.push(match sensitivity {
+                    NearEndSensitivity::Low => 0,
+                    NearEndSensitivity::Medium => 1,
+                    NearEndSensitivity::High => 2,
+                });
+            }
+            Self::EnterPageMode => out.extend_from_slice(PAGE_MODE),
+            Self::ExitPageMode => out.extend_from_slice(STANDARD_MODE),
+            Self::PrintPage => out.extend_from_slice(PRINT_PAGE),
+            Self::PrintPageAndExit => out.extend_from_slice(PRINT_PAGE_AND_EXIT),
+            Self::CancelPageData => out.extend_from_slice(CANCEL),
+            Self::PageArea {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                out.extend_from_slice(PAGE_AREA);
+                for value in [x, y, width, height] {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            Self::PageDirection(direction) => {
+                out.extend_from_slice(PAGE_DIRECTION);
+                out.push(match direction {
+                    PageDirection::LeftToRight => 0,
+                    PageDirection::BottomToTop => 1,
+                    PageDirection::RightToLeft => 2,
+                    PageDirection::TopToBottom => 3,
+                });
+            }
+            Self::Position { x, y } => {
+                out.extend_from_slice(ABSOLUTE_X);
+                out.extend_from_slice(&x.to_le_bytes());
+                out.extend_from_slice(ABSOLUTE_Y);
+                out.extend_from_slice(&y.to_le_bytes());
+            }
+            Self::BitImage { mode, width, data } => {
+                let width = *width as usize;
+                let (m, k) = match mode {
+                    BitImageMode::Dots8SingleDensity => (0x00, width),
+                    BitImageMode::Dots8DoubleDensity => (0x01, width),
+                    BitImageMode::Dots24SingleDensity => (0x20, width * 3),
+                    BitImageMode::Dots24DoubleDensity => (0x21, width * 3),
+                };
+                if k == 0 {
+                    return;
+                }
+
+                for bank in data.chunks_exact(k) {
+                    out.extend_from_slice(BIT_IMAGE);
+                    out.extend_from_slice(&[m, (width % 256) as u8, (width / 256) as u8]);
+                    out.extend_from_slice(bank);
+                }
+            }
+            Self::Barcode { system, data } => {
+                let data = &data[..data.len().min(u8::MAX as usize)];
+                out.extend_from_slice(BARCODE);
+                out.push(match system {
+                    BarcodeSystem::UpcA => 65,
+                    BarcodeSystem::UpcE => 66,
+                    BarcodeSystem::Ean13 => 67,
+                    BarcodeSystem::Ean8 => 68,
+                    BarcodeSystem::Code39 => 69,
+                    BarcodeSystem::Itf => 70,
+                    BarcodeSystem::Codabar => 71,
+                    BarcodeSystem::Code93 => 72,
+                    BarcodeSystem::Code128 => 73,
+                });
+                out.push(data.len() as u8);
+                out.extend_from_slice(data);
+            }
+            Self::BarcodeStyle {
+                module_width,
+                hri,
+                hri_font,
+            } => {
+                out.extend_from_slice(BARCODE_WIDTH);
+                out.push(*module_width);
+                out.extend_from_slice(HRI_POSITION);
+                out.push(match hri {
+                    HriPosition::None => 0,
+                    HriPosition::Above => 1,
+                    HriPosition::Below => 2,
+                    HriPosition::Both => 3,
+                });
+                out.extend_from_slice(HRI_FONT);
+                out.push(match hri_font {
+                    HriFont::A => 0,
+                    HriFont::B => 1,
+                });
+            }
+            Self::QrCode {
+                data,
+                module_size,
+                error_correction,
+            } => {
+                let data = &data[..data.len().min(QR_MAX_BYTES)];
+                out.extend_from_slice(QR_MODEL_2);
+                out.extend_from_slice(QR_MODULE_SIZE);
+                out.push(*module_size);
+                out.extend_from_slice(QR_ERROR_CORRECTION);
+                out.push(match error_correction {
+                    QrErrorCorrection::Low => 48,
+                    QrErrorCorrection::Medium => 49,
+                    QrErrorCorrection::Quartile => 50,
+                    QrErrorCorrection::High => 51,
+                });
+                // the length counts the 3 bytes of the function before the data
+                out.extend_from_slice(QR_STORE);
+                out.extend_from_slice(&(data.len() as u16 + 3).to_le_bytes());
+                out.extend_from_slice(&[0x31, 0x50, 0x30]);
+                out.extend_from_slice(data);
+                out.extend_from_slice(QR_PRINT);
+            }
+            Self::CutPaper(cut_type) => out.extend_from_slice(match cut_type {
+                CutType::TotalCut => TOTAL_CUT,
+                CutType::PartialCut => PARTIAL_CUT,
+            }),
+            Self::FeedAndCutPaper { cut_type, amount } => {
+                out.extend_from_slice(FEED_AND_CUT);
+                out.extend_from_slice(&[
+                    match cut_type {
+                        CutType::TotalCut => 0x41,
+                        CutType::PartialCut => 0x42,
+                    },
+                    *amount,
+                ]);
+            }
+            Self::Raw(bytes) => out.extend_from_slice(bytes),
+        }
+    }
+
+    /// Length of the bytes the command is encoded to.
+    pub(crate) fn encoded_len(&self) -> usize {
+        match self {
+            Self::BitImage { mode, width, data } => {
+                let k = match mode {
+                    BitImageMode::Dots8SingleDensity | BitImageMode::Dots8DoubleDensity => {
+                        *width as usize
+                    }
+                    BitImageMode::Dots24SingleDensity | BitImageMode::Dots24DoubleDensity => {
+                        *width as usize * 3
+                    }
+                };
+                if k == 0 {
+                    return 0;
+                }
+
+                data.len() / k * (BIT_IMAGE.len() + 3 + k)
+            }
+            Self::Barcode { data, .. } => BARCODE.len() + 2 + data.len().min(u8::MAX as usize),
+            Self::QrCode { data, .. } => {
+                QR_MODEL_2.len()
+                    + QR_MODULE_SIZE.len()
+                    + 1
+                    + QR_ERROR_CORRECTION.len()
+                    + 1
+                    + QR_STORE.len()
+                    + 5
+                    + data.len().min(QR_MAX_BYTES)
+                    + QR_PRINT.len()
+            }
+            Self::Raw(bytes) => bytes.len(),
+            Self::Initialize => INITIALIZE.len(),
+            Self::Print => PRINT.len(),
+            Self::PrintAndFeed { unit, .. } => {
+                1 + match unit {
+                    FeedUnit::Inches => PRINT_FEED_INCHES.len(),
+                    FeedUnit::Lines => PRINT_FEED_LINES.len(),
+                }
+            }
+            Self::PrintAndReverseFeed { unit, .. } => {
+                1 + match unit {
+                    FeedUnit::Inches => PRINT_REVERSE_FEED_INCHES.len(),
+                    FeedUnit::Lines => PRINT_REVERSE_FEED_LINES.len(),
+                }
+            }
+            Self::MotionUnits { .. } => MOTION_UNITS.len() + 2,
+            Self::PaperSaving(_) => PAPER_SAVING.len() + 1,
+            Self::Speed(_) => SPEED_QUALITY.len() + 1,
+            Self::Density(_) => DENSITY.len() + 1,
+            Self::CodePage(_) => CODE_PAGE.len() + 1,
+            Self::Bold(_) => BOLD.len() + 1,
+            Self::Underline(_) => UNDERLINE.len() + 1,
+            Self::CharacterSize { .. } => CHARACTER_SIZE.len() + 1,
+            Self::Justification(_) => JUSTIFICATION.len() + 1,
+            Self::PrintArea { .. } => LEFT_MARGIN.len() + PRINT_AREA_WIDTH.len() + 4,
+            Self::FeedToMark => FEED_TO_MARK.len(),
+            Self::MarkOffset { .. } => MARK_OFFSET.len() + 4,
+            Self::OpenDrawer { .. } => DRAWER_PULSE.len() + 3,
+            Self::Beep { .. } => BUZZER.len() + 2,
+            Self::FeedButton(_) => PANEL_BUTTONS.len() + 1,
+            Self::Sleep => SLEEP.len(),
+            Self::SleepTimeout(_) => SLEEP_TIMEOUT.len() + 1,
+            Self::PresenterLoop(_) | Self::PresentTicket(_) | Self::RetractOnTimeout(_) => {
+                PRESENTER.len() + 2
+            }
+            Self::EjectTicket | Self::RetractTicket => PRESENTER.len() + 1,
+            Self::StopOnNearEnd(_) => NEAR_END_STOP.len() + 1,
+            Self::NearEndSensitivity(_) => NEAR_END_SENSITIVITY.len() + 1,
+            Self::EnterPageMode => PAGE_MODE.len(),
+            Self::ExitPageMode => STANDARD_MODE.len(),
+            Self::PrintPage => PRINT_PAGE.len(),
+            Self::PrintPageAndExit => PRINT_PAGE_AND_EXIT.len(),
+            Self::CancelPageData => CANCEL.len(),
+            Self::PageArea { .. } => PAGE_AREA.len() + 8,
+            Self::PageDirection(_) => PAGE_DIRECTION.len() + 1,
+            Self::Position { .. } => ABSOLUTE_X.len() + ABSOLUTE_Y.len() + 4,
+            Self::BarcodeStyle { .. } => {
+                BARCODE_WIDTH.len() + HRI_POSITION.len() + HRI_FONT.len() + 3
+            }
+            Self::CutPaper(cut_type) => match cut_type {
+                CutType::TotalCut => TOTAL_CUT.len(),
+                CutType::PartialCut => PARTIAL_CUT.len(),
+            },
+            Self::FeedAndCutPaper { .. } => FEED_AND_CUT.len() + 2,
+        }
+    }
+
+    /// Append the bytes of the bit image of the command to `out` as raster images, printed at once instead of by
+    /// the following [`Command::Print`], and return whether the command is a bit image of the 24 dot modes.
+    ///
+    /// The dots of the 8 dot modes are printed taller than those of the raster images, so they're kept. The single
+    /// density mode is printed in double width, and each line is padded to whole bytes.
+    pub(crate) fn encode_raster(&self, out: &mut Vec<u8>) -> bool {
+        let Self::BitImage { mode, width, data } = self else {
+            return false;
+        };
+        let (bank, m) = match mode {
+            BitImageMode::Dots8SingleDensity | BitImageMode::Dots8DoubleDensity => return false,
+            BitImageMode::Dots24SingleDensity => (24, 0x01),
+            BitImageMode::Dots24DoubleDensity => (24, 0x00),
+        };
+        let width = *width as usize;
+        let k = width * bank / 8;
+        if k == 0 {
+            return true;
+        }
+
+        // number of bytes in a line of the raster images
+        let stride = width.div_ceil(8);
+        let banks = data.len() / k;
+        for block in data[..banks * k].chunks(RASTER_MAX_LINES / bank * k) {
+            let lines = block.len() / k * bank;
+            out.extend_from_slice(RASTER_IMAGE);
+            out.push(m);
+            out.extend_from_slice(&(stride as u16).to_le_bytes());
+            out.extend_from_slice(&(lines as u16).to_le_bytes());
+
+            let start = out.len();
+            out.resize(start + stride * lines, 0);
+            let raster = &mut out[start..];
+            for (i, bytes) in block.chunks_exact(k).enumerate() {
+                for (x, column) in bytes.chunks_exact(bank / 8).enumerate() {
+                    for y in (0..bank).filter(|y| column[y / 8] & (0x80 >> (y % 8)) != 0) {
+                        raster[(i * bank + y) * stride + x / 8] |= 0x80 >> (x % 8);
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    fn encode_presenter(out: &mut Vec<u8>, function: u8, params: &[u8]) {
+        out.extend_from_slice(PRESENTER);
+        out.push(function);
+        out.extend_from_slice(params);
+    }
+}
+
+/// Check that no data is left in the print buffer at the end of `commands`, e.g. a bit image not followed by
+/// [`CommandBuilder::print()`](crate::CommandBuilder::print()), which would be stuck in the printer until the
+/// next job prints it.
+pub(crate) fn check_printed(commands: &[Command]) -> Result<(), Error> {
+    let mut unprinted = None;
+    for (i, command) in commands.iter().enumerate() {
+        match command {
+            Command::BitImage { .. } => {
+                unprinted.get_or_insert(i);
+            }
+            Command::Initialize
+            | Command::Print
+            | Command::PrintAndFeed { .. }
+            | Command::PrintAndReverseFeed { .. }
+            | Command::FeedToMark
+            | Command::PrintPage
+            | Command::PrintPageAndExit
+            | Command::ExitPageMode => unprinted = None,
+            _ => {}
+        }
+    }
+
+    match unprinted {
+        Some(index) => Err(Error::UnprintedData { index }),
+        None => Ok(()),
+    }
+}
+
+/// Check that every command of `commands` is supported by the printer of `profile`, see [`Profile::supports()`].
+#[cfg(feature = "std")]
+pub(crate) fn check_supported<'a>(
+    commands: impl IntoIterator<Item = &'a Command>,
+    profile: &Profile,
+) -> Result<(), Error> {
+    match commands
+        .into_iter()
+        .find(|command| !profile.supports(command))
+    {
+        Some(command) => Err(Error::Unsupported {
+            command: command.kind(),
+            printer: profile.name.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check that the parameters of every command of `commands` are within the limits of the printer of `profile`, see
+/// [`LimitPolicy`](crate::LimitPolicy).
+#[cfg(feature = "std")]
+pub(crate) fn check_limits<'a>(
+    commands: impl IntoIterator<Item = &'a Command>,
+    profile: &Profile,
+) -> Result<(), Error> {
+    match commands
+        .into_iter()
+        .find_map(|command| profile.exceeded_limit(command))
+    {
+        Some(reason) => Err(Error::InvalidParameter(reason)),
+        None => Ok(()),
+    }
+}
+
+/// Encode `commands` to bytes one after the other.
+pub(crate) fn encode(commands: &[Command]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(commands.iter().map(Command::encoded_len).sum());
+    for command in commands {
+        command.encode(&mut out);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_encoded_len() {
+        // every command, the lengths being worked out from the fields
+        let commands = [
+            Command::Initialize,
+            Command::Print,
+            Command::PrintAndFeed {
+                unit: FeedUnit::Inches,
+                amount: 1,
+            },
+            Command::PrintAndReverseFeed {
+                unit: FeedUnit::Lines,
+                amount: 1,
+            },
+            Command::MotionUnits { x: 1, y: 2 },
+            Command::PaperSaving(PaperSaving::default()),
+            Command::Speed(Speed::High),
+            Command::Density(Density::Zero),
+            Command::CodePage(1),
+            Command::Bold(true),
+            Command::Underline(true),
+            Command::CharacterSize {
+                width: 2,
+                height: 2,
+            },
+            Command::Justification(Justification::Center),
+            Command::PrintArea { left: 1, width: 2 },
+            Command::FeedToMark,
+            Command::MarkOffset {
+                position: MarkPosition::Cut,
+                offset: -1,
+            },
+            Command::OpenDrawer {
+                pin: DrawerPin::Pin2,
+                on_ms: 100,
+                off_ms: 100,
+            },
+            Command::Beep {
+                count: 1,
+                duration_ms: 100,
+            },
+            Command::FeedButton(false),
+            Command::Sleep,
+            Command::SleepTimeout(Some(1)),
+            Command::PresenterLoop(1),
+            Command::PresentTicket(1),
+            Command::EjectTicket,
+            Command::RetractTicket,
+            Command::RetractOnTimeout(None),
+            Command::StopOnNearEnd(true),
+            Command::NearEndSensitivity(NearEndSensitivity::Low),
+            Command::EnterPageMode,
+            Command::ExitPageMode,
+            Command::PrintPage,
+            Command::PrintPageAndExit,
+            Command::CancelPageData,
+            Command::PageArea {
+                x: 1,
+                y: 2,
+                width: 3,
+                height: 4,
+            },
+            Command::PageDirection(PageDirection::TopToBottom),
+            Command::Position { x: 1, y: 2 },
+            Command::BitImage {
+                mode: BitImageMode::Dots24DoubleDensity,
+                width: 2,
+                data: vec![0xFF; 12],
+            },
+            Command::Barcode {
+                system: BarcodeSystem::Code128,
+                data: vec![0x41; 300],
+            },
+            Command::BarcodeStyle {
+                module_width: 2,
+                hri: HriPosition::Below,
+                hri_font: HriFont::A,
+            },
+            Command::QrCode {
+                data: vec![0x41; 10],
+                module_size: 6,
+                error_correction: QrErrorCorrection::Medium,
+            },
+            Command::CutPaper(CutType::PartialCut),
+            Command::FeedAndCutPaper {
+                cut_type: CutType::TotalCut,
+                amount: 0,
+            },
+            Command::Raw(vec![0x00; 3]),
+        ];
+        for command in commands {
+            let mut out = Vec::new();
+            command.encode(&mut out);
+            assert_eq!(command.encoded_len(), out.len(), "{command:?}");
+        }
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(Command::Initialize.kind(), "initialize");
+        assert_eq!(Command::EnterPageMode.kind(), "enter_page_mode");
+        assert_eq!(Command::Density(Density::Zero).kind(), "density");
+        assert_eq!(
+            Command::FeedAndCutPaper {
+                cut_type: CutType::TotalCut,
+                amount: 0
+            }
+            .kind(),
+            "feed_and_cut_paper"
+        );
+
+        assert_eq!(Command::Print.payload_len(), 0);
+        assert_eq!(Command::Raw(vec![0x00; 5]).payload_len(), 5);
+        assert_eq!(
+            Command::BitImage {
+                mode: BitImageMode::Dots8SingleDensity,
+                width: 8,
+                data: vec![0xFF; 16]
+            }
+            .payload_len(),
+            16
+        );
+    }
+
+    #[test]
+    fn test_encode_bit_image() {
+        let command = Command::BitImage {
+            mode: BitImageMode::Dots8DoubleDensity,
+            width: 2,
+            data: vec![0x01, 0x02, 0x03, 0x04],
+        };
+        assert_eq!(
+            encode(&[command]),
+            &[0x1B, 0x2A, 0x01, 2, 0, 0x01, 0x02, 0x1B, 0x2A, 0x01, 2, 0, 0x03, 0x04]
+        );
+
+        // an image without any column has nothing to print
+        let empty = Command::BitImage {
+            mode: BitImageMode::Dots24DoubleDensity,
+            width: 0,
+            data: vec![0xFF; 3],
+        };
+        assert_eq!(empty.encoded_len(), 0);
+        assert_eq!(encode(&[empty]), [0; 0]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_check_printed() {
+        let bit_image = Command::BitImage {
+            mode: BitImageMode::Dots8SingleDensity,
+            width: 1,
+            data: vec![0xFF],
+        };
+        assert!(check_printed(&[]).is_ok());
+        assert!(check_printed(&[bit_image.clone(), Command::Print]).is_ok());
+        assert!(matches!(
+            check_printed(&[
+                Command::Print,
+                bit_image.clone(),
+                bit_image,
+                Command::CutPaper(CutType::TotalCut)
+            ]),
+            Err(Error::UnprintedData { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            encode(&[
+                Command::Initialize,
+                Command::Print,
+                Command::CutPaper(CutType::TotalCut),
+                Command::Speed(Speed::Low)
+            ]),
+            &[0x1B, 0x40, 0x0A, 0x1B, 0x69, 0x1B, 0x78, 2]
+        );
+    }
+}
@@ -0,0 +1,417 @@
+//! Compact binary representation of the commands in job files.
+
+use alloc::{format, string::String, vec::Vec};
+
+use super::Command;
+use crate::{
+    BarcodeSystem, BitImageMode, CutType, Density, DrawerPin, FeedUnit, HriFont, HriPosition,
+    Justification, MarkPosition, NearEndSensitivity, PageDirection, PaperSaving, QrErrorCorrection,
+    Speed,
+};
+
+/// A parameter of a command, written in little-endian order.
+trait Field: Sized {
+    fn put(&self, out: &mut Vec<u8>);
+    fn take(rest: &mut &[u8]) -> Option<Self>;
+}
+
+/// Take `len` bytes from the start of `rest`.
+fn take_bytes<'a>(rest: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    let bytes = rest.get(..len)?;
+    *rest = &rest[len..];
+
+    Some(bytes)
+}
+
+impl Field for u8 {
+    fn put(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+
+    fn take(rest: &mut &[u8]) -> Option<Self> {
+        take_bytes(rest, 1).map(|bytes| bytes[0])
+    }
+}
+
+impl Field for u16 {
+    fn put(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn take(rest: &mut &[u8]) -> Option<Self> {
+        take_bytes(rest, 2).map(|bytes| Self::from_le_bytes([bytes[0], bytes[1]]))
+    }
+}
+
+impl Field for i16 {
+    fn put(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn take(rest: &mut &[u8]) -> Option<Self> {
+        u16::take(rest).map(|word| word as Self)
+    }
+}
+
+impl Field for bool {
+    fn put(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn take(rest: &mut &[u8]) -> Option<Self> {
+        match u8::take(rest)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// An optional byte, prefixed by whether it's present.
+impl Field for Option<u8> {
+    fn put(&self, out: &mut Vec<u8>) {
+        self.is_some().put(out);
+        if let Some(value) = self {
+            value.put(out);
+        }
+    }
+
+    fn take(rest: &mut &[u8]) -> Option<Self> {
+        match bool::take(rest)? {
+            false => Some(None),
+            true => u8::take(rest).map(Some),
+        }
+    }
+}
+
+/// Bytes prefixed by their 32-bit length.
+impl Field for Vec<u8> {
+    fn put(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(self.len() as u32).to_le_bytes());
+        out.extend_from_slice(self);
+    }
+
+    fn take(rest: &mut &[u8]) -> Option<Self> {
+        let len = take_bytes(rest, 4)?;
+        let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+
+        take_bytes(rest, len).map(<[u8]>::to_vec)
+    }
+}
+
+impl Field for PaperSaving {
+    fn put(&self, out: &mut Vec<u8>) {
+        self.bits().put(out);
+    }
+
+    fn take(rest: &mut &[u8]) -> Option<Self> {
+        u8::take(rest).map(Self::from_bits)
+    }
+}
+
+/// Option types written as the index of their variant, in the order of declaration.
+macro_rules! indexed {
+    ($ty:ty { $($variant:ident),* $(,)? }) => {
+        impl Field for $ty {
+            fn put(&self, out: &mut Vec<u8>) {
+                let index = [$(Self::$variant),*].iter().position(|variant| variant == self);
+                out.push(index.unwrap_or_default() as u8);
+            }
+
+            fn take(rest: &mut &[u8]) -> Option<Self> {
+                [$(Self::$variant),*].get(u8::take(rest)? as usize).copied()
+            }
+        }
+    };
+}
+
+indexed!(BitImageMode {
+    Dots8SingleDensity,
+    Dots8DoubleDensity,
+    Dots24SingleDensity,
+    Dots24DoubleDensity,
+});
+indexed!(BarcodeSystem {
+    UpcA,
+    UpcE,
+    Ean13,
+    Ean8,
+    Code39,
+    Itf,
+    Codabar,
+    Code93,
+    Code128,
+});
+indexed!(QrErrorCorrection {
+    Low,
+    Medium,
+    Quartile,
+    High
+});
+indexed!(Justification {
+    Left,
+    Center,
+    Right
+});
+indexed!(HriPosition {
+    None,
+    Above,
+    Below,
+    Both
+});
+indexed!(HriFont { A, B });
+indexed!(CutType {
+    TotalCut,
+    PartialCut
+});
+indexed!(FeedUnit { Inches, Lines });
+indexed!(MarkPosition { PrintStart, Cut });
+indexed!(PageDirection {
+    LeftToRight,
+    BottomToTop,
+    RightToLeft,
+    TopToBottom,
+});
+indexed!(Speed { High, Normal, Low });
+indexed!(Density {
+    Minus50,
+    Minus25,
+    Zero,
+    Plus25,
+    Plus50
+});
+indexed!(DrawerPin { Pin2, Pin5 });
+indexed!(NearEndSensitivity { Low, Medium, High });
+
+impl Command {
+    /// Write the command to `out` as a tag of its kind followed by its parameters, as saved in job files.
+    pub(crate) fn to_frame(&self, out: &mut Vec<u8>) {
+        macro_rules! put {
+            ($tag:literal $(, $field:expr)*) => {{
+                out.push($tag);
+                $($field.put(out);)*
+            }};
+        }
+
+        match self {
+            Self::Initialize => put!(0),
+            Self::Print => put!(1),
+            Self::PrintAndFeed { unit, amount } => put!(2, unit, amount),
+            Self::PrintAndReverseFeed { unit, amount } => put!(3, unit, amount),
+            Self::MotionUnits { x, y } => put!(4, x, y),
+            Self::PaperSaving(saving) => put!(5, saving),
+            Self::Speed(speed) => put!(6, speed),
+            Self::Density(density) => put!(7, density),
+            Self::CodePage(page) => put!(8, page),
+            Self::Bold(enabled) => put!(9, enabled),
+            Self::Underline(enabled) => put!(10, enabled),
+            Self::CharacterSize { width, height } => put!(11, width, height),
+            Self::Justification(justification) => put!(12, justification),
+            Self::PrintArea { left, width } => put!(13, left, width),
+            Self::FeedToMark => put!(14),
+            Self::MarkOffset { position, offset } => put!(15, position, offset),
+            Self::OpenDrawer { pin, on_ms, off_ms } => put!(16, pin, on_ms, off_ms),
+            Self::Beep { count, duration_ms } => put!(17, count, duration_ms),
+            Self::FeedButton(enabled) => put!(18, enabled),
+            Self::Sleep => put!(19),
+            Self::SleepTimeout(minutes) => put!(20, minutes),
+            Self::PresenterLoop(length) => put!(21, length),
+            Self::PresentTicket(length) => put!(22, length),
+            Self::EjectTicket => put!(23),
+            Self::RetractTicket => put!(24),
+            Self::RetractOnTimeout(seconds) => put!(25, seconds),
+            Self::StopOnNearEnd(enabled) => put!(26, enabled),
+            Self::NearEndSensitivity(sensitivity) => put!(27, sensitivity),
+            Self::EnterPageMode => put!(28),
+            Self::ExitPageMode => put!(29),
+            Self::PrintPage => put!(30),
+            Self::PrintPageAndExit => put!(31),
+            Self::CancelPageData => put!(32),
+            Self::PageArea {
+                x,
+                y,
+                width,
+                height,
+            } => put!(33, x, y, width, height),
+            Self::PageDirection(direction) => put!(34, direction),
+            Self::Position { x, y } => put!(35, x, y),
+            Self::BitImage { mode, width, data } => put!(36, mode, width, data),
+            Self::Barcode { system, data } => put!(37, system, data),
+            Self::BarcodeStyle {
+                module_width,
+                hri,
+                hri_font,
+            } => put!(38, module_width, hri, hri_font),
+            Self::QrCode {
+                data,
+                module_size,
+                error_correction,
+            } => put!(39, data, module_size, error_correction),
+            Self::CutPaper(cut_type) => put!(40, cut_type),
+            Self::FeedAndCutPaper { cut_type, amount } => put!(41, cut_type, amount),
+            Self::Raw(bytes) => put!(42, bytes),
+        }
+    }
+
+    /// Read a command written by [`to_frame()`](Command::to_frame()), describing why `frame` isn't one.
+    pub(crate) fn from_frame(frame: &[u8]) -> Result<Self, String> {
+        let (&tag, mut rest) = frame
+            .split_first()
+            .ok_or_else(|| String::from("empty command"))?;
+        let command = Self::take_fields(tag, &mut rest).filter(|_| rest.is_empty());
+
+        command.ok_or_else(|| format!("invalid command of tag {tag}"))
+    }
+
+    fn take_fields(tag: u8, rest: &mut &[u8]) -> Option<Self> {
+        fn get<T: Field>(rest: &mut &[u8]) -> Option<T> {
+            T::take(rest)
+        }
+
+        Some(match tag {
+            0 => Self::Initialize,
+            1 => Self::Print,
+            2 => Self::PrintAndFeed {
+                unit: get(rest)?,
+                amount: get(rest)?,
+            },
+            3 => Self::PrintAndReverseFeed {
+                unit: get(rest)?,
+                amount: get(rest)?,
+            },
+            4 => Self::MotionUnits {
+                x: get(rest)?,
+                y: get(rest)?,
+            },
+            5 => Self::PaperSaving(get(rest)?),
+            6 => Self::Speed(get(rest)?),
+            7 => Self::Density(get(rest)?),
+            8 => Self::CodePage(get(rest)?),
+            9 => Self::Bold(get(rest)?),
+            10 => Self::Underline(get(rest)?),
+            11 => Self::CharacterSize {
+                width: get(rest)?,
+                height: get(rest)?,
+            },
+            12 => Self::Justification(get(rest)?),
+            13 => Self::PrintArea {
+                left: get(rest)?,
+                width: get(rest)?,
+            },
+            14 => Self::FeedToMark,
+            15 => Self::MarkOffset {
+                position: get(rest)?,
+                offset: get(rest)?,
+            },
+            16 => Self::OpenDrawer {
+                pin: get(rest)?,
+                on_ms: get(rest)?,
+                off_ms: get(rest)?,
+            },
+            17 => Self::Beep {
+                count: get(rest)?,
+                duration_ms: get(rest)?,
+            },
+            18 => Self::FeedButton(get(rest)?),
+            19 => Self::Sleep,
+            20 => Self::SleepTimeout(get(rest)?),
+            21 => Self::PresenterLoop(get(rest)?),
+            22 => Self::PresentTicket(get(rest)?),
+            23 => Self::EjectTicket,
+            24 => Self::RetractTicket,
+            25 => Self::RetractOnTimeout(get(rest)?),
+            26 => Self::StopOnNearEnd(get(rest)?),
+            27 => Self::NearEndSensitivity(get(rest)?),
+            28 => Self::EnterPageMode,
+            29 => Self::ExitPageMode,
+            30 => Self::PrintPage,
+            31 => Self::PrintPageAndExit,
+            32 => Self::CancelPageData,
+            33 => Self::PageArea {
+                x: get(rest)?,
+                y: get(rest)?,
+                width: get(rest)?,
+                height: get(rest)?,
+            },
+            34 => Self::PageDirection(get(rest)?),
+            35 => Self::Position {
+                x: get(rest)?,
+                y: get(rest)?,
+            },
+            36 => Self::BitImage {
+                mode: get(rest)?,
+                width: get(rest)?,
+                data: get(rest)?,
+            },
+            37 => Self::Barcode {
+                system: get(rest)?,
+                data: get(rest)?,
+            },
+            38 => Self::BarcodeStyle {
+                module_width: get(rest)?,
+                hri: get(rest)?,
+                hri_font: get(rest)?,
+            },
+            39 => Self::QrCode {
+                data: get(rest)?,
+                module_size: get(rest)?,
+                error_correction: get(rest)?,
+            },
+            40 => Self::CutPaper(get(rest)?),
+            41 => Self::FeedAndCutPaper {
+                cut_type: get(rest)?,
+                amount: get(rest)?,
+            },
+            42 => Self::Raw(get(rest)?),
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_frame() {
+        let mut out = Vec::new();
+        Command::PrintAndFeed {
+            unit: FeedUnit::Lines,
+            amount: 3,
+        }
+        .to_frame(&mut out);
+        assert_eq!(out, [2, 1, 3]);
+
+        out.clear();
+        Command::MarkOffset {
+            position: MarkPosition::Cut,
+            offset: -2,
+        }
+        .to_frame(&mut out);
+        assert_eq!(out, [15, 1, 0xFE, 0xFF]);
+        assert_eq!(
+            Command::from_frame(&out),
+            Ok(Command::MarkOffset {
+                position: MarkPosition::Cut,
+                offset: -2,
+            })
+        );
+
+        out.clear();
+        Command::Raw(vec![0x1B]).to_frame(&mut out);
+        assert_eq!(out, [42, 1, 0, 0, 0, 0x1B]);
+
+        for frame in [
+            &[][..],
+            &[43],
+            &[2, 2, 3],
+            &[2, 1],
+            &[1, 0],
+            &[42, 2, 0, 0, 0, 0x1B],
+        ] {
+            assert!(Command::from_frame(frame).is_err(), "{frame:?}");
+        }
+    }
+}
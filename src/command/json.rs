@@ -0,0 +1,530 @@
+//! JSON representation of the commands.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use super::Command;
+use crate::{
+    builder,
+    json::{self, Value},
+    BarcodeSystem, BitImageMode, CutType, Density, DrawerPin, FeedUnit, HriFont, HriPosition,
+    Justification, MarkPosition, NearEndSensitivity, PageDirection, PaperSaving, QrErrorCorrection,
+    Speed,
+};
+
+/// Option types represented by their names in JSON.
+pub(crate) trait Named: Sized {
+    fn name(&self) -> &'static str;
+    fn from_name(name: &str) -> Option<Self>;
+}
+
+macro_rules! named {
+    ($ty:ty { $($variant:ident => $name:literal),* $(,)? }) => {
+        impl Named for $ty {
+            fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+pub(crate) use named;
+
+named!(BitImageMode {
+    Dots8SingleDensity => "dots8_single_density",
+    Dots8DoubleDensity => "dots8_double_density",
+    Dots24SingleDensity => "dots24_single_density",
+    Dots24DoubleDensity => "dots24_double_density",
+});
+named!(BarcodeSystem {
+    UpcA => "upc_a",
+    UpcE => "upc_e",
+    Ean13 => "ean13",
+    Ean8 => "ean8",
+    Code39 => "code39",
+    Itf => "itf",
+    Codabar => "codabar",
+    Code93 => "code93",
+    Code128 => "code128",
+});
+named!(QrErrorCorrection {
+    Low => "low",
+    Medium => "medium",
+    Quartile => "quartile",
+    High => "high",
+});
+named!(Justification { Left => "left", Center => "center", Right => "right" });
+named!(HriPosition {
+    None => "none",
+    Above => "above",
+    Below => "below",
+    Both => "both",
+});
+named!(HriFont { A => "a", B => "b" });
+named!(CutType { TotalCut => "total", PartialCut => "partial" });
+named!(FeedUnit { Inches => "inches", Lines => "lines" });
+named!(MarkPosition { PrintStart => "print_start", Cut => "cut" });
+named!(PageDirection {
+    LeftToRight => "left_to_right",
+    BottomToTop => "bottom_to_top",
+    RightToLeft => "right_to_left",
+    TopToBottom => "top_to_bottom",
+});
+named!(Speed { High => "high", Normal => "normal", Low => "low" });
+named!(Density {
+    Minus50 => "minus50",
+    Minus25 => "minus25",
+    Zero => "zero",
+    Plus25 => "plus25",
+    Plus50 => "plus50",
+});
+named!(DrawerPin { Pin2 => "pin2", Pin5 => "pin5" });
+named!(NearEndSensitivity { Low => "low", Medium => "medium", High => "high" });
+
+/// An object of `command` with its `fields`.
+fn tagged<const N: usize>(command: &str, fields: [(&str, Value); N]) -> Value {
+    let mut members = vec![("command".to_string(), Value::from(command))];
+    members.extend(
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value)),
+    );
+
+    Value::Object(members)
+}
+
+/// Accessor of the fields of an object, describing the missing or invalid ones.
+pub(crate) struct Fields<'a>(pub(crate) &'a Value);
+
+impl Fields<'_> {
+    pub(crate) fn get(&self, key: &str) -> Result<&Value, String> {
+        self.0
+            .get(key)
+            .ok_or_else(|| format!("missing field {key}"))
+    }
+
+    pub(crate) fn invalid(key: &str) -> String {
+        format!("invalid field {key}")
+    }
+
+    pub(crate) fn int<T: TryFrom<i64>>(&self, key: &str) -> Result<T, String> {
+        self.get(key)?
+            .as_i64()
+            .and_then(|value| T::try_from(value).ok())
+            .ok_or_else(|| Self::invalid(key))
+    }
+
+    pub(crate) fn optional_int<T: TryFrom<i64>>(&self, key: &str) -> Result<Option<T>, String> {
+        match self.get(key)? {
+            Value::Null => Ok(None),
+            _ => self.int(key).map(Some),
+        }
+    }
+
+    pub(crate) fn bool(&self, key: &str) -> Result<bool, String> {
+        self.get(key)?.as_bool().ok_or_else(|| Self::invalid(key))
+    }
+
+    pub(crate) fn str(&self, key: &str) -> Result<&str, String> {
+        self.get(key)?.as_str().ok_or_else(|| Self::invalid(key))
+    }
+
+    pub(crate) fn named<T: Named>(&self, key: &str) -> Result<T, String> {
+        T::from_name(self.str(key)?).ok_or_else(|| Self::invalid(key))
+    }
+
+    pub(crate) fn hex(&self, key: &str) -> Result<Vec<u8>, String> {
+        json::from_hex(self.str(key)?).ok_or_else(|| Self::invalid(key))
+    }
+}
+
+impl Command {
+    /// The JSON object of the command, tagged by its name in `command`.
+    pub(crate) fn to_json(&self) -> Value {
+        match self {
+            Self::Initialize => tagged(self.kind(), []),
+            Self::Print => tagged(self.kind(), []),
+            Self::PrintAndFeed { unit, amount } => tagged(
+                self.kind(),
+                [("unit", unit.name().into()), ("amount", (*amount).into())],
+            ),
+            Self::PrintAndReverseFeed { unit, amount } => tagged(
+                self.kind(),
+                [("unit", unit.name().into()), ("amount", (*amount).into())],
+            ),
+            Self::MotionUnits { x, y } => {
+                tagged(self.kind(), [("x", (*x).into()), ("y", (*y).into())])
+            }
+            Self::PaperSaving(saving) => tagged(
+                self.kind(),
+                [
+                    ("top_margin", saving.top_margin.into()),
+                    ("bottom_margin", saving.bottom_margin.into()),
+                    ("line_spacing", saving.line_spacing.into()),
+                    ("blank_lines", saving.blank_lines.into()),
+                    ("barcode_height", saving.barcode_height.into()),
+                ],
+            ),
+            Self::Speed(speed) => tagged(self.kind(), [("speed", speed.name().into())]),
+            Self::Density(density) => tagged(self.kind(), [("density", density.name().into())]),
+            Self::CodePage(page) => tagged(self.kind(), [("page", (*page).into())]),
+            Self::Bold(enabled) => tagged(self.kind(), [("enabled", (*enabled).into())]),
+            Self::Underline(enabled) => tagged(self.kind(), [("enabled", (*enabled).into())]),
+            Self::CharacterSize { width, height } => tagged(
+                self.kind(),
+                [("width", (*width).into()), ("height", (*height).into())],
+            ),
+            Self::Justification(justification) => tagged(
+                self.kind(),
+                [("justification", justification.name().into())],
+            ),
+            Self::PrintArea { left, width } => tagged(
+                self.kind(),
+                [("left", (*left).into()), ("width", (*width).into())],
+            ),
+            Self::FeedToMark => tagged(self.kind(), []),
+            Self::MarkOffset { position, offset } => tagged(
+                self.kind(),
+                [
+                    ("position", position.name().into()),
+                    ("offset", (*offset).into()),
+                ],
+            ),
+            Self::OpenDrawer { pin, on_ms, off_ms } => tagged(
+                self.kind(),
+                [
+                    ("pin", pin.name().into()),
+                    ("on_ms", (*on_ms).into()),
+                    ("off_ms", (*off_ms).into()),
+                ],
+            ),
+            Self::Beep { count, duration_ms } => tagged(
+                self.kind(),
+                [
+                    ("count", (*count).into()),
+                    ("duration_ms", (*duration_ms).into()),
+                ],
+            ),
+            Self::FeedButton(enabled) => tagged(self.kind(), [("enabled", (*enabled).into())]),
+            Self::Sleep => tagged(self.kind(), []),
+            Self::SleepTimeout(minutes) => tagged(self.kind(), [("minutes", (*minutes).into())]),
+            Self::PresenterLoop(length_mm) => {
+                tagged(self.kind(), [("length_mm", (*length_mm).into())])
+            }
+            Self::PresentTicket(length_mm) => {
+                tagged(self.kind(), [("length_mm", (*length_mm).into())])
+            }
+            Self::EjectTicket => tagged(self.kind(), []),
+            Self::RetractTicket => tagged(self.kind(), []),
+            Self::RetractOnTimeout(timeout_s) => {
+                tagged(self.kind(), [("timeout_s", (*timeout_s).into())])
+            }
+            Self::StopOnNearEnd(enabled) => tagged(self.kind(), [("enabled", (*enabled).into())]),
+            Self::NearEndSensitivity(sensitivity) => {
+                tagged(self.kind(), [("sensitivity", sensitivity.name().into())])
+            }
+            Self::EnterPageMode => tagged(self.kind(), []),
+            Self::ExitPageMode => tagged(self.kind(), []),
+            Self::PrintPage => tagged(self.kind(), []),
+            Self::PrintPageAndExit => tagged(self.kind(), []),
+            Self::CancelPageData => tagged(self.kind(), []),
+            Self::PageArea {
+                x,
+                y,
+                width,
+                height,
+            } => tagged(
+                self.kind(),
+                [
+                    ("x", (*x).into()),
+                    ("y", (*y).into()),
+                    ("width", (*width).into()),
+                    ("height", (*height).into()),
+                ],
+            ),
+            Self::PageDirection(direction) => {
+                tagged(self.kind(), [("direction", direction.name().into())])
+            }
+            Self::Position { x, y } => {
+                tagged(self.kind(), [("x", (*x).into()), ("y", (*y).into())])
+            }
+            Self::BitImage { mode, width, data } => tagged(
+                self.kind(),
+                [
+                    ("mode", mode.name().into()),
+                    ("width", (*width).into()),
+                    ("data", json::to_hex(data).into()),
+                ],
+            ),
+            Self::Barcode { system, data } => tagged(
+                self.kind(),
+                [
+                    ("system", system.name().into()),
+                    ("data", json::to_hex(data).into()),
+                ],
+            ),
+            Self::BarcodeStyle {
+                module_width,
+                hri,
+                hri_font,
+            } => tagged(
+                self.kind(),
+                [
+                    ("module_width", (*module_width).into()),
+                    ("hri", hri.name().into()),
+                    ("hri_font", hri_font.name().into()),
+                ],
+            ),
+            Self::QrCode {
+                data,
+                module_size,
+                error_correction,
+            } => tagged(
+                self.kind(),
+                [
+                    ("data", json::to_hex(data).into()),
+                    ("module_size", (*module_size).into()),
+                    ("error_correction", error_correction.name().into()),
+                ],
+            ),
+            Self::CutPaper(cut_type) => tagged(self.kind(), [("cut_type", cut_type.name().into())]),
+            Self::FeedAndCutPaper { cut_type, amount } => tagged(
+                self.kind(),
+                [
+                    ("cut_type", cut_type.name().into()),
+                    ("amount", (*amount).into()),
+                ],
+            ),
+            Self::Raw(bytes) => tagged(self.kind(), [("bytes", json::to_hex(bytes).into())]),
+        }
+    }
+
+    /// Parse the command from its JSON object, see [`to_json()`](Command::to_json()).
+    pub(crate) fn from_json(value: &Value) -> Result<Self, String> {
+        let fields = Fields(value);
+
+        Ok(match fields.str("command")? {
+            "initialize" => Self::Initialize,
+            "print" => Self::Print,
+            "print_and_feed" => Self::PrintAndFeed {
+                unit: fields.named("unit")?,
+                amount: fields.int("amount")?,
+            },
+            "print_and_reverse_feed" => Self::PrintAndReverseFeed {
+                unit: fields.named("unit")?,
+                amount: fields.int("amount")?,
+            },
+            "motion_units" => Self::MotionUnits {
+                x: fields.int("x")?,
+                y: fields.int("y")?,
+            },
+            "paper_saving" => Self::PaperSaving(PaperSaving {
+                top_margin: fields.bool("top_margin")?,
+                bottom_margin: fields.bool("bottom_margin")?,
+                line_spacing: fields.bool("line_spacing")?,
+                blank_lines: fields.bool("blank_lines")?,
+                barcode_height: fields.bool("barcode_height")?,
+            }),
+            "speed" => Self::Speed(fields.named("speed")?),
+            "density" => Self::Density(fields.named("density")?),
+            "code_page" => Self::CodePage(fields.int("page")?),
+            "bold" => Self::Bold(fields.bool("enabled")?),
+            "underline" => Self::Underline(fields.bool("enabled")?),
+            "character_size" => Self::CharacterSize {
+                width: fields.int("width")?,
+                height: fields.int("height")?,
+            },
+            "justification" => Self::Justification(fields.named("justification")?),
+            "print_area" => Self::PrintArea {
+                left: fields.int("left")?,
+                width: fields.int("width")?,
+            },
+            "feed_to_mark" => Self::FeedToMark,
+            "mark_offset" => Self::MarkOffset {
+                position: fields.named("position")?,
+                offset: fields.int("offset")?,
+            },
+            "open_drawer" => Self::OpenDrawer {
+                pin: fields.named("pin")?,
+                on_ms: fields.int("on_ms")?,
+                off_ms: fields.int("off_ms")?,
+            },
+            "beep" => Self::Beep {
+                count: fields.int("count")?,
+                duration_ms: fields.int("duration_ms")?,
+            },
+            "feed_button" => Self::FeedButton(fields.bool("enabled")?),
+            "sleep" => Self::Sleep,
+            "sleep_timeout" => Self::SleepTimeout(fields.optional_int("minutes")?),
+            "presenter_loop" => Self::PresenterLoop(fields.int("length_mm")?),
+            "present_ticket" => Self::PresentTicket(fields.int("length_mm")?),
+            "eject_ticket" => Self::EjectTicket,
+            "retract_ticket" => Self::RetractTicket,
+            "retract_on_timeout" => Self::RetractOnTimeout(fields.optional_int("timeout_s")?),
+            "stop_on_near_end" => Self::StopOnNearEnd(fields.bool("enabled")?),
+            "near_end_sensitivity" => Self::NearEndSensitivity(fields.named("sensitivity")?),
+            "enter_page_mode" => Self::EnterPageMode,
+            "exit_page_mode" => Self::ExitPageMode,
+            "print_page" => Self::PrintPage,
+            "print_page_and_exit" => Self::PrintPageAndExit,
+            "cancel_page_data" => Self::CancelPageData,
+            "page_area" => Self::PageArea {
+                x: fields.int("x")?,
+                y: fields.int("y")?,
+                width: fields.int("width")?,
+                height: fields.int("height")?,
+            },
+            "page_direction" => Self::PageDirection(fields.named("direction")?),
+            "position" => Self::Position {
+                x: fields.int("x")?,
+                y: fields.int("y")?,
+            },
+            "bit_image" => {
+                let (mode, width, data) = (
+                    fields.named("mode")?,
+                    fields.int::<u16>("width")?,
+                    fields.hex("data")?,
+                );
+                // whole banks of at least one line, as encoded by the printer
+                let bank = width as usize * (builder::bank_lines(mode) / 8);
+                if bank == 0 || data.is_empty() || !data.len().is_multiple_of(bank) {
+                    return Err(format!(
+                        "bit image of {} bytes doesn't fill its {width} columns",
+                        data.len()
+                    ));
+                }
+                Self::BitImage { mode, width, data }
+            }
+            "barcode" => Self::Barcode {
+                system: fields.named("system")?,
+                data: fields.hex("data")?,
+            },
+            "barcode_style" => Self::BarcodeStyle {
+                module_width: fields.int("module_width")?,
+                hri: fields.named("hri")?,
+                hri_font: fields.named("hri_font")?,
+            },
+            "qr_code" => Self::QrCode {
+                data: fields.hex("data")?,
+                module_size: fields.int("module_size")?,
+                error_correction: fields.named("error_correction")?,
+            },
+            "cut_paper" => Self::CutPaper(fields.named("cut_type")?),
+            "feed_and_cut_paper" => Self::FeedAndCutPaper {
+                cut_type: fields.named("cut_type")?,
+                amount: fields.int("amount")?,
+            },
+            "raw" => Self::Raw(fields.hex("bytes")?),
+            command => return Err(format!("unknown command {command}")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let commands = [
+            Command::PrintAndFeed {
+                unit: FeedUnit::Inches,
+                amount: 3,
+            },
+            Command::MarkOffset {
+                position: MarkPosition::Cut,
+                offset: -8,
+            },
+            Command::SleepTimeout(None),
+            Command::RetractOnTimeout(Some(30)),
+            Command::PaperSaving(PaperSaving {
+                top_margin: true,
+                ..Default::default()
+            }),
+            Command::BitImage {
+                mode: BitImageMode::Dots24DoubleDensity,
+                width: 1,
+                data: vec![0x01, 0x02, 0xFF],
+            },
+            Command::CodePage(17),
+            Command::Bold(true),
+            Command::Underline(false),
+            Command::CharacterSize {
+                width: 2,
+                height: 3,
+            },
+            Command::Barcode {
+                system: BarcodeSystem::Itf,
+                data: b"0123".to_vec(),
+            },
+            Command::Justification(Justification::Center),
+            Command::BarcodeStyle {
+                module_width: 2,
+                hri: HriPosition::Below,
+                hri_font: HriFont::B,
+            },
+            Command::QrCode {
+                data: b"https://example.com".to_vec(),
+                module_size: 6,
+                error_correction: QrErrorCorrection::Quartile,
+            },
+            Command::Raw(vec![0x1B, 0x40]),
+        ];
+        for command in commands {
+            assert_eq!(Command::from_json(&command.to_json()), Ok(command));
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            Command::FeedAndCutPaper {
+                cut_type: CutType::PartialCut,
+                amount: 5
+            }
+            .to_json()
+            .to_string(),
+            r#"{"command":"feed_and_cut_paper","cut_type":"partial","amount":5}"#
+        );
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let parse = |text: &str| Command::from_json(&json::parse(text).unwrap());
+        assert_eq!(
+            parse(r#"{"command":"fly"}"#),
+            Err("unknown command fly".to_string())
+        );
+        assert_eq!(
+            parse(r#"{"command":"speed"}"#),
+            Err("missing field speed".to_string())
+        );
+        assert_eq!(
+            parse(r#"{"command":"print_and_feed","unit":"lines","amount":256}"#),
+            Err("invalid field amount".to_string())
+        );
+        for (width, data) in [(0, "ff"), (1, ""), (2, "ffffff")] {
+            assert_eq!(
+                parse(&format!(
+                    r#"{{"command":"bit_image","mode":"dots8_single_density","width":{width},"data":"{data}"}}"#
+                )),
+                Err(format!(
+                    "bit image of {} bytes doesn't fill its {width} columns",
+                    data.len() / 2
+                ))
+            );
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Command sets encoding the typed commands to the bytes of a firmware.
+
+use alloc::vec::Vec;
+
+use crate::{Command, Error};
+
+mod star;
+
+pub use star::StarLineMode;
+
+/// Encoding of the typed [`Command`]s to the bytes understood by a firmware, so the same job can be sent to
+/// printers whose firmware differs in opcodes or parameter ranges.
+///
+/// [`CustomEscPos`] is the command set of the CUSTOM printers and the default of
+/// [`CustomPrinter`](crate::CustomPrinter), see
+/// [`CustomPrinter::set_command_set()`](crate::CustomPrinter::set_command_set()).
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Command, CommandBuilder, CommandSet, CustomEscPos, JobBuilder};
+/// /// CUSTOM ESC/POS, except the firmware only cuts by `GS V`.
+/// struct FeedCutOnly;
+///
+/// impl CommandSet for FeedCutOnly {
+///     fn name(&self) -> &'static str {
+///         "feed cut only"
+///     }
+///
+///     fn encode(&self, command: &Command, out: &mut Vec<u8>) {
+///         match command {
+///             Command::CutPaper(cut_type) => CustomEscPos.encode(
+///                 &Command::FeedAndCutPaper { cut_type: *cut_type, amount: 0 },
+///                 out,
+///             ),
+///             command => CustomEscPos.encode(command, out),
+///         }
+///     }
+/// }
+///
+/// let job = JobBuilder::new().cut_paper(custom_printer::CutType::TotalCut).build();
+/// assert_eq!(FeedCutOnly.encode_all(job.commands()).unwrap(), [0x1D, 0x56, 0x41, 0]);
+/// ```
+pub trait CommandSet {
+    /// Name of the command set, reported by [`Error::Unsupported`] for the commands it can't encode.
+    fn name(&self) -> &'static str;
+
+    /// Whether `command` can be encoded, every command by default.
+    ///
+    /// The unsupported commands are refused by [`encode_all()`](CommandSet::encode_all()) and
+    /// [`CustomPrinter::run()`](crate::CustomPrinter::run()) instead of being encoded.
+    fn supports(&self, command: &Command) -> bool {
+        let _ = command;
+        true
+    }
+
+    /// Append the bytes of `command` to `out`.
+    ///
+    /// Only called for the commands the command set [`supports()`](CommandSet::supports()).
+    fn encode(&self, command: &Command, out: &mut Vec<u8>);
+
+    /// Append the bytes of the bit image `command` printed at once as raster images to `out`, replacing the banks of
+    /// the bit image and the following [`Command::Print`], and return whether it's supported, not by default.
+    ///
+    /// Only called for the bit images printed on their own outside page mode, if the [`Profile`](crate::Profile)
+    /// of the printer prints raster images, see [`Profile::raster_images`](crate::Profile::raster_images).
+    fn encode_raster(&self, command: &Command, out: &mut Vec<u8>) -> bool {
+        let _ = (command, out);
+        false
+    }
+
+    /// Length of the bytes `command` is encoded to, used to reserve buffers and to limit the size of the commands.
+    fn encoded_len(&self, command: &Command) -> usize {
+        let mut out = Vec::new();
+        self.encode(command, &mut out);
+
+        out.len()
+    }
+
+    /// Encode `commands` to bytes one after the other.
+    ///
+    /// Returns [`Error::Unsupported`] for the first command the command set doesn't support.
+    fn encode_all(&self, commands: &[Command]) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::with_capacity(
+            commands
+                .iter()
+                .map(|command| self.encoded_len(command))
+                .sum(),
+        );
+        for command in commands {
+            check(self, command)?;
+            self.encode(command, &mut out);
+        }
+
+        Ok(out)
+    }
+}
+
+/// The ESC/POS dialect of the CUSTOM printers, encoding the commands like [`Command::encode()`].
+///
+/// Every command is supported, the optional firmware features are checked against the
+/// [`Profile`](crate::Profile) of the printer instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CustomEscPos;
+
+impl CommandSet for CustomEscPos {
+    fn name(&self) -> &'static str {
+        "CUSTOM ESC/POS"
+    }
+
+    fn encode(&self, command: &Command, out: &mut Vec<u8>) {
+        command.encode(out);
+    }
+
+    fn encode_raster(&self, command: &Command, out: &mut Vec<u8>) -> bool {
+        command.encode_raster(out)
+    }
+
+    fn encoded_len(&self, command: &Command) -> usize {
+        command.encoded_len()
+    }
+}
+
+/// State of the printer after the commands encoded so far, deciding whether a bit image is printed on its own.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Line {
+    /// Whether the printer is in page mode
+    pub(crate) page_mode: bool,
+    /// Whether no data is left on the line
+    pub(crate) empty: bool,
+}
+
+#[cfg(feature = "std")]
+impl Default for Line {
+    fn default() -> Self {
+        Self {
+            page_mode: false,
+            empty: true,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Line {
+    /// The state after `commands` from the start of a job.
+    pub(crate) fn after<'a>(commands: impl IntoIterator<Item = &'a Command>) -> Self {
+        let mut line = Self::default();
+        commands
+            .into_iter()
+            .for_each(|command| line.update(command));
+
+        line
+    }
+
+    /// Update the state after `command`.
+    pub(crate) fn update(&mut self, command: &Command) {
+        match command {
+            Command::EnterPageMode => self.page_mode = true,
+            Command::Initialize | Command::ExitPageMode | Command::PrintPageAndExit => {
+                *self = Self::default();
+            }
+            Command::Print
+            | Command::PrintAndFeed { .. }
+            | Command::PrintAndReverseFeed { .. }
+            | Command::FeedToMark
+            | Command::PrintPage => self.empty = true,
+            // the other data left on the line would be printed before the raster image
+            Command::BitImage { .. } => self.empty = false,
+            Command::Raw(bytes) if !bytes.is_empty() => self.empty = bytes.ends_with(b"\n"),
+            _ => {}
+        }
+    }
+
+    /// Whether a bit image encoded now is printed on its own, so it can be sent as raster images.
+    pub(crate) fn is_clear(&self) -> bool {
+        self.empty && !self.page_mode
+    }
+}
+
+/// Encode `commands` by `command_set` into `out` from the state `line`, coalescing each bit image printed on its
+/// own by the following [`Command::Print`] into raster images if `raster`, see [`CommandSet::encode_raster()`].
+///
+/// `line` is updated, so the commands of a job can be encoded in several calls.
+#[cfg(feature = "std")]
+pub(crate) fn encode_into<'a, C: CommandSet + ?Sized>(
+    command_set: &C,
+    commands: impl IntoIterator<Item = &'a Command>,
+    raster: bool,
+    line: &mut Line,
+    out: &mut Vec<u8>,
+) {
+    let mut commands = commands.into_iter().peekable();
+    while let Some(command) = commands.next() {
+        let printed = matches!(command, Command::BitImage { .. })
+            && matches!(commands.peek(), Some(Command::Print));
+        if raster && printed && line.is_clear() && command_set.encode_raster(command, out) {
+            line.empty = true;
+            commands.next();
+            continue;
+        }
+        command_set.encode(command, out);
+        line.update(command);
+    }
+}
+
+/// Check that `command` is supported by `command_set`, see [`CommandSet::supports()`].
+pub(crate) fn check<C: CommandSet + ?Sized>(
+    command_set: &C,
+    command: &Command,
+) -> Result<(), Error> {
+    if command_set.supports(command) {
+        Ok(())
+    } else {
+        Err(Error::Unsupported {
+            command: command.kind(),
+            printer: command_set.name().into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command, CommandBuilder, CutType, JobBuilder};
+
+    /// CUSTOM ESC/POS without the presenter.
+    struct Kiosk;
+
+    impl CommandSet for Kiosk {
+        fn name(&self) -> &'static str {
+            "kiosk"
+        }
+
+        fn supports(&self, command: &Command) -> bool {
+            !matches!(command, Command::EjectTicket)
+        }
+
+        fn encode(&self, command: &Command, out: &mut Vec<u8>) {
+            CustomEscPos.encode(command, out);
+        }
+    }
+
+    #[test]
+    fn test_encode_all() {
+        let job = JobBuilder::new()
+            .print()
+            .cut_paper(CutType::TotalCut)
+            .build();
+        assert_eq!(
+            CustomEscPos.encode_all(job.commands()).unwrap(),
+            command::encode(job.commands())
+        );
+        assert_eq!(Kiosk.encode_all(job.commands()).unwrap(), job.to_bytes());
+
+        assert!(matches!(
+            Kiosk.encode_all(&[Command::Print, Command::EjectTicket]),
+            Err(Error::Unsupported { command: "eject_ticket", printer }) if printer == "kiosk"
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_encode_raster() {
+        use crate::BitImageMode;
+
+        // a diagonal line of 8 x 8 dots at the top of a bank of 24 lines
+        let image = Command::BitImage {
+            mode: BitImageMode::Dots24DoubleDensity,
+            width: 8,
+            data: (0..8).flat_map(|x| [0x80 >> x, 0, 0]).collect(),
+        };
+        let encode = |command_set: &dyn CommandSet, commands: &[Command], raster| {
+            let mut out = Vec::new();
+            encode_into(
+                command_set,
+                commands,
+                raster,
+                &mut Line::default(),
+                &mut out,
+            );
+            out
+        };
+        let mut raster = Vec::from([0x1D, 0x76, 0x30, 0, 1, 0, 24, 0]);
+        raster.extend((0..24).map(|y| if y < 8 { 0x80 >> y } else { 0 }));
+        let printed = [image.clone(), Command::Print];
+        assert_eq!(encode(&CustomEscPos, &printed, true), raster);
+        assert_eq!(
+            encode(&CustomEscPos, &printed, false),
+            command::encode(&printed)
+        );
+        assert_eq!(encode(&Kiosk, &printed, true), command::encode(&printed));
+
+        // the bit images of the 8 dot modes, printed with other data on the line or in page mode are kept
+        let dots8 = Command::BitImage {
+            mode: BitImageMode::Dots8DoubleDensity,
+            width: 8,
+            data: (0..8).map(|x| 0x80 >> x).collect(),
+        };
+        for commands in [
+            [Command::Raw(Vec::new()), dots8, Command::Print],
+            [Command::Raw(b"A".to_vec()), image.clone(), Command::Print],
+            [image.clone(), image.clone(), Command::Print],
+            [Command::EnterPageMode, image.clone(), Command::Print],
+        ] {
+            assert_eq!(
+                encode(&CustomEscPos, &commands, true),
+                command::encode(&commands)
+            );
+        }
+        let line = [Command::Raw(b"A\n".to_vec()), image.clone(), Command::Print];
+        assert!(encode(&CustomEscPos, &line, true).ends_with(&raster));
+
+        // 96 banks of 24 lines split after 2280 lines, the single density in double width
+        let tall = Command::BitImage {
+            mode: BitImageMode::Dots24SingleDensity,
+            width: 16,
+            data: alloc::vec![0xFF; 96 * 16 * 3],
+        };
+        let mnemonics = crate::mnemonics(&encode(&CustomEscPos, &[tall, Command::Print], true));
+        assert_eq!(
+            mnemonics,
+            [
+                "GS v 0 m=1 x=2 y=2280 [4560 bytes]",
+                "GS v 0 m=1 x=2 y=24 [48 bytes]"
+            ]
+        );
+    }
+}
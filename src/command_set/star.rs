@@ -0,0 +1,274 @@
+//! Star line mode command set.
+
+use alloc::vec::Vec;
+
+use super::CommandSet;
+use crate::{BitImageMode, Command, CutType, Density, DrawerPin, FeedUnit, Justification, Speed};
+
+const INITIALIZE: &[u8] = &[0x1B, 0x40];
+const PRINT: &[u8] = &[0x0A];
+const FEED_MM: &[u8] = &[0x1B, 0x4A];
+const FEED_LINES: &[u8] = &[0x1B, 0x61];
+const REVERSE_FEED_MM: &[u8] = &[0x1B, 0x6A];
+const SPEED: &[u8] = &[0x1B, 0x1E, 0x72];
+const DENSITY: &[u8] = &[0x1B, 0x1E, 0x64];
+const DRAWER_PULSE: &[u8] = &[0x1B, 0x07];
+const DRAWER_1: &[u8] = &[0x07];
+const DRAWER_2: &[u8] = &[0x1C];
+const ABSOLUTE_X: &[u8] = &[0x1B, 0x1D, 0x41];
+const NORMAL_GRAPHICS: &[u8] = &[0x1B, 0x4B];
+const HIGH_GRAPHICS: &[u8] = &[0x1B, 0x4C];
+const FINE_GRAPHICS: &[u8] = &[0x1B, 0x58];
+const CUT: &[u8] = &[0x1B, 0x64];
+const BOLD_ON: &[u8] = &[0x1B, 0x45];
+const BOLD_OFF: &[u8] = &[0x1B, 0x46];
+const UNDERLINE: &[u8] = &[0x1B, 0x2D];
+const EXPANSION: &[u8] = &[0x1B, 0x69];
+const ALIGNMENT: &[u8] = &[0x1B, 0x1D, 0x61];
+
+/// Line spacing after initializing the printer in 1/4 millimeters, i.e. 1/6 inch.
+const LINE_SPACING: u32 = 17;
+
+/// The line mode of the Star Micronics printers, e.g. TSP100 and TSP650, so mixed fleets are driven by the same
+/// job.
+///
+/// The commands are encoded as follows:
+///
+/// * Feeds: Star feeds by 1/4 mm instead of motion units, so feeds in [`FeedUnit::Inches`] are converted assuming
+///   the 203 dpi default unit, and reverse feeds in [`FeedUnit::Lines`] assuming a line spacing of 1/6 inch.
+/// * Density: the five densities are mapped to the levels -2 to +2 of the seven Star levels.
+/// * Character size: only up to 6 times, the largest Star expansion.
+/// * Position: only the horizontal position is supported, i.e. `y` must be 0.
+/// * Bit images: the 8 dot single and double density modes are printed by the normal and high density graphics,
+///   and the 24 dot double density mode by the fine density graphics.
+///   [`BitImageMode::Dots24SingleDensity`] has no equivalent.
+/// * Cuts: [`Command::FeedAndCutPaper`] feeds its amount before moving to the cut position, so the total feed is
+///   the same.
+///
+/// The other commands have no equivalent in Star line mode and are refused with
+/// [`Error::Unsupported`](crate::Error::Unsupported), including the motion units, code pages (the Star tables are
+/// numbered differently), QR code, buzzer, black mark, paper-saving, sleep, presenter, near-end sensor, feed button and page
+/// mode commands, see [`supports()`](CommandSet::supports()).
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{CommandBuilder, CommandSet, CutType, JobBuilder, StarLineMode};
+/// let job = JobBuilder::new().print().feed_and_cut_paper(CutType::PartialCut, 0).build();
+/// assert_eq!(StarLineMode.encode_all(job.commands()).unwrap(), [0x0A, 0x1B, 0x64, 0x03]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StarLineMode;
+
+impl CommandSet for StarLineMode {
+    fn name(&self) -> &'static str {
+        "Star line mode"
+    }
+
+    fn supports(&self, command: &Command) -> bool {
+        match command {
+            Command::Initialize
+            | Command::Print
+            | Command::PrintAndFeed { .. }
+            | Command::PrintAndReverseFeed { .. }
+            | Command::Speed(_)
+            | Command::Density(_)
+            | Command::OpenDrawer { .. }
+            | Command::Bold(_)
+            | Command::Underline(_)
+            | Command::CharacterSize {
+                width: 1..=6,
+                height: 1..=6,
+            }
+            | Command::Justification(_)
+            | Command::Position { y: 0, .. }
+            | Command::CutPaper(_)
+            | Command::FeedAndCutPaper { .. }
+            | Command::Raw(_) => true,
+            Command::BitImage { mode, .. } => *mode != BitImageMode::Dots24SingleDensity,
+            _ => false,
+        }
+    }
+
+    fn encode(&self, command: &Command, out: &mut Vec<u8>) {
+        match command {
+            Command::Initialize => out.extend_from_slice(INITIALIZE),
+            Command::Print => out.extend_from_slice(PRINT),
+            Command::PrintAndFeed { unit, amount } => match unit {
+                FeedUnit::Inches => {
+                    out.extend_from_slice(FEED_MM);
+                    out.push(quarter_mm(*amount));
+                }
+                FeedUnit::Lines => {
+                    out.extend_from_slice(FEED_LINES);
+                    out.push(*amount);
+                }
+            },
+            Command::PrintAndReverseFeed { unit, amount } => {
+                out.extend_from_slice(REVERSE_FEED_MM);
+                out.push(match unit {
+                    FeedUnit::Inches => quarter_mm(*amount),
+                    FeedUnit::Lines => (*amount as u32 * LINE_SPACING).min(u8::MAX as u32) as u8,
+                });
+            }
+            Command::Speed(speed) => {
+                out.extend_from_slice(SPEED);
+                out.push(match speed {
+                    Speed::High => 0,
+                    Speed::Normal => 1,
+                    Speed::Low => 2,
+                });
+            }
+            Command::Density(density) => {
+                // 0 is the darkest level, 3 the standard one and 6 the lightest one
+                out.extend_from_slice(DENSITY);
+                out.push(match density {
+                    Density::Plus50 => 1,
+                    Density::Plus25 => 2,
+                    Density::Zero => 3,
+                    Density::Minus25 => 4,
+                    Density::Minus50 => 5,
+                });
+            }
+            Command::OpenDrawer { pin, on_ms, off_ms } => {
+                let to_units = |ms: u16| (ms / 10).min(u8::MAX as u16) as u8;
+
+                out.extend_from_slice(DRAWER_PULSE);
+                out.extend_from_slice(&[to_units(*on_ms), to_units(*off_ms)]);
+                out.extend_from_slice(match pin {
+                    DrawerPin::Pin2 => DRAWER_1,
+                    DrawerPin::Pin5 => DRAWER_2,
+                });
+            }
+            Command::Bold(enabled) => {
+                out.extend_from_slice(if *enabled { BOLD_ON } else { BOLD_OFF });
+            }
+            Command::Underline(enabled) => {
+                out.extend_from_slice(UNDERLINE);
+                out.push(*enabled as u8);
+            }
+            Command::CharacterSize { width, height } => {
+                out.extend_from_slice(EXPANSION);
+                out.extend_from_slice(&[height - 1, width - 1]);
+            }
+            Command::Justification(justification) => {
+                out.extend_from_slice(ALIGNMENT);
+                out.push(match justification {
+                    Justification::Left => 0,
+                    Justification::Center => 1,
+                    Justification::Right => 2,
+                });
+            }
+            Command::Position { x, .. } => {
+                out.extend_from_slice(ABSOLUTE_X);
+                out.extend_from_slice(&x.to_le_bytes());
+            }
+            Command::BitImage { mode, width, data } => {
+                let (graphics, k) = match mode {
+                    BitImageMode::Dots8SingleDensity => (NORMAL_GRAPHICS, *width as usize),
+                    BitImageMode::Dots8DoubleDensity => (HIGH_GRAPHICS, *width as usize),
+                    BitImageMode::Dots24SingleDensity | BitImageMode::Dots24DoubleDensity => {
+                        (FINE_GRAPHICS, *width as usize * 3)
+                    }
+                };
+                if k == 0 {
+                    return;
+                }
+
+                for bank in data.chunks_exact(k) {
+                    out.extend_from_slice(graphics);
+                    out.extend_from_slice(&width.to_le_bytes());
+                    out.extend_from_slice(bank);
+                }
+            }
+            Command::CutPaper(cut_type) => {
+                out.extend_from_slice(CUT);
+                out.push(match cut_type {
+                    CutType::TotalCut => 0,
+                    CutType::PartialCut => 1,
+                });
+            }
+            Command::FeedAndCutPaper { cut_type, amount } => {
+                if *amount > 0 {
+                    out.extend_from_slice(FEED_MM);
+                    out.push(quarter_mm(*amount));
+                }
+                out.extend_from_slice(CUT);
+                out.push(match cut_type {
+                    CutType::TotalCut => 2,
+                    CutType::PartialCut => 3,
+                });
+            }
+            Command::Raw(bytes) => out.extend_from_slice(bytes),
+            // refused by supports()
+            _ => {}
+        }
+    }
+}
+
+/// Convert `dots` of 1/203 inch to 1/4 millimeters, rounded to the nearest.
+fn quarter_mm(dots: u8) -> u8 {
+    // 255 dots are 127.6 quarter millimeters, so the result always fits
+    ((dots as u32 * 1016 + 1015) / 2030) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandBuilder, Error, JobBuilder};
+    use alloc::vec;
+
+    #[test]
+    fn test_star_line_mode() {
+        let job = JobBuilder::new()
+            .initialize()
+            .print_and_feed_paper(FeedUnit::Inches, 203)
+            .print_and_feed_paper(FeedUnit::Lines, 2)
+            .print_and_reverse_feed_paper(FeedUnit::Lines, 1)
+            .unwrap()
+            .density(&Density::Plus25)
+            .justification(Justification::Center)
+            .open_drawer(DrawerPin::Pin5, 100, 200)
+            .feed_and_cut_paper(CutType::TotalCut, 10)
+            .build();
+        assert_eq!(
+            StarLineMode.encode_all(job.commands()).unwrap(),
+            [
+                0x1B, 0x40, 0x1B, 0x4A, 102, 0x1B, 0x61, 2, 0x1B, 0x6A, 17, 0x1B, 0x1E, 0x64, 2,
+                0x1B, 0x1D, 0x61, 1, 0x1B, 0x07, 10, 20, 0x1C, 0x1B, 0x4A, 5, 0x1B, 0x64, 2
+            ]
+        );
+
+        let image = Command::BitImage {
+            mode: BitImageMode::Dots24DoubleDensity,
+            width: 2,
+            data: vec![0xFF; 12],
+        };
+        assert_eq!(
+            StarLineMode.encode_all(&[image]).unwrap(),
+            [
+                [&[0x1B, 0x58, 2, 0][..], &[0xFF; 6]].concat(),
+                [&[0x1B, 0x58, 2, 0][..], &[0xFF; 6]].concat()
+            ]
+            .concat()
+        );
+
+        assert!(matches!(
+            StarLineMode.encode_all(&[Command::Position { x: 0, y: 10 }]),
+            Err(Error::Unsupported {
+                command: "position",
+                ..
+            })
+        ));
+        assert!(matches!(
+            StarLineMode.encode_all(&[Command::Beep {
+                count: 1,
+                duration_ms: 100
+            }]),
+            Err(Error::Unsupported {
+                command: "beep",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+//! Configuration of the printer consulted when constructing commands.
+
+use crate::{CommandBuilder, Density, Model, Profile, Speed};
+
+/// Configuration of the paper width, resolution and default settings of a printer, set by
+/// [`CustomPrinter::with_config()`](crate::CustomPrinter::with_config()) or
+/// [`JobBuilder::set_config()`](crate::JobBuilder::set_config()) and returned by [`CommandBuilder::config()`].
+///
+/// The default is a 384 dot wide print head at 203 DPI without any default settings,
+/// [`for_model()`](PrinterConfig::for_model()) gives the configuration of a supported [`Model`], and
+/// [`for_paper()`](PrinterConfig::for_paper()) the configuration of a [`PaperWidth`] preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrinterConfig {
+    /// Number of dots in a printed line
+    pub dots_per_line: u16,
+    /// Resolution of the print head in dots per inch
+    pub dpi: u16,
+    /// Printing density applied by [`CommandBuilder::apply_config()`]
+    pub density: Option<Density>,
+    /// Speed / quality mode applied by [`CommandBuilder::apply_config()`]
+    pub speed: Option<Speed>,
+    /// Character code table applied by [`CommandBuilder::apply_config()`]
+    pub code_page: Option<u8>,
+    /// Left and right margins in dots inside the printed line, applied by [`CommandBuilder::apply_config()`]
+    pub margin: Option<u16>,
+    /// What to do with parameters beyond the limits of the [`Profile`], e.g.
+    /// [`max_reverse_feed`](Profile::max_reverse_feed)
+    pub limits: LimitPolicy,
+}
+
+/// Policies for the parameters beyond the limits of the [`Profile`] of the printer, set in
+/// [`PrinterConfig::limits`].
+///
+/// The limits are only enforced if the profile is known.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LimitPolicy {
+    /// Construct the commands as they are, and refuse them by
+    /// [`CustomPrinter::run()`](crate::CustomPrinter::run()) with [`Error::InvalidParameter`](crate::Error::InvalidParameter)
+    /// instead of sending the out-of-range bytes
+    #[default]
+    Refuse,
+    /// Clamp the parameters to the limits when constructing the commands
+    Clamp,
+}
+
+/// Presets of the common paper widths, setting the printed line of the [`PrinterConfig`] by
+/// [`PrinterConfig::for_paper()`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{PaperWidth, PrinterConfig};
+/// let config = PrinterConfig::for_paper(PaperWidth::Mm58);
+/// assert_eq!(config.dots_per_line, 384);
+/// assert_eq!(config.printable_dots(), 368);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaperWidth {
+    /// 58 mm paper, printed 48 mm wide
+    Mm58,
+    /// 80 mm paper, printed 72 mm wide
+    Mm80,
+    /// 112 mm paper, printed 104 mm wide
+    Mm112,
+}
+
+impl PaperWidth {
+    /// Width of the paper in millimeters.
+    pub fn width_mm(&self) -> u16 {
+        match self {
+            Self::Mm58 => 58,
+            Self::Mm80 => 80,
+            Self::Mm112 => 112,
+        }
+    }
+
+    /// Width of the printed line in millimeters.
+    pub fn print_width_mm(&self) -> u16 {
+        match self {
+            Self::Mm58 => 48,
+            Self::Mm80 => 72,
+            Self::Mm112 => 104,
+        }
+    }
+
+    /// Number of dots in the printed line at `dpi`, rounded to the nearest whole byte of the bit images.
+    pub fn dots_per_line(&self, dpi: u16) -> u16 {
+        let dots = self.print_width_mm() as u32 * dpi as u32 * 10 / 254;
+
+        ((dots + 4) / 8 * 8) as u16
+    }
+
+    /// Default left and right margins in dots at `dpi` inside the printed line, 1 mm rounded up to whole bytes
+    /// of the bit images, so text and images don't touch the edge of the paper.
+    pub fn margin(&self, dpi: u16) -> u16 {
+        let dots = dpi as u32 * 10 / 254;
+
+        dots.div_ceil(8) as u16 * 8
+    }
+}
+
+impl Default for PrinterConfig {
+    fn default() -> Self {
+        Self {
+            dots_per_line: 384,
+            dpi: 203,
+            density: None,
+            speed: None,
+            code_page: None,
+            margin: None,
+            limits: LimitPolicy::Refuse,
+        }
+    }
+}
+
+impl PrinterConfig {
+    /// The configuration of `model` without any default settings.
+    pub fn for_model(model: Model) -> Self {
+        Self::for_profile(&Profile::for_model(model))
+    }
+
+    /// The configuration of the printer of `profile` without any default settings.
+    pub fn for_profile(profile: &Profile) -> Self {
+        Self {
+            dots_per_line: profile.dots_per_line,
+            dpi: profile.dpi,
+            ..Self::default()
+        }
+    }
+
+    /// The configuration of `paper` on a 203 dpi print head, with its default [`margin`](PaperWidth::margin()).
+    ///
+    /// Print heads of other resolutions are configured from their `dpi`, e.g.
+    /// `PaperWidth::Mm80.dots_per_line(300)`.
+    pub fn for_paper(paper: PaperWidth) -> Self {
+        let dpi = Self::default().dpi;
+        Self {
+            dots_per_line: paper.dots_per_line(dpi),
+            dpi,
+            margin: Some(paper.margin(dpi)),
+            ..Self::default()
+        }
+    }
+
+    /// The configuration of `profile`, or the default configuration if the profile isn't known.
+    pub(crate) fn of(profile: Option<&Profile>) -> Self {
+        profile.map_or_else(Self::default, Self::for_profile)
+    }
+
+    /// Width of a printed line in millimeters.
+    pub fn line_width_mm(&self) -> f32 {
+        self.dots_per_line as f32 * 25.4 / self.dpi as f32
+    }
+
+    /// Number of dots in a printed line between the [`margin`](PrinterConfig::margin)s.
+    pub fn printable_dots(&self) -> u16 {
+        self.dots_per_line
+            .saturating_sub(self.margin.unwrap_or(0).saturating_mul(2))
+    }
+
+    /// Append the commands of the default settings to `builder`, see [`CommandBuilder::apply_config()`].
+    pub(crate) fn apply<B: CommandBuilder + ?Sized>(&self, builder: &mut B) {
+        if let Some(density) = &self.density {
+            builder.density(density);
+        }
+        if let Some(speed) = &self.speed {
+            builder.speed(speed);
+        }
+        if let Some(page) = self.code_page {
+            builder.code_page(page);
+        }
+        if let Some(margin) = self.margin {
+            builder.print_area(margin, self.printable_dots());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JobBuilder;
+
+    #[test]
+    fn test_for_model() {
+        let config = PrinterConfig::for_model(Model::Tg2480h);
+        assert_eq!(config.dots_per_line, 576);
+        assert_eq!(config.dpi, 203);
+        assert_eq!(config.density, None);
+        assert_eq!(PrinterConfig::for_model(Model::Vkp80).dpi, 200);
+        assert!((PrinterConfig::default().line_width_mm() - 48.04).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_for_paper() {
+        let config = PrinterConfig::for_paper(PaperWidth::Mm80);
+        assert_eq!(config.dots_per_line, 576);
+        assert_eq!(config.margin, Some(8));
+        assert_eq!(config.printable_dots(), 560);
+        assert_eq!(
+            PrinterConfig::for_paper(PaperWidth::Mm112).dots_per_line,
+            832
+        );
+        assert_eq!(PaperWidth::Mm58.dots_per_line(300), 568);
+        assert_eq!(PaperWidth::Mm58.margin(300), 16);
+
+        // the margins are applied by the print area
+        let mut builder = JobBuilder::new();
+        builder.set_config(Some(config));
+        assert_eq!(
+            builder.apply_config().build().to_bytes(),
+            [0x1D, 0x4C, 8, 0, 0x1D, 0x57, 0x30, 0x02]
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Decoding of command bytes into readable mnemonics for logging and debugging.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Decode `bytes` of commands into readable mnemonics, one for each command, e.g. `ESC * m=0x21 w=384 [1152 bytes]`
+/// or `GS V partial n=0`.
+///
+/// Printable text is decoded as a quoted string, and bytes which aren't recognized, e.g. an incomplete
+/// command at the end, are decoded one by one in hexadecimal.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{mnemonics, CommandBuilder, CutType, JobBuilder};
+/// let job = JobBuilder::new()
+///     .print()
+///     .feed_and_cut_paper(CutType::PartialCut, 0)
+///     .build();
+/// assert_eq!(mnemonics(&job.to_bytes()), ["LF", "GS V partial n=0"]);
+/// ```
+pub fn mnemonics(bytes: &[u8]) -> Vec<String> {
+    let mut mnemonics = Vec::new();
+    let mut rest = bytes;
+
+    while !rest.is_empty() {
+        let (len, mnemonic) = decode_one(rest);
+        mnemonics.push(mnemonic);
+        rest = &rest[len..];
+    }
+
+    mnemonics
+}
+
+/// Name of the control character `byte`.
+fn control(byte: u8) -> Option<&'static str> {
+    Some(match byte {
+        0x00 => "NUL",
+        0x0A => "LF",
+        0x0C => "FF",
+        0x10 => "DLE",
+        0x18 => "CAN",
+        0x1B => "ESC",
+        0x1C => "FS",
+        0x1D => "GS",
+        _ => return None,
+    })
+}
+
+fn on_off(n: u8) -> &'static str {
+    if n & 0x01 != 0 {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Decode the command at the start of `bytes`, returning its length and mnemonic.
+fn decode_one(bytes: &[u8]) -> (usize, String) {
+    let word = |n: u8, m: u8| u16::from_le_bytes([n, m]);
+
+    match *bytes {
+        [0x0A, ..] => (1, "LF".to_string()),
+        [0x0C, ..] => (1, "FF".to_string()),
+        [0x18, ..] => (1, "CAN".to_string()),
+        [0x00, ..] => (1, "NUL".to_string()),
+
+        [0x1B, 0x40, ..] => (2, "ESC @".to_string()),
+        [0x1B, 0x0C, ..] => (2, "ESC FF".to_string()),
+        [0x1B, 0x4C, ..] => (2, "ESC L".to_string()),
+        [0x1B, 0x53, ..] => (2, "ESC S".to_string()),
+        [0x1B, 0x69, ..] => (2, "ESC i total".to_string()),
+        [0x1B, 0x6D, ..] => (2, "ESC m partial".to_string()),
+        [0x1B, 0x4A, n, ..] => (3, format!("ESC J n={n}")),
+        [0x1B, 0x64, n, ..] => (3, format!("ESC d n={n}")),
+        [0x1B, 0x4B, n, ..] => (3, format!("ESC K n={n}")),
+        [0x1B, 0x65, n, ..] => (3, format!("ESC e n={n}")),
+        [0x1B, 0x78, n, ..] => (3, format!("ESC x n={n}")),
+        [0x1B, 0x54, n, ..] => (3, format!("ESC T n={n}")),
+        [0x1B, 0x74, n, ..] => (3, format!("ESC t n={n}")),
+        [0x1B, 0x45, n, ..] => (3, format!("ESC E {}", on_off(n))),
+        [0x1B, 0x2D, n, ..] => (3, format!("ESC - n={n}")),
+        [0x1B, 0x61, n, ..] => (3, format!("ESC a n={n}")),
+        [0x1B, 0x24, n, m, ..] => (4, format!("ESC $ x={}", word(n, m))),
+        [0x1B, 0x42, n, t, ..] => (4, format!("ESC B n={n} t={t}")),
+        [0x1B, 0x63, 0x34, n, ..] => (4, format!("ESC c 4 n={n:#04x}")),
+        [0x1B, 0x63, 0x35, n, ..] => (4, format!("ESC c 5 {}", on_off(n))),
+        [0x1B, 0x70, m, t1, t2, ..] => (5, format!("ESC p m={m} t1={t1} t2={t2}")),
+        [0x1B, 0x8E, 0x00, ..] => (3, "ESC 0x8e sleep".to_string()),
+        [0x1B, 0x8E, 0x01, n, ..] => (4, format!("ESC 0x8e timeout n={n}")),
+        [0x1B, 0x57, xl, xh, yl, yh, dxl, dxh, dyl, dyh, ..] => (
+            10,
+            format!(
+                "ESC W x={} y={} w={} h={}",
+                word(xl, xh),
+                word(yl, yh),
+                word(dxl, dxh),
+                word(dyl, dyh)
+            ),
+        ),
+        [0x1B, 0x2A, m, nl, nh, ref data @ ..] => {
+            let width = word(nl, nh) as usize;
+            let k = if m & 0x20 != 0 { width * 3 } else { width };
+            if data.len() < k {
+                return hex(bytes[0]);
+            }
+            (5 + k, format!("ESC * m={m:#04x} w={width} [{k} bytes]"))
+        }
+
+        [0x1D, 0x76, 0x30, m, xl, xh, yl, yh, ref data @ ..] => {
+            let (width, height) = (word(xl, xh) as usize, word(yl, yh) as usize);
+            if data.len() < width * height {
+                return hex(bytes[0]);
+            }
+            (
+                8 + width * height,
+                format!(
+                    "GS v 0 m={m} x={width} y={height} [{} bytes]",
+                    width * height
+                ),
+            )
+        }
+        [0x1D, 0x0C, ..] => (2, "GS FF".to_string()),
+        [0x1D, 0x6B, m @ 65..=73, n, ref data @ ..] => {
+            let n = n as usize;
+            if data.len() < n {
+                return hex(bytes[0]);
+            }
+            (
+                4 + n,
+                format!("GS k m={m} {:?}", String::from_utf8_lossy(&data[..n])),
+            )
+        }
+        [0x1D, 0x77, n, ..] => (3, format!("GS w n={n}")),
+        [0x1D, 0x48, n, ..] => (3, format!("GS H n={n}")),
+        [0x1D, 0x66, n, ..] => (3, format!("GS f n={n}")),
+        [0x1D, 0x7C, n, ..] => (3, format!("GS | n={n}")),
+        [0x1D, 0x21, n, ..] => (3, format!("GS ! w={} h={}", (n >> 4) + 1, (n & 0x0F) + 1)),
+        [0x1D, 0xF7, n, ..] => (3, format!("GS 0xf7 n={n:#010b}")),
+        [0x1D, 0xE6, n, ..] => (3, format!("GS 0xe6 n={n}")),
+        [0x1D, 0x49, n, ..] => (3, format!("GS I n={n:#04x}")),
+        [0x1D, 0x50, x, y, ..] => (4, format!("GS P x={x} y={y}")),
+        [0x1D, 0x24, n, m, ..] => (4, format!("GS $ y={}", word(n, m))),
+        [0x1D, 0x4C, n, m, ..] => (4, format!("GS L n={}", word(n, m))),
+        [0x1D, 0x57, n, m, ..] => (4, format!("GS W n={}", word(n, m))),
+        [0x1D, 0x56, 0x41, n, ..] => (4, format!("GS V total n={n}")),
+        [0x1D, 0x56, 0x42, n, ..] => (4, format!("GS V partial n={n}")),
+        [0x1D, 0x56, m, ..] => (3, format!("GS V m={m:#04x}")),
+        [0x1D, 0x65, f @ (0x02 | 0x05), ..] => (3, format!("GS e fn={f:#04x}")),
+        [0x1D, 0x65, f, n, ..] => (4, format!("GS e fn={f:#04x} n={n}")),
+        [0x1D, 0x67, 0x32, 0x00, nl, nh, ..] => (6, format!("GS g 2 n={}", word(nl, nh))),
+        [0x1D, 0x28, c, pl, ph, ref params @ ..] => {
+            let p = word(pl, ph) as usize;
+            if params.len() < p {
+                return hex(bytes[0]);
+            }
+            (
+                5 + p,
+                format!("GS ( {} {:02x?}", char::from(c), &params[..p]),
+            )
+        }
+
+        [0x10, 0x04, n, ..] => (3, format!("DLE EOT n={n:#04x}")),
+        [0x10, 0x05, n, ..] => (3, format!("DLE ENQ n={n}")),
+        [0x10, 0x14, f, ref params @ ..] if params.len() >= 7 => {
+            (10, format!("DLE DC4 fn={f} {:02x?}", &params[..7]))
+        }
+
+        [b, ..] if b.is_ascii_graphic() || b == b' ' => {
+            let len = bytes
+                .iter()
+                .take_while(|b| b.is_ascii_graphic() || **b == b' ')
+                .count();
+            (len, format!("{:?}", String::from_utf8_lossy(&bytes[..len])))
+        }
+        [b, ..] => hex(b),
+        [] => (0, String::new()),
+    }
+}
+
+/// Decode a single unrecognized byte.
+fn hex(byte: u8) -> (usize, String) {
+    match control(byte) {
+        Some(name) => (1, name.to_string()),
+        None => (1, format!("{byte:#04x}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_mnemonics() {
+        let mut bytes = vec![0x1B, 0x40, b'H', b'i', b'!', 0x0A];
+        bytes.extend_from_slice(&[0x1B, 0x2A, 0x21, 2, 0]);
+        bytes.extend_from_slice(&[0xFF; 6]);
+        bytes.extend_from_slice(&[0x1D, 0x6B, 70, 2, b'4', b'2']);
+        bytes.extend_from_slice(&[0x1D, 0x76, 0x30, 0, 1, 0, 2, 0, 0x80, 0x01]);
+        bytes.extend_from_slice(&[0x1D, 0x56, 0x42, 0, 0x1D, 0x65, 0x03, 50, 0xFE]);
+        assert_eq!(
+            mnemonics(&bytes),
+            [
+                "ESC @",
+                "\"Hi!\"",
+                "LF",
+                "ESC * m=0x21 w=2 [6 bytes]",
+                "GS k m=70 \"42\"",
+                "GS v 0 m=0 x=1 y=2 [2 bytes]",
+                "GS V partial n=0",
+                "GS e fn=0x03 n=50",
+                "0xfe"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mnemonics_incomplete() {
+        // the bit image is truncated
+        assert_eq!(
+            mnemonics(&[0x1B, 0x2A, 0x00, 4, 0, 0x01]),
+            ["ESC", "\"*\"", "NUL", "0x04", "NUL", "0x01"]
+        );
+        assert_eq!(mnemonics(&[0x1D, 0x56]), ["GS", "\"V\""]);
+        assert_eq!(mnemonics(&[0x1B, 0x74, 2]), ["ESC t n=2"]);
+    }
+}
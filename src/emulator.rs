@@ -0,0 +1,317 @@
+//! Emulated printer recording the commands, for snapshot tests without any physical printer.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    command, mnemonics, parse, BitImageMode, Command, CommandBuilder, CutType, FeedUnit, Job,
+    Model, Profile,
+};
+#[cfg(feature = "image")]
+use crate::{preview, Error, PrinterConfig};
+
+/// Line spacing after initializing the printer in dots at 203 dpi, i.e. 1/6 inch.
+const LINE_SPACING_DOTS: u16 = 34;
+
+/// An emulated printer accepting every command of the [`Profile::emulated()`] profile and recording them, as the
+/// stable target of snapshot tests of the applications, independent of the quirks of any physical model.
+///
+/// The recorded commands are described by the normalized [`trace()`](Emulator::trace()), and drawn as text by
+/// [`render()`](Emulator::render()).
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{CommandBuilder, CutType, Emulator, FeedUnit};
+/// let mut emulator = Emulator::new();
+/// emulator
+///     .raw(b"CAFE")
+///     .raw(b" ROMA")
+///     .print_and_feed_paper(FeedUnit::Lines, 1)
+///     .cut_paper(CutType::TotalCut);
+/// assert_eq!(emulator.trace(), ["\"CAFE ROMA\"", "ESC d n=1", "ESC i total"]);
+/// assert_eq!(emulator.render(), "CAFE ROMA\n\n--- cut ---\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Emulator {
+    cmd: Vec<Command>,
+}
+
+impl Emulator {
+    /// Create an [`Emulator`] without any recorded commands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded commands, in the order they were constructed or printed.
+    pub fn commands(&self) -> &[Command] {
+        &self.cmd
+    }
+
+    /// Record the commands of `job` as if it were printed.
+    pub fn print_job(&mut self, job: &Job) -> &mut Self {
+        self.cmd.extend_from_slice(job.commands());
+
+        self
+    }
+
+    /// Record the commands encoded by `bytes` as if they were printed, e.g. the job of another application captured
+    /// from its printer port, see [`parse()`](crate::parse()).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::Emulator;
+    /// let mut emulator = Emulator::new();
+    /// emulator.print_bytes(b"\x1b@\x1bE\x01TOTAL 9.90\n\x1bE\x00\x1d\x56\x42\x00");
+    /// assert_eq!(emulator.trace(), ["ESC @", "ESC E on", "\"TOTAL 9.90\"", "LF", "ESC E off", "GS V partial n=0"]);
+    /// assert_eq!(emulator.render(), "TOTAL 9.90\n--- partial cut ---\n");
+    /// ```
+    pub fn print_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.cmd.extend(parse(bytes));
+
+        self
+    }
+
+    /// Remove the recorded commands, e.g. between the cases of a test.
+    pub fn clear(&mut self) -> &mut Self {
+        self.cmd.clear();
+
+        self
+    }
+
+    /// Describe the recorded commands by the [`mnemonics()`] of their CUSTOM ESC/POS bytes, one for each command.
+    ///
+    /// The trace is normalized, so it's the same however the commands were split, e.g. consecutive text appended
+    /// by several [`raw()`](CommandBuilder::raw()) calls is a single quoted string.
+    pub fn trace(&self) -> Vec<String> {
+        mnemonics(&command::encode(&self.cmd))
+    }
+
+    /// Draw the paper printed by the recorded commands as text, one line of text for each printed line.
+    ///
+    /// Printable ASCII is drawn as it is, bit images are drawn by `#` for each black dot, barcodes by their system
+    /// and data in brackets, e.g. `[Ean13 400638133393]`, QR codes by their data in brackets, e.g.
+    /// `[QR https://example.com]`, blank lines are drawn for the feeds, and cuts by `--- cut ---` or
+    /// `--- partial cut ---`. The commands which don't mark the paper, e.g. the settings and the justification, are
+    /// left out.
+    pub fn render(&self) -> String {
+        let mut paper = Paper::default();
+        for command in &self.cmd {
+            paper.apply(command);
+        }
+        paper.flush();
+
+        paper.lines.iter().fold(String::new(), |mut out, line| {
+            out.push_str(line.trim_end());
+            out.push('\n');
+            out
+        })
+    }
+
+    /// Draw the paper printed by the recorded commands on the printer of `config` as a PNG image, one pixel for each
+    /// dot, e.g. to compare what a job prints with a reference image in snapshot tests.
+    ///
+    /// The paper is drawn like [`Receipt::to_png()`](crate::Receipt::to_png()) draws it, the margins being the ones
+    /// set by the recorded commands, if any.
+    #[cfg(feature = "image")]
+    pub fn to_png(&self, config: &PrinterConfig) -> Result<Vec<u8>, Error> {
+        let raster = preview::rasterize(&self.cmd, config.dots_per_line);
+
+        preview::png(&raster, config.dpi)
+    }
+}
+
+impl CommandBuilder for Emulator {
+    fn push(&mut self, command: Command) -> &mut Self {
+        self.cmd.push(command);
+
+        self
+    }
+
+    fn model(&self) -> Option<Model> {
+        None
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        Some(Profile::emulated())
+    }
+}
+
+/// Paper drawn by [`Emulator::render()`].
+#[derive(Default)]
+struct Paper {
+    lines: Vec<String>,
+    // text and bit image rows of the line being printed
+    text: String,
+    rows: Vec<String>,
+}
+
+impl Paper {
+    fn apply(&mut self, command: &Command) {
+        match command {
+            Command::Initialize => {
+                self.text.clear();
+                self.rows.clear();
+            }
+            Command::Raw(bytes) => {
+                for &byte in bytes {
+                    match byte {
+                        b'\n' => self.print(),
+                        b' '..=b'~' => self.text.push(byte as char),
+                        _ => {}
+                    }
+                }
+            }
+            Command::Print => self.print(),
+            Command::PrintAndFeed { unit, amount } => {
+                self.flush();
+                let lines = match unit {
+                    FeedUnit::Lines => *amount as u16,
+                    FeedUnit::Inches => {
+                        (*amount as u16 + LINE_SPACING_DOTS / 2) / LINE_SPACING_DOTS
+                    }
+                };
+                self.feed(lines);
+            }
+            Command::PrintAndReverseFeed { .. }
+            | Command::FeedToMark
+            | Command::PrintPage
+            | Command::PrintPageAndExit => self.flush(),
+            Command::BitImage { mode, width, data } => {
+                let bank = match mode {
+                    BitImageMode::Dots8SingleDensity | BitImageMode::Dots8DoubleDensity => 8,
+                    BitImageMode::Dots24SingleDensity | BitImageMode::Dots24DoubleDensity => 24,
+                };
+                let k = *width as usize * bank / 8;
+                if k == 0 {
+                    return;
+                }
+                for bytes in data.chunks_exact(k) {
+                    for row in 0..bank {
+                        let line = bytes
+                            .chunks_exact(bank / 8)
+                            .map(|column| {
+                                if column[row / 8] & (0x80 >> (row % 8)) != 0 {
+                                    '#'
+                                } else {
+                                    ' '
+                                }
+                            })
+                            .collect();
+                        self.rows.push(line);
+                    }
+                }
+            }
+            Command::Barcode { system, data } => {
+                self.flush();
+                self.lines.push(format!(
+                    "[{system:?} {}]",
+                    String::from_utf8_lossy(&data[..data.len().min(u8::MAX as usize)])
+                ));
+            }
+            Command::QrCode { data, .. } => {
+                self.flush();
+                self.lines
+                    .push(format!("[QR {}]", String::from_utf8_lossy(data)));
+            }
+            Command::CutPaper(cut_type) | Command::FeedAndCutPaper { cut_type, .. } => {
+                self.flush();
+                self.lines.push(
+                    match cut_type {
+                        CutType::TotalCut => "--- cut ---",
+                        CutType::PartialCut => "--- partial cut ---",
+                    }
+                    .to_string(),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Print the line being printed, drawing a blank line if it's empty.
+    fn print(&mut self) {
+        if self.text.is_empty() && self.rows.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.flush();
+    }
+
+    /// Print the line being printed, if it isn't empty.
+    fn flush(&mut self) {
+        self.lines.append(&mut self.rows);
+        if !self.text.is_empty() {
+            self.lines.push(core::mem::take(&mut self.text));
+        }
+    }
+
+    fn feed(&mut self, lines: u16) {
+        for _ in 0..lines {
+            self.lines.push(String::new());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JobBuilder, Length};
+
+    #[test]
+    fn test_emulator() {
+        let mut emulator = Emulator::new();
+        emulator
+            .push(Command::PresentTicket(10))
+            .raw(b"A\nB")
+            .print()
+            .print()
+            .bitmap(
+                8,
+                2,
+                &[0b1000_0001, 0b0111_1110],
+                BitImageMode::Dots8SingleDensity,
+            )
+            .unwrap()
+            .print()
+            .feed(Length::dots(68))
+            .feed_and_cut_paper(CutType::PartialCut, 0);
+        // every command is accepted, and the 6 blank rows of the bank are followed by the 2 lines fed
+        let profile = emulator.profile().unwrap();
+        assert!(emulator
+            .commands()
+            .iter()
+            .all(|command| profile.supports(command)));
+        assert_eq!(
+            emulator.render(),
+            "A\nB\n\n#      #\n ######\n\n\n\n\n\n\n\n\n--- partial cut ---\n"
+        );
+
+        let job = JobBuilder::new().raw(b"C").print().build();
+        emulator.clear().print_job(&job);
+        assert_eq!(emulator.trace(), ["\"C\"", "LF"]);
+    }
+
+    #[test]
+    fn test_print_bytes() {
+        let job = JobBuilder::new()
+            .bold(true)
+            .raw(b"CAFE")
+            .print()
+            .cut_paper(CutType::TotalCut)
+            .build();
+        let mut emulator = Emulator::new();
+        emulator.print_bytes(&job.to_bytes());
+        assert_eq!(emulator.commands(), job.commands());
+
+        #[cfg(feature = "image")]
+        {
+            let png = emulator.to_png(&PrinterConfig::default()).unwrap();
+            assert_eq!(png[..8], *b"\x89PNG\r\n\x1a\n");
+            // 384 dots wide
+            assert_eq!(png[16..20], [0, 0, 1, 128]);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Error type of this crate.
+
+use alloc::{borrow::Cow, string::String};
+#[cfg(feature = "std")]
+use std::{io, path::PathBuf};
+
+use crate::{InsufficientMemory, PrinterFault};
+
+/// Errors returned by [`CustomPrinter`](crate::CustomPrinter).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The device node couldn't be opened
+    #[cfg(feature = "std")]
+    #[error("failed to open device {}", path.display())]
+    DeviceOpen {
+        /// Path of the device node
+        path: PathBuf,
+        /// Underlying I/O error
+        #[source]
+        source: io::Error,
+    },
+    /// The image couldn't be opened or decoded
+    #[cfg(feature = "image")]
+    #[error("failed to decode image")]
+    ImageDecode(#[from] image::ImageError),
+    /// The image was decoded but can't be printed
+    #[error("unsupported image: {0}")]
+    UnsupportedImage(String),
+    /// The printer was created without any device by [`CustomPrinter::detached()`](crate::CustomPrinter::detached())
+    #[error("no device is attached to the printer")]
+    NoDevice,
+    /// Data is left in the print buffer without any command printing it, e.g. a bit image not followed by
+    /// [`CommandBuilder::print()`](crate::CommandBuilder::print())
+    #[error("data of command {index} isn't printed, append print() or a feed")]
+    UnprintedData {
+        /// Index of the first command whose data isn't printed
+        index: usize,
+    },
+    /// Constructed commands are left pending when finishing, see
+    /// [`CustomPrinter::finish()`](crate::CustomPrinter::finish())
+    #[error("{count} commands are pending, run() or discard() them")]
+    PendingCommands {
+        /// Number of the pending commands
+        count: usize,
+    },
+    /// The constructed commands exceed the limit set by
+    /// [`CustomPrinter::set_max_size()`](crate::CustomPrinter::set_max_size())
+    #[error("commands exceed the maximum size of {max_size} bytes")]
+    TooLarge {
+        /// Maximum size of the commands in bytes
+        max_size: usize,
+    },
+    /// Commands couldn't be written to the printer
+    #[cfg(feature = "std")]
+    #[error("failed to write to the printer")]
+    Write(#[source] io::Error),
+    /// Responses couldn't be read from the printer
+    #[cfg(feature = "std")]
+    #[error("failed to read from the printer")]
+    Read(#[source] io::Error),
+    /// The printer didn't respond or complete in time
+    #[error("timed out waiting for the printer")]
+    Timeout,
+    /// The printer sent a response that couldn't be parsed
+    #[error("invalid response from the printer")]
+    InvalidResponse,
+    /// A command isn't supported by the printer, see [`Profile::supports()`](crate::Profile::supports()) and
+    /// [`CommandSet::supports()`](crate::CommandSet::supports())
+    #[error("command {command} isn't supported by {printer}")]
+    Unsupported {
+        /// Kind of the command, see [`Command::kind()`](crate::Command::kind())
+        command: &'static str,
+        /// Name of the profile of the printer, see [`Profile::name`](crate::Profile::name), or of the command set,
+        /// see [`CommandSet::name()`](crate::CommandSet::name())
+        printer: Cow<'static, str>,
+    },
+    /// The output buffer is too small, see [`convert_bitmap_to_bitimage_into()`](crate::convert_bitmap_to_bitimage_into())
+    #[error("output buffer is too small, {required} bytes are required")]
+    BufferTooSmall {
+        /// Number of bytes required
+        required: usize,
+    },
+    /// A parameter is invalid or missing
+    #[error("invalid parameter: {0}")]
+    InvalidParameter(String),
+    /// A job file couldn't be written or read
+    #[cfg(feature = "std")]
+    #[error("failed to access job file {}", path.display())]
+    JobFile {
+        /// Path of the job file
+        path: PathBuf,
+        /// Underlying I/O error
+        #[source]
+        source: io::Error,
+    },
+    /// A saved or serialized job couldn't be parsed
+    #[error("invalid job format: {0}")]
+    InvalidFormat(String),
+    /// A printer profile file couldn't be read
+    #[cfg(feature = "std")]
+    #[error("failed to read profile file {}", path.display())]
+    ProfileFile {
+        /// Path of the profile file
+        path: PathBuf,
+        /// Underlying I/O error
+        #[source]
+        source: io::Error,
+    },
+    /// The file of a [`FileSequence`](crate::FileSequence) couldn't be read or written
+    #[cfg(feature = "std")]
+    #[error("failed to access sequence file {}", path.display())]
+    SequenceFile {
+        /// Path of the sequence file
+        path: PathBuf,
+        /// Underlying I/O error
+        #[source]
+        source: io::Error,
+    },
+    /// A printer profile couldn't be parsed, see `Profile::from_json()` and `Profile::from_toml()`
+    #[error("invalid printer profile: {0}")]
+    InvalidProfile(String),
+    /// A receipt template couldn't be parsed or instantiated, see `Template`
+    #[error("invalid template: {0}")]
+    InvalidTemplate(String),
+    /// The printer reported a fault
+    #[error(transparent)]
+    Fault(#[from] PrinterFault),
+    /// An asset doesn't fit in the NV memory
+    #[error(transparent)]
+    InsufficientMemory(#[from] InsufficientMemory),
+}
@@ -0,0 +1,213 @@
+//! Estimation of the paper length and print duration of the commands.
+
+use core::time::Duration;
+
+use crate::{BitImageMode, Command, Density, FeedUnit, PrinterConfig, Profile, Speed};
+
+/// Line spacing after initializing the printer in millimeters, i.e. 1/6 inch.
+const LINE_SPACING_MM: f32 = 25.4 / 6.0;
+
+/// Time taken by the autocutter for a cut.
+const CUT_DURATION: Duration = Duration::from_millis(300);
+
+/// Predicted paper length and print duration of commands, returned by
+/// [`Job::estimate()`](crate::Job::estimate()), [`JobBuilder::estimate()`](crate::JobBuilder::estimate())
+/// and [`CustomPrinter::estimate()`](crate::CustomPrinter::estimate()).
+///
+/// The estimate is approximate: the printer is assumed to feed 150, 100 and 50 mm/s at the high, normal and
+/// low speeds, slowed down by 15% and 30% at the +25% and +50% densities, with a line spacing of 1/6 inch.
+/// Text in [`Command::Raw`] bytes, barcodes and feeds to the black mark aren't taken into account.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Estimate {
+    /// Length of the fed paper in millimeters
+    pub length_mm: f32,
+    /// Duration of feeding the paper, cutting it, sounding the buzzer and pulsing the cash drawer
+    pub duration: Duration,
+}
+
+impl Estimate {
+    /// Feed the paper by `mm` forwards, or backwards if `mm` is negative.
+    fn feed(&mut self, mm: f32, speed: Speed, density: Density) {
+        let rate = match speed {
+            Speed::High => 150.0,
+            Speed::Normal => 100.0,
+            Speed::Low => 50.0,
+        } * match density {
+            Density::Plus25 => 0.85,
+            Density::Plus50 => 0.7,
+            Density::Minus50 | Density::Minus25 | Density::Zero => 1.0,
+        };
+        self.length_mm = (self.length_mm + mm).max(0.0);
+        self.duration += Duration::from_secs_f32(mm.abs() / rate);
+    }
+}
+
+/// Estimate the paper length and print duration of `commands` on the printer of `config`, including the feeds
+/// to the cut position if `profile` is known.
+pub(crate) fn estimate<'a>(
+    commands: impl IntoIterator<Item = &'a Command>,
+    profile: Option<&Profile>,
+    config: &PrinterConfig,
+) -> Estimate {
+    let dot_mm = 25.4 / config.dpi as f32;
+    let mut estimate = Estimate::default();
+    let mut speed = config.speed.unwrap_or(Speed::Normal);
+    let mut density = config.density.unwrap_or(Density::Zero);
+    let mut unit_mm = dot_mm;
+    // height of the bit images in the line being printed
+    let mut image_mm: f32 = 0.0;
+    let mut page_mode = false;
+    let mut page_mm: f32 = 0.0;
+
+    for command in commands {
+        match command {
+            Command::Initialize => {
+                speed = config.speed.unwrap_or(Speed::Normal);
+                density = config.density.unwrap_or(Density::Zero);
+                unit_mm = dot_mm;
+                image_mm = 0.0;
+                page_mode = false;
+            }
+            Command::Print if !page_mode => {
+                estimate.feed(image_mm.max(LINE_SPACING_MM), speed, density);
+                image_mm = 0.0;
+            }
+            Command::PrintAndFeed { unit, amount } if !page_mode => {
+                let mm = match unit {
+                    FeedUnit::Inches => *amount as f32 * unit_mm,
+                    FeedUnit::Lines => *amount as f32 * LINE_SPACING_MM,
+                };
+                estimate.feed(image_mm.max(mm), speed, density);
+                image_mm = 0.0;
+            }
+            Command::PrintAndReverseFeed { unit, amount } if !page_mode => {
+                let mm = match unit {
+                    FeedUnit::Inches => *amount as f32 * unit_mm,
+                    FeedUnit::Lines => *amount as f32 * LINE_SPACING_MM,
+                };
+                estimate.feed(image_mm, speed, density);
+                estimate.feed(-mm, speed, density);
+                image_mm = 0.0;
+            }
+            Command::MotionUnits { y, .. } => {
+                unit_mm = if *y == 0 { dot_mm } else { 25.4 / *y as f32 };
+            }
+            Command::Speed(s) => speed = *s,
+            Command::Density(d) => density = *d,
+            Command::BitImage { mode, width, data } if !page_mode && *width > 0 => {
+                let bank = match mode {
+                    BitImageMode::Dots8SingleDensity | BitImageMode::Dots8DoubleDensity => 8,
+                    BitImageMode::Dots24SingleDensity | BitImageMode::Dots24DoubleDensity => 24,
+                };
+                let banks = data.len() / (*width as usize * bank / 8);
+                image_mm = image_mm.max((banks * bank) as f32 * dot_mm);
+            }
+            Command::EnterPageMode => page_mode = true,
+            Command::ExitPageMode => {
+                page_mode = false;
+                page_mm = 0.0;
+            }
+            // the page extends to the bottom of its lowest print area
+            Command::PageArea { y, height, .. } => {
+                page_mm = page_mm.max((*y as u32 + *height as u32) as f32 * unit_mm);
+            }
+            Command::PrintPage | Command::PrintPageAndExit if page_mode => {
+                estimate.feed(page_mm, speed, density);
+                page_mode = matches!(command, Command::PrintPage);
+                if !page_mode {
+                    page_mm = 0.0;
+                }
+            }
+            Command::CutPaper(_) => estimate.duration += CUT_DURATION,
+            Command::FeedAndCutPaper { amount, .. } => {
+                let cutter_mm = profile.map_or(0.0, |profile| profile.head_to_cutter_mm);
+                estimate.feed(cutter_mm + *amount as f32 * unit_mm, speed, density);
+                estimate.duration += CUT_DURATION;
+            }
+            Command::Beep { count, duration_ms } => {
+                estimate.duration += Duration::from_millis(*count as u64 * *duration_ms as u64);
+            }
+            Command::OpenDrawer { on_ms, off_ms, .. } => {
+                estimate.duration += Duration::from_millis(*on_ms as u64 + *off_ms as u64);
+            }
+            _ => {}
+        }
+    }
+
+    estimate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandBuilder, CutType, JobBuilder, Model};
+    use alloc::vec;
+
+    #[test]
+    fn test_estimate() {
+        let config = PrinterConfig::default();
+        let job = JobBuilder::new()
+            .print()
+            .print_and_feed_paper(FeedUnit::Lines, 2)
+            .print_and_feed_paper(FeedUnit::Inches, 203)
+            .cut_paper(CutType::TotalCut)
+            .build();
+        let estimate = job.estimate(&config);
+        assert!((estimate.length_mm - (3.0 * LINE_SPACING_MM + 25.4)).abs() < 0.01);
+        let feed = (3.0 * LINE_SPACING_MM + 25.4) / 100.0;
+        assert!((estimate.duration.as_secs_f32() - feed - 0.3).abs() < 0.01);
+
+        // slower at low speed and high density
+        let slow = JobBuilder::new()
+            .speed(&Speed::Low)
+            .density(&Density::Plus50)
+            .print_and_feed_paper(FeedUnit::Inches, 203)
+            .build()
+            .estimate(&config);
+        assert!((slow.duration.as_secs_f32() - 25.4 / 35.0).abs() < 0.01);
+
+        // a bit image is taller than the line spacing
+        let image = estimate_of(vec![
+            Command::BitImage {
+                mode: BitImageMode::Dots24SingleDensity,
+                width: 8,
+                data: vec![0; 8 * 3 * 2],
+            },
+            Command::Print,
+        ]);
+        assert!((image.length_mm - 48.0 * 25.4 / 203.0).abs() < 0.01);
+
+        // reverse feeds reduce the length
+        let reverse = estimate_of(vec![
+            Command::Print,
+            Command::PrintAndReverseFeed {
+                unit: FeedUnit::Lines,
+                amount: 1,
+            },
+        ]);
+        assert_eq!(reverse.length_mm, 0.0);
+
+        // a page extends to the bottom of its lowest area
+        let page = JobBuilder::new()
+            .enter_page_mode()
+            .page_area(0, 100, 200, 103)
+            .page_area(200, 0, 100, 50)
+            .print_page_and_exit()
+            .build()
+            .estimate(&config);
+        assert!((page.length_mm - 25.4).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_estimate_cut() {
+        let mut builder = JobBuilder::new();
+        builder.feed_and_cut_paper(CutType::TotalCut, 0);
+        assert_eq!(builder.estimate().length_mm, 0.0);
+        builder.set_model(Some(Model::Tg2480h));
+        assert_eq!(builder.estimate().length_mm, 14.5);
+    }
+
+    fn estimate_of(commands: alloc::vec::Vec<Command>) -> Estimate {
+        estimate(&commands, None, &PrinterConfig::default())
+    }
+}
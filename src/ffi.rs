@@ -0,0 +1,359 @@
+//! C ABI of the printer for C/C++ applications, see `include/custom_printer.h`.
+//!
+//! Every function taking a printer handle expects one returned by [`custom_printer_new()`] or
+//! [`custom_printer_detached()`] and not yet freed by [`custom_printer_free()`]. Functions returning `int`
+//! return [`CUSTOM_PRINTER_OK`] on success or a negative error code.
+
+use std::{
+    ffi::{c_char, c_int, CStr},
+    slice,
+};
+
+use crate::{BitImageMode, CommandBuilder, CustomPrinter, CutType, Error, FeedUnit};
+
+/// The function succeeded
+pub const CUSTOM_PRINTER_OK: c_int = 0;
+/// A handle, string or buffer argument is null or invalid
+pub const CUSTOM_PRINTER_INVALID_ARGUMENT: c_int = -1;
+/// The device node couldn't be opened
+pub const CUSTOM_PRINTER_DEVICE_OPEN: c_int = -2;
+/// Writing to or reading from the printer failed
+pub const CUSTOM_PRINTER_IO: c_int = -3;
+/// The printer didn't respond in time
+pub const CUSTOM_PRINTER_TIMEOUT: c_int = -4;
+/// The image or bitmap can't be printed
+pub const CUSTOM_PRINTER_UNSUPPORTED_IMAGE: c_int = -5;
+/// The commands were refused before sending them, e.g. data left in the print buffer
+pub const CUSTOM_PRINTER_REFUSED: c_int = -6;
+/// The printer has no device, or reported a fault or an invalid response
+pub const CUSTOM_PRINTER_PRINTER: c_int = -7;
+
+/// Status of the printer returned by [`custom_printer_status()`], each field is 0 or 1.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CustomPrinterStatus {
+    /// The paper has run out
+    pub paper_end: u8,
+    /// The paper is near its end
+    pub paper_near_end: u8,
+    /// A ticket is present in the output
+    pub ticket_present: u8,
+    /// The cover is open
+    pub cover_open: u8,
+    /// The print head is overheated
+    pub head_overheated: u8,
+    /// The autocutter has an error
+    pub cutter_error: u8,
+    /// The printer is idle
+    pub idle: u8,
+}
+
+fn error_code(error: &Error) -> c_int {
+    match error {
+        Error::DeviceOpen { .. } => CUSTOM_PRINTER_DEVICE_OPEN,
+        Error::Write(_) | Error::Read(_) | Error::JobFile { .. } | Error::ProfileFile { .. } => {
+            CUSTOM_PRINTER_IO
+        }
+        Error::Timeout => CUSTOM_PRINTER_TIMEOUT,
+        #[cfg(feature = "image")]
+        Error::ImageDecode(_) => CUSTOM_PRINTER_UNSUPPORTED_IMAGE,
+        Error::UnsupportedImage(_) => CUSTOM_PRINTER_UNSUPPORTED_IMAGE,
+        Error::UnprintedData { .. }
+        | Error::TooLarge { .. }
+        | Error::Unsupported { .. }
+        | Error::PendingCommands { .. } => CUSTOM_PRINTER_REFUSED,
+        Error::InvalidParameter(_)
+        | Error::InvalidFormat(_)
+        | Error::InvalidProfile(_)
+        | Error::InvalidTemplate(_)
+        | Error::BufferTooSmall { .. } => CUSTOM_PRINTER_INVALID_ARGUMENT,
+        _ => CUSTOM_PRINTER_PRINTER,
+    }
+}
+
+fn result_code<T>(result: Result<T, Error>) -> c_int {
+    match result {
+        Ok(_) => CUSTOM_PRINTER_OK,
+        Err(e) => error_code(&e),
+    }
+}
+
+fn bit_image_mode(mode: c_int) -> Option<BitImageMode> {
+    Some(match mode {
+        0 => BitImageMode::Dots8SingleDensity,
+        1 => BitImageMode::Dots8DoubleDensity,
+        2 => BitImageMode::Dots24SingleDensity,
+        3 => BitImageMode::Dots24DoubleDensity,
+        _ => return None,
+    })
+}
+
+/// Run `f` on the printer behind `printer`, or return [`CUSTOM_PRINTER_INVALID_ARGUMENT`] if it's null.
+///
+/// # Safety
+///
+/// `printer` must be null or a valid handle.
+unsafe fn with_printer(
+    printer: *mut CustomPrinter,
+    f: impl FnOnce(&mut CustomPrinter) -> c_int,
+) -> c_int {
+    match printer.as_mut() {
+        Some(printer) => f(printer),
+        None => CUSTOM_PRINTER_INVALID_ARGUMENT,
+    }
+}
+
+/// Open the printer connected to the device node `dev`, returning null if it can't be opened.
+///
+/// # Safety
+///
+/// `dev` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn custom_printer_new(dev: *const c_char) -> *mut CustomPrinter {
+    if dev.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let dev = CStr::from_ptr(dev);
+    #[cfg(unix)]
+    let printer = {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+        CustomPrinter::new(OsStr::from_bytes(dev.to_bytes()))
+    };
+    #[cfg(not(unix))]
+    let printer = match dev.to_str() {
+        Ok(dev) => CustomPrinter::new(dev),
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    match printer {
+        Ok(printer) => Box::into_raw(Box::new(printer)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Create a printer without any device, see [`CustomPrinter::detached()`].
+#[no_mangle]
+pub extern "C" fn custom_printer_detached() -> *mut CustomPrinter {
+    Box::into_raw(Box::new(CustomPrinter::detached()))
+}
+
+/// Free the printer, discarding the pending commands.
+///
+/// # Safety
+///
+/// `printer` must be null or a valid handle, which mustn't be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn custom_printer_free(printer: *mut CustomPrinter) {
+    if !printer.is_null() {
+        let mut printer = Box::from_raw(printer);
+        printer.discard();
+    }
+}
+
+/// Append the NUL-terminated `text` as it is, in the code page of the printer.
+///
+/// # Safety
+///
+/// `printer` must be a valid handle and `text` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn custom_printer_text(
+    printer: *mut CustomPrinter,
+    text: *const c_char,
+) -> c_int {
+    if text.is_null() {
+        return CUSTOM_PRINTER_INVALID_ARGUMENT;
+    }
+
+    with_printer(printer, |printer| {
+        printer.raw(CStr::from_ptr(text).to_bytes());
+        CUSTOM_PRINTER_OK
+    })
+}
+
+/// Print the data in the print buffer and feed the paper by `lines` lines, or by one line if `lines` is 0.
+///
+/// # Safety
+///
+/// `printer` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn custom_printer_print(printer: *mut CustomPrinter, lines: u8) -> c_int {
+    with_printer(printer, |printer| {
+        match lines {
+            0 => printer.print(),
+            lines => printer.print_and_feed_paper(FeedUnit::Lines, lines),
+        };
+        CUSTOM_PRINTER_OK
+    })
+}
+
+/// Append the bit image of the 1 bpp `bitmap` of `len` bytes, `width` x `height` dots, in `mode`
+/// (0 to 3 for 8 dot single, 8 dot double, 24 dot single and 24 dot double density), see
+/// [`CommandBuilder::bitmap()`].
+///
+/// # Safety
+///
+/// `printer` must be a valid handle and `bitmap` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn custom_printer_bitmap(
+    printer: *mut CustomPrinter,
+    width: usize,
+    height: usize,
+    bitmap: *const u8,
+    len: usize,
+    mode: c_int,
+) -> c_int {
+    let Some(mode) = bit_image_mode(mode) else {
+        return CUSTOM_PRINTER_INVALID_ARGUMENT;
+    };
+    if bitmap.is_null() {
+        return CUSTOM_PRINTER_INVALID_ARGUMENT;
+    }
+
+    with_printer(printer, |printer| {
+        result_code(printer.bitmap(width, height, slice::from_raw_parts(bitmap, len), mode))
+    })
+}
+
+/// Append the bit image from the image file `path` in `mode`, see [`custom_printer_bitmap()`] and
+/// [`CommandBuilder::bit_image()`].
+///
+/// # Safety
+///
+/// `printer` must be a valid handle and `path` a NUL-terminated string.
+#[cfg(feature = "image")]
+#[no_mangle]
+pub unsafe extern "C" fn custom_printer_image(
+    printer: *mut CustomPrinter,
+    path: *const c_char,
+    mode: c_int,
+) -> c_int {
+    let Some(mode) = bit_image_mode(mode) else {
+        return CUSTOM_PRINTER_INVALID_ARGUMENT;
+    };
+    let Some(path) = path
+        .as_ref()
+        .and_then(|path| CStr::from_ptr(path).to_str().ok())
+    else {
+        return CUSTOM_PRINTER_INVALID_ARGUMENT;
+    };
+
+    with_printer(printer, |printer| {
+        result_code(printer.bit_image(path, mode))
+    })
+}
+
+/// Feed the paper to the cut position and cut it, partially if `partial` isn't 0.
+///
+/// # Safety
+///
+/// `printer` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn custom_printer_cut(printer: *mut CustomPrinter, partial: c_int) -> c_int {
+    let cut_type = match partial {
+        0 => CutType::TotalCut,
+        _ => CutType::PartialCut,
+    };
+
+    with_printer(printer, |printer| {
+        printer.feed_and_cut_paper(cut_type, 0);
+        CUSTOM_PRINTER_OK
+    })
+}
+
+/// Send the pending commands to the printer, see [`CustomPrinter::run()`].
+///
+/// # Safety
+///
+/// `printer` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn custom_printer_run(printer: *mut CustomPrinter) -> c_int {
+    with_printer(printer, |printer| result_code(printer.run()))
+}
+
+/// Query the status of the printer into `status`, see [`CustomPrinter::status()`].
+///
+/// # Safety
+///
+/// `printer` must be a valid handle and `status` must point to a writable [`CustomPrinterStatus`].
+#[no_mangle]
+pub unsafe extern "C" fn custom_printer_status(
+    printer: *mut CustomPrinter,
+    status: *mut CustomPrinterStatus,
+) -> c_int {
+    let Some(out) = status.as_mut() else {
+        return CUSTOM_PRINTER_INVALID_ARGUMENT;
+    };
+
+    with_printer(printer, |printer| match printer.status() {
+        Ok(status) => {
+            *out = CustomPrinterStatus {
+                paper_end: status.paper_end().into(),
+                paper_near_end: status.paper_near_end().into(),
+                ticket_present: status.ticket_present().into(),
+                cover_open: status.cover_open().into(),
+                head_overheated: status.head_overheated().into(),
+                cutter_error: status.cutter_error().into(),
+                idle: status.is_idle().into(),
+            };
+            CUSTOM_PRINTER_OK
+        }
+        Err(e) => error_code(&e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi() {
+        unsafe {
+            assert!(custom_printer_new(c"/nonexistent/lp0".as_ptr()).is_null());
+            assert!(custom_printer_new(std::ptr::null()).is_null());
+
+            let printer = custom_printer_new(c"/dev/null".as_ptr());
+            assert!(!printer.is_null());
+            assert_eq!(
+                custom_printer_text(printer, c"Hi".as_ptr()),
+                CUSTOM_PRINTER_OK
+            );
+            assert_eq!(custom_printer_print(printer, 0), CUSTOM_PRINTER_OK);
+            let bitmap = [0xFF_u8; 8];
+            assert_eq!(
+                custom_printer_bitmap(printer, 8, 8, bitmap.as_ptr(), bitmap.len(), 0),
+                CUSTOM_PRINTER_OK
+            );
+            assert_eq!(
+                custom_printer_bitmap(printer, 0, 8, bitmap.as_ptr(), bitmap.len(), 0),
+                CUSTOM_PRINTER_UNSUPPORTED_IMAGE
+            );
+            assert_eq!(
+                custom_printer_bitmap(printer, 8, 8, bitmap.as_ptr(), bitmap.len(), 4),
+                CUSTOM_PRINTER_INVALID_ARGUMENT
+            );
+            // the bit image isn't printed
+            assert_eq!(custom_printer_cut(printer, 0), CUSTOM_PRINTER_OK);
+            assert_eq!(custom_printer_run(printer), CUSTOM_PRINTER_REFUSED);
+            (*printer).discard();
+
+            assert_eq!(custom_printer_print(printer, 3), CUSTOM_PRINTER_OK);
+            assert_eq!(custom_printer_cut(printer, 1), CUSTOM_PRINTER_OK);
+            assert_eq!((*printer).to_bytes(), &[0x1B, 0x64, 3, 0x1D, 0x56, 0x42, 0]);
+            assert_eq!(custom_printer_run(printer), CUSTOM_PRINTER_OK);
+            custom_printer_free(printer);
+
+            let printer = custom_printer_detached();
+            let mut status = CustomPrinterStatus::default();
+            assert_eq!(
+                custom_printer_status(printer, &mut status),
+                CUSTOM_PRINTER_PRINTER
+            );
+            custom_printer_free(printer);
+            assert_eq!(
+                custom_printer_run(std::ptr::null_mut()),
+                CUSTOM_PRINTER_INVALID_ARGUMENT
+            );
+        }
+    }
+}
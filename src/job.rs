@@ -0,0 +1,771 @@
+//! Jobs built without any device.
+
+#[cfg(feature = "json")]
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(any(feature = "std", feature = "json"))]
+use alloc::{format, string::ToString};
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+#[cfg(feature = "json")]
+use crate::json::{self, Value};
+use crate::{
+    command, estimate, validate, Command, CommandBuilder, Error, Estimate, Model, PrinterConfig,
+    Problem, Profile, Transport,
+};
+
+// Framed binary format of job files
+#[cfg(feature = "std")]
+const JOB_FILE_MAGIC: &[u8] = b"CPJB";
+#[cfg(feature = "std")]
+const JOB_FILE_VERSION: u8 = 2;
+
+/// Version of the JSON format of jobs written by [`Job::to_json()`].
+#[cfg(feature = "json")]
+pub const JOB_FORMAT_VERSION: u32 = 1;
+
+/// Commands built by [`JobBuilder`], e.g. to be transmitted by [`CustomPrinter::print_job()`](crate::CustomPrinter::print_job())
+/// or stored as bytes by [`to_bytes()`](Job::to_bytes()).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Job {
+    commands: Vec<Command>,
+}
+
+impl Job {
+    /// The commands of the job.
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Whether the job has no commands.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Estimate the paper length and print duration of the job on the printer of `config`, e.g. to warn about
+    /// long tickets or budget paper rolls, see [`Estimate`].
+    ///
+    /// The feeds to the cut position aren't included as the profile isn't known, see
+    /// [`JobBuilder::estimate()`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, FeedUnit, JobBuilder, PrinterConfig};
+    /// let job = JobBuilder::new().print_and_feed_paper(FeedUnit::Inches, 203).build();
+    /// let estimate = job.estimate(&PrinterConfig::default());
+    /// assert!((estimate.length_mm - 25.4).abs() < 0.01);
+    /// ```
+    pub fn estimate(&self, config: &PrinterConfig) -> Estimate {
+        estimate::estimate(&self.commands, None, config)
+    }
+
+    /// Check the job against `profile`, if known, and the printer of `config` without any device, returning all
+    /// the problems found, e.g. unsupported commands, bit images wider than the paper, parameters out of range
+    /// and data left unprinted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CutType, JobBuilder, Model, PrinterConfig, Profile};
+    /// let job = JobBuilder::new().cut_paper(CutType::PartialCut).build();
+    /// let profile = Profile::for_model(Model::Tg2480h);
+    /// for problem in job.validate(Some(&profile), &PrinterConfig::for_profile(&profile)) {
+    ///     println!("command {}: {}", problem.index, problem.error);
+    /// }
+    /// ```
+    pub fn validate(&self, profile: Option<&Profile>, config: &PrinterConfig) -> Vec<Problem> {
+        validate::validate(&self.commands, profile, config)
+    }
+
+    /// The bytes of the commands of the job.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        command::encode(&self.commands)
+    }
+
+    /// Transmit the bytes of the commands of the job over `transport`, e.g. a serial port, see [`Transport`].
+    pub fn transmit<T: Transport>(&self, transport: &mut T) -> Result<(), T::Error> {
+        transport.transmit(&self.to_bytes())
+    }
+
+    /// Save the job to the file `path` in a compact framed binary format, e.g. to print it later by
+    /// [`CustomPrinter::replay()`](crate::CustomPrinter::replay()) or to recover receipts which were generated but
+    /// never transmitted.
+    ///
+    /// The file starts with a magic number and a version, followed by each command framed by its length, and ends
+    /// with a CRC-32 of the preceding bytes. A command is a tag of its kind followed by its parameters, so the job
+    /// is loaded back with the same typed commands.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CutType, Job, JobBuilder};
+    /// let job = JobBuilder::new().print().cut_paper(CutType::TotalCut).build();
+    /// let path = std::env::temp_dir().join("receipt.job");
+    /// job.save(&path).unwrap();
+    ///
+    /// assert_eq!(Job::load(&path).unwrap(), job);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        fs::write(path, self.to_frames()).map_err(|source| Error::JobFile {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Load a job from the file `path` saved by [`save()`](Job::save()).
+    ///
+    /// The commands of the files of version 1, which saved the bytes of each command, are loaded as [`Command::Raw`]
+    /// of their bytes.
+    ///
+    /// Returns [`Error::InvalidFormat`] if the file isn't a job file, its version isn't supported or it's truncated or
+    /// corrupted.
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let frames = fs::read(path).map_err(|source| Error::JobFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Self::from_frames(&frames)
+    }
+
+    #[cfg(feature = "std")]
+    fn to_frames(&self) -> Vec<u8> {
+        let mut out = Vec::from(JOB_FILE_MAGIC);
+        out.push(JOB_FILE_VERSION);
+        out.extend_from_slice(&(self.commands.len() as u32).to_le_bytes());
+
+        let mut bytes = Vec::new();
+        for command in &self.commands {
+            bytes.clear();
+            command.to_frame(&mut bytes);
+            out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            out.extend_from_slice(&bytes);
+        }
+        out.extend_from_slice(&crc32(&out).to_le_bytes());
+
+        out
+    }
+
+    #[cfg(feature = "std")]
+    fn from_frames(frames: &[u8]) -> Result<Self, Error> {
+        let invalid = |message: &str| Error::InvalidFormat(message.to_string());
+
+        let (data, crc) = frames
+            .split_last_chunk::<4>()
+            .ok_or_else(|| invalid("truncated job file"))?;
+        let rest = data
+            .strip_prefix(JOB_FILE_MAGIC)
+            .ok_or_else(|| invalid("not a job file"))?;
+        if crc32(data) != u32::from_le_bytes(*crc) {
+            return Err(invalid("checksum mismatch"));
+        }
+        let (version, mut rest) = rest
+            .split_first()
+            .ok_or_else(|| invalid("truncated job file"))?;
+        if !(1..=JOB_FILE_VERSION).contains(version) {
+            return Err(Error::InvalidFormat(format!(
+                "unsupported version {version}"
+            )));
+        }
+
+        let count = take_word(&mut rest)?;
+        let mut commands = Vec::new();
+        for index in 0..count {
+            let len = take_word(&mut rest)?;
+            let frame = take(&mut rest, len)?;
+            commands.push(match version {
+                1 => Command::Raw(frame.to_vec()),
+                _ => Command::from_frame(frame).map_err(|message| {
+                    Error::InvalidFormat(format!("command {index}: {message}"))
+                })?,
+            });
+        }
+        if !rest.is_empty() {
+            return Err(invalid("trailing bytes in job file"));
+        }
+
+        Ok(Self { commands })
+    }
+
+    /// Serialize the job into versioned JSON, e.g. to persist it, send it to another service or replay it later
+    /// by [`from_json()`](Job::from_json()).
+    ///
+    /// Every command is an object tagged by its name in `command`, and bytes are written in hexadecimal.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CutType, Job, JobBuilder};
+    /// let job = JobBuilder::new().print().cut_paper(CutType::TotalCut).build();
+    /// let json = job.to_json();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"version":1,"commands":[{"command":"print"},{"command":"cut_paper","cut_type":"total"}]}"#
+    /// );
+    /// assert_eq!(Job::from_json(&json).unwrap(), job);
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        json::object([
+            ("version", JOB_FORMAT_VERSION.into()),
+            (
+                "commands",
+                Value::Array(self.commands.iter().map(Command::to_json).collect()),
+            ),
+        ])
+        .to_string()
+    }
+
+    /// Deserialize a job from the JSON written by [`to_json()`](Job::to_json()).
+    ///
+    /// Returns [`Error::InvalidFormat`] if the JSON is malformed, its version isn't supported or any command is
+    /// invalid.
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self, Error> {
+        let value = json::parse(text).map_err(Error::InvalidFormat)?;
+        match value.get("version").and_then(Value::as_i64) {
+            Some(version) if version == JOB_FORMAT_VERSION as i64 => {}
+            Some(version) => {
+                return Err(Error::InvalidFormat(format!(
+                    "unsupported version {version}"
+                )))
+            }
+            None => return Err(Error::InvalidFormat("missing version".to_string())),
+        }
+
+        let commands = value
+            .get("commands")
+            .and_then(Value::as_array)
+            .ok_or_else(|| Error::InvalidFormat("missing commands".to_string()))?
+            .iter()
+            .enumerate()
+            .map(|(index, command)| {
+                Command::from_json(command)
+                    .map_err(|message| Error::InvalidFormat(format!("command {index}: {message}")))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { commands })
+    }
+}
+
+/// Take `len` bytes from the start of `rest` of a job file.
+#[cfg(feature = "std")]
+fn take<'a>(rest: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if rest.len() < len {
+        return Err(Error::InvalidFormat("truncated job file".to_string()));
+    }
+    let (bytes, tail) = rest.split_at(len);
+    *rest = tail;
+
+    Ok(bytes)
+}
+
+/// Take a little-endian 32-bit length from the start of `rest` of a job file.
+#[cfg(feature = "std")]
+fn take_word(rest: &mut &[u8]) -> Result<usize, Error> {
+    let bytes = take(rest, 4)?;
+
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// CRC-32 (IEEE 802.3) of `bytes`, detecting job files which are corrupted.
+#[cfg(feature = "std")]
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+/// Builder of a [`Job`] without any device handle, e.g. for generating jobs in CI or on a server.
+///
+/// The commands are constructed by the functions of [`CommandBuilder`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{CommandBuilder, CutType, JobBuilder};
+/// let job = JobBuilder::new()
+///     .print()
+///     .cut_paper(CutType::TotalCut)
+///     .build();
+/// assert_eq!(job.to_bytes(), [0x0A, 0x1B, 0x69]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JobBuilder {
+    cmd: Vec<Command>,
+    model: Option<Model>,
+    profile: Option<Profile>,
+    config: Option<PrinterConfig>,
+    savepoint: Option<usize>,
+}
+
+impl JobBuilder {
+    /// Create a new [`JobBuilder`] without any commands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new [`JobBuilder`] with capacity for at least `capacity` commands.
+    ///
+    /// The capacity is taken by the built [`Job`], see [`reserve()`](JobBuilder::reserve()).
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            cmd: Vec::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Reserve capacity for at least `additional` more commands of the next job.
+    pub fn reserve(&mut self, additional: usize) -> &mut Self {
+        self.cmd.reserve(additional);
+
+        self
+    }
+
+    /// Set the [`Model`] of the printer the job is built for, whose physical characteristics are used by functions
+    /// like [`feed_to_cut()`](CommandBuilder::feed_to_cut()).
+    pub fn set_model(&mut self, model: Option<Model>) -> &mut Self {
+        self.model = model;
+
+        self
+    }
+
+    /// Set the [`Profile`] of the printer the job is built for, e.g. a model which isn't supported by the crate, or
+    /// use the profile of the model if `profile` is `None`, see [`CommandBuilder::profile()`].
+    pub fn set_profile(&mut self, profile: Option<Profile>) -> &mut Self {
+        self.profile = profile;
+
+        self
+    }
+
+    /// Set the [`PrinterConfig`] of the printer the job is built for, or use the configuration of the model
+    /// if `config` is `None`, see [`CommandBuilder::config()`].
+    pub fn set_config(&mut self, config: Option<PrinterConfig>) -> &mut Self {
+        self.config = config;
+
+        self
+    }
+
+    /// Iterate over the constructed commands of the next job, see [`CustomPrinter::commands()`](crate::CustomPrinter::commands()).
+    pub fn commands(&self) -> impl ExactSizeIterator<Item = &Command> {
+        self.cmd.iter()
+    }
+
+    /// Begin a transaction, so the commands constructed until [`commit()`](JobBuilder::commit()) can be removed
+    /// all at once by [`rollback()`](JobBuilder::rollback()), see [`CustomPrinter::begin()`](crate::CustomPrinter::begin()).
+    ///
+    /// Building the job ends the transaction.
+    pub fn begin(&mut self) -> &mut Self {
+        self.savepoint = Some(self.cmd.len());
+
+        self
+    }
+
+    /// Commit the transaction begun by [`begin()`](JobBuilder::begin()), keeping its commands.
+    pub fn commit(&mut self) -> &mut Self {
+        self.savepoint = None;
+
+        self
+    }
+
+    /// Roll back the transaction begun by [`begin()`](JobBuilder::begin()), removing its commands.
+    pub fn rollback(&mut self) -> &mut Self {
+        if let Some(len) = self.savepoint.take() {
+            self.cmd.truncate(len);
+        }
+
+        self
+    }
+
+    /// Construct commands by the fallible `f` in a transaction, keeping them all if `f` succeeds or removing
+    /// them all on the first error, see [`CustomPrinter::transaction()`](crate::CustomPrinter::transaction()).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, JobBuilder};
+    /// let mut builder = JobBuilder::new();
+    /// builder.print();
+    /// assert!(builder
+    ///     .transaction(|b| b.print().bitmap(16, 1, &[0xFF], BitImageMode::Dots8SingleDensity))
+    ///     .is_err());
+    /// assert_eq!(builder.build().commands().len(), 1);
+    /// ```
+    pub fn transaction(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<&mut Self, Error>,
+    ) -> Result<&mut Self, Error> {
+        let len = self.cmd.len();
+        if let Err(e) = f(self).map(|_| ()) {
+            self.cmd.truncate(len);
+            return Err(e);
+        }
+
+        Ok(self)
+    }
+
+    /// Estimate the paper length and print duration of the constructed commands on the printer of the
+    /// [`config()`](CommandBuilder::config()), including the feeds to the cut position of the profile if it's known,
+    /// see [`Job::estimate()`].
+    pub fn estimate(&self) -> Estimate {
+        estimate::estimate(&self.cmd, self.profile().as_ref(), &self.config())
+    }
+
+    /// Check the constructed commands against the model, if it's set, and the printer of the
+    /// [`config()`](CommandBuilder::config()), see [`Job::validate()`].
+    pub fn validate(&self) -> Vec<Problem> {
+        validate::validate(&self.cmd, self.profile().as_ref(), &self.config())
+    }
+
+    /// Build a [`Job`] of the constructed commands, leaving the builder empty for the next job.
+    pub fn build(&mut self) -> Job {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(commands = self.cmd.len(), "job built");
+        self.savepoint = None;
+        Job {
+            commands: core::mem::take(&mut self.cmd),
+        }
+    }
+}
+
+impl CommandBuilder for JobBuilder {
+    fn push(&mut self, command: Command) -> &mut Self {
+        self.cmd.push(command);
+
+        self
+    }
+
+    fn model(&self) -> Option<Model> {
+        self.model
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.profile
+            .clone()
+            .or_else(|| self.model.map(Profile::for_model))
+    }
+
+    fn config(&self) -> PrinterConfig {
+        self.config
+            .unwrap_or_else(|| PrinterConfig::of(self.profile().as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "embedded-hal")]
+    use crate::Serial;
+    use crate::{CutType, FeedUnit};
+
+    #[test]
+    fn test_build() {
+        let mut builder = JobBuilder::new();
+        builder.set_model(Some(Model::Tg2480h));
+        let job = builder
+            .print()
+            .feed_to_cut()
+            .unwrap()
+            .cut_paper(CutType::TotalCut)
+            .build();
+        assert_eq!(
+            job.commands(),
+            &[
+                Command::Print,
+                Command::PrintAndFeed {
+                    unit: FeedUnit::Inches,
+                    amount: 116
+                },
+                Command::CutPaper(CutType::TotalCut)
+            ]
+        );
+        assert_eq!(job.to_bytes(), &[0x0A, 0x1B, 0x4A, 116, 0x1B, 0x69]);
+        // the builder is left empty
+        assert_eq!(builder.commands().len(), 0);
+        assert!(builder.build().is_empty());
+    }
+
+    #[test]
+    fn test_config() {
+        let mut builder = JobBuilder::new();
+        assert_eq!(builder.config(), PrinterConfig::default());
+        builder.set_model(Some(Model::Tl60));
+        assert_eq!(builder.config().dots_per_line, 448);
+
+        let config = PrinterConfig {
+            dots_per_line: 512,
+            speed: Some(crate::Speed::Low),
+            code_page: Some(2),
+            ..Default::default()
+        };
+        builder.set_config(Some(config));
+        assert_eq!(builder.config(), config);
+        assert_eq!(
+            builder.apply_config().build().to_bytes(),
+            &[0x1B, 0x78, 2, 0x1B, 0x74, 2]
+        );
+    }
+
+    #[test]
+    fn test_with() {
+        use crate::BitImageMode;
+
+        let builder = JobBuilder::new().with(|b| b.print().print());
+        assert_eq!(builder.commands().len(), 2);
+
+        let mut builder = builder
+            .try_with(|b| b.bitmap(8, 1, &[0xFF], BitImageMode::Dots8SingleDensity))
+            .unwrap();
+        assert_eq!(builder.build().commands().len(), 3);
+
+        assert!(JobBuilder::new()
+            .try_with(|b| b.bitmap(16, 1, &[0xFF], BitImageMode::Dots8SingleDensity))
+            .is_err());
+    }
+
+    #[test]
+    fn test_transaction() {
+        use crate::BitImageMode;
+
+        let mut builder = JobBuilder::new();
+        builder.print().begin().print().print().rollback();
+        assert_eq!(builder.commands().len(), 1);
+        builder.begin().print().commit().rollback();
+        assert_eq!(builder.commands().len(), 2);
+
+        builder
+            .transaction(|b| {
+                b.print()
+                    .bitmap(8, 1, &[0xFF], BitImageMode::Dots8SingleDensity)
+            })
+            .unwrap();
+        assert_eq!(builder.commands().len(), 4);
+        assert!(builder
+            .transaction(|b| b
+                .print()
+                .bitmap(16, 1, &[0xFF], BitImageMode::Dots8SingleDensity))
+            .is_err());
+        assert_eq!(builder.commands().len(), 4);
+
+        // building ends the transaction
+        builder.begin().print();
+        assert_eq!(builder.build().commands().len(), 5);
+        builder.print().rollback();
+        assert_eq!(builder.commands().len(), 1);
+    }
+
+    #[test]
+    fn test_transmit() {
+        struct Link(Vec<u8>);
+
+        impl Transport for Link {
+            type Error = ();
+
+            fn transmit(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+                self.0.extend_from_slice(bytes);
+                Ok(())
+            }
+        }
+
+        let job = JobBuilder::new().print().build();
+        let mut link = Link(Vec::new());
+        job.transmit(&mut link).unwrap();
+        job.transmit(&mut link).unwrap();
+        assert_eq!(link.0, &[0x0A, 0x0A]);
+    }
+
+    #[cfg(feature = "embedded-hal")]
+    #[test]
+    fn test_transmit_serial() {
+        use embedded_hal_nb::nb;
+        use embedded_hal_nb::serial::{ErrorKind, ErrorType, Write};
+
+        // UART busy before every byte
+        #[derive(Default)]
+        struct Uart {
+            written: Vec<u8>,
+            busy: bool,
+            flushed: bool,
+        }
+
+        impl ErrorType for Uart {
+            type Error = ErrorKind;
+        }
+
+        impl Write for Uart {
+            fn write(&mut self, byte: u8) -> nb::Result<(), ErrorKind> {
+                self.busy = !self.busy;
+                if self.busy {
+                    return Err(nb::Error::WouldBlock);
+                }
+                self.flushed = false;
+                self.written.push(byte);
+                Ok(())
+            }
+
+            fn flush(&mut self) -> nb::Result<(), ErrorKind> {
+                self.flushed = true;
+                Ok(())
+            }
+        }
+
+        let job = JobBuilder::new()
+            .print()
+            .cut_paper(CutType::TotalCut)
+            .build();
+        let mut serial = Serial(Uart::default());
+        job.transmit(&mut serial).unwrap();
+        assert_eq!(serial.0.written, job.to_bytes());
+        assert!(serial.0.flushed);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_save_load() {
+        use crate::{BarcodeSystem, BitImageMode, FeedUnit, PaperSaving, QrErrorCorrection};
+
+        let job = JobBuilder::new()
+            .initialize()
+            .paper_saving(&PaperSaving {
+                blank_lines: true,
+                ..Default::default()
+            })
+            .bold(true)
+            .print_and_feed_paper(FeedUnit::Lines, 2)
+            .raw(&[])
+            .bitmap(8, 1, &[0xAA], BitImageMode::Dots24DoubleDensity)
+            .unwrap()
+            .barcode(BarcodeSystem::Code39, b"CAFE")
+            .unwrap()
+            .qr_code(b"https://example.com", 4, QrErrorCorrection::Medium)
+            .unwrap()
+            .sleep_timeout(Some(5))
+            .beep(2, 100)
+            .unwrap()
+            .cut_paper(CutType::PartialCut)
+            .build();
+        let path = std::env::temp_dir().join(format!("custom-printer-{}.job", std::process::id()));
+        job.save(&path).unwrap();
+        let loaded = Job::load(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded, job);
+
+        assert!(matches!(
+            Job::load("/nonexistent/receipt.job"),
+            Err(Error::JobFile { .. })
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_frames() {
+        let frames = JobBuilder::new().print().build().to_frames();
+        assert_eq!(
+            frames[..14],
+            [b'C', b'P', b'J', b'B', 2, 1, 0, 0, 0, 1, 0, 0, 0, 1]
+        );
+        // check value of CRC-32
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let invalid =
+            |frames: &[u8]| matches!(Job::from_frames(frames), Err(Error::InvalidFormat(_)));
+        assert!(invalid(&frames[..frames.len() - 1]));
+        assert!(invalid(b"JSON"));
+        let mut corrupted = frames.clone();
+        corrupted[13] = 0x0C;
+        assert!(invalid(&corrupted));
+        let with_version = |version: u8, command: u8| {
+            let mut frames = frames[..frames.len() - 4].to_vec();
+            frames[4] = version;
+            frames[13] = command;
+            frames.extend_from_slice(&crc32(&frames).to_le_bytes());
+            Job::from_frames(&frames)
+        };
+        assert!(matches!(
+            with_version(3, 1),
+            Err(Error::InvalidFormat(message)) if message == "unsupported version 3"
+        ));
+        assert!(matches!(
+            with_version(2, 0xFF),
+            Err(Error::InvalidFormat(message)) if message == "command 0: invalid command of tag 255"
+        ));
+        // the bytes of the commands of version 1
+        assert_eq!(
+            with_version(1, 0x0A).unwrap().commands(),
+            [Command::Raw(vec![0x0A])]
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json() {
+        use crate::{BitImageMode, DrawerPin};
+
+        let job = JobBuilder::new()
+            .initialize()
+            .bitmap(8, 1, &[0xAA], BitImageMode::Dots8SingleDensity)
+            .unwrap()
+            .print()
+            .open_drawer(DrawerPin::Pin5, 100, 200)
+            .raw(&[0x1B, 0x40])
+            .build();
+        assert_eq!(Job::from_json(&job.to_json()).unwrap(), job);
+        assert_eq!(
+            Job::from_json(r#"{"version":1,"commands":[]}"#).unwrap(),
+            Job::default()
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_errors() {
+        assert!(matches!(Job::from_json("{"), Err(Error::InvalidFormat(_))));
+        assert!(matches!(
+            Job::from_json(r#"{"version":2,"commands":[]}"#),
+            Err(Error::InvalidFormat(message)) if message == "unsupported version 2"
+        ));
+        assert!(matches!(
+            Job::from_json(r#"{"commands":[]}"#),
+            Err(Error::InvalidFormat(message)) if message == "missing version"
+        ));
+        assert!(matches!(
+            Job::from_json(r#"{"version":1,"commands":[{"command":"print"},{"command":"fly"}]}"#),
+            Err(Error::InvalidFormat(message)) if message == "command 1: unknown command fly"
+        ));
+        // a bit image which can't be encoded
+        assert!(matches!(
+            Job::from_json(
+                r#"{"version":1,"commands":[{"command":"bit_image","mode":"dots24_double_density","width":0,"data":"ff"}]}"#
+            ),
+            Err(Error::InvalidFormat(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::{BitImageMode, CutType, FeedUnit};
+
+        let job = JobBuilder::new()
+            .initialize()
+            .bitmap(8, 1, &[0xAA], BitImageMode::Dots8SingleDensity)
+            .unwrap()
+            .print_and_feed_paper(FeedUnit::Lines, 2)
+            .bold(true)
+            .cut_paper(CutType::PartialCut)
+            .build();
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(json.contains(r#"{"print_and_feed":{"unit":"lines","amount":2}}"#));
+        assert_eq!(serde_json::from_str::<Job>(&json).unwrap(), job);
+    }
+}
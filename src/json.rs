@@ -0,0 +1,485 @@
+//! Minimal JSON values, parser and writer used by the JSON formats of this crate.
+
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Write};
+
+/// A JSON value, keeping the members of objects in order.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The member `key` of an object.
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of an integer number.
+    pub(crate) fn as_i64(&self) -> Option<i64> {
+        match self {
+            // f64::fract() isn't available without std
+            Self::Number(value) if (*value as i64) as f64 == *value => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for Value {
+            fn from(value: $ty) -> Self {
+                Self::Number(value as f64)
+            }
+        })*
+    };
+}
+
+impl_from_number!(u8, u16, i16, u32, usize);
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        // round-trip through the shortest representation, so 12.3 isn't written as 12.300000190734863
+        Self::Number(value.to_string().parse().unwrap_or(value as f64))
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Number(value) => write!(f, "{value}"),
+            Self::String(value) => write_string(f, value),
+            Self::Array(values) => {
+                f.write_char('[')?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_char(']')
+            }
+            Self::Object(members) => {
+                f.write_char('{')?;
+                for (i, (name, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Parse `text` into a JSON value, returning a description of the error with its position if it's invalid.
+pub(crate) fn parse(text: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        bytes: text.as_bytes(),
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.bytes.len() {
+        return Err(parser.error("trailing characters"));
+    }
+
+    Ok(value)
+}
+
+/// Maximum nesting of arrays and objects, so untrusted input can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// Arrays and objects being parsed
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{message} at position {}", self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) != Some(&byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> Result<Value, String> {
+        if !self.bytes[self.pos..].starts_with(keyword.as_bytes()) {
+            return Err(self.error("invalid keyword"));
+        }
+        self.pos += keyword.len();
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(b'n') => self.keyword("null", Value::Null),
+            Some(b't') => self.keyword("true", Value::Bool(true)),
+            Some(b'f') => self.keyword("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[' | b'{') if self.depth == MAX_DEPTH => Err(self.error("too deeply nested")),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'{') => self.nested(Self::object),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Value, String>) -> Result<Value, String> {
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while matches!(
+            self.bytes.get(self.pos),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+
+        core::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()
+            .and_then(|number| number.parse::<f64>().ok())
+            // e.g. 1e999, which can't be written back as JSON
+            .filter(|number| number.is_finite())
+            .map(Value::Number)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut string = Vec::new();
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escaped = match self.bytes.get(self.pos) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{08}',
+                        Some(b'f') => '\u{0c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let code = self.unicode_escape()?;
+                            self.pos += 4;
+                            code
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.pos += 1;
+                    let mut buf = [0; 4];
+                    string.extend_from_slice(escaped.encode_utf8(&mut buf).as_bytes());
+                }
+                Some(byte) => {
+                    string.push(*byte);
+                    self.pos += 1;
+                }
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+
+        // the input is a str and escapes are encoded in UTF-8, so this never fails
+        String::from_utf8(string).map_err(|_| self.error("invalid string"))
+    }
+
+    /// The character of the `\u` escape at the position, or of the UTF-16 surrogate pair of two escapes,
+    /// leaving the position at the last digit.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let hex = |at: usize| {
+            self.bytes
+                .get(at..at + 4)
+                .and_then(|hex| core::str::from_utf8(hex).ok())
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        };
+        let code = match hex(self.pos + 1) {
+            Some(high @ 0xD800..=0xDBFF) => {
+                let low = match self.bytes.get(self.pos + 5..self.pos + 7) {
+                    Some(b"\\u") => hex(self.pos + 7).filter(|low| (0xDC00..=0xDFFF).contains(low)),
+                    _ => None,
+                };
+                low.map(|low| {
+                    self.pos += 6;
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                })
+            }
+            code => code,
+        };
+
+        // lone surrogates aren't characters
+        code.and_then(char::from_u32)
+            .ok_or_else(|| self.error("invalid escape"))
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect(b'[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(values));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let name = self.string()?;
+            self.expect(b':')?;
+            members.push((name, self.value()?));
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+/// Encode `bytes` as a string of hexadecimal digits.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        // writing to a String never fails
+        let _ = write!(hex, "{byte:02x}");
+    }
+
+    hex
+}
+
+/// Decode a string of hexadecimal digits into bytes.
+pub(crate) fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Build an object from its members.
+pub(crate) fn object<const N: usize>(members: [(&str, Value); N]) -> Value {
+    Value::Object(
+        members
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse(r#" {"a": [1, -2.5e1, true, null], "b": "x\"é"} "#),
+            Ok(Value::Object(vec![
+                (
+                    "a".to_string(),
+                    Value::Array(vec![
+                        Value::Number(1.0),
+                        Value::Number(-25.0),
+                        Value::Bool(true),
+                        Value::Null
+                    ])
+                ),
+                ("b".to_string(), Value::String("x\"é".to_string()))
+            ]))
+        );
+        assert!(parse("[1, 2").is_err());
+        assert!(parse("{\"a\" 1}").is_err());
+        assert!(parse("1 2").is_err());
+        assert_eq!(
+            parse(r#""\ud83d\ude00 \u00e9""#),
+            Ok(Value::String("\u{1F600} é".to_string()))
+        );
+        assert_eq!(
+            parse(r#"["\ud83d"]"#),
+            Err("invalid escape at position 3".to_string())
+        );
+        assert!(parse(r#""\ude00""#).is_err());
+        assert!(parse(r#""\ud83d\u0041""#).is_err());
+        assert_eq!(
+            parse("[1e999]"),
+            Err("invalid number at position 6".to_string())
+        );
+        assert!(parse("-1e999").is_err());
+
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse(&nested(MAX_DEPTH + 1)),
+            Err(format!("too deeply nested at position {MAX_DEPTH}"))
+        );
+        assert!(parse(&"[".repeat(1_000_000)).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let value = object([
+            ("a", Value::Array(vec![1u8.into(), Value::Null])),
+            ("b", "line\n\"quoted\"".into()),
+        ]);
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":[1,null],"b":"line\n\"quoted\""}"#
+        );
+        assert_eq!(parse(&value.to_string()), Ok(value));
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(to_hex(&[0x00, 0x1B, 0xFF]), "001bff");
+        assert_eq!(from_hex("001bff"), Some(vec![0x00, 0x1B, 0xFF]));
+        assert_eq!(from_hex("0g"), None);
+        assert_eq!(from_hex("123"), None);
+    }
+}
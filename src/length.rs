@@ -0,0 +1,138 @@
+//! Physical lengths converted to the dots of the print head.
+
+#[cfg(feature = "json")]
+use alloc::string::String;
+
+#[cfg(feature = "json")]
+use crate::{
+    command::json::Fields,
+    json::{self, Value},
+};
+
+/// A length in millimeters, inches or dots, converted to the dots of the print head by
+/// [`to_dots()`](Length::to_dots()) at its resolution, so layout code doesn't hardcode dot counts that differ
+/// between 203 and 300 dpi print heads.
+///
+/// Lengths are used by [`CommandBuilder::feed()`](crate::CommandBuilder::feed()),
+/// [`CommandBuilder::margins()`](crate::CommandBuilder::margins()) and the blank spaces of the receipts, e.g.
+/// [`Element::Space`](crate::Element::Space), which convert them at the resolution of
+/// [`CommandBuilder::config()`](crate::CommandBuilder::config()).
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::Length;
+/// assert_eq!(Length::mm(10.0).to_dots(203), 80);
+/// assert_eq!(Length::mm(10.0).to_dots(300), 118);
+/// assert_eq!(Length::inches(0.5).to_dots(203), 102);
+/// assert_eq!(Length::dots(80).to_dots(300), 80);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Length(Unit);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Mm(f32),
+    Inches(f32),
+    Dots(u16),
+}
+
+impl Length {
+    /// A length of `mm` millimeters, negative lengths are taken as 0.
+    pub const fn mm(mm: f32) -> Self {
+        Self(Unit::Mm(mm))
+    }
+
+    /// A length of `inches` inches, negative lengths are taken as 0.
+    pub const fn inches(inches: f32) -> Self {
+        Self(Unit::Inches(inches))
+    }
+
+    /// A length of `dots` dots of the print head, regardless of its resolution.
+    pub const fn dots(dots: u16) -> Self {
+        Self(Unit::Dots(dots))
+    }
+
+    /// Number of dots of the length on a print head of `dpi`, rounded to the nearest dot and saturated at
+    /// [`u16::MAX`].
+    pub fn to_dots(&self, dpi: u16) -> u16 {
+        let inches = match self.0 {
+            Unit::Mm(mm) => mm / 25.4,
+            Unit::Inches(inches) => inches,
+            Unit::Dots(dots) => return dots,
+        };
+        // f32::round() isn't available without std, and the cast saturates
+        (inches.max(0.0) * dpi as f32 + 0.5) as u16
+    }
+
+    /// The length in millimeters on a print head of `dpi`.
+    pub fn to_mm(&self, dpi: u16) -> f32 {
+        match self.0 {
+            Unit::Mm(mm) => mm.max(0.0),
+            Unit::Inches(inches) => inches.max(0.0) * 25.4,
+            Unit::Dots(dots) => dots as f32 * 25.4 / dpi as f32,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl Length {
+    /// The JSON object of the length, its amount in its unit, e.g. `{"mm":15}`.
+    pub(crate) fn to_json(self) -> Value {
+        match self.0 {
+            Unit::Mm(mm) => json::object([("mm", mm.into())]),
+            Unit::Inches(inches) => json::object([("inches", inches.into())]),
+            Unit::Dots(dots) => json::object([("dots", dots.into())]),
+        }
+    }
+
+    /// Parse the length from its JSON object of `mm`, `inches` or `dots`, see [`to_json()`](Length::to_json()).
+    pub(crate) fn from_json(value: &Value) -> Result<Self, String> {
+        let fields = Fields(value);
+        let float = |key: &str| {
+            fields
+                .get(key)?
+                .as_f64()
+                .map(|amount| amount as f32)
+                .ok_or_else(|| Fields::invalid(key))
+        };
+
+        if value.get("mm").is_some() {
+            float("mm").map(Self::mm)
+        } else if value.get("inches").is_some() {
+            float("inches").map(Self::inches)
+        } else {
+            fields.int("dots").map(Self::dots)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_dots() {
+        assert_eq!(Length::mm(25.4).to_dots(203), 203);
+        assert_eq!(Length::mm(5.0).to_dots(200), 39);
+        assert_eq!(Length::mm(-1.0).to_dots(203), 0);
+        assert_eq!(Length::inches(1000.0).to_dots(300), u16::MAX);
+        assert!((Length::dots(203).to_mm(203) - 25.4).abs() < 0.001);
+        assert_eq!(Length::inches(-1.0).to_mm(203), 0.0);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json() {
+        use alloc::string::ToString;
+
+        for length in [Length::mm(15.5), Length::inches(0.5), Length::dots(40)] {
+            assert_eq!(Length::from_json(&length.to_json()), Ok(length));
+        }
+        assert_eq!(Length::mm(15.0).to_json().to_string(), r#"{"mm":15}"#);
+        assert_eq!(
+            Length::from_json(&json::parse(r#"{"mm":"15"}"#).unwrap()),
+            Err("invalid field mm".into())
+        );
+    }
+}
@@ -1,24 +1,100 @@
 #![doc = include_str!("../README.md")]
-
-use std::{
-    fs::File,
-    io::{self, Write},
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod builder;
+mod command;
+mod command_set;
+mod config;
+mod decode;
+mod emulator;
+mod error;
+mod estimate;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod job;
+#[cfg(feature = "json")]
+mod json;
+mod length;
+mod model;
+#[cfg(feature = "std")]
+mod monitor;
+mod parse;
+pub mod prelude;
+mod preview;
+#[cfg(feature = "std")]
+mod printer;
+mod profile;
+#[cfg(feature = "python")]
+pub mod python;
+mod queue;
+mod raster;
+mod receipt;
+mod sequence;
+mod status;
+#[cfg(feature = "std")]
+mod streaming;
+#[cfg(feature = "templates")]
+mod template;
+#[cfg(feature = "std")]
+mod trace;
+mod transport;
+mod validate;
+
+pub use builder::{
+    bitimage_len, convert_bitmap_to_bitimage, convert_bitmap_to_bitimage_into, CommandBuilder,
 };
-
-// List of supported commands
-// Printing commands
-const PRINT: &[u8] = &[0x0A];
-const PRINT_FEED_INCHES: &[u8] = &[0x1B, 0x4A];
-const PRINT_FEED_LINES: &[u8] = &[0x1B, 0x64];
-const SPEED_QUALITY: &[u8] = &[0x1B, 0x78];
-const DENSITY: &[u8] = &[0x1D, 0x7C];
-// Bit-image commands
-const BIT_IMAGE: &[u8] = &[0x1B, 0x2A];
-// Mechanism control commands
-const TOTAL_CUT: &[u8] = &[0x1B, 0x69];
-const PARTIAL_CUT: &[u8] = &[0x1B, 0x6D];
-
-/// Modes supported by [`CustomPrinter::bit_image()`] function.
+pub use command::Command;
+pub use command_set::{CommandSet, CustomEscPos, StarLineMode};
+pub use config::{LimitPolicy, PaperWidth, PrinterConfig};
+pub use decode::mnemonics;
+pub use emulator::Emulator;
+pub use error::Error;
+pub use estimate::Estimate;
+#[cfg(feature = "json")]
+pub use job::JOB_FORMAT_VERSION;
+pub use job::{Job, JobBuilder};
+pub use length::Length;
+pub use model::Model;
+#[cfg(feature = "std")]
+pub use monitor::{PrinterEvent, StatusMonitor};
+pub use parse::parse;
+#[cfg(feature = "std")]
+pub use printer::{CustomPrinter, PrintOutcome};
+pub use profile::Profile;
+pub use queue::{JobQueue, Priority};
+#[cfg(feature = "json")]
+pub use receipt::RECEIPT_FORMAT_VERSION;
+pub use receipt::{
+    render_html, render_markdown, Align, BorderStyle, Canvas, Column, ColumnWidth, DateFormat,
+    DateHeader, Element, HeaderStyle, Layout, LayoutMode, LayoutNode, List, ListItem, ListStyle,
+    MoneyFormat, Paragraph, Receipt, Region, RenderStyle, SaleItem, SaleLabels, Separator,
+    SeparatorStyle, Span, SymbolPlacement, Table, TableMode, TaxLine, TextBox, TextStyle, Totals,
+    Track, TwoColumns, Watermark, WatermarkImage,
+};
+#[cfg(feature = "std")]
+pub use sequence::FileSequence;
+pub use sequence::Sequence;
+pub use status::{
+    Features, InsufficientMemory, MaintenanceCounters, NvMemory, OfflineCause, PrinterFault,
+    PrinterInfo, Status,
+};
+#[cfg(feature = "std")]
+pub use streaming::StreamingPrinter;
+#[cfg(feature = "templates")]
+pub use template::{JsonVariables, Template, Variables};
+#[cfg(feature = "std")]
+pub use trace::{TraceEvent, TraceHook};
+#[cfg(feature = "embedded-hal")]
+pub use transport::Serial;
+pub use transport::Transport;
+pub use validate::Problem;
+
+/// Modes supported by [`CommandBuilder::bit_image()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum BitImageMode {
     /// 8 dot single density
     Dots8SingleDensity,
@@ -30,465 +106,457 @@ pub enum BitImageMode {
     Dots24DoubleDensity,
 }
 
-/// Cut types supported by [`CustomPrinter::cut_paper()`] function.
-pub enum CutType {
-    /// Total cut
-    TotalCut,
-    /// Partial cut, only valid for TL60 and TL80 printers.
-    PartialCut,
+/// Barcode systems supported by [`CommandBuilder::barcode()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BarcodeSystem {
+    /// UPC-A, 11 or 12 digits
+    UpcA,
+    /// UPC-E, 11 or 12 digits
+    UpcE,
+    /// EAN-13 (JAN-13), 12 or 13 digits
+    Ean13,
+    /// EAN-8 (JAN-8), 7 or 8 digits
+    Ean8,
+    /// CODE39, digits, upper case letters, space and `$%*+-./`
+    Code39,
+    /// ITF (Interleaved 2 of 5), an even number of digits
+    Itf,
+    /// CODABAR (NW-7), digits and `$+-./:` between the start and stop characters `A` to `D`
+    Codabar,
+    /// CODE93, ASCII
+    Code93,
+    /// CODE128, ASCII prefixed by the code set, e.g. `{B`
+    Code128,
 }
 
-/// Feed units supported by [`CustomPrinter::print_and_feed_paper()`] function.
-pub enum FeedUnit {
-    /// Feed the paper by number of vertical or horizontal motion unit inches
-    Inches,
-    /// Feed the paper by number of lines
-    Lines,
-}
-
-/// Speeds supported by [`CustomPrinter::speed()`] function.
-pub enum Speed {
-    /// High speed (draft mode)
-    High,
-    /// Normal mode
-    Normal,
-    /// Low speed (high quality)
-    Low,
-}
-
-/// Densities supported by [`CustomPrinter::density()`] function.
-pub enum Density {
-    /// -50%
-    Minus50,
-    /// -25%
-    Minus25,
-    /// 0%
-    Zero,
-    /// 25%
-    Plus25,
-    /// 50%
-    Plus50,
-}
-
-/// The main struct to construct printing commands and accomplish actual printing.
-///
-/// The APIs are designed to be able to concatenate one after the other.
-/// # Examples
-///
-/// ```no_run
-/// # use custom_printer::{BitImageMode, CustomPrinter, CutType, FeedUnit};
-/// let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
-/// printer
-///     .bit_image(
-///         "logo.bmp",
-///         BitImageMode::Dots24DoubleDensity
-///     )
-///     .unwrap()
-///     .print()
-///     .cut_paper(CutType::PartialCut)
-///     .run()
-///     .unwrap()
-///     .bit_image(
-///         "greeting.bmp",
-///         BitImageMode::Dots24DoubleDensity
-///     )
-///     .unwrap()
-///     .print_and_feed_paper(FeedUnit::Lines, 10)
-///     .cut_paper(CutType::TotalCut)
-///     .run()
-///     .unwrap();
-/// ```
-pub struct CustomPrinter {
-    file: File,
-    cmd: Vec<u8>,
-}
-
-impl CustomPrinter {
-    /// Create a new [`CustomPrinter`] with the device node `dev`.
-    ///
-    /// **NOTE:** Device node `dev` must be readable and writable by current user.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// # use custom_printer::CustomPrinter;
-    /// CustomPrinter::new("/dev/usb/lp0")
-    /// # ;
-    /// ```
-    pub fn new(dev: &str) -> Result<Self, io::Error> {
-        let file = File::options().read(true).write(true).open(dev)?;
-        Ok(Self {
-            file,
-            cmd: Vec::new(),
-        })
-    }
-
-    pub(crate) fn convert_bitmap_to_bitimage(
-        width: usize,
-        height: usize,
-        bitmap: &[u8],
-        mode: &BitImageMode,
-    ) -> Vec<u8> {
-        // number of lines in a bank
-        let bank = match mode {
-            BitImageMode::Dots8SingleDensity | BitImageMode::Dots8DoubleDensity => 8,
-            BitImageMode::Dots24SingleDensity | BitImageMode::Dots24DoubleDensity => 24,
-        };
-        // number of banks in bit image (might have padding lines in the last bank)
-        let banks = (height + bank - 1) / bank;
-        // number of bytes in bit image
-        let size = banks * (bank / 8) * width;
-        let mut bitimage = vec![0; size];
-        // number of bytes in a line
-        let step = width / 8;
-
-        for i in 0..banks {
-            for j in 0..width {
-                for k in 0..bank {
-                    let src = i * step * bank + k * step + j / 8;
-                    let dst = i * width * (bank / 8) + j * (bank / 8) + k / 8;
-                    if src < bitmap.len() && bitmap[src] & (0x80 >> (j % 8)) != 0 {
-                        bitimage[dst] |= 0x80 >> (k % 8);
-                    }
+impl BarcodeSystem {
+    /// Describe why `data` can't be printed as a barcode of the system, if it can't.
+    pub(crate) fn invalid_data(&self, data: &[u8]) -> Option<alloc::string::String> {
+        let digits = data.iter().all(u8::is_ascii_digit);
+        let valid = match self {
+            Self::UpcA | Self::UpcE => digits && (11..=12).contains(&data.len()),
+            Self::Ean13 => digits && (12..=13).contains(&data.len()),
+            Self::Ean8 => digits && (7..=8).contains(&data.len()),
+            Self::Code39 => data
+                .iter()
+                .all(|b| b.is_ascii_digit() || b.is_ascii_uppercase() || b" $%*+-./".contains(b)),
+            Self::Itf => digits && data.len().is_multiple_of(2),
+            Self::Codabar => match data {
+                [start, middle @ .., stop] => {
+                    [start, stop].iter().all(|b| (b'A'..=b'D').contains(b))
+                        && middle
+                            .iter()
+                            .all(|b| b.is_ascii_digit() || b"$+-./:".contains(b))
                 }
-            }
-        }
+                _ => false,
+            },
+            Self::Code93 => data.is_ascii(),
+            Self::Code128 => data.is_ascii() && matches!(data, [b'{', b'A'..=b'C', ..]),
+        };
 
-        bitimage
+        if data.is_empty() || data.len() > u8::MAX as usize {
+            Some(alloc::format!(
+                "barcode of {} bytes isn't 1 to 255 bytes long",
+                data.len()
+            ))
+        } else if !valid {
+            Some(alloc::format!(
+                "barcode {:?} isn't valid {self:?} data",
+                alloc::string::String::from_utf8_lossy(data)
+            ))
+        } else {
+            None
+        }
     }
 
-    /// Append commands for printing a bit image from `path` in `mode`. See [`BitImageMode`] for supported modes.
+    /// Number of narrow modules of the barcode of `data`, without its quiet zones, or `None` if the data isn't
+    /// valid, e.g. to fit the barcode in a width by [`CommandBuilder::barcode_style()`].
     ///
-    /// **NOTE:** Because opening and reading the image file may fail, so the return Self is wrapped in a [`Result`]
-    /// and needs to be unwrapped before concatenating with other constructing functions.
+    /// The wide bars are counted 3 times as wide as the narrow ones, the largest ratio of the printers, and the
+    /// start and stop characters of CODE39 are added.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// # use custom_printer::{BitImageMode, CustomPrinter};
-    /// # let mut printer = CustomPrinter::new("/dev/null").unwrap();
-    /// printer
-    ///     .bit_image(
-    ///         "tests/data/Thermal_Test_Image.png",
-    ///         BitImageMode::Dots24DoubleDensity
-    ///     )
-    ///     .unwrap();
+    /// # use custom_printer::BarcodeSystem;
+    /// assert_eq!(BarcodeSystem::Ean13.modules(b"400638133393"), Some(95));
+    /// // the start code, 2 characters, the check character and the stop code
+    /// assert_eq!(BarcodeSystem::Code128.modules(b"{BAB"), Some(57));
+    /// assert_eq!(BarcodeSystem::Ean13.modules(b"ABC"), None);
     /// ```
-    pub fn bit_image(&mut self, path: &str, mode: BitImageMode) -> Result<&mut Self, io::Error> {
-        // Open image and convert to grayscale
-        let img = image::open(path)
-            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?
-            .grayscale();
-
-        let width = img.width() as usize;
-        let height = img.height() as usize;
-
-        // convert 8bpp grayscaled image to 1 bpp bitmap
-        let mut bitmap: Vec<u8> = vec![0; img.as_bytes().len() / 8];
-        for (i, byte) in img.as_bytes().iter().enumerate() {
-            // invert the bits
-            if *byte == 0x00 {
-                bitmap[i / 8] |= 0x80 >> (i % 8);
-            }
+    pub fn modules(&self, data: &[u8]) -> Option<u32> {
+        if self.invalid_data(data).is_some() {
+            return None;
         }
+        let n = data.len() as u32;
+
+        Some(match self {
+            Self::UpcA | Self::Ean13 => 95,
+            Self::UpcE => 51,
+            Self::Ean8 => 67,
+            // 3 wide and 6 narrow elements and the gap of each character
+            Self::Code39 => 16 * (n + 2) - 1,
+            // 2 wide and 3 narrow elements a digit, between the start and stop patterns
+            Self::Itf => 9 * n + 9,
+            Self::Codabar => {
+                let wide: u32 = data
+                    .iter()
+                    .map(|b| {
+                        if b.is_ascii_digit() || b"-$".contains(b) {
+                            11
+                        } else {
+                            13
+                        }
+                    })
+                    .sum();
+                wide + n - 1
+            }
+            // the characters out of the 47 of the symbology are shifted, and 2 check characters are added
+            Self::Code93 => {
+                let shifted = data
+                    .iter()
+                    .filter(|b| {
+                        !(b.is_ascii_digit() || b.is_ascii_uppercase() || b"-. $/+%".contains(b))
+                    })
+                    .count() as u32;
+                9 * (n + shifted + 4) + 1
+            }
+            Self::Code128 => 11 * (code128_symbols(data).0 + 1) + 13,
+        })
+    }
 
-        // for (i, byte) in bitmap.iter().enumerate() {
-        //     for j in 0..8 {
-        //         print!("{}", if byte & (0x80 >> j) != 0 { 1 } else { 0 });
-        //     }
-        //     if i % (width / 8) == ((width / 8) - 1) {
-        //         println!();
-        //     }
-        // }
-
-        let bitimage = Self::convert_bitmap_to_bitimage(width, height, &bitmap, &mode);
-
-        let (m, k) = match mode {
-            BitImageMode::Dots8SingleDensity => (0x00, width),
-            BitImageMode::Dots8DoubleDensity => (0x01, width),
-            BitImageMode::Dots24SingleDensity => (0x20, width * 3),
-            BitImageMode::Dots24DoubleDensity => (0x21, width * 3),
-        };
-
-        for i in 0..bitimage.len() / k {
-            self.cmd.extend_from_slice(BIT_IMAGE);
-            self.cmd
-                .extend_from_slice(&[m, (width % 256) as u8, (width / 256) as u8]);
-            self.cmd.extend_from_slice(&bitimage[i * k..(i + 1) * k]);
-            // for j in 0..k {
-            //     print!("{:02x} ", bitimage[i * k + j]);
-            // }
-            // println!();
+    /// Number of characters of the human readable interpretation of the barcode of valid `data`.
+    pub(crate) fn hri_chars(&self, data: &[u8]) -> usize {
+        match self {
+            Self::UpcA => 12,
+            Self::UpcE | Self::Ean8 => 8,
+            Self::Ean13 => 13,
+            Self::Code39 => data.len() + 2,
+            Self::Itf | Self::Codabar | Self::Code93 => data.len(),
+            Self::Code128 => code128_symbols(data).1,
         }
-
-        Ok(self)
     }
+}
 
-    /// Append a command for cutting the paper totally ([`CutType::TotalCut`]) or partially ([`CutType::PartialCut`]).
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// # use custom_printer::{CustomPrinter, CutType};
-    /// # let mut printer = CustomPrinter::new("/dev/null").unwrap();
-    /// printer.cut_paper(CutType::TotalCut);
-    /// ```
-    pub fn cut_paper(&mut self, cut_type: CutType) -> &mut Self {
-        match cut_type {
-            CutType::TotalCut => {
-                self.cmd.extend_from_slice(TOTAL_CUT);
-            }
-            CutType::PartialCut => {
-                self.cmd.extend_from_slice(PARTIAL_CUT);
-            }
+/// Number of symbols of the CODE128 barcode of `data`, including the start code, and number of characters of its
+/// human readable interpretation.
+///
+/// The pairs `{A`, `{B` and `{C` select a code set, `{{` is a `{` and the other pairs are functions; the bytes in
+/// code set C are pairs of digits.
+fn code128_symbols(data: &[u8]) -> (u32, usize) {
+    let (mut symbols, mut chars) = (0, 0);
+    let mut set_c = false;
+    let mut bytes = data.iter();
+    while let Some(&b) = bytes.next() {
+        symbols += 1;
+        match (b, set_c) {
+            (b'{', _) => match bytes.next() {
+                Some(b'{') => chars += 1,
+                Some(&set) => set_c = set == b'C' || (set_c && !matches!(set, b'A' | b'B')),
+                None => {}
+            },
+            (_, true) => chars += 2,
+            (_, false) => chars += 1,
         }
-
-        self
     }
 
-    /// Append a command for printing and line feeding.
-    ///
-    /// Either [`print()`](CustomPrinter::print()) or [`print_and_feed_paper()`](CustomPrinter::print_and_feed_paper()) should be appended
-    /// before calling [`run()`](CustomPrinter::run()) to do actual printing.
-    pub fn print(&mut self) -> &mut Self {
-        self.cmd.extend_from_slice(PRINT);
-
-        self
-    }
+    (symbols, chars)
+}
 
-    /// Append a command for printing and feeding the paper by `amount` of `unit`.
-    ///
-    /// Either [`print()`](CustomPrinter::print()) or [`print_and_feed_paper()`](CustomPrinter::print_and_feed_paper()) should be appended
-    /// before calling [`run()`](CustomPrinter::run()) to do actual printing.
-    pub fn print_and_feed_paper(&mut self, unit: FeedUnit, amount: u8) -> &mut Self {
-        self.cmd.extend_from_slice(match unit {
-            FeedUnit::Inches => PRINT_FEED_INCHES,
-            FeedUnit::Lines => PRINT_FEED_LINES,
-        });
-        self.cmd.extend_from_slice(&[amount]);
-
-        self
-    }
+/// Position of the human readable interpretation, i.e. the text, of the barcodes supported by
+/// [`CommandBuilder::barcode_style()`] function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum HriPosition {
+    /// Not printed
+    #[default]
+    None,
+    /// Above the barcode
+    Above,
+    /// Below the barcode
+    Below,
+    /// Above and below the barcode
+    Both,
+}
 
-    /// Append a command for selecting speed / quality mode.
-    pub fn speed(&mut self, speed: &Speed) -> &mut Self {
-        self.cmd.extend_from_slice(SPEED_QUALITY);
-        self.cmd.extend_from_slice(&[match speed {
-            Speed::High => 0,
-            Speed::Normal => 1,
-            Speed::Low => 2,
-        }]);
+/// Fonts of the human readable interpretation of the barcodes supported by [`CommandBuilder::barcode_style()`]
+/// function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum HriFont {
+    /// Font A, 12 dots wide
+    #[default]
+    A,
+    /// Font B, 9 dots wide
+    B,
+}
 
-        self
+impl HriFont {
+    /// Width of the characters of the font in dots.
+    pub(crate) fn width(self) -> u16 {
+        match self {
+            Self::A => 12,
+            Self::B => 9,
+        }
     }
+}
 
-    /// Append a command for setting printing density.
-    pub fn density(&mut self, density: &Density) -> &mut Self {
-        self.cmd.extend_from_slice(DENSITY);
-        self.cmd.extend_from_slice(&[match density {
-            Density::Minus50 => 0,
-            Density::Minus25 => 1,
-            Density::Zero => 2,
-            Density::Plus25 => 3,
-            Density::Plus50 => 4,
-        }]);
-
-        self
-    }
+/// Error correction levels of the QR codes supported by [`CommandBuilder::qr_code()`] function, the larger levels
+/// restoring more damaged codes at the cost of larger codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum QrErrorCorrection {
+    /// Level L, restoring about 7% of the code
+    Low,
+    /// Level M, restoring about 15% of the code
+    Medium,
+    /// Level Q, restoring about 25% of the code
+    Quartile,
+    /// Level H, restoring about 30% of the code
+    High,
+}
 
-    /// Run the constructed commands in the [`CustomPrinter`].
+impl QrErrorCorrection {
+    /// Number of modules along a side of the smallest QR code holding `len` bytes at the level, or `None` if even
+    /// version 40 can't hold them.
     ///
-    /// The constructed commands will be cleared if the printing succeeds.
-    ///
-    /// **NOTE:** Because writing to the device node may fail, so the return Self is wrapped in a [`Result`]
-    /// and needs to be unwrapped before concatenating with other constructing functions.
+    /// The capacities are those of the byte mode, so the printer may print a smaller code for digits or upper case
+    /// letters only, never a larger one.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// # use custom_printer::{BitImageMode, CustomPrinter, CutType};
-    /// # let mut printer = CustomPrinter::new("/dev/null").unwrap();
-    /// printer
-    ///     .bit_image(
-    ///         "tests/data/Thermal_Test_Image.png",
-    ///         BitImageMode::Dots24DoubleDensity
-    ///     )
-    ///     .unwrap()
-    ///     .cut_paper(CutType::TotalCut)
-    ///     .run()
-    ///     .unwrap();
+    /// # use custom_printer::QrErrorCorrection;
+    /// // version 2
+    /// assert_eq!(QrErrorCorrection::Medium.modules(20), Some(25));
+    /// assert_eq!(QrErrorCorrection::High.modules(2000), None);
     /// ```
-    pub fn run(&mut self) -> Result<&mut Self, io::Error> {
-        self.file.write_all(&self.cmd)?;
-
-        self.cmd.clear();
-        Ok(self)
+    pub fn modules(&self, len: usize) -> Option<u16> {
+        // byte capacities of the versions 1 to 40 at the levels L, M, Q and H
+        const CAPACITIES: [[u16; 4]; 40] = [
+            [17, 14, 11, 7],
+            [32, 26, 20, 14],
+            [53, 42, 32, 24],
+            [78, 62, 46, 34],
+            [106, 84, 60, 44],
+            [134, 106, 74, 58],
+            [154, 122, 86, 64],
+            [192, 152, 108, 84],
+            [230, 180, 130, 98],
+            [271, 213, 151, 119],
+            [321, 251, 177, 137],
+            [367, 287, 203, 155],
+            [425, 331, 241, 177],
+            [458, 362, 258, 194],
+            [520, 412, 292, 220],
+            [586, 450, 322, 250],
+            [644, 504, 364, 280],
+            [718, 560, 394, 310],
+            [792, 624, 442, 338],
+            [858, 666, 482, 382],
+            [929, 711, 509, 403],
+            [1003, 779, 565, 439],
+            [1091, 857, 611, 461],
+            [1171, 911, 661, 511],
+            [1273, 997, 715, 535],
+            [1367, 1059, 751, 593],
+            [1465, 1125, 805, 625],
+            [1528, 1190, 868, 658],
+            [1628, 1264, 908, 698],
+            [1732, 1370, 982, 742],
+            [1840, 1452, 1030, 790],
+            [1952, 1538, 1112, 842],
+            [2068, 1628, 1168, 898],
+            [2188, 1722, 1228, 958],
+            [2303, 1809, 1283, 983],
+            [2431, 1911, 1351, 1051],
+            [2563, 1989, 1423, 1093],
+            [2699, 2099, 1499, 1139],
+            [2809, 2213, 1579, 1219],
+            [2953, 2331, 1663, 1273],
+        ];
+
+        let level = *self as usize;
+        let version = CAPACITIES
+            .iter()
+            .position(|capacities| len <= capacities[level] as usize)?;
+
+        // version 1 is 21 modules wide, each version adding 4 modules
+        Some(21 + 4 * version as u16)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    const THERMAL_WIDTH: usize = 384;
-    const THERMAL_HEIGHT: usize = 288;
-    const THERMAL_TXT: &str = include_str!("../tests/data/thermal.txt");
-    const THERMAL_8DOTS: &[u8] = include_bytes!("../tests/data/thermal.b8");
-    const THERMAL_24DOTS: &[u8] = include_bytes!("../tests/data/thermal.b24");
-    const THERMAL_PNG_PATH: &str = "tests/data/Thermal_Test_Image.png";
-    const DEV_NULL: &str = "/dev/null";
-
-    #[test]
-    fn test_cut_paper() {
-        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
-        assert_eq!(printer.cut_paper(CutType::TotalCut).cmd, TOTAL_CUT);
-
-        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
-        assert_eq!(printer.cut_paper(CutType::PartialCut).cmd, PARTIAL_CUT);
-    }
+/// Cut types supported by [`CommandBuilder::cut_paper()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CutType {
+    /// Total cut
+    TotalCut,
+    /// Partial cut, only valid for TL60, TL80 and KPM150H printers, see [`Model::supports()`].
+    PartialCut,
+}
 
-    #[test]
-    #[ignore]
-    fn helper_prepare_bitimage() {
-        let converter = |text: &str, inverted: bool, output: &mut File, bank: usize| {
-            let lines: Vec<&str> = text.trim().split('\n').collect();
-            let width = lines[0].len();
-            let banks = (lines.len() + (bank - 1)) / bank;
-
-            for i in 0..banks {
-                for j in 0..width {
-                    let mut byte: u8 = 0;
-                    for k in 0..bank {
-                        let line_no = i * bank + k;
-                        // padding lines are always 0
-                        if line_no < lines.len() {
-                            let b = lines[line_no].chars().nth(j).unwrap();
-                            if inverted {
-                                if b == '0' {
-                                    byte |= 0x80 >> (k % 8);
-                                }
-                            } else {
-                                if b == '1' {
-                                    byte |= 0x80 >> (k % 8);
-                                }
-                            }
-                        }
-                        if k % 8 == 7 {
-                            output.write(&[byte]).ok();
-                            byte = 0;
-                        }
-                    }
-                }
-            }
-        };
+/// Cut appended automatically by [`CustomPrinter::run()`], see [`CustomPrinter::set_auto_cut()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoCut {
+    /// Cut type
+    pub cut_type: CutType,
+    /// Amount of vertical motion units to feed beyond the cut position before cutting
+    pub feed: u8,
+}
 
-        let mut output = File::options()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open("tests/data/thermal.b8")
-            .unwrap();
-        converter(THERMAL_TXT, true, &mut output, 8);
-
-        let mut output = File::options()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open("tests/data/thermal.b24")
-            .unwrap();
-        converter(THERMAL_TXT, true, &mut output, 24);
-    }
+/// Feed units supported by [`CommandBuilder::print_and_feed_paper()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FeedUnit {
+    /// Feed the paper by number of vertical or horizontal motion units in inches,
+    /// see [`CommandBuilder::motion_units()`].
+    Inches,
+    /// Feed the paper by number of lines
+    Lines,
+}
 
-    fn convert_text_to_bitmap(text: &str, inverted: bool) -> Vec<u8> {
-        let mut data = Vec::new();
+/// Positions relative to the black mark or label gap supported by [`CommandBuilder::mark_offset()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MarkPosition {
+    /// Position where printing starts
+    PrintStart,
+    /// Position where the paper is cut
+    Cut,
+}
 
-        text.trim().split('\n').for_each(|line| {
-            if line.len() % 8 != 0 {
-                eprintln!("Length of each line of text must be dividable by 8");
-                return;
-            }
-            for i in (0..line.len()).step_by(8) {
-                if let Ok(byte) = u8::from_str_radix(&line[i..i + 8], 2) {
-                    data.extend_from_slice(&[if inverted { !byte } else { byte }]);
-                } else {
-                    eprintln!("text contains characters neither 0 nor 1");
-                    return;
-                }
-            }
-        });
+/// Paper-saving settings supported by [`CommandBuilder::paper_saving()`] function.
+///
+/// Every setting is disabled by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PaperSaving {
+    /// Reduce the top margin by feeding the paper backwards before printing the next ticket
+    pub top_margin: bool,
+    /// Reduce the bottom margin before cutting
+    pub bottom_margin: bool,
+    /// Reduce the line spacing
+    pub line_spacing: bool,
+    /// Reduce consecutive blank lines
+    pub blank_lines: bool,
+    /// Reduce the height of barcodes
+    pub barcode_height: bool,
+}
 
-        data
+impl PaperSaving {
+    pub(crate) fn bits(&self) -> u8 {
+        [
+            self.top_margin,
+            self.bottom_margin,
+            self.line_spacing,
+            self.blank_lines,
+            self.barcode_height,
+        ]
+        .into_iter()
+        .enumerate()
+        .filter(|(_, enabled)| *enabled)
+        .fold(0, |bits, (i, _)| bits | (1 << i))
     }
 
-    #[test]
-    fn test_convert_bitmap_to_bitimage_8dots() {
-        let bitmap = convert_text_to_bitmap(THERMAL_TXT, true);
-        assert_eq!(
-            &CustomPrinter::convert_bitmap_to_bitimage(
-                THERMAL_WIDTH,
-                THERMAL_HEIGHT,
-                &bitmap,
-                &BitImageMode::Dots8SingleDensity
-            ),
-            THERMAL_8DOTS
-        );
-        assert_eq!(
-            &CustomPrinter::convert_bitmap_to_bitimage(
-                THERMAL_WIDTH,
-                THERMAL_HEIGHT,
-                &bitmap,
-                &BitImageMode::Dots8DoubleDensity
-            ),
-            THERMAL_8DOTS
-        );
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        let enabled = |i: u8| bits & (1 << i) != 0;
+        Self {
+            top_margin: enabled(0),
+            bottom_margin: enabled(1),
+            line_spacing: enabled(2),
+            blank_lines: enabled(3),
+            barcode_height: enabled(4),
+        }
     }
+}
 
-    #[test]
-    fn test_convert_bitmap_to_bitimage_24dots() {
-        let bitmap = convert_text_to_bitmap(THERMAL_TXT, true);
-        assert_eq!(
-            &CustomPrinter::convert_bitmap_to_bitimage(
-                THERMAL_WIDTH,
-                THERMAL_HEIGHT,
-                &bitmap,
-                &BitImageMode::Dots24SingleDensity
-            ),
-            THERMAL_24DOTS
-        );
-        assert_eq!(
-            &CustomPrinter::convert_bitmap_to_bitimage(
-                THERMAL_WIDTH,
-                THERMAL_HEIGHT,
-                &bitmap,
-                &BitImageMode::Dots24DoubleDensity
-            ),
-            THERMAL_24DOTS
-        );
-    }
+/// Print directions in page mode supported by [`CommandBuilder::page_direction()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PageDirection {
+    /// Left to right, starting from the upper left corner
+    LeftToRight,
+    /// Bottom to top, starting from the lower left corner
+    BottomToTop,
+    /// Right to left, starting from the lower right corner
+    RightToLeft,
+    /// Top to bottom, starting from the upper right corner
+    TopToBottom,
+}
 
-    #[test]
-    fn test_bit_image() {
-        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
-        printer
-            .bit_image(THERMAL_PNG_PATH, BitImageMode::Dots8SingleDensity)
-            .unwrap();
-
-        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
-        printer
-            .bit_image(THERMAL_PNG_PATH, BitImageMode::Dots8DoubleDensity)
-            .unwrap();
-
-        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
-        printer
-            .bit_image(THERMAL_PNG_PATH, BitImageMode::Dots24SingleDensity)
-            .unwrap();
-
-        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
-        printer
-            .bit_image(THERMAL_PNG_PATH, BitImageMode::Dots24DoubleDensity)
-            .unwrap();
-    }
+/// Justifications of the lines supported by [`CommandBuilder::justification()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Justification {
+    /// Aligned to the left of the printable area
+    Left,
+    /// Centered on the printable area
+    Center,
+    /// Aligned to the right of the printable area
+    Right,
+}
+
+/// Speeds supported by [`CommandBuilder::speed()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Speed {
+    /// High speed (draft mode)
+    High,
+    /// Normal mode
+    Normal,
+    /// Low speed (high quality)
+    Low,
+}
+
+/// Densities supported by [`CommandBuilder::density()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Density {
+    /// -50%
+    Minus50,
+    /// -25%
+    Minus25,
+    /// 0%
+    Zero,
+    /// 25%
+    Plus25,
+    /// 50%
+    Plus50,
+}
 
-    #[test]
-    fn test_multiple_run() {}
+/// Connector pins of the cash drawer supported by [`CommandBuilder::open_drawer()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DrawerPin {
+    /// Drawer kick-out connector pin 2
+    Pin2,
+    /// Drawer kick-out connector pin 5
+    Pin5,
+}
+
+/// Sensitivities of the paper near-end sensor supported by [`CommandBuilder::near_end_sensitivity()`] function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NearEndSensitivity {
+    /// Low sensitivity, detects the near-end with less paper left on the roll
+    Low,
+    /// Medium sensitivity
+    Medium,
+    /// High sensitivity, detects the near-end with more paper left on the roll
+    High,
 }
@@ -0,0 +1,143 @@
+//! Supported printer models and their physical characteristics.
+
+use alloc::string::String;
+
+use crate::{Command, Profile};
+
+/// Models supported by [`CustomPrinter::set_model()`](crate::CustomPrinter::set_model()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// TG2460H
+    Tg2460h,
+    /// TG2480H
+    Tg2480h,
+    /// TL60
+    Tl60,
+    /// TL80
+    Tl80,
+    /// VKP80 with presenter
+    Vkp80,
+    /// KPM150H
+    Kpm150h,
+}
+
+impl Model {
+    /// Find the model by its name reported in [`PrinterInfo::model`](crate::PrinterInfo::model), ignoring case,
+    /// spaces and dashes, e.g. `TG2480-H`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match normalize_name(name).as_str() {
+            "TG2460H" => Some(Self::Tg2460h),
+            "TG2480H" => Some(Self::Tg2480h),
+            "TL60" => Some(Self::Tl60),
+            "TL80" => Some(Self::Tl80),
+            name if name.starts_with("VKP80") => Some(Self::Vkp80),
+            name if name.starts_with("KPM150") => Some(Self::Kpm150h),
+            _ => None,
+        }
+    }
+
+    /// Resolution of the print head in dots per inch.
+    pub fn dpi(&self) -> u16 {
+        match self {
+            Self::Tg2460h | Self::Tg2480h | Self::Tl60 | Self::Tl80 | Self::Kpm150h => 203,
+            Self::Vkp80 => 200,
+        }
+    }
+
+    /// Name of the model, e.g. `TG2480H`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Tg2460h => "TG2460H",
+            Self::Tg2480h => "TG2480H",
+            Self::Tl60 => "TL60",
+            Self::Tl80 => "TL80",
+            Self::Vkp80 => "VKP80",
+            Self::Kpm150h => "KPM150H",
+        }
+    }
+
+    /// Whether `command` is supported by the model, e.g. partial cuts are only supported by TL60, TL80 and
+    /// KPM150H, and
+    /// the presenter commands only by VKP80, see [`Profile::supports()`].
+    pub fn supports(&self, command: &Command) -> bool {
+        Profile::for_model(*self).supports(command)
+    }
+
+    /// Number of dots in a printed line.
+    pub fn dots_per_line(&self) -> u16 {
+        match self {
+            Self::Tg2460h | Self::Tl60 => 448,
+            Self::Tg2480h | Self::Tl80 | Self::Vkp80 | Self::Kpm150h => 576,
+        }
+    }
+
+    /// Distance between the print head and the cutter in millimeters.
+    pub fn head_to_cutter_mm(&self) -> f32 {
+        match self {
+            Self::Tg2460h | Self::Tg2480h => 14.5,
+            Self::Tl60 | Self::Tl80 => 18.0,
+            Self::Vkp80 => 11.0,
+            Self::Kpm150h => 16.0,
+        }
+    }
+
+    /// Distance between the print head and the cutter in dot lines, rounded up.
+    pub fn head_to_cutter_dots(&self) -> u16 {
+        Profile::for_model(*self).head_to_cutter_dots()
+    }
+}
+
+/// `name` of a printer in upper case without spaces, dashes and underscores, so names are compared regardless of
+/// how the firmware spells them.
+pub(crate) fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CutType;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Model::from_name("TG2480H"), Some(Model::Tg2480h));
+        assert_eq!(Model::from_name("tg2460-h"), Some(Model::Tg2460h));
+        assert_eq!(Model::from_name("TL 80"), Some(Model::Tl80));
+        assert_eq!(Model::from_name("VKP80III"), Some(Model::Vkp80));
+        assert_eq!(Model::from_name("KPM150H"), Some(Model::Kpm150h));
+        assert_eq!(Model::from_name("KPM180H"), None);
+        for model in [
+            Model::Tg2460h,
+            Model::Tg2480h,
+            Model::Tl60,
+            Model::Tl80,
+            Model::Vkp80,
+            Model::Kpm150h,
+        ] {
+            assert_eq!(Model::from_name(model.name()), Some(model));
+        }
+    }
+
+    #[test]
+    fn test_supports() {
+        let partial_cut = Command::CutPaper(CutType::PartialCut);
+        assert!(Model::Tl60.supports(&partial_cut));
+        assert!(!Model::Tg2460h.supports(&partial_cut));
+        assert!(!Model::Vkp80.supports(&partial_cut));
+        assert!(Model::Kpm150h.supports(&partial_cut));
+        assert!(Model::Vkp80.supports(&Command::EjectTicket));
+        assert!(!Model::Tl80.supports(&Command::EjectTicket));
+        assert!(Model::Tg2480h.supports(&Command::CutPaper(CutType::TotalCut)));
+    }
+
+    #[test]
+    fn test_head_to_cutter_dots() {
+        assert_eq!(Model::Tg2480h.head_to_cutter_dots(), 116);
+        assert_eq!(Model::Tl80.head_to_cutter_dots(), 144);
+        assert_eq!(Model::Vkp80.head_to_cutter_dots(), 87);
+        assert_eq!(Model::Kpm150h.head_to_cutter_dots(), 128);
+    }
+}
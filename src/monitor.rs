@@ -0,0 +1,258 @@
+//! Background polling of the printer status.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{CustomPrinter, PrinterFault, Status};
+
+/// Events delivered by [`StatusMonitor`] when the status of the printer changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrinterEvent {
+    /// The printer stopped responding to status queries
+    Offline,
+    /// The printer is back online
+    Online,
+    /// The paper is near its end
+    PaperLow,
+    /// The paper is no longer near its end, e.g. the roll has been replaced
+    PaperOk,
+    /// A fault has been reported
+    Fault(PrinterFault),
+    /// A previously reported fault has been cleared
+    FaultCleared(PrinterFault),
+}
+
+/// Events caused by the status changing from `old` to `new`, where `None` means the printer is offline.
+///
+/// Coming back online is compared against the default status so current conditions are reported again.
+fn diff(old: Option<&Status>, new: Option<&Status>) -> Vec<PrinterEvent> {
+    let (old, new) = match (old, new) {
+        (None, None) => return vec![],
+        (Some(_), None) => return vec![PrinterEvent::Offline],
+        (None, Some(new)) => (Status::default(), *new),
+        (Some(old), Some(new)) => (*old, *new),
+    };
+
+    let mut events = Vec::new();
+    let old_faults = old.faults();
+    let new_faults = new.faults();
+    events.extend(
+        old_faults
+            .iter()
+            .filter(|fault| !new_faults.contains(fault))
+            .map(|fault| PrinterEvent::FaultCleared(*fault)),
+    );
+    events.extend(
+        new_faults
+            .iter()
+            .filter(|fault| !old_faults.contains(fault))
+            .map(|fault| PrinterEvent::Fault(*fault)),
+    );
+    match (old.paper_near_end(), new.paper_near_end()) {
+        (false, true) => events.push(PrinterEvent::PaperLow),
+        (true, false) => events.push(PrinterEvent::PaperOk),
+        _ => {}
+    }
+
+    events
+}
+
+/// Debouncer reporting a status only after it has been observed in a number of consecutive polls.
+struct Debouncer {
+    reported: Option<Status>,
+    pending: Option<Status>,
+    count: usize,
+    threshold: usize,
+}
+
+impl Debouncer {
+    fn new(threshold: usize) -> Self {
+        Self {
+            reported: Some(Status::default()),
+            pending: Some(Status::default()),
+            count: 0,
+            threshold: threshold.max(1),
+        }
+    }
+
+    /// Feed a polled status and return the events to be delivered.
+    fn update(&mut self, status: Option<Status>) -> Vec<PrinterEvent> {
+        if status != self.pending {
+            self.pending = status;
+            self.count = 0;
+        }
+        self.count = self.count.saturating_add(1);
+
+        if self.count < self.threshold || self.pending == self.reported {
+            return vec![];
+        }
+
+        let mut events = diff(self.reported.as_ref(), self.pending.as_ref());
+        if self.reported.is_none() {
+            events.insert(0, PrinterEvent::Online);
+        }
+        self.reported = self.pending;
+
+        events
+    }
+}
+
+/// Monitor polling the status of a [`CustomPrinter`] on its own thread.
+///
+/// [`PrinterEvent`]s are delivered on a channel only after a change of status has been observed
+/// in `debounce` consecutive polls, so a flickering sensor doesn't flood the application with events.
+///
+/// **NOTE:** The monitor takes the ownership of a dedicated [`CustomPrinter`], so the responses to its
+/// status queries are never mixed up with the ones of another handle. The printer is given back by
+/// [`stop()`](StatusMonitor::stop()).
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use custom_printer::{CustomPrinter, PrinterEvent, StatusMonitor};
+/// let printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+/// let monitor = StatusMonitor::spawn(printer, Duration::from_secs(1), 2);
+/// for event in monitor.events() {
+///     match event {
+///         PrinterEvent::PaperLow => println!("Paper is low"),
+///         PrinterEvent::Online => println!("Printer is back online"),
+///         _ => println!("{event:?}"),
+///     }
+/// }
+/// ```
+pub struct StatusMonitor {
+    running: Arc<AtomicBool>,
+    events: Receiver<PrinterEvent>,
+    handle: Option<JoinHandle<CustomPrinter>>,
+}
+
+impl StatusMonitor {
+    /// Spawn a thread polling the status of `printer` every `interval`.
+    pub fn spawn(printer: CustomPrinter, interval: Duration, debounce: usize) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let (sender, events) = mpsc::channel();
+
+        let handle = {
+            let running = running.clone();
+            thread::spawn(move || Self::poll(printer, interval, debounce, &running, &sender))
+        };
+
+        Self {
+            running,
+            events,
+            handle: Some(handle),
+        }
+    }
+
+    fn poll(
+        mut printer: CustomPrinter,
+        interval: Duration,
+        debounce: usize,
+        running: &AtomicBool,
+        sender: &Sender<PrinterEvent>,
+    ) -> CustomPrinter {
+        let mut debouncer = Debouncer::new(debounce);
+
+        while running.load(Ordering::Acquire) {
+            for event in debouncer.update(printer.status().ok()) {
+                if sender.send(event).is_err() {
+                    return printer;
+                }
+            }
+            thread::park_timeout(interval);
+        }
+
+        printer
+    }
+
+    /// Receiver of the [`PrinterEvent`]s.
+    pub fn events(&self) -> &Receiver<PrinterEvent> {
+        &self.events
+    }
+
+    fn join(&mut self) -> Option<CustomPrinter> {
+        self.running.store(false, Ordering::Release);
+        let handle = self.handle.take()?;
+        handle.thread().unpark();
+        handle.join().ok()
+    }
+
+    /// Stop polling and give back the [`CustomPrinter`].
+    pub fn stop(mut self) -> Option<CustomPrinter> {
+        self.join()
+    }
+}
+
+impl Drop for StatusMonitor {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(paper: u8, recoverable: u8) -> Option<Status> {
+        Status::parse(&[0x10, 0x0F, paper, 0x00, recoverable, 0x00])
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            diff(status(0x00, 0x00).as_ref(), None),
+            [PrinterEvent::Offline]
+        );
+        assert_eq!(
+            diff(status(0x00, 0x00).as_ref(), status(0x04, 0x40).as_ref()),
+            [
+                PrinterEvent::Fault(PrinterFault::PaperJam),
+                PrinterEvent::PaperLow
+            ]
+        );
+        assert_eq!(
+            diff(status(0x04, 0x40).as_ref(), status(0x00, 0x00).as_ref()),
+            [
+                PrinterEvent::FaultCleared(PrinterFault::PaperJam),
+                PrinterEvent::PaperOk
+            ]
+        );
+    }
+
+    #[test]
+    fn test_debouncer() {
+        let mut debouncer = Debouncer::new(2);
+        assert_eq!(debouncer.update(status(0x00, 0x00)), []);
+        assert_eq!(debouncer.update(status(0x04, 0x00)), []);
+        assert_eq!(debouncer.update(status(0x00, 0x00)), []);
+        assert_eq!(debouncer.update(status(0x04, 0x00)), []);
+        assert_eq!(
+            debouncer.update(status(0x04, 0x00)),
+            [PrinterEvent::PaperLow]
+        );
+        assert_eq!(debouncer.update(status(0x04, 0x00)), []);
+        assert_eq!(debouncer.update(None), []);
+        assert_eq!(debouncer.update(None), [PrinterEvent::Offline]);
+        assert_eq!(debouncer.update(status(0x04, 0x00)), []);
+        // conditions are reported again when the printer is back online
+        assert_eq!(
+            debouncer.update(status(0x04, 0x00)),
+            [PrinterEvent::Online, PrinterEvent::PaperLow]
+        );
+    }
+
+    #[test]
+    fn test_stop() {
+        let printer = CustomPrinter::new("/dev/null").unwrap();
+        let monitor = StatusMonitor::spawn(printer, Duration::from_millis(10), 1);
+        assert!(monitor.stop().is_some());
+    }
+}
@@ -0,0 +1,427 @@
+//! Parsing of command bytes back into typed commands, e.g. to test the jobs of other applications.
+
+use alloc::vec::Vec;
+
+use crate::{
+    command, BarcodeSystem, BitImageMode, Command, CutType, Density, DrawerPin, FeedUnit, HriFont,
+    HriPosition, Justification, MarkPosition, NearEndSensitivity, PageDirection, PaperSaving,
+    QrErrorCorrection, Speed,
+};
+
+/// Parse `bytes` of CUSTOM ESC/POS commands, e.g. transmitted by this crate or by another application, back into
+/// the [`Command`]s they encode.
+///
+/// The bytes which don't encode a command exactly as [`Command::encode()`] would, e.g. text, the commands not
+/// constructed by this crate or an incomplete command at the end, are kept as they are in [`Command::Raw`],
+/// consecutive ones being merged into a single command. The consecutive banks of bit images of the same mode and
+/// width are merged into a single [`Command::BitImage`]. Encoding the parsed commands thus gives `bytes` back.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{parse, Command, CutType, FeedUnit};
+/// let commands = parse(b"\x1b@CAFE ROMA\x1bd\x02\x1bi");
+/// assert_eq!(
+///     commands,
+///     [
+///         Command::Initialize,
+///         Command::Raw(b"CAFE ROMA".to_vec()),
+///         Command::PrintAndFeed { unit: FeedUnit::Lines, amount: 2 },
+///         Command::CutPaper(CutType::TotalCut),
+///     ]
+/// );
+/// ```
+pub fn parse(bytes: &[u8]) -> Vec<Command> {
+    let mut commands: Vec<Command> = Vec::new();
+    let mut encoded = Vec::new();
+    let mut rest = bytes;
+
+    while !rest.is_empty() {
+        let parsed = parse_one(rest).filter(|(len, command)| {
+            // the fields of some commands are coarser than their bytes, e.g. the buzzer durations
+            encoded.clear();
+            command.encode(&mut encoded);
+            encoded == rest[..*len]
+        });
+        let (len, command) = parsed.unwrap_or_else(|| (1, Command::Raw(rest[..1].to_vec())));
+        rest = &rest[len..];
+
+        match (commands.last_mut(), command) {
+            (Some(Command::Raw(bytes)), Command::Raw(more)) => bytes.extend_from_slice(&more),
+            (
+                Some(Command::BitImage { mode, width, data }),
+                Command::BitImage {
+                    mode: next_mode,
+                    width: next_width,
+                    data: bank,
+                },
+            ) if *mode == next_mode && *width == next_width => data.extend_from_slice(&bank),
+            (_, command) => commands.push(command),
+        }
+    }
+
+    commands
+}
+
+/// Parse the command at the start of `bytes`, returning its length and the command, if it's one of the commands
+/// constructed by this crate.
+fn parse_one(bytes: &[u8]) -> Option<(usize, Command)> {
+    let word = |n: u8, m: u8| u16::from_le_bytes([n, m]);
+    let feed = |unit, amount| Command::PrintAndFeed { unit, amount };
+    let reverse_feed = |unit, amount| Command::PrintAndReverseFeed { unit, amount };
+
+    Some(match *bytes {
+        [0x0A, ..] => (1, Command::Print),
+        [0x0C, ..] => (1, Command::PrintPageAndExit),
+        [0x18, ..] => (1, Command::CancelPageData),
+
+        [0x1B, 0x40, ..] => (2, Command::Initialize),
+        [0x1B, 0x0C, ..] => (2, Command::PrintPage),
+        [0x1B, 0x4C, ..] => (2, Command::EnterPageMode),
+        [0x1B, 0x53, ..] => (2, Command::ExitPageMode),
+        [0x1B, 0x69, ..] => (2, Command::CutPaper(CutType::TotalCut)),
+        [0x1B, 0x6D, ..] => (2, Command::CutPaper(CutType::PartialCut)),
+        [0x1B, 0x4A, n, ..] => (3, feed(FeedUnit::Inches, n)),
+        [0x1B, 0x64, n, ..] => (3, feed(FeedUnit::Lines, n)),
+        [0x1B, 0x4B, n, ..] => (3, reverse_feed(FeedUnit::Inches, n)),
+        [0x1B, 0x65, n, ..] => (3, reverse_feed(FeedUnit::Lines, n)),
+        [0x1B, 0x78, n, ..] => {
+            let speed = match n {
+                0 => Speed::High,
+                1 => Speed::Normal,
+                2 => Speed::Low,
+                _ => return None,
+            };
+            (3, Command::Speed(speed))
+        }
+        [0x1B, 0x54, n, ..] => {
+            let direction = match n {
+                0 => PageDirection::LeftToRight,
+                1 => PageDirection::BottomToTop,
+                2 => PageDirection::RightToLeft,
+                3 => PageDirection::TopToBottom,
+                _ => return None,
+            };
+            (3, Command::PageDirection(direction))
+        }
+        [0x1B, 0x74, n, ..] => (3, Command::CodePage(n)),
+        [0x1B, 0x45, n, ..] => (3, Command::Bold(n == 1)),
+        [0x1B, 0x2D, n, ..] => (3, Command::Underline(n == 1)),
+        [0x1B, 0x61, n, ..] => {
+            let justification = match n {
+                0 => Justification::Left,
+                1 => Justification::Center,
+                2 => Justification::Right,
+                _ => return None,
+            };
+            (3, Command::Justification(justification))
+        }
+        [0x1B, 0x24, xl, xh, 0x1D, 0x24, yl, yh, ..] => (
+            8,
+            Command::Position {
+                x: word(xl, xh),
+                y: word(yl, yh),
+            },
+        ),
+        [0x1B, 0x42, n, t, ..] => (
+            4,
+            Command::Beep {
+                count: n,
+                duration_ms: t as u16 * 100,
+            },
+        ),
+        [0x1B, 0x63, 0x34, n, ..] => (4, Command::StopOnNearEnd(n == 0x03)),
+        [0x1B, 0x63, 0x35, n, ..] => (4, Command::FeedButton(n == 0x00)),
+        [0x1B, 0x70, m, t1, t2, ..] => {
+            let pin = match m {
+                0 => DrawerPin::Pin2,
+                1 => DrawerPin::Pin5,
+                _ => return None,
+            };
+            (
+                5,
+                Command::OpenDrawer {
+                    pin,
+                    on_ms: t1 as u16 * 2,
+                    off_ms: t2 as u16 * 2,
+                },
+            )
+        }
+        [0x1B, 0x8E, 0x00, ..] => (3, Command::Sleep),
+        [0x1B, 0x8E, 0x01, n, ..] => (4, Command::SleepTimeout((n > 0).then_some(n))),
+        [0x1B, 0x57, xl, xh, yl, yh, dxl, dxh, dyl, dyh, ..] => (
+            10,
+            Command::PageArea {
+                x: word(xl, xh),
+                y: word(yl, yh),
+                width: word(dxl, dxh),
+                height: word(dyl, dyh),
+            },
+        ),
+        [0x1B, 0x2A, m, nl, nh, ref data @ ..] => {
+            let width = word(nl, nh);
+            let (mode, k) = match m {
+                0x00 => (BitImageMode::Dots8SingleDensity, width as usize),
+                0x01 => (BitImageMode::Dots8DoubleDensity, width as usize),
+                0x20 => (BitImageMode::Dots24SingleDensity, width as usize * 3),
+                0x21 => (BitImageMode::Dots24DoubleDensity, width as usize * 3),
+                _ => return None,
+            };
+            if width == 0 || data.len() < k {
+                return None;
+            }
+            (
+                5 + k,
+                Command::BitImage {
+                    mode,
+                    width,
+                    data: data[..k].to_vec(),
+                },
+            )
+        }
+
+        [0x1D, 0x0C, ..] => (2, Command::FeedToMark),
+        [0x1D, 0x77, w, 0x1D, 0x48, h, 0x1D, 0x66, f, ..] => {
+            let hri = match h {
+                0 => HriPosition::None,
+                1 => HriPosition::Above,
+                2 => HriPosition::Below,
+                3 => HriPosition::Both,
+                _ => return None,
+            };
+            let hri_font = match f {
+                0 => HriFont::A,
+                1 => HriFont::B,
+                _ => return None,
+            };
+            (
+                9,
+                Command::BarcodeStyle {
+                    module_width: w,
+                    hri,
+                    hri_font,
+                },
+            )
+        }
+        [0x1D, 0x6B, m, n, ref data @ ..] => {
+            let system = match m {
+                65 => BarcodeSystem::UpcA,
+                66 => BarcodeSystem::UpcE,
+                67 => BarcodeSystem::Ean13,
+                68 => BarcodeSystem::Ean8,
+                69 => BarcodeSystem::Code39,
+                70 => BarcodeSystem::Itf,
+                71 => BarcodeSystem::Codabar,
+                72 => BarcodeSystem::Code93,
+                73 => BarcodeSystem::Code128,
+                _ => return None,
+            };
+            let n = n as usize;
+            if data.len() < n {
+                return None;
+            }
+            (
+                4 + n,
+                Command::Barcode {
+                    system,
+                    data: data[..n].to_vec(),
+                },
+            )
+        }
+        [0x1D, 0x7C, n, ..] => {
+            let density = match n {
+                0 => Density::Minus50,
+                1 => Density::Minus25,
+                2 => Density::Zero,
+                3 => Density::Plus25,
+                4 => Density::Plus50,
+                _ => return None,
+            };
+            (3, Command::Density(density))
+        }
+        [0x1D, 0x21, n, ..] => (
+            3,
+            Command::CharacterSize {
+                width: (n >> 4) + 1,
+                height: (n & 0x0F) + 1,
+            },
+        ),
+        [0x1D, 0xF7, n, ..] => (3, Command::PaperSaving(PaperSaving::from_bits(n))),
+        [0x1D, 0xE6, n, ..] => {
+            let sensitivity = match n {
+                0 => NearEndSensitivity::Low,
+                1 => NearEndSensitivity::Medium,
+                2 => NearEndSensitivity::High,
+                _ => return None,
+            };
+            (3, Command::NearEndSensitivity(sensitivity))
+        }
+        [0x1D, 0x50, x, y, ..] => (4, Command::MotionUnits { x, y }),
+        [0x1D, 0x4C, ll, lh, 0x1D, 0x57, wl, wh, ..] => (
+            8,
+            Command::PrintArea {
+                left: word(ll, lh),
+                width: word(wl, wh),
+            },
+        ),
+        [0x1D, 0x56, m @ (0x41 | 0x42), n, ..] => {
+            let cut_type = match m {
+                0x41 => CutType::TotalCut,
+                _ => CutType::PartialCut,
+            };
+            (
+                4,
+                Command::FeedAndCutPaper {
+                    cut_type,
+                    amount: n,
+                },
+            )
+        }
+        [0x1D, 0x65, 0x01, n, ..] => (4, Command::PresenterLoop(n)),
+        [0x1D, 0x65, 0x02, ..] => (3, Command::RetractTicket),
+        [0x1D, 0x65, 0x03, n, ..] => (4, Command::PresentTicket(n)),
+        [0x1D, 0x65, 0x05, ..] => (3, Command::EjectTicket),
+        [0x1D, 0x65, 0x12, n, ..] => (4, Command::RetractOnTimeout((n > 0).then_some(n))),
+        [0x1D, 0x28, 0x6B, ..] => return parse_qr_code(bytes),
+        [0x1D, 0x28, 0x46, 0x04, 0x00, p, sign @ (0 | 1), lo, hi, ..] => {
+            let position = match p {
+                1 => MarkPosition::PrintStart,
+                2 => MarkPosition::Cut,
+                _ => return None,
+            };
+            let amount = word(lo, hi) as i32;
+            let offset = i16::try_from(if sign == 1 { -amount } else { amount }).ok()?;
+            (9, Command::MarkOffset { position, offset })
+        }
+
+        _ => return None,
+    })
+}
+
+/// Parse the functions of the QR code at the start of `bytes`, in the order [`Command::encode()`] writes them.
+fn parse_qr_code(bytes: &[u8]) -> Option<(usize, Command)> {
+    let rest = bytes
+        .strip_prefix(command::QR_MODEL_2)?
+        .strip_prefix(command::QR_MODULE_SIZE)?;
+    let (&module_size, rest) = rest.split_first()?;
+    let (&level, rest) = rest
+        .strip_prefix(command::QR_ERROR_CORRECTION)?
+        .split_first()?;
+    let error_correction = match level {
+        48 => QrErrorCorrection::Low,
+        49 => QrErrorCorrection::Medium,
+        50 => QrErrorCorrection::Quartile,
+        51 => QrErrorCorrection::High,
+        _ => return None,
+    };
+    let [pl, ph, 0x31, 0x50, 0x30, ref rest @ ..] = *rest.strip_prefix(command::QR_STORE)? else {
+        return None;
+    };
+    // the length counts the 3 bytes of the function before the data
+    let n = (u16::from_le_bytes([pl, ph]) as usize).checked_sub(3)?;
+    let data = rest.get(..n)?;
+    if !rest[n..].starts_with(command::QR_PRINT) {
+        return None;
+    }
+
+    Some((
+        bytes.len() - rest.len() + n + command::QR_PRINT.len(),
+        Command::QrCode {
+            data: data.to_vec(),
+            module_size,
+            error_correction,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_parse() {
+        let commands = vec![
+            Command::Initialize,
+            Command::Raw(b"Hi!".to_vec()),
+            Command::Print,
+            Command::PrintArea {
+                left: 16,
+                width: 512,
+            },
+            Command::CharacterSize {
+                width: 2,
+                height: 3,
+            },
+            Command::Bold(true),
+            Command::BitImage {
+                mode: BitImageMode::Dots24DoubleDensity,
+                width: 2,
+                data: vec![0xFF; 12],
+            },
+            Command::Print,
+            Command::BarcodeStyle {
+                module_width: 2,
+                hri: HriPosition::Both,
+                hri_font: HriFont::B,
+            },
+            Command::Barcode {
+                system: BarcodeSystem::Ean13,
+                data: b"400638133393".to_vec(),
+            },
+            Command::Justification(Justification::Center),
+            Command::QrCode {
+                data: b"https://example.com".to_vec(),
+                module_size: 6,
+                error_correction: QrErrorCorrection::Low,
+            },
+            Command::Justification(Justification::Left),
+            Command::MarkOffset {
+                position: MarkPosition::Cut,
+                offset: -300,
+            },
+            Command::PaperSaving(PaperSaving {
+                line_spacing: true,
+                ..Default::default()
+            }),
+            Command::OpenDrawer {
+                pin: DrawerPin::Pin5,
+                on_ms: 100,
+                off_ms: 200,
+            },
+            Command::SleepTimeout(None),
+            Command::EnterPageMode,
+            Command::PageArea {
+                x: 0,
+                y: 8,
+                width: 384,
+                height: 200,
+            },
+            Command::PageDirection(PageDirection::TopToBottom),
+            Command::Position { x: 300, y: 2 },
+            Command::PrintPageAndExit,
+            Command::RetractOnTimeout(Some(30)),
+            Command::FeedAndCutPaper {
+                cut_type: CutType::PartialCut,
+                amount: 0,
+            },
+        ];
+        assert_eq!(parse(&command::encode(&commands)), commands);
+    }
+
+    #[test]
+    fn test_parse_raw() {
+        // the print modes aren't constructed by this crate, and the timeout of the buzzer is too long
+        let bytes = b"\x1b!\x08Total\x1bB\x02\x0f\x0a\x1b*\x00\x04\x00\x01";
+        assert_eq!(
+            parse(bytes),
+            [
+                Command::Raw(b"\x1b!\x08Total\x1bB\x02\x0f".to_vec()),
+                Command::Print,
+                // the bit image is truncated
+                Command::Raw(b"\x1b*\x00\x04\x00\x01".to_vec()),
+            ]
+        );
+        assert_eq!(command::encode(&parse(bytes)), bytes);
+    }
+}
@@ -0,0 +1,26 @@
+//! Commonly used types and traits, to be imported together by `use custom_printer::prelude::*;`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use custom_printer::prelude::*;
+//!
+//! let job = JobBuilder::new()
+//!     .print_and_feed_paper(FeedUnit::Lines, 2)
+//!     .cut_paper(CutType::TotalCut)
+//!     .build();
+//! # #[cfg(feature = "std")]
+//! # {
+//! let mut printer = CustomPrinter::new("/dev/null").unwrap();
+//! printer.print_job(&job).unwrap();
+//! # }
+//! ```
+
+#[cfg(feature = "std")]
+pub use crate::CustomPrinter;
+pub use crate::{
+    AutoCut, BarcodeSystem, BitImageMode, Command, CommandBuilder, CommandSet, CutType, Density,
+    DrawerPin, Emulator, Error, FeedUnit, HriFont, HriPosition, Job, JobBuilder, Justification,
+    Length, MarkPosition, Model, NearEndSensitivity, PageDirection, PaperSaving, PaperWidth,
+    PrinterConfig, Profile, QrErrorCorrection, Receipt, Speed, Status, Transport,
+};
@@ -0,0 +1,620 @@
+//! Preview of the paper printed by the commands as a bitmap, for design reviews without any printer.
+
+#[cfg(feature = "image")]
+use alloc::string::ToString;
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(feature = "image")]
+use image::{codecs::png::PngEncoder, ColorType, ImageEncoder};
+
+#[cfg(feature = "image")]
+use crate::{job::crc32, Error};
+use crate::{
+    raster::{Raster, CELL_HEIGHT, CELL_WIDTH},
+    BitImageMode, Command, CutType, FeedUnit, Justification, PageDirection,
+};
+
+/// Line spacing after initializing the printer in dots, i.e. 1/6 inch at 203 dpi.
+const LINE_SPACING_DOTS: usize = 34;
+
+/// Style of the characters being printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Style {
+    bold: bool,
+    underline: bool,
+    width: usize,
+    height: usize,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            bold: false,
+            underline: false,
+            width: 1,
+            height: 1,
+        }
+    }
+}
+
+/// Data of the line being printed, at `x` dots from the start of the line.
+#[derive(Debug, Clone)]
+enum Item {
+    Char { x: usize, c: char, style: Style },
+    Image { x: usize, raster: Raster },
+}
+
+/// Paper, or print area in page mode, drawn line by line.
+#[derive(Debug, Clone)]
+struct Sheet {
+    width: usize,
+    // left margin and length of the printed lines
+    left: usize,
+    line: usize,
+    justification: Justification,
+    bands: Vec<Raster>,
+    items: Vec<Item>,
+    x: usize,
+}
+
+impl Sheet {
+    fn new(width: usize) -> Self {
+        Self {
+            width,
+            left: 0,
+            line: width,
+            justification: Justification::Left,
+            bands: Vec::new(),
+            items: Vec::new(),
+            x: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bands.is_empty() && self.items.is_empty()
+    }
+
+    /// Append the character `c` to the line, printing the line first if `c` doesn't fit.
+    fn text(&mut self, c: char, style: Style) {
+        let width = CELL_WIDTH * style.width;
+        if self.x > 0 && self.x + width > self.line {
+            self.print();
+        }
+        self.items.push(Item::Char {
+            x: self.x,
+            c,
+            style,
+        });
+        self.x += width;
+    }
+
+    /// Append the bit image `raster` of `width` dots to the line, printing the line first if it doesn't fit.
+    ///
+    /// The raster may be wider than the image, as its rows are padded to whole bytes.
+    fn image(&mut self, raster: Raster, width: usize) {
+        if self.x > 0 && self.x + width > self.line {
+            self.print();
+        }
+        self.items.push(Item::Image { x: self.x, raster });
+        self.x += width;
+    }
+
+    /// Print the line, a blank line of the line spacing if it's empty.
+    ///
+    /// The data of the line is aligned at the bottom of the tallest of them, and a line of text is at least as
+    /// tall as the line spacing, while the lines of bit images only are as tall as the images, so consecutive
+    /// banks are printed without any gap.
+    fn print(&mut self) {
+        let heights = self.items.iter().map(|item| match item {
+            Item::Char { style, .. } => CELL_HEIGHT * style.height,
+            Item::Image { raster, .. } => raster.height(),
+        });
+        let bottom = heights.max().unwrap_or(0);
+        let text = self
+            .items
+            .iter()
+            .any(|item| matches!(item, Item::Char { .. }));
+        let height = if text || self.items.is_empty() {
+            bottom.max(LINE_SPACING_DOTS)
+        } else {
+            bottom
+        };
+
+        let left = self.left
+            + match self.justification {
+                Justification::Left => 0,
+                Justification::Center => self.line.saturating_sub(self.x) / 2,
+                Justification::Right => self.line.saturating_sub(self.x),
+            };
+        let mut band = Raster::new(self.width, height);
+        for item in self.items.drain(..) {
+            match item {
+                Item::Char { x, c, style } => {
+                    let (width, height) = (CELL_WIDTH * style.width, CELL_HEIGHT * style.height);
+                    let (left, top) = (left + x, bottom - height);
+                    let mut cell = Raster::new(CELL_WIDTH, CELL_HEIGHT);
+                    cell.text(0, 0, c.encode_utf8(&mut [0; 4]), style.bold);
+                    for y in 0..CELL_HEIGHT {
+                        for x in (0..CELL_WIDTH).filter(|x| cell.get(*x, y)) {
+                            band.fill(
+                                left + x * style.width,
+                                top + y * style.height,
+                                style.width,
+                                style.height,
+                            );
+                        }
+                    }
+                    if style.underline {
+                        band.fill(left, top + height - 2, width, 2);
+                    }
+                }
+                Item::Image { x, raster } => {
+                    blit(&mut band, &raster, left + x, bottom - raster.height());
+                }
+            }
+        }
+        self.bands.push(band);
+        self.x = 0;
+    }
+
+    /// Print the line if anything is left in it.
+    fn flush(&mut self) {
+        if !self.items.is_empty() {
+            self.print();
+        }
+    }
+
+    /// Append `band` below the printed lines.
+    fn band(&mut self, band: Raster) {
+        self.flush();
+        self.bands.push(band);
+    }
+
+    /// The printed lines stacked in a raster.
+    fn finish(mut self) -> Raster {
+        self.flush();
+        let mut raster = Raster::new(self.width, self.bands.iter().map(Raster::height).sum());
+        let mut y = 0;
+        for band in &self.bands {
+            blit(&mut raster, band, 0, y);
+            y += band.height();
+        }
+
+        raster
+    }
+}
+
+/// A print area of page mode, and its print direction.
+#[derive(Debug, Clone)]
+struct Area {
+    // origin and size, or the whole width and as tall as the data if unset
+    rect: Option<(usize, usize, usize, usize)>,
+    direction: PageDirection,
+    sheet: Sheet,
+}
+
+impl Area {
+    fn new(
+        rect: Option<(usize, usize, usize, usize)>,
+        direction: PageDirection,
+        dots: usize,
+    ) -> Self {
+        let line = match (rect, direction) {
+            (None, _) => dots,
+            (Some((_, _, width, _)), PageDirection::LeftToRight | PageDirection::RightToLeft) => {
+                width
+            }
+            (Some((_, _, _, height)), _) => height,
+        };
+
+        Self {
+            rect,
+            direction,
+            sheet: Sheet::new(line),
+        }
+    }
+}
+
+/// State of the printer drawing the paper.
+struct Preview {
+    dots: usize,
+    style: Style,
+    paper: Sheet,
+    // print areas of the page, in page mode
+    page: Option<Vec<Area>>,
+    rect: Option<(usize, usize, usize, usize)>,
+    direction: PageDirection,
+}
+
+impl Preview {
+    fn new(dots: usize) -> Self {
+        Self {
+            dots,
+            style: Style::default(),
+            paper: Sheet::new(dots),
+            page: None,
+            rect: None,
+            direction: PageDirection::LeftToRight,
+        }
+    }
+
+    /// The sheet the data is printed on, the current print area in page mode.
+    fn sheet(&mut self) -> &mut Sheet {
+        match &mut self.page {
+            Some(areas) => {
+                if areas.is_empty() {
+                    areas.push(Area::new(self.rect, self.direction, self.dots));
+                }
+                &mut areas.last_mut().unwrap().sheet
+            }
+            None => &mut self.paper,
+        }
+    }
+
+    fn apply(&mut self, command: &Command) {
+        match command {
+            Command::Initialize => {
+                self.style = Style::default();
+                self.paper.flush();
+                (self.paper.left, self.paper.line) = (0, self.dots);
+                self.paper.justification = Justification::Left;
+                self.page = None;
+                self.rect = None;
+                self.direction = PageDirection::LeftToRight;
+            }
+            Command::Raw(bytes) => {
+                let style = self.style;
+                for &byte in bytes {
+                    match byte {
+                        b'\n' => self.sheet().print(),
+                        b' '..=b'~' => self.sheet().text(byte as char, style),
+                        0x80.. => self.sheet().text('?', style),
+                        _ => {}
+                    }
+                }
+            }
+            Command::Print => self.sheet().print(),
+            Command::PrintAndFeed { unit, amount } => {
+                let dots = match unit {
+                    FeedUnit::Lines => *amount as usize * LINE_SPACING_DOTS,
+                    FeedUnit::Inches => *amount as usize,
+                };
+                let sheet = self.sheet();
+                let width = sheet.width;
+                sheet.band(Raster::new(width, dots));
+            }
+            Command::PrintAndReverseFeed { .. } | Command::FeedToMark => self.sheet().flush(),
+            Command::Bold(bold) => self.style.bold = *bold,
+            Command::Underline(underline) => self.style.underline = *underline,
+            Command::CharacterSize { width, height } => {
+                self.style.width = (*width).clamp(1, 8) as usize;
+                self.style.height = (*height).clamp(1, 8) as usize;
+            }
+            Command::PrintArea { left, width } => {
+                let left = (*left as usize).min(self.dots);
+                (self.paper.left, self.paper.line) =
+                    (left, (*width as usize).min(self.dots - left));
+            }
+            Command::BitImage { mode, width, data } => {
+                if let Some((raster, dots)) = bit_image(*mode, *width as usize, data) {
+                    self.sheet().image(raster, dots);
+                }
+            }
+            Command::Barcode { system, data } => {
+                let style = self.style;
+                let text = format!("[{system:?} {}]", String::from_utf8_lossy(data));
+                let sheet = self.sheet();
+                sheet.flush();
+                for c in text.chars() {
+                    sheet.text(c, style);
+                }
+                sheet.print();
+            }
+            Command::QrCode {
+                data,
+                module_size,
+                error_correction,
+            } => {
+                let modules = error_correction.modules(data.len()).unwrap_or(177);
+                let sheet = self.sheet();
+                sheet.flush();
+                let dots = modules as usize * *module_size as usize;
+                sheet.image(qr_code(modules as usize, *module_size as usize), dots);
+                sheet.print();
+            }
+            // page mode ignores the justification
+            Command::Justification(justification) => self.paper.justification = *justification,
+            Command::CutPaper(cut_type) | Command::FeedAndCutPaper { cut_type, .. } => {
+                let (dash, period) = match cut_type {
+                    CutType::TotalCut => (8, 12),
+                    CutType::PartialCut => (4, 8),
+                };
+                let mut band = Raster::new(self.dots, LINE_SPACING_DOTS);
+                for x in (0..self.dots).step_by(period) {
+                    band.fill(x, LINE_SPACING_DOTS / 2, dash, 1);
+                }
+                self.paper.band(band);
+            }
+            Command::EnterPageMode => self.page = Some(Vec::new()),
+            Command::ExitPageMode => self.page = None,
+            Command::PageArea {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                self.rect = Some((*x as usize, *y as usize, *width as usize, *height as usize));
+                if let Some(areas) = &mut self.page {
+                    areas.push(Area::new(self.rect, self.direction, self.dots));
+                }
+            }
+            Command::PageDirection(direction) => {
+                self.direction = *direction;
+                if let Some(areas) = &mut self.page {
+                    // the direction applies to the data following it
+                    if areas.last().is_some_and(|area| area.sheet.is_empty()) {
+                        areas.pop();
+                    }
+                    areas.push(Area::new(self.rect, self.direction, self.dots));
+                }
+            }
+            Command::CancelPageData => {
+                if let Some(area) = self.page.as_mut().and_then(|areas| areas.last_mut()) {
+                    area.sheet = Sheet::new(area.sheet.width);
+                }
+            }
+            Command::PrintPage | Command::PrintPageAndExit => {
+                if let Some(areas) = &self.page {
+                    let page = compose(areas, self.dots);
+                    self.paper.band(page);
+                }
+                if matches!(command, Command::PrintPageAndExit) {
+                    self.page = None;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The bit image of `width` columns in `mode` and its width in dots, the dots of the single density modes being
+/// twice as wide.
+fn bit_image(mode: BitImageMode, width: usize, data: &[u8]) -> Option<(Raster, usize)> {
+    let (bank, scale) = match mode {
+        BitImageMode::Dots8SingleDensity => (8, 2),
+        BitImageMode::Dots8DoubleDensity => (8, 1),
+        BitImageMode::Dots24SingleDensity => (24, 2),
+        BitImageMode::Dots24DoubleDensity => (24, 1),
+    };
+    let k = width * bank / 8;
+    if k == 0 {
+        return None;
+    }
+
+    let banks = data.len() / k;
+    let mut raster = Raster::new(width * scale, banks * bank);
+    for (i, bytes) in data.chunks_exact(k).enumerate() {
+        for (x, column) in bytes.chunks_exact(bank / 8).enumerate() {
+            for y in (0..bank).filter(|y| column[y / 8] & (0x80 >> (y % 8)) != 0) {
+                raster.fill(x * scale, i * bank + y, scale, 1);
+            }
+        }
+    }
+
+    Some((raster, width * scale))
+}
+
+/// A QR code of `modules` of `size` dots, drawn by its finder and timing patterns only since the data isn't
+/// encoded, so its size and position can be reviewed.
+fn qr_code(modules: usize, size: usize) -> Raster {
+    let mut raster = Raster::new(modules * size, modules * size);
+    let mut module = |x: usize, y: usize| raster.fill(x * size, y * size, size, size);
+    for (left, top) in [(0, 0), (modules - 7, 0), (0, modules - 7)] {
+        for i in 0..7 {
+            for j in 0..7 {
+                // a square ring of 7 modules around a square of 3 modules
+                let ring = i == 0 || i == 6 || j == 0 || j == 6;
+                let center = (2..=4).contains(&i) && (2..=4).contains(&j);
+                if ring || center {
+                    module(left + i, top + j);
+                }
+            }
+        }
+    }
+    for i in (8..modules - 8).step_by(2) {
+        module(i, 6);
+        module(6, i);
+    }
+
+    raster
+}
+
+/// Compose the print `areas` of a page on a line of `dots`, each rotated by its print direction.
+fn compose(areas: &[Area], dots: usize) -> Raster {
+    let areas: Vec<_> = areas
+        .iter()
+        .map(|area| {
+            let raster = area.sheet.clone().finish();
+            let rect = area.rect.unwrap_or(match area.direction {
+                PageDirection::LeftToRight | PageDirection::RightToLeft => {
+                    (0, 0, dots, raster.height())
+                }
+                PageDirection::BottomToTop | PageDirection::TopToBottom => {
+                    (0, 0, raster.height(), dots)
+                }
+            });
+            (rect, area.direction, raster)
+        })
+        .collect();
+
+    let height = areas
+        .iter()
+        .map(|((_, y, _, h), ..)| y + h)
+        .max()
+        .unwrap_or(0);
+    let mut page = Raster::new(dots, height);
+    for ((left, top, width, height), direction, raster) in &areas {
+        for y in 0..raster.height() {
+            for x in (0..raster.width()).filter(|x| raster.get(*x, y)) {
+                let position = match direction {
+                    PageDirection::LeftToRight => Some((x, y)),
+                    PageDirection::RightToLeft => {
+                        width.checked_sub(x + 1).zip(height.checked_sub(y + 1))
+                    }
+                    PageDirection::BottomToTop => Some(y).zip(height.checked_sub(x + 1)),
+                    PageDirection::TopToBottom => width.checked_sub(y + 1).zip(Some(x)),
+                };
+                if let Some((x, y)) = position.filter(|(x, y)| x < width && y < height) {
+                    page.set(left + x, top + y);
+                }
+            }
+        }
+    }
+
+    page
+}
+
+/// Draw the black dots of `source` on `target` from `(x, y)`.
+fn blit(target: &mut Raster, source: &Raster, x: usize, y: usize) {
+    for row in 0..source.height() {
+        for column in (0..source.width()).filter(|column| source.get(*column, row)) {
+            target.set(x + column, y + row);
+        }
+    }
+}
+
+/// Draw the paper printed by `commands` on a line of `dots`, one dot of the raster for each dot of the print head.
+///
+/// Text is drawn by the glyphs of the characters of font A, enlarged and emphasized by the character size, bold
+/// and underline styles, bit images by their dots, barcodes by their system and data in brackets like
+/// [`Emulator::render()`](crate::Emulator::render()) and QR codes by their finder patterns. The lines are justified
+/// and the areas of page mode are rotated by their print direction. Each motion unit is assumed to be one dot, and the other settings, e.g. density, are left out.
+pub(crate) fn rasterize(commands: &[Command], dots: u16) -> Raster {
+    let mut preview = Preview::new(dots as usize);
+    for command in commands {
+        preview.apply(command);
+    }
+
+    preview.paper.finish()
+}
+
+/// Encode `raster` as a grayscale PNG image, black on white, with the physical resolution of `dpi`.
+#[cfg(feature = "image")]
+pub(crate) fn png(raster: &Raster, dpi: u16) -> Result<Vec<u8>, Error> {
+    // an image has at least a row
+    let (width, height) = (raster.width(), raster.height().max(1));
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        pixels.extend((0..width).map(|x| if raster.get(x, y) { 0x00 } else { 0xFF }));
+    }
+
+    let mut bytes = Vec::new();
+    PngEncoder::new(&mut bytes)
+        .write_image(&pixels, width as u32, height as u32, ColorType::L8)
+        .map_err(|error| Error::UnsupportedImage(error.to_string()))?;
+
+    // the pHYs chunk of the dots per meter follows the IHDR chunk after the signature
+    let dots_per_meter = ((dpi as f32 / 0.0254).round() as u32).to_be_bytes();
+    let mut chunk = Vec::from(*b"pHYs");
+    chunk.extend_from_slice(&dots_per_meter);
+    chunk.extend_from_slice(&dots_per_meter);
+    chunk.push(1);
+    let crc = crc32(&chunk).to_be_bytes();
+    let phys = [&9u32.to_be_bytes()[..], &chunk, &crc].concat();
+    bytes.splice(33..33, phys);
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandBuilder, JobBuilder};
+    use alloc::vec;
+
+    #[test]
+    fn test_rasterize() {
+        let job = JobBuilder::new()
+            .raw(b"I")
+            .character_size(2, 3)
+            .bold(true)
+            .raw(b"I\n")
+            .character_size(1, 1)
+            .print_and_feed_paper(FeedUnit::Inches, 10)
+            .cut_paper(CutType::PartialCut)
+            .build();
+        let raster = rasterize(job.commands(), 100);
+        assert_eq!((raster.width(), raster.height()), (104, 72 + 10 + 34));
+        // the small I is aligned at the bottom of the large one
+        assert!(raster.get(5, 48 + 10) && !raster.get(5, 48 - 2));
+        assert!(raster.get(12 + 11, 20) && raster.get(12 + 15, 20));
+        // the partial cut is dashed
+        assert!(raster.get(0, 72 + 10 + 17) && !raster.get(5, 72 + 10 + 17));
+    }
+
+    #[test]
+    fn test_bit_image() {
+        // a 24 dot bank of 2 columns, printed twice as wide in single density
+        let commands = [
+            Command::BitImage {
+                mode: BitImageMode::Dots24SingleDensity,
+                width: 2,
+                data: vec![0x80, 0, 0x01, 0, 0, 0],
+            },
+            Command::Print,
+        ];
+        let raster = rasterize(&commands, 16);
+        assert_eq!(raster.height(), 24);
+        assert!(raster.get(0, 0) && raster.get(1, 0) && raster.get(1, 23));
+        assert!(!raster.get(2, 0) && !raster.get(0, 1));
+    }
+
+    #[test]
+    fn test_page_mode() {
+        let job = JobBuilder::new()
+            .enter_page_mode()
+            .page_area(0, 0, 24, 48)
+            .page_direction(PageDirection::BottomToTop)
+            .raw(b"-")
+            .print_page_and_exit()
+            .build();
+        let raster = rasterize(job.commands(), 48);
+        assert_eq!(raster.height(), 48);
+        // the dash is drawn upright at the bottom of the area, running upwards
+        assert!((37..47).all(|y| raster.get(11, y) || raster.get(12, y)));
+        assert!(!raster.get(11, 20) && !raster.get(30, 40));
+    }
+
+    #[test]
+    fn test_qr_code() {
+        let job = JobBuilder::new()
+            .justification(Justification::Center)
+            .qr_code(b"12345", 2, crate::QrErrorCorrection::Low)
+            .unwrap()
+            .build();
+        let raster = rasterize(job.commands(), 100);
+        // version 1 of 21 modules of 2 dots, centered in 100 dots
+        assert_eq!(raster.height(), 42);
+        assert!(raster.get(29, 0) && !raster.get(28, 0) && raster.get(70, 0) && raster.get(29, 41));
+        // the center of the finder pattern, inside its blank ring
+        assert!(raster.get(29 + 6, 6) && !raster.get(29 + 3, 3));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_png() {
+        let mut raster = Raster::new(8, 2);
+        raster.set(0, 1);
+        let bytes = png(&raster, 203).unwrap();
+        assert_eq!(&bytes[37..41], b"pHYs");
+        // 203 dpi is 7992 dots per meter
+        assert_eq!(bytes[41..45], 7992u32.to_be_bytes());
+
+        let image = image::load_from_memory(&bytes).unwrap().to_luma8();
+        assert_eq!(image.dimensions(), (8, 2));
+        assert_eq!(
+            (image.get_pixel(0, 1).0, image.get_pixel(0, 0).0),
+            ([0], [255])
+        );
+    }
+}
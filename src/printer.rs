@@ -0,0 +1,3060 @@
+//! The printer connected to a device node.
+
+#[cfg(unix)]
+use std::os::unix::io::{FromRawFd, RawFd};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    net::TcpStream,
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "image")]
+use crate::{bitimage_len, builder, BitImageMode};
+use crate::{
+    command,
+    command_set::{self, Line},
+    estimate, mnemonics, model, status,
+    trace::TraceHook,
+    validate, AutoCut, Command, CommandBuilder, CommandSet, CustomEscPos, Error, Estimate,
+    Features, Job, JobBuilder, MaintenanceCounters, Model, NvMemory, OfflineCause, PrinterConfig,
+    PrinterFault, PrinterInfo, Problem, Profile, Status, TraceEvent,
+};
+// Status commands
+const TRANSMIT_ID: &[u8] = &[0x1D, 0x49];
+const FULL_STATUS: &[u8] = &[0x10, 0x04, 0x14];
+const OFFLINE_STATUS: &[u8] = &[0x10, 0x04, 0x02];
+const MAINTENANCE_COUNTER: &[u8] = &[0x1D, 0x67, 0x32, 0x00];
+const NV_CAPACITY: &[u8] = &[0x1D, 0x28, 0x4C, 0x02, 0x00, 0x30, 0x00];
+const NV_REMAINING: &[u8] = &[0x1D, 0x28, 0x4C, 0x02, 0x00, 0x30, 0x03];
+// Real-time commands
+const WAKE_UP: &[u8] = &[0x00];
+const RECOVER_AND_RESTART: &[u8] = &[0x10, 0x05, 0x01];
+const CLEAR_BUFFER: &[u8] = &[0x10, 0x14, 0x08, 0x01, 0x03, 0x14, 0x01, 0x06, 0x02, 0x08];
+
+// Parameters of TRANSMIT_ID command
+const ID_TYPE: u8 = 0x02;
+const ID_FIRMWARE_VERSION: u8 = 0x41;
+const ID_MODEL_NAME: u8 = 0x43;
+
+// Numbers of MAINTENANCE_COUNTER command
+const COUNTER_DOT_LINES: u16 = 20;
+const COUNTER_CUTS: u16 = 50;
+const COUNTER_POWER_ON_HOURS: u16 = 70;
+
+// Headers of the responses to NV_CAPACITY and NV_REMAINING commands
+const NV_CAPACITY_HEADER: &[u8] = &[0x37, 0x30];
+const NV_REMAINING_HEADER: &[u8] = &[0x37, 0x31];
+
+// Maximum length of a text block transmitted by the printer
+const MAX_TEXT_BLOCK_LEN: usize = 80;
+// Time to wait for the response of a query command
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(500);
+// Interval between attempts to read the response
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+// Time for the printer to wake up from sleep mode
+const WAKE_UP_DELAY: Duration = Duration::from_millis(100);
+// Interval between status queries while waiting for the printer
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// Number of buffers of the bit images kept for reuse, e.g. a logo and a receipt rendered as an image
+const MAX_SCRATCH_BUFFERS: usize = 4;
+
+/// Handle of the device the printer is connected to.
+#[derive(Debug)]
+enum Device {
+    File(File),
+    Stream(TcpStream),
+}
+
+impl Read for Device {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::File(file) => file.read(buf),
+            Self::Stream(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Device {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::File(file) => file.write(buf),
+            Self::Stream(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::File(file) => file.flush(),
+            Self::Stream(stream) => stream.flush(),
+        }
+    }
+}
+
+/// The main struct to construct printing commands and accomplish actual printing.
+///
+/// The APIs are designed to be able to concatenate one after the other.
+/// The constructing functions are provided by [`CommandBuilder`], which also builds a [`Job`] without any device
+/// by [`JobBuilder`](crate::JobBuilder).
+///
+/// # Examples
+///
+/// ```no_run
+/// # use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter, CutType, FeedUnit};
+/// let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+/// printer
+///     .bit_image(
+///         "logo.bmp",
+///         BitImageMode::Dots24DoubleDensity
+///     )
+///     .unwrap()
+///     .print()
+///     .cut_paper(CutType::PartialCut)
+///     .run_chained()
+///     .unwrap()
+///     .bit_image(
+///         "greeting.bmp",
+///         BitImageMode::Dots24DoubleDensity
+///     )
+///     .unwrap()
+///     .print_and_feed_paper(FeedUnit::Lines, 10)
+///     .cut_paper(CutType::TotalCut)
+///     .run()
+///     .unwrap();
+/// ```
+pub struct CustomPrinter {
+    device: Option<Device>,
+    cmd: Vec<Command>,
+    buf: Vec<u8>,
+    scratch: Vec<Vec<u8>>,
+    auto_cut: Option<AutoCut>,
+    model: Option<Model>,
+    profile: Option<Profile>,
+    config: Option<PrinterConfig>,
+    max_size: Option<usize>,
+    size: usize,
+    overflowed: bool,
+    trace: Option<TraceHook>,
+    savepoint: Option<Savepoint>,
+    jobs: u64,
+    command_set: Box<dyn CommandSet + Send>,
+}
+
+/// Outcome of running the commands by [`CustomPrinter::run()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PrintOutcome {
+    /// Number of bytes written to the device
+    pub bytes_written: usize,
+    /// Time taken to write the bytes to the device
+    pub duration: Duration,
+    /// Sequence number of the job on the printer, starting from 1 and counting the jobs sent by
+    /// [`print_job()`](CustomPrinter::print_job()) and [`print_jobs()`](CustomPrinter::print_jobs()) as well
+    pub job_id: u64,
+}
+
+/// State of the constructed commands restored by [`CustomPrinter::rollback()`].
+#[derive(Debug, Clone, Copy)]
+struct Savepoint {
+    len: usize,
+    size: usize,
+    overflowed: bool,
+}
+
+impl CustomPrinter {
+    /// Create a new [`CustomPrinter`] with the device node `dev`.
+    ///
+    /// **NOTE:** Device node `dev` must be readable and writable by current user.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::CustomPrinter;
+    /// CustomPrinter::new("/dev/usb/lp0")
+    /// # ;
+    /// ```
+    pub fn new(dev: impl AsRef<Path>) -> Result<Self, Error> {
+        let dev = dev.as_ref();
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .open(dev)
+            .map_err(|source| Error::DeviceOpen {
+                path: dev.to_path_buf(),
+                source,
+            })?;
+        Ok(Self::from_file(file))
+    }
+
+    /// Open the printer connected to the device node `dev` like [`new()`](CustomPrinter::new()), identify its
+    /// model and use the profile of the model, see [`detect_profile()`](CustomPrinter::detect_profile()).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use custom_printer::{CommandBuilder, CustomPrinter};
+    /// let mut printer = CustomPrinter::auto("/dev/usb/lp0").unwrap();
+    /// if let Some(profile) = printer.profile() {
+    ///     println!("printing on {}", profile.name);
+    /// }
+    /// ```
+    pub fn auto(dev: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut printer = Self::new(dev)?;
+        printer.detect_profile(&[])?;
+
+        Ok(printer)
+    }
+
+    /// Create a new [`CustomPrinter`] with the already opened device node `file`, e.g. opened by a privileged
+    /// process or passed by systemd.
+    ///
+    /// **NOTE:** `file` must be opened for reading and writing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::fs::File;
+    /// # use custom_printer::CustomPrinter;
+    /// let file = File::options().read(true).write(true).open("/dev/null").unwrap();
+    /// let printer = CustomPrinter::from_file(file);
+    /// ```
+    pub fn from_file(file: File) -> Self {
+        Self::with_device(Device::File(file))
+    }
+
+    /// Create a new [`CustomPrinter`] with the connected `stream`, e.g. to a network printer or a serial
+    /// device server, so the commands are sent and the responses are received over the connection.
+    ///
+    /// The read timeout of `stream` is set for polling the responses of the printer.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::net::TcpStream;
+    /// # use custom_printer::CustomPrinter;
+    /// let stream = TcpStream::connect("192.168.1.100:9100").unwrap();
+    /// let printer = CustomPrinter::from_stream(stream).unwrap();
+    /// ```
+    pub fn from_stream(stream: TcpStream) -> Result<Self, Error> {
+        stream
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(Error::Read)?;
+
+        Ok(Self::with_device(Device::Stream(stream)))
+    }
+
+    fn with_device(device: Device) -> Self {
+        let mut printer = Self::detached();
+        printer.device = Some(device);
+
+        printer
+    }
+
+    /// Create a new [`CustomPrinter`] without any device, e.g. to generate jobs for spooling,
+    /// network transmission or storing in a database.
+    ///
+    /// The constructed commands can be taken by [`to_bytes()`](CustomPrinter::to_bytes()) or
+    /// [`into_bytes()`](CustomPrinter::into_bytes()), while the functions communicating with
+    /// the printer return [`Error::NoDevice`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, CutType};
+    /// let bytes = CustomPrinter::detached()
+    ///     .print()
+    ///     .cut_paper(CutType::TotalCut)
+    ///     .to_bytes();
+    /// assert_eq!(bytes, [0x0A, 0x1B, 0x69]);
+    /// ```
+    pub fn detached() -> Self {
+        Self {
+            device: None,
+            cmd: Vec::new(),
+            buf: Vec::new(),
+            scratch: Vec::new(),
+            auto_cut: None,
+            model: None,
+            profile: None,
+            config: None,
+            max_size: None,
+            size: 0,
+            overflowed: false,
+            trace: None,
+            savepoint: None,
+            jobs: 0,
+            command_set: Box::new(CustomEscPos),
+        }
+    }
+
+    /// The printer with `capacity` bytes preallocated for the encoding of the commands, e.g. the size of the
+    /// largest receipt of a kiosk, so running them doesn't grow the buffer again and again.
+    ///
+    /// Like the capacity of [`reserve()`](CustomPrinter::reserve()), it's kept by
+    /// [`run()`](CustomPrinter::run()), and the buffers of the bit images run are reused by the next
+    /// [`bit_image()`](CommandBuilder::bit_image()) and [`bitmap()`](CommandBuilder::bitmap()).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap().with_buffer_capacity(256 * 1024);
+    /// for _ in 0..3 {
+    ///     printer
+    ///         .bitmap(384, 288, &[0x55; 384 * 288 / 8], BitImageMode::Dots24DoubleDensity)
+    ///         .unwrap()
+    ///         .print()
+    ///         .run()
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn with_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.buf.reserve(capacity);
+
+        self
+    }
+
+    /// Clear the constructed commands, keeping the buffers of their bit images for reuse.
+    fn clear(&mut self) {
+        for command in self.cmd.drain(..) {
+            if let Command::BitImage { data, .. } = command {
+                if self.scratch.len() < MAX_SCRATCH_BUFFERS {
+                    self.scratch.push(data);
+                }
+            }
+        }
+    }
+
+    fn device(&mut self) -> Result<&mut Device, Error> {
+        self.device.as_mut().ok_or(Error::NoDevice)
+    }
+
+    /// Set the [`Model`] of the printer, whose physical characteristics are used by functions
+    /// like [`feed_to_cut()`](CommandBuilder::feed_to_cut()).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use custom_printer::CustomPrinter;
+    /// let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// let model = printer.printer_info().unwrap().supported_model();
+    /// printer.set_model(model);
+    /// ```
+    pub fn set_model(&mut self, model: Option<Model>) -> &mut Self {
+        self.model = model;
+
+        self
+    }
+
+    /// Set the [`Profile`] of the printer, e.g. for a model which isn't supported by the crate, or use the profile
+    /// of the [`Model`] set by [`set_model()`](CustomPrinter::set_model()) if `profile` is `None`.
+    ///
+    /// The profile is consulted for the physical characteristics of the printer and the commands it supports,
+    /// see [`CommandBuilder::profile()`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, CutType, Model, Profile};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer.set_profile(Some(Profile {
+    ///     name: "TG2480H with partial cut".into(),
+    ///     partial_cut: true,
+    ///     ..Profile::for_model(Model::Tg2480h)
+    /// }));
+    /// printer.cut_paper(CutType::PartialCut).run().unwrap();
+    /// ```
+    pub fn set_profile(&mut self, profile: Option<Profile>) -> &mut Self {
+        self.profile = profile;
+
+        self
+    }
+
+    /// Set the [`CommandSet`] encoding the commands for the firmware of the printer, or use [`CustomEscPos`] if
+    /// `command_set` is `None`.
+    ///
+    /// The commands the command set doesn't support are refused by [`run()`](CustomPrinter::run()). The status
+    /// queries, e.g. [`status()`](CustomPrinter::status()), are sent in CUSTOM ESC/POS regardless.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomEscPos, CustomPrinter};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer.set_command_set(Some(Box::new(CustomEscPos)));
+    /// printer.print().run().unwrap();
+    /// ```
+    pub fn set_command_set(
+        &mut self,
+        command_set: Option<Box<dyn CommandSet + Send>>,
+    ) -> &mut Self {
+        self.command_set = command_set.unwrap_or_else(|| Box::new(CustomEscPos));
+        if self.max_size.is_some() {
+            self.size = self.encoded_len(&self.cmd);
+        }
+
+        self
+    }
+
+    /// Identify the model of the printer by [`printer_info()`](CustomPrinter::printer_info()) and use its profile,
+    /// looked up by the reported model name in `profiles` first, e.g. loaded from JSON files for OEM
+    /// models, and then among the supported [`Model`]s.
+    ///
+    /// Names are compared ignoring case, spaces and dashes, like [`Model::from_name()`]. If the model isn't found,
+    /// the model and profile are unset, so [`profile()`](CommandBuilder::profile()) returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use custom_printer::{CustomPrinter, Model, Profile};
+    /// let oem = Profile {
+    ///     name: "ACME 80".into(),
+    ///     ..Profile::for_model(Model::Tg2480h)
+    /// };
+    /// let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// printer.detect_profile(&[oem]).unwrap();
+    /// ```
+    pub fn detect_profile(&mut self, profiles: &[Profile]) -> Result<&mut Self, Error> {
+        let info = self.printer_info()?;
+        let name = model::normalize_name(&info.model);
+        let profile = profiles
+            .iter()
+            .find(|profile| model::normalize_name(&profile.name) == name)
+            .cloned();
+        self.set_model(info.supported_model()).set_profile(profile);
+
+        Ok(self)
+    }
+
+    /// Open the printer connected to the device node `dev` like [`new()`](CustomPrinter::new()), with the
+    /// [`PrinterConfig`] of its paper width, resolution and default settings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, Density, PrinterConfig};
+    /// let config = PrinterConfig {
+    ///     dots_per_line: 576,
+    ///     density: Some(Density::Plus25),
+    ///     ..Default::default()
+    /// };
+    /// let mut printer = CustomPrinter::with_config("/dev/null", config).unwrap();
+    /// assert_eq!(printer.config().dots_per_line, 576);
+    /// printer.initialize().apply_config().run().unwrap();
+    /// ```
+    pub fn with_config(dev: impl AsRef<Path>, config: PrinterConfig) -> Result<Self, Error> {
+        let mut printer = Self::new(dev)?;
+        printer.config = Some(config);
+
+        Ok(printer)
+    }
+
+    /// Set the [`PrinterConfig`] of the printer, or use the configuration of the [`Model`] if `config` is `None`,
+    /// see [`CommandBuilder::config()`].
+    pub fn set_config(&mut self, config: Option<PrinterConfig>) -> &mut Self {
+        self.config = config;
+
+        self
+    }
+
+    /// Set the cut appended automatically at the end of the constructed commands by every [`run()`](CustomPrinter::run()),
+    /// or disable it if `auto_cut` is `None`.
+    ///
+    /// The cut is appended by [`feed_and_cut_paper()`](CommandBuilder::feed_and_cut_paper()) unless
+    /// there are no constructed commands to run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{AutoCut, CommandBuilder, CustomPrinter, CutType};
+    /// # let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer
+    ///     .set_auto_cut(Some(AutoCut {
+    ///         cut_type: CutType::PartialCut,
+    ///         feed: 0,
+    ///     }))
+    ///     .print()
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn set_auto_cut(&mut self, auto_cut: Option<AutoCut>) -> &mut Self {
+        self.auto_cut = auto_cut;
+
+        self
+    }
+
+    /// Set the hook receiving the [`TraceEvent`]s of the transmissions to and responses from the printer, or remove
+    /// it if `hook` is `None`, e.g. to forward them to `tracing` or `log`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, TraceEvent};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer.set_trace(Some(Box::new(|event: &TraceEvent| match event {
+    ///     TraceEvent::Transmitted { bytes, duration } => eprintln!("sent {bytes} bytes in {duration:?}"),
+    ///     TraceEvent::Failed(e) => eprintln!("printer error: {e}"),
+    ///     event => eprintln!("{event:?}"),
+    /// })));
+    /// printer.print().run().unwrap();
+    /// ```
+    pub fn set_trace(&mut self, hook: Option<TraceHook>) -> &mut Self {
+        self.trace = hook;
+
+        self
+    }
+
+    /// Wake up the printer from the sleep mode.
+    ///
+    /// Unlike the constructing functions, the wake-up command is sent to the printer immediately
+    /// and the constructed commands are left untouched. It returns after the printer has had time to wake up.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter};
+    /// # let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer
+    ///     .wake_up()
+    ///     .unwrap()
+    ///     .print()
+    ///     .sleep()
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn wake_up(&mut self) -> Result<&mut Self, Error> {
+        self.transmit(WAKE_UP)?;
+        thread::sleep(WAKE_UP_DELAY);
+
+        Ok(self)
+    }
+
+    /// Write `bytes` to the printer immediately, reporting the transmission to the trace hook, and return how
+    /// long it took.
+    fn transmit(&mut self, bytes: &[u8]) -> Result<Duration, Error> {
+        let start = Instant::now();
+        let result = self
+            .device()
+            .and_then(|device| device.write_all(bytes).map_err(Error::Write))
+            .map(|()| start.elapsed());
+        match &result {
+            Ok(duration) => self.trace(TraceEvent::Transmitted {
+                bytes: bytes.len(),
+                duration: *duration,
+            }),
+            Err(e) => self.trace(TraceEvent::Failed(e)),
+        }
+
+        result
+    }
+
+    /// Send `commands` immediately from the state `line`, bypassing the pending commands, see
+    /// [`StreamingPrinter`](crate::StreamingPrinter).
+    ///
+    /// A bit image followed by a print in `commands` is sent as raster images like by [`run()`](CustomPrinter::run()).
+    pub(crate) fn send(&mut self, commands: &[Command], line: &mut Line) -> Result<(), Error> {
+        let profile = self.profile();
+        for command in commands {
+            if let Some(profile) = profile
+                .as_ref()
+                .filter(|profile| !profile.supports(command))
+            {
+                return Err(Error::Unsupported {
+                    command: command.kind(),
+                    printer: profile.name.clone(),
+                });
+            }
+            command_set::check(&*self.command_set, command)?;
+        }
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        command_set::encode_into(
+            &*self.command_set,
+            commands,
+            self.raster_images(),
+            line,
+            &mut buf,
+        );
+        let result = self.transmit(&buf);
+        self.buf = buf;
+
+        result.map(|_| ())
+    }
+
+    fn trace(&mut self, event: TraceEvent<'_>) {
+        #[cfg(feature = "tracing")]
+        event.emit();
+        if let Some(hook) = self.trace.as_mut() {
+            hook(&event);
+        }
+    }
+
+    /// Read at most `max_len` bytes of response from the printer into `response`, stopping early when
+    /// `terminator` is received.
+    ///
+    /// [`Error::Timeout`] is returned if the response isn't complete within `timeout`, leaving the bytes received so far in `response`.
+    fn receive_into(
+        &mut self,
+        response: &mut Vec<u8>,
+        max_len: usize,
+        terminator: Option<u8>,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let result = self.read_into(response, max_len, terminator, timeout);
+        match &result {
+            Ok(()) => self.trace(TraceEvent::Received(response)),
+            Err(e) => self.trace(TraceEvent::Failed(e)),
+        }
+
+        result
+    }
+
+    fn read_into(
+        &mut self,
+        response: &mut Vec<u8>,
+        max_len: usize,
+        terminator: Option<u8>,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let deadline = Instant::now() + timeout;
+        let mut byte = [0; 1];
+
+        while response.len() < max_len {
+            match self.device()?.read(&mut byte) {
+                Ok(1) => {
+                    response.push(byte[0]);
+                    if terminator == Some(byte[0]) {
+                        break;
+                    }
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock
+                            | io::ErrorKind::TimedOut
+                            | io::ErrorKind::Interrupted
+                    ) => {}
+                Err(e) => return Err(Error::Read(e)),
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        Ok(())
+    }
+
+    /// Read at most `max_len` bytes of response from the printer, stopping early when `terminator` is received.
+    ///
+    /// [`Error::Timeout`] is returned if the response isn't complete within `timeout`.
+    pub(crate) fn receive(
+        &mut self,
+        max_len: usize,
+        terminator: Option<u8>,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, Error> {
+        let mut response = Vec::with_capacity(max_len);
+        self.receive_into(&mut response, max_len, terminator, timeout)?;
+
+        Ok(response)
+    }
+
+    /// Read the raw response of the printer to a command sent previously, e.g. a query not wrapped by this crate.
+    ///
+    /// Bytes are read until `max_len` bytes have been received or `timeout` elapses, and the bytes
+    /// received so far are returned. [`Error::Timeout`] is returned only if nothing has been received within `timeout`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use custom_printer::{CommandBuilder, CustomPrinter};
+    /// # let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// // transmit the printer ID of type n = 0x42
+    /// let response = printer
+    ///     .raw(&[0x1D, 0x49, 0x42])
+    ///     .run_chained()
+    ///     .unwrap()
+    ///     .read_response(16, Duration::from_millis(500))
+    ///     .unwrap();
+    /// println!("{response:02x?}");
+    /// ```
+    pub fn read_response(&mut self, max_len: usize, timeout: Duration) -> Result<Vec<u8>, Error> {
+        let mut response = Vec::with_capacity(max_len);
+
+        match self.receive_into(&mut response, max_len, None, timeout) {
+            Err(Error::Timeout) if !response.is_empty() => Ok(response),
+            Err(e) => Err(e),
+            Ok(()) => Ok(response),
+        }
+    }
+
+    /// Send the query command `cmd` to the printer immediately and receive its response.
+    ///
+    /// The constructed commands are left untouched.
+    pub(crate) fn query(
+        &mut self,
+        cmd: &[u8],
+        max_len: usize,
+        terminator: Option<u8>,
+    ) -> Result<Vec<u8>, Error> {
+        self.transmit(cmd)?;
+        self.receive(max_len, terminator, RESPONSE_TIMEOUT)
+    }
+
+    fn query_id(&mut self, n: u8) -> Result<u8, Error> {
+        let response = self.query(&[TRANSMIT_ID, &[n]].concat(), 1, None)?;
+        Ok(response[0])
+    }
+
+    fn query_text_id(&mut self, n: u8) -> Result<String, Error> {
+        let response = self.query(
+            &[TRANSMIT_ID, &[n]].concat(),
+            MAX_TEXT_BLOCK_LEN,
+            Some(status::BLOCK_TERMINATOR),
+        )?;
+        status::parse_text_block(&response).ok_or(Error::InvalidResponse)
+    }
+
+    fn query_counter(&mut self, n: u16) -> Result<u64, Error> {
+        let response = self.query(
+            &[MAINTENANCE_COUNTER, &n.to_le_bytes()].concat(),
+            MAX_TEXT_BLOCK_LEN,
+            Some(status::BLOCK_TERMINATOR),
+        )?;
+        status::parse_text_block(&response)
+            .and_then(|text| text.parse().ok())
+            .ok_or(Error::InvalidResponse)
+    }
+
+    fn query_number(&mut self, cmd: &[u8], header: &[u8]) -> Result<usize, Error> {
+        let response = self.query(cmd, MAX_TEXT_BLOCK_LEN, Some(status::BLOCK_TERMINATOR))?;
+        status::parse_number_block(&response, header).ok_or(Error::InvalidResponse)
+    }
+
+    /// Query the model name, firmware version and supported features of the printer.
+    ///
+    /// Unlike the constructing functions, the query is sent to the printer immediately
+    /// and the constructed commands are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use custom_printer::CustomPrinter;
+    /// let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// let info = printer.printer_info().unwrap();
+    /// println!("{} (firmware {})", info.model, info.firmware);
+    /// ```
+    pub fn printer_info(&mut self) -> Result<PrinterInfo, Error> {
+        let model = self.query_text_id(ID_MODEL_NAME)?;
+        let firmware = self.query_text_id(ID_FIRMWARE_VERSION)?;
+        let features = Features::from_bits(self.query_id(ID_TYPE)?);
+
+        Ok(PrinterInfo {
+            model,
+            firmware,
+            features,
+        })
+    }
+
+    /// Query the maintenance counters of the printer, e.g. to schedule head cleaning and roll replacement.
+    ///
+    /// Unlike the constructing functions, the query is sent to the printer immediately
+    /// and the constructed commands are left untouched.
+    pub fn maintenance_counters(&mut self) -> Result<MaintenanceCounters, Error> {
+        Ok(MaintenanceCounters {
+            printed_dot_lines: self.query_counter(COUNTER_DOT_LINES)?,
+            cuts: self.query_counter(COUNTER_CUTS)?,
+            power_on_hours: self.query_counter(COUNTER_POWER_ON_HOURS)?,
+        })
+    }
+
+    /// Query the capacity and remaining capacity of the NV graphics memory.
+    ///
+    /// Unlike the constructing functions, the query is sent to the printer immediately
+    /// and the constructed commands are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use custom_printer::CustomPrinter;
+    /// # let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// let logo_size = 384 * 288 / 8;
+    /// if let Err(e) = printer.nv_memory().unwrap().ensure_fits(logo_size) {
+    ///     eprintln!("Logo can't be downloaded: {e}");
+    /// }
+    /// ```
+    pub fn nv_memory(&mut self) -> Result<NvMemory, Error> {
+        Ok(NvMemory {
+            capacity: self.query_number(NV_CAPACITY, NV_CAPACITY_HEADER)?,
+            remaining: self.query_number(NV_REMAINING, NV_REMAINING_HEADER)?,
+        })
+    }
+
+    /// Query the full status of the printer.
+    ///
+    /// Unlike the constructing functions, the query is sent to the printer immediately
+    /// and the constructed commands are left untouched.
+    pub fn status(&mut self) -> Result<Status, Error> {
+        let response = self.query(FULL_STATUS, Status::LEN, None)?;
+        let status = Status::parse(&response).ok_or(Error::InvalidResponse)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(?status, "status of the printer");
+
+        Ok(status)
+    }
+
+    /// Query the causes of the printer being offline, e.g. to show the operator what to fix.
+    ///
+    /// An empty list is returned if the printer is online. Unlike the constructing functions,
+    /// the query is sent to the printer immediately and the constructed commands are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use custom_printer::{CustomPrinter, OfflineCause};
+    /// # let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// for cause in printer.offline_causes().unwrap() {
+    ///     match cause {
+    ///         OfflineCause::CoverOpen => println!("Please close the cover"),
+    ///         OfflineCause::PaperEnd => println!("Please replace the paper roll"),
+    ///         _ => println!("Printer is offline: {cause:?}"),
+    ///     }
+    /// }
+    /// ```
+    pub fn offline_causes(&mut self) -> Result<Vec<OfflineCause>, Error> {
+        let response = self.query(OFFLINE_STATUS, 1, None)?;
+        OfflineCause::parse(response[0]).ok_or(Error::InvalidResponse)
+    }
+
+    /// Block until the printer reports its receive buffer is empty and printing is complete.
+    ///
+    /// The status is polled until the printer is idle. [`Error::Timeout`] is returned if the printer is still busy after `timeout`.
+    ///
+    /// **NOTE:** Only the commands already sent by [`run()`](CustomPrinter::run()) are waited for.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, CutType, FeedUnit};
+    /// # let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// printer
+    ///     .print_and_feed_paper(FeedUnit::Lines, 10)
+    ///     .cut_paper(CutType::TotalCut)
+    ///     .run_chained()
+    ///     .unwrap()
+    ///     .wait_until_idle(Duration::from_secs(10))
+    ///     .unwrap();
+    /// ```
+    pub fn wait_until_idle(&mut self, timeout: Duration) -> Result<&mut Self, Error> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            match self.status() {
+                Ok(status) if status.is_idle() => return Ok(self),
+                Ok(_) => {}
+                Err(Error::Timeout) => {}
+                Err(e) => return Err(e),
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout);
+            }
+            thread::sleep(STATUS_POLL_INTERVAL);
+        }
+    }
+
+    /// Block until the print head of the printer has cooled down, e.g. to pause a long batch job
+    /// instead of producing fading output.
+    ///
+    /// The status is polled until the print head isn't overheated. [`PrinterFault::HeadOverheat`]
+    /// is returned if it's still overheated after `timeout`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter};
+    /// # let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// # let coupons = ["coupon.png"];
+    /// for coupon in coupons {
+    ///     printer
+    ///         .wait_until_cooled(Duration::from_secs(60))
+    ///         .unwrap()
+    ///         .bit_image(coupon, BitImageMode::Dots24DoubleDensity)
+    ///         .unwrap()
+    ///         .print()
+    ///         .run()
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn wait_until_cooled(&mut self, timeout: Duration) -> Result<&mut Self, Error> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if !self.status()?.head_overheated() {
+                return Ok(self);
+            }
+
+            if Instant::now() >= deadline {
+                return Err(PrinterFault::HeadOverheat.into());
+            }
+            thread::sleep(STATUS_POLL_INTERVAL);
+        }
+    }
+
+    /// Clear the receive and print buffers of the printer, e.g. to abort a partially transmitted job
+    /// after an error in the application.
+    ///
+    /// Unlike the constructing functions, the command is sent to the printer immediately
+    /// and the constructed commands are left untouched.
+    pub fn clear_buffer(&mut self) -> Result<&mut Self, Error> {
+        self.transmit(CLEAR_BUFFER)?;
+
+        Ok(self)
+    }
+
+    /// Recover the printer from an autocutter error, e.g. a jammed cutter, without power-cycling it.
+    ///
+    /// The recovery command is sent to the printer immediately and printing restarts from the line
+    /// where the error occurred. The status is queried afterwards to confirm the error has been
+    /// cleared, otherwise [`PrinterFault::CutterLocked`] is returned.
+    ///
+    /// **NOTE:** The cause of the error, e.g. jammed paper, should be removed before recovering.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use custom_printer::CustomPrinter;
+    /// # let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// if printer.status().unwrap().cutter_error() {
+    ///     printer.recover_cutter().unwrap();
+    /// }
+    /// ```
+    pub fn recover_cutter(&mut self) -> Result<&mut Self, Error> {
+        self.transmit(RECOVER_AND_RESTART)?;
+
+        if self.status()?.cutter_error() {
+            return Err(PrinterFault::CutterLocked.into());
+        }
+
+        Ok(self)
+    }
+
+    /// The constructed commands pending to be sent by [`run()`](CustomPrinter::run()), without the automatic cut.
+    pub fn pending(&self) -> &[Command] {
+        &self.cmd
+    }
+
+    /// Iterate over the pending commands, e.g. to assert on the structure of a job in tests by their
+    /// [`kind()`](Command::kind()), parameters and [`payload_len()`](Command::payload_len()) rather than raw bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Command, CommandBuilder, CustomPrinter, CutType};
+    /// let mut printer = CustomPrinter::detached();
+    /// printer.raw(&[0x1B, 0x40]).cut_paper(CutType::TotalCut);
+    /// let mut commands = printer.commands();
+    /// assert_eq!(commands.next().map(Command::payload_len), Some(2));
+    /// assert_eq!(commands.next(), Some(&Command::CutPaper(CutType::TotalCut)));
+    /// assert_eq!(commands.next(), None);
+    /// ```
+    pub fn commands(&self) -> impl ExactSizeIterator<Item = &Command> {
+        self.cmd.iter()
+    }
+
+    /// Estimate the paper length and print duration of the pending commands on the printer of the
+    /// [`config()`](CommandBuilder::config()), see [`JobBuilder::estimate()`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, CutType, Model};
+    /// let mut printer = CustomPrinter::detached();
+    /// printer.set_model(Some(Model::Tg2480h));
+    /// printer.feed_and_cut_paper(CutType::TotalCut, 0);
+    /// if printer.estimate().length_mm > 300.0 {
+    ///     println!("Long ticket");
+    /// }
+    /// ```
+    pub fn estimate(&self) -> Estimate {
+        estimate::estimate(&self.cmd, self.profile().as_ref(), &self.config())
+    }
+
+    /// Check the pending commands and the automatic cut against the model and the printer of the
+    /// [`config()`](CommandBuilder::config()) without touching the device, returning all the problems found,
+    /// see [`Job::validate()`].
+    ///
+    /// Unlike [`run()`](CustomPrinter::run()), which stops at the first problem, every problem is returned,
+    /// e.g. to check templates of receipts in CI.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter, CutType, Model};
+    /// let mut printer = CustomPrinter::detached();
+    /// printer.set_model(Some(Model::Tg2480h));
+    /// printer
+    ///     .cut_paper(CutType::PartialCut)
+    ///     .bitmap(8, 1, &[0xFF], BitImageMode::Dots8SingleDensity)
+    ///     .unwrap();
+    /// assert_eq!(printer.validate().len(), 2);
+    /// ```
+    pub fn validate(&self) -> Vec<Problem> {
+        let profile = self.profile();
+        let mut problems = validate::validate(&self.cmd, profile.as_ref(), &self.config());
+        if let (Some(profile), Some(command)) = (profile, self.auto_cut_command(&self.cmd)) {
+            if !profile.supports(&command) {
+                problems.push(Problem {
+                    index: self.cmd.len(),
+                    error: Error::Unsupported {
+                        command: command.kind(),
+                        printer: profile.name,
+                    },
+                });
+            }
+        }
+
+        problems
+    }
+
+    /// Reserve capacity for at least `additional` more commands and `bytes` more bytes of their encoding, e.g.
+    /// before building a long job in high-volume printing.
+    ///
+    /// The capacity is kept by [`run()`](CustomPrinter::run()) and [`discard()`](CustomPrinter::discard()),
+    /// so it's reused by the next jobs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer.reserve(64, 384 * 288 / 8 + 1024);
+    /// for _ in 0..64 {
+    ///     printer.print();
+    /// }
+    /// printer.run().unwrap();
+    /// ```
+    pub fn reserve(&mut self, additional: usize, bytes: usize) -> &mut Self {
+        self.cmd.reserve(additional);
+        self.buf.reserve(bytes);
+
+        self
+    }
+
+    /// Number of the constructed commands pending to be sent by [`run()`](CustomPrinter::run()).
+    pub fn pending_len(&self) -> usize {
+        self.cmd.len()
+    }
+
+    /// Limit the size in bytes of the constructed commands, or remove the limit if `max_size` is `None`.
+    ///
+    /// Commands exceeding the limit, e.g. constructed by a runaway loop, are dropped instead of being
+    /// accumulated, and the next [`run()`](CustomPrinter::run()) returns [`Error::TooLarge`] without sending
+    /// anything until the commands are [`discard()`](CustomPrinter::discard())ed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, Error};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer.set_max_size(Some(2));
+    /// printer.print().print().print();
+    /// assert_eq!(printer.pending_len(), 2);
+    /// assert!(matches!(printer.run(), Err(Error::TooLarge { max_size: 2 })));
+    /// ```
+    pub fn set_max_size(&mut self, max_size: Option<usize>) -> &mut Self {
+        self.max_size = max_size;
+        if max_size.is_some() {
+            self.size = self.encoded_len(&self.cmd);
+        }
+
+        self
+    }
+
+    /// Discard the constructed commands without sending them, e.g. when the sale is voided while
+    /// the ticket is being built.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter};
+    /// let mut printer = CustomPrinter::detached();
+    /// printer.print().print();
+    /// assert_eq!(printer.pending_len(), 2);
+    /// printer.discard();
+    /// assert_eq!(printer.pending_len(), 0);
+    /// ```
+    pub fn discard(&mut self) -> &mut Self {
+        self.clear();
+        self.size = 0;
+        self.overflowed = false;
+        self.savepoint = None;
+
+        self
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        Savepoint {
+            len: self.cmd.len(),
+            size: self.size,
+            overflowed: self.overflowed,
+        }
+    }
+
+    fn restore(&mut self, savepoint: Savepoint) {
+        self.cmd.truncate(savepoint.len);
+        self.size = savepoint.size;
+        self.overflowed = savepoint.overflowed;
+    }
+
+    /// Begin a transaction, so the commands constructed until [`commit()`](CustomPrinter::commit()) can be
+    /// removed all at once by [`rollback()`](CustomPrinter::rollback()), e.g. when building a bit image fails
+    /// halfway through the job.
+    ///
+    /// Transactions don't nest, beginning a transaction again moves its start to the current commands.
+    /// Running or discarding the commands ends the transaction.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer.print().begin().print();
+    /// if printer.bitmap(16, 1, &[0xFF], BitImageMode::Dots8SingleDensity).is_err() {
+    ///     printer.rollback();
+    /// } else {
+    ///     printer.commit();
+    /// }
+    /// assert_eq!(printer.pending_len(), 1);
+    /// ```
+    pub fn begin(&mut self) -> &mut Self {
+        self.savepoint = Some(self.savepoint());
+
+        self
+    }
+
+    /// Commit the transaction begun by [`begin()`](CustomPrinter::begin()), keeping its commands.
+    pub fn commit(&mut self) -> &mut Self {
+        self.savepoint = None;
+
+        self
+    }
+
+    /// Roll back the transaction begun by [`begin()`](CustomPrinter::begin()), removing its commands.
+    ///
+    /// Nothing is removed if no transaction has been begun.
+    pub fn rollback(&mut self) -> &mut Self {
+        if let Some(savepoint) = self.savepoint.take() {
+            self.restore(savepoint);
+        }
+
+        self
+    }
+
+    /// Construct commands by the fallible `f` in a transaction, keeping them all if `f` succeeds or removing
+    /// them all on the first error, see [`begin()`](CustomPrinter::begin()).
+    ///
+    /// The commands are also removed and [`Error::TooLarge`] is returned if any of them exceeded the limit
+    /// set by [`set_max_size()`](CustomPrinter::set_max_size()). Unlike [`begin()`](CustomPrinter::begin()),
+    /// transactions by this function can be nested.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter, Error};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// let result = printer.transaction(|p| {
+    ///     p.print()
+    ///         .bitmap(16, 1, &[0xFF], BitImageMode::Dots8SingleDensity)
+    /// });
+    /// assert!(matches!(result, Err(Error::UnsupportedImage(_))));
+    /// assert_eq!(printer.pending_len(), 0);
+    /// ```
+    pub fn transaction(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<&mut Self, Error>,
+    ) -> Result<&mut Self, Error> {
+        let savepoint = self.savepoint();
+        if let Err(e) = f(self).map(|_| ()) {
+            self.restore(savepoint);
+            return Err(e);
+        }
+        if let (true, false, Some(max_size)) =
+            (self.overflowed, savepoint.overflowed, self.max_size)
+        {
+            self.restore(savepoint);
+            return Err(Error::TooLarge { max_size });
+        }
+
+        Ok(self)
+    }
+
+    /// The cut appended automatically to `commands`, see [`set_auto_cut()`](CustomPrinter::set_auto_cut()).
+    fn auto_cut_command(&self, commands: &[Command]) -> Option<Command> {
+        match self.auto_cut {
+            Some(auto_cut) if !commands.is_empty() => Some(Command::FeedAndCutPaper {
+                cut_type: auto_cut.cut_type,
+                amount: auto_cut.feed,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Check `commands` and the automatic cut before sending them, see [`run()`](CustomPrinter::run()).
+    fn check(&self, commands: &[Command]) -> Result<(), Error> {
+        let result = self.check_commands(commands);
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &result {
+            tracing::warn!(%error, "commands refused");
+        }
+
+        result
+    }
+
+    fn check_commands(&self, commands: &[Command]) -> Result<(), Error> {
+        command::check_printed(commands)?;
+        if let Some(profile) = self.profile() {
+            command::check_supported(
+                commands
+                    .iter()
+                    .chain(self.auto_cut_command(commands).as_ref()),
+                &profile,
+            )?;
+            command::check_limits(commands, &profile)?;
+        }
+        for command in commands
+            .iter()
+            .chain(self.auto_cut_command(commands).as_ref())
+        {
+            command_set::check(&*self.command_set, command)?;
+        }
+
+        Ok(())
+    }
+
+    fn encoded_len(&self, commands: &[Command]) -> usize {
+        commands
+            .iter()
+            .map(|command| self.command_set.encoded_len(command))
+            .sum()
+    }
+
+    fn encode_into(&self, commands: &[Command], out: &mut Vec<u8>) {
+        let auto_cut = self.auto_cut_command(commands);
+        // reserve the bytes of bit images at once instead of growing the buffer bank by bank
+        out.reserve(
+            commands
+                .iter()
+                .chain(auto_cut.as_ref())
+                .map(|command| self.command_set.encoded_len(command))
+                .sum(),
+        );
+        command_set::encode_into(
+            &*self.command_set,
+            commands.iter().chain(auto_cut.as_ref()),
+            self.raster_images(),
+            &mut Line::default(),
+            out,
+        );
+    }
+
+    /// Whether the bit images are sent as raster images, see [`Profile::raster_images`].
+    pub(crate) fn raster_images(&self) -> bool {
+        self.profile().is_some_and(|profile| profile.raster_images)
+    }
+
+    /// The bytes of the constructed commands, exactly as they would be sent by [`run()`](CustomPrinter::run()),
+    /// including the automatic cut.
+    ///
+    /// The constructed commands are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, FeedUnit};
+    /// let mut printer = CustomPrinter::detached();
+    /// printer.print_and_feed_paper(FeedUnit::Lines, 3);
+    /// assert_eq!(printer.to_bytes(), [0x1B, 0x64, 3]);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_into(&self.cmd, &mut out);
+
+        out
+    }
+
+    /// Consume the [`CustomPrinter`] and return the bytes of the constructed commands,
+    /// see [`to_bytes()`](CustomPrinter::to_bytes()).
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        self.as_bytes();
+        self.cmd.clear();
+
+        std::mem::take(&mut self.buf)
+    }
+
+    /// Finish using the [`CustomPrinter`], checking that no constructed commands are left pending, e.g. a job
+    /// which was built but never [`run()`](CustomPrinter::run()).
+    ///
+    /// [`Error::PendingCommands`] is returned if any commands are pending, and they are discarded.
+    /// Dropping a [`CustomPrinter`] with pending commands without calling [`finish()`](CustomPrinter::finish())
+    /// prints a warning in debug builds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, Error};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer.print().run().unwrap();
+    /// printer.finish().unwrap();
+    ///
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer.print();
+    /// assert!(matches!(printer.finish(), Err(Error::PendingCommands { count: 1 })));
+    /// ```
+    pub fn finish(mut self) -> Result<(), Error> {
+        let count = self.cmd.len();
+        self.discard();
+
+        match count {
+            0 => Ok(()),
+            count => Err(Error::PendingCommands { count }),
+        }
+    }
+
+    /// Borrow the bytes of the constructed commands, see [`to_bytes()`](CustomPrinter::to_bytes()).
+    ///
+    /// The bytes are encoded into a buffer owned by the [`CustomPrinter`], which is reused by
+    /// every call and by [`run()`](CustomPrinter::run()).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Write;
+    /// # use custom_printer::{CommandBuilder, CustomPrinter};
+    /// let mut printer = CustomPrinter::detached();
+    /// let mut spool = Vec::new();
+    /// spool.write_all(printer.print().as_bytes()).unwrap();
+    /// ```
+    pub fn as_bytes(&mut self) -> &[u8] {
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        self.encode_into(&self.cmd, &mut buf);
+        self.buf = buf;
+
+        &self.buf
+    }
+
+    /// Run the constructed commands in the [`CustomPrinter`] and return the [`PrintOutcome`], e.g. to log and
+    /// meter the printing.
+    ///
+    /// The constructed commands will be cleared if the printing succeeds.
+    ///
+    /// If an automatic cut is set by [`set_auto_cut()`](CustomPrinter::set_auto_cut()), it's appended
+    /// before the commands are sent.
+    ///
+    /// [`Error::UnprintedData`] is returned without sending anything if data would be left in the print buffer,
+    /// e.g. a bit image isn't followed by [`print()`](CommandBuilder::print()), [`Error::TooLarge`] if
+    /// commands were dropped by the limit of [`set_max_size()`](CustomPrinter::set_max_size()), and
+    /// [`Error::Unsupported`] if a command isn't supported by the model set by
+    /// [`set_model()`](CustomPrinter::set_model()), e.g. a partial cut on TG2480H.
+    ///
+    /// See [`run_chained()`](CustomPrinter::run_chained()) for concatenating other functions after running
+    /// the commands.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter, CutType};
+    /// # let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// let outcome = printer
+    ///     .bit_image(
+    ///         "tests/data/Thermal_Test_Image.png",
+    ///         BitImageMode::Dots24DoubleDensity
+    ///     )
+    ///     .unwrap()
+    ///     .print()
+    ///     .cut_paper(CutType::TotalCut)
+    ///     .run()
+    ///     .unwrap();
+    /// println!("job {}: {} bytes in {:?}", outcome.job_id, outcome.bytes_written, outcome.duration);
+    /// ```
+    pub fn run(&mut self) -> Result<PrintOutcome, Error> {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::info_span!("run", job_id = self.jobs + 1, commands = self.cmd.len()).entered();
+        if let (true, Some(max_size)) = (self.overflowed, self.max_size) {
+            return Err(Error::TooLarge { max_size });
+        }
+        self.check(&self.cmd)?;
+        self.as_bytes();
+        let buf = std::mem::take(&mut self.buf);
+        let result = self.transmit(&buf);
+        let bytes_written = buf.len();
+        self.buf = buf;
+        let duration = result?;
+
+        self.clear();
+        self.size = 0;
+        self.savepoint = None;
+        Ok(PrintOutcome {
+            bytes_written,
+            duration,
+            job_id: self.next_job_id(),
+        })
+    }
+
+    /// Run the constructed commands in the [`CustomPrinter`] like [`run()`](CustomPrinter::run()), returning
+    /// the printer for concatenating other functions instead of the [`PrintOutcome`].
+    ///
+    /// **NOTE:** Because writing to the device node may fail, so the return Self is wrapped in a [`Result`]
+    /// and needs to be unwrapped before concatenating with other constructing functions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, CutType};
+    /// # let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer
+    ///     .print()
+    ///     .cut_paper(CutType::TotalCut)
+    ///     .run_chained()
+    ///     .unwrap()
+    ///     .print()
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn run_chained(&mut self) -> Result<&mut Self, Error> {
+        self.run()?;
+
+        Ok(self)
+    }
+
+    /// Run the pending commands like [`run()`](CustomPrinter::run()) without the automatic cut, which is left to
+    /// the end of the ticket, before sending more commands of the same ticket.
+    fn flush(&mut self) -> Result<(), Error> {
+        let auto_cut = self.auto_cut.take();
+        let result = self.run();
+        self.auto_cut = auto_cut;
+
+        result.map(|_| ())
+    }
+
+    /// Number the next job sent to the printer.
+    fn next_job_id(&mut self) -> u64 {
+        self.jobs += 1;
+
+        self.jobs
+    }
+
+    /// Run the constructed commands in the [`CustomPrinter`] and confirm they have been printed successfully.
+    ///
+    /// After the commands are sent, the status is polled until the printer is idle and the final
+    /// [`Status`] is returned, e.g. to record that a legally required receipt has been printed.
+    ///
+    /// The first [`PrinterFault`] reported while printing, e.g. [`PrinterFault::PaperEnd`], is returned
+    /// as [`Error::Fault`], and [`Error::Timeout`] is returned if the printer is still busy or doesn't
+    /// respond after `timeout`. A status reply missed while printing is polled again until then.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, CutType, Error};
+    /// # let mut printer = CustomPrinter::new("/dev/usb/lp0").unwrap();
+    /// match printer
+    ///     .cut_paper(CutType::TotalCut)
+    ///     .run_checked(Duration::from_secs(10))
+    /// {
+    ///     Ok(_) => println!("Receipt printed"),
+    ///     Err(Error::Fault(fault)) => println!("Receipt not printed: {fault}"),
+    ///     Err(e) => println!("Printer not responding: {e}"),
+    /// }
+    /// ```
+    pub fn run_checked(&mut self, timeout: Duration) -> Result<Status, Error> {
+        self.run()?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            // a busy printer can miss a reply, so only the deadline fails the job
+            match self.status() {
+                Ok(status) => {
+                    status.check()?;
+                    if status.is_idle() {
+                        return Ok(status);
+                    }
+                }
+                Err(Error::Timeout) => {}
+                Err(e) => return Err(e),
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout);
+            }
+            thread::sleep(STATUS_POLL_INTERVAL);
+        }
+    }
+
+    /// Transmit the commands of `job` built by [`JobBuilder`](crate::JobBuilder) to the printer.
+    ///
+    /// If an automatic cut is set by [`set_auto_cut()`](CustomPrinter::set_auto_cut()), it's appended
+    /// after the commands of the job. The constructed commands are left untouched.
+    ///
+    /// Like [`run()`](CustomPrinter::run()), [`Error::UnprintedData`] is returned if data would be left in the print buffer,
+    /// and [`Error::Unsupported`] if a command isn't supported by the model.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, CutType, JobBuilder};
+    /// let job = JobBuilder::new()
+    ///     .print()
+    ///     .cut_paper(CutType::TotalCut)
+    ///     .build();
+    ///
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer.print_job(&job).unwrap();
+    /// ```
+    pub fn print_job(&mut self, job: &Job) -> Result<&mut Self, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!(
+            "print_job",
+            job_id = self.jobs + 1,
+            commands = job.commands().len()
+        )
+        .entered();
+        self.check(job.commands())?;
+        let mut out = Vec::new();
+        self.encode_into(job.commands(), &mut out);
+        self.transmit(&out)?;
+        self.next_job_id();
+
+        Ok(self)
+    }
+
+    /// Transmit several jobs one after the other, e.g. a kitchen ticket and a customer receipt built independently
+    /// by their own [`JobBuilder`](crate::JobBuilder)s, each followed by the automatic cut as in
+    /// [`print_job()`](CustomPrinter::print_job()).
+    ///
+    /// Every job is checked before any of them is transmitted, so [`Error::UnprintedData`] of a job, with the
+    /// index of the command in that job, leaves the printer untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CommandBuilder, CustomPrinter, CutType};
+    /// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// let mut kitchen = printer.job_builder();
+    /// let mut receipt = printer.job_builder();
+    /// kitchen.print();
+    /// receipt.print().print();
+    /// kitchen.cut_paper(CutType::TotalCut);
+    /// receipt.cut_paper(CutType::TotalCut);
+    ///
+    /// printer.print_jobs(&[kitchen.build(), receipt.build()]).unwrap();
+    /// ```
+    pub fn print_jobs<'a>(
+        &mut self,
+        jobs: impl IntoIterator<Item = &'a Job>,
+    ) -> Result<&mut Self, Error> {
+        let jobs: Vec<&Job> = jobs.into_iter().collect();
+        for job in &jobs {
+            self.check(job.commands())?;
+        }
+
+        let mut out = Vec::new();
+        for job in &jobs {
+            self.encode_into(job.commands(), &mut out);
+        }
+        self.transmit(&out)?;
+        self.jobs += jobs.len() as u64;
+
+        Ok(self)
+    }
+
+    /// Create a [`JobBuilder`](crate::JobBuilder) for the [`Model`] and [`Profile`] of the printer, to build a job independently
+    /// of the constructed commands and of other jobs, and print it by [`print_job()`](CustomPrinter::print_job()).
+    pub fn job_builder(&self) -> JobBuilder {
+        let mut builder = JobBuilder::new();
+        builder
+            .set_model(self.model)
+            .set_profile(self.profile.clone())
+            .set_config(self.config);
+
+        builder
+    }
+
+    /// Print the job saved in the file `path` by [`Job::save()`], e.g. a receipt which was generated but never
+    /// transmitted before a crash.
+    ///
+    /// The job is printed like [`print_job()`](CustomPrinter::print_job()), and the pending commands are untouched.
+    pub fn replay(&mut self, path: impl AsRef<Path>) -> Result<&mut Self, Error> {
+        let job = Job::load(path)?;
+
+        self.print_job(&job)
+    }
+
+    /// Print a bit image from `path` in `mode` like [`bit_image()`](CommandBuilder::bit_image()) followed by
+    /// [`print()`](CommandBuilder::print()), converting and transmitting it bank by bank, after running the pending
+    /// commands.
+    ///
+    /// Only a bank of 8 or 24 lines of the bit image is held at a time instead of all of it, plus its encoded
+    /// copy, so large images print with less memory and their first bank is on the wire right after decoding. If
+    /// the bit image is sent as raster images, see [`Profile::raster_images`], a raster image of up to 2280 lines is
+    /// held instead. The pending commands are run without the automatic cut, which is left to the end of the ticket,
+    /// so the bytes written are the same as those [`run()`](CustomPrinter::run()) would send for the pending
+    /// commands, [`bit_image()`](CommandBuilder::bit_image()) and [`print()`](CommandBuilder::print()), without the
+    /// automatic cut.
+    ///
+    /// **NOTE:** Like [`StreamingPrinter`](crate::StreamingPrinter), the banks are only checked against the
+    /// [`Profile`] of the printer, the limit of [`set_max_size()`](CustomPrinter::set_max_size()) isn't applied,
+    /// and the banks sent before an error are printed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter, CutType};
+    /// # let mut printer = CustomPrinter::new("/dev/null").unwrap();
+    /// printer
+    ///     .stream_bit_image(
+    ///         "tests/data/Thermal_Test_Image.png",
+    ///         BitImageMode::Dots24DoubleDensity
+    ///     )
+    ///     .unwrap()
+    ///     .cut_paper(CutType::TotalCut)
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "image")]
+    pub fn stream_bit_image(
+        &mut self,
+        path: impl AsRef<Path>,
+        mode: BitImageMode,
+    ) -> Result<&mut Self, Error> {
+        let img = image::open(path)?.grayscale();
+
+        let width = img.width() as usize;
+        let height = img.height() as usize;
+        let pixels = img.as_bytes();
+        builder::check_image_size(width, height)?;
+        let mut line = Line::after(&self.cmd);
+        if !self.cmd.is_empty() {
+            self.flush()?;
+        }
+
+        // the raster images of the 24 dot modes print each block of banks at once
+        let raster = self.raster_images()
+            && line.is_clear()
+            && matches!(
+                mode,
+                BitImageMode::Dots24SingleDensity | BitImageMode::Dots24DoubleDensity
+            );
+        let lines = if raster {
+            command::RASTER_MAX_LINES
+        } else {
+            builder::bank_lines(mode)
+        };
+        // a command of a single block, its data reused for all of them
+        let mut commands = vec![Command::BitImage {
+            mode,
+            width: width as u16,
+            data: Vec::with_capacity(bitimage_len(width, lines.min(height), mode)),
+        }];
+        if raster {
+            commands.push(Command::Print);
+        }
+        for top in (0..height).step_by(lines) {
+            if let Command::BitImage { data, .. } = &mut commands[0] {
+                let lines = lines.min(height - top);
+                data.clear();
+                data.resize(bitimage_len(width, lines, mode), 0);
+                builder::convert_dots_into(width, lines, mode, data, |x, y| {
+                    pixels[(top + y) * width + x] == 0x00
+                });
+            }
+            self.send(&commands, &mut line)?;
+        }
+        if !raster {
+            self.send(&[Command::Print], &mut line)?;
+        }
+
+        Ok(self)
+    }
+}
+
+#[cfg(unix)]
+impl FromRawFd for CustomPrinter {
+    /// Create a new [`CustomPrinter`] with the open file descriptor `fd` of the device node, e.g. passed by
+    /// systemd socket activation, see [`from_file()`](CustomPrinter::from_file()).
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor for reading and writing, owned by the [`CustomPrinter`] from now on.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_file(File::from_raw_fd(fd))
+    }
+}
+
+/// The pending commands are formatted as readable mnemonics, see [`mnemonics()`].
+impl fmt::Debug for CustomPrinter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomPrinter")
+            .field("detached", &self.device.is_none())
+            .field("model", &self.model)
+            .field("auto_cut", &self.auto_cut)
+            .field("pending", &mnemonics(&self.to_bytes()))
+            .finish()
+    }
+}
+
+/// Warn in debug builds when pending commands are thrown away, see [`CustomPrinter::finish()`].
+impl Drop for CustomPrinter {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) && !self.cmd.is_empty() && !thread::panicking() {
+            eprintln!(
+                "warning: CustomPrinter dropped with {} pending commands, run() or discard() them",
+                self.cmd.len()
+            );
+        }
+    }
+}
+
+impl CommandBuilder for CustomPrinter {
+    fn push(&mut self, command: Command) -> &mut Self {
+        // the size is only tracked while it's limited, see set_max_size()
+        if let Some(max_size) = self.max_size {
+            let size = self.size + self.command_set.encoded_len(&command);
+            if size > max_size {
+                self.overflowed = true;
+                return self;
+            }
+            self.size = size;
+        }
+        self.cmd.push(command);
+
+        self
+    }
+
+    fn model(&self) -> Option<Model> {
+        self.model
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.profile
+            .clone()
+            .or_else(|| self.model.map(Profile::for_model))
+    }
+
+    fn config(&self) -> PrinterConfig {
+        self.config
+            .unwrap_or_else(|| PrinterConfig::of(self.profile().as_ref()))
+    }
+
+    fn scratch_buffer(&mut self, len: usize) -> Vec<u8> {
+        // the largest buffer, the least likely to grow
+        let largest = (0..self.scratch.len()).max_by_key(|i| self.scratch[*i].capacity());
+        let mut buffer = largest.map_or_else(Vec::new, |i| self.scratch.swap_remove(i));
+        buffer.clear();
+        buffer.resize(len, 0);
+
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder, BitImageMode, CutType, DrawerPin, FeedUnit, JobBuilder, Length, LimitPolicy,
+        MarkPosition, NearEndSensitivity, PageDirection, PaperSaving,
+    };
+
+    const THERMAL_WIDTH: usize = 384;
+    const THERMAL_HEIGHT: usize = 288;
+    const THERMAL_TXT: &str = include_str!("../tests/data/thermal.txt");
+    const THERMAL_8DOTS: &[u8] = include_bytes!("../tests/data/thermal.b8");
+    const THERMAL_24DOTS: &[u8] = include_bytes!("../tests/data/thermal.b24");
+    #[cfg(feature = "image")]
+    const THERMAL_PNG_PATH: &str = "tests/data/Thermal_Test_Image.png";
+    const DEV_NULL: &str = "/dev/null";
+
+    #[test]
+    fn test_cut_paper() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.cut_paper(CutType::TotalCut).cmd),
+            [0x1B, 0x69]
+        );
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.cut_paper(CutType::PartialCut).cmd),
+            [0x1B, 0x6D]
+        );
+    }
+
+    #[test]
+    fn test_feed_to_cut() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert!(matches!(
+            printer.feed_to_cut(),
+            Err(Error::InvalidParameter(_))
+        ));
+
+        printer.set_model(Some(Model::Tl80)).feed_to_cut().unwrap();
+        assert_eq!(command::encode(&printer.cmd), &[0x1B, 0x4A, 144]);
+        printer.discard();
+    }
+
+    #[test]
+    fn test_length() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        // 40 mm are 320 dots at 203 dpi
+        printer.feed(Length::mm(40.0)).feed(Length::dots(0));
+        assert_eq!(
+            command::encode(&printer.cmd),
+            &[0x1B, 0x4A, 255, 0x1B, 0x4A, 65]
+        );
+
+        // 4 mm margins of 576 dots
+        printer.discard().set_model(Some(Model::Tg2480h));
+        printer.margins(Length::mm(4.0)).unwrap();
+        assert_eq!(
+            printer.cmd,
+            [Command::PrintArea {
+                left: 32,
+                width: 512
+            }]
+        );
+        assert!(matches!(
+            printer.margins(Length::mm(40.0)),
+            Err(Error::InvalidParameter(_))
+        ));
+        printer.discard();
+    }
+
+    #[test]
+    fn test_print_and_reverse_feed_paper() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(
+                &printer
+                    .print_and_reverse_feed_paper(FeedUnit::Inches, 30)
+                    .unwrap()
+                    .print_and_reverse_feed_paper(FeedUnit::Lines, 2)
+                    .unwrap()
+                    .cmd
+            ),
+            &[0x1B, 0x4B, 30, 0x1B, 0x65, 2]
+        );
+        printer.discard();
+
+        printer.set_profile(Some(Profile {
+            reverse_feed: false,
+            ..Profile::for_model(Model::Tg2480h)
+        }));
+        assert!(matches!(
+            printer.print_and_reverse_feed_paper(FeedUnit::Lines, 2),
+            Err(Error::Unsupported {
+                command: "print_and_reverse_feed",
+                ..
+            })
+        ));
+        assert!(printer.pending().is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        // the limits are unknown without a profile
+        printer
+            .print_and_reverse_feed_paper(FeedUnit::Inches, 60)
+            .unwrap();
+        printer.run().unwrap();
+
+        printer
+            .set_model(Some(Model::Tg2480h))
+            .print_and_reverse_feed_paper(FeedUnit::Inches, 60)
+            .unwrap();
+        assert!(matches!(
+            printer.run(),
+            Err(Error::InvalidParameter(reason)) if reason == "reverse feed of 60 is above 48"
+        ));
+        assert_eq!(printer.validate().len(), 1);
+
+        printer.discard().set_config(Some(PrinterConfig {
+            limits: LimitPolicy::Clamp,
+            ..PrinterConfig::for_model(Model::Tg2480h)
+        }));
+        printer
+            .print_and_reverse_feed_paper(FeedUnit::Lines, 5)
+            .unwrap()
+            .feed_and_cut_paper(CutType::TotalCut, 255);
+        assert_eq!(
+            printer.cmd[0],
+            Command::PrintAndReverseFeed {
+                unit: FeedUnit::Lines,
+                amount: 2
+            }
+        );
+        printer.run().unwrap();
+    }
+
+    #[test]
+    fn test_motion_units() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.motion_units(203, 0).cmd),
+            &[0x1D, 0x50, 203, 0]
+        );
+    }
+
+    #[test]
+    fn test_black_mark() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(
+                &printer
+                    .mark_offset(MarkPosition::PrintStart, 300)
+                    .mark_offset(MarkPosition::Cut, -8)
+                    .feed_to_mark()
+                    .unwrap()
+                    .cmd
+            ),
+            &[
+                0x1D, 0x28, 0x46, 0x04, 0x00, 1, 0, 44, 1, 0x1D, 0x28, 0x46, 0x04, 0x00, 2, 1, 8,
+                0, 0x1D, 0x0C
+            ]
+        );
+        printer.discard().set_profile(Some(Profile {
+            black_mark: false,
+            ..Profile::for_model(Model::Tl80)
+        }));
+        assert!(matches!(
+            printer.feed_to_mark(),
+            Err(Error::Unsupported {
+                command: "feed_to_mark",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_paper_saving() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(
+                &printer
+                    .paper_saving(&PaperSaving::default())
+                    .paper_saving(&PaperSaving {
+                        top_margin: true,
+                        blank_lines: true,
+                        barcode_height: true,
+                        ..Default::default()
+                    })
+                    .cmd
+            ),
+            &[0x1D, 0xF7, 0x00, 0x1D, 0xF7, 0x19]
+        );
+    }
+
+    #[test]
+    fn test_open_drawer() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.open_drawer(DrawerPin::Pin5, 100, 1000).cmd),
+            &[0x1B, 0x70, 0x01, 50, 255]
+        );
+    }
+
+    #[test]
+    fn test_beep() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.beep(3, 200).unwrap().cmd),
+            &[0x1B, 0x42, 3, 2]
+        );
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.beep(20, 5000).unwrap().cmd),
+            &[0x1B, 0x42, 9, 9]
+        );
+
+        // only the models equipped with a buzzer
+        printer.discard().set_model(Some(Model::Tl80));
+        assert!(printer.beep(1, 100).is_ok());
+        printer.discard().set_model(Some(Model::Tg2480h));
+        assert!(matches!(
+            printer.beep(1, 100),
+            Err(Error::Unsupported {
+                command: "beep",
+                printer,
+            }) if printer == "TG2480H"
+        ));
+    }
+
+    #[test]
+    fn test_feed_button() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.feed_button(false).feed_button(true).cmd),
+            &[0x1B, 0x63, 0x35, 0x01, 0x1B, 0x63, 0x35, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_sleep() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.sleep_timeout(Some(10)).sleep().cmd),
+            &[0x1B, 0x8E, 0x01, 10, 0x1B, 0x8E, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_presenter() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer
+            .presenter_loop(100)
+            .and_then(|printer| printer.retract_on_timeout(None))
+            .and_then(|printer| printer.present_ticket(50))
+            .and_then(|printer| printer.eject_ticket())
+            .and_then(|printer| printer.retract_ticket())
+            .unwrap();
+        assert_eq!(
+            command::encode(&printer.cmd),
+            &[
+                0x1D, 0x65, 0x01, 100, 0x1D, 0x65, 0x12, 0, 0x1D, 0x65, 0x03, 50, 0x1D, 0x65, 0x05,
+                0x1D, 0x65, 0x02
+            ]
+        );
+
+        // the presenter commands are refused by the profile of a printer without a presenter
+        printer.discard().set_model(Some(Model::Tg2480h));
+        assert!(matches!(
+            printer.eject_ticket(),
+            Err(Error::Unsupported {
+                command: "eject_ticket",
+                ..
+            })
+        ));
+        assert!(printer.cmd.is_empty());
+        printer.set_model(Some(Model::Vkp80));
+        assert!(printer.eject_ticket().is_ok());
+        printer.discard();
+    }
+
+    #[test]
+    fn test_near_end_sensor() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(
+                &printer
+                    .stop_on_near_end(false)
+                    .near_end_sensitivity(NearEndSensitivity::High)
+                    .cmd
+            ),
+            &[0x1B, 0x63, 0x34, 0x00, 0x1D, 0xE6, 0x02]
+        );
+    }
+
+    #[test]
+    fn test_auto_cut() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer.set_auto_cut(Some(AutoCut {
+            cut_type: CutType::PartialCut,
+            feed: 5,
+        }));
+
+        // nothing is cut without constructed commands
+        assert!(printer.to_bytes().is_empty());
+
+        assert_eq!(printer.print().to_bytes(), &[0x0A, 0x1D, 0x56, 0x42, 5]);
+        // the automatic cut isn't constructed
+        assert_eq!(printer.cmd, [Command::Print]);
+    }
+
+    #[test]
+    fn test_discard() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer.print().cut_paper(CutType::TotalCut);
+        assert_eq!(printer.pending_len(), 2);
+        assert_eq!(
+            printer.pending(),
+            &[Command::Print, Command::CutPaper(CutType::TotalCut)]
+        );
+
+        printer.discard().print();
+        assert_eq!(printer.pending(), &[Command::Print]);
+    }
+
+    #[test]
+    fn test_raw() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.raw(&[0x1B, 0x74, 0x02]).print().cmd),
+            &[0x1B, 0x74, 0x02, 0x0A]
+        );
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut printer = CustomPrinter::detached();
+        printer.print().cut_paper(CutType::TotalCut);
+        assert_eq!(
+            format!("{printer:?}"),
+            "CustomPrinter { detached: true, model: None, auto_cut: None, pending: [\"LF\", \"ESC i total\"] }"
+        );
+    }
+
+    #[test]
+    fn test_detached() {
+        let mut printer = CustomPrinter::detached();
+        printer.print().cut_paper(CutType::TotalCut);
+        assert_eq!(printer.to_bytes(), &[0x0A, 0x1B, 0x69]);
+        assert_eq!(printer.as_bytes(), &[0x0A, 0x1B, 0x69]);
+        assert!(matches!(printer.run(), Err(Error::NoDevice)));
+        assert!(matches!(printer.status(), Err(Error::NoDevice)));
+        assert_eq!(printer.into_bytes(), &[0x0A, 0x1B, 0x69]);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .open(DEV_NULL)
+            .unwrap();
+        CustomPrinter::from_file(file).print().run().unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::IntoRawFd;
+
+            let fd = File::options()
+                .read(true)
+                .write(true)
+                .open(DEV_NULL)
+                .unwrap()
+                .into_raw_fd();
+            let mut printer = unsafe { CustomPrinter::from_raw_fd(fd) };
+            printer.print().run().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_from_stream() {
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            stream.read_to_end(&mut received).unwrap();
+            received
+        });
+
+        let mut printer = CustomPrinter::from_stream(TcpStream::connect(addr).unwrap()).unwrap();
+        printer.print().cut_paper(CutType::TotalCut).run().unwrap();
+        // reading times out instead of blocking on the connection
+        assert!(matches!(
+            printer.read_response(1, Duration::from_millis(50)),
+            Err(Error::Timeout)
+        ));
+        drop(printer);
+        assert_eq!(server.join().unwrap(), [0x0A, 0x1B, 0x69]);
+    }
+
+    #[test]
+    fn test_detect_profile() {
+        use std::net::TcpListener;
+
+        // answer the model name, firmware version and type ID queries of printer_info()
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut query = [0; 3];
+            while stream.read_exact(&mut query).is_ok() {
+                let response: &[u8] = match query[2] {
+                    ID_MODEL_NAME => b"_TG2480-H OEM\0",
+                    ID_FIRMWARE_VERSION => b"_1.01\0",
+                    _ => &[0x02],
+                };
+                stream.write_all(response).unwrap();
+            }
+        });
+
+        let mut printer = CustomPrinter::from_stream(TcpStream::connect(addr).unwrap()).unwrap();
+        // the OEM model isn't supported by the crate
+        printer.detect_profile(&[]).unwrap();
+        assert_eq!(printer.model(), None);
+        assert_eq!(printer.profile(), None);
+
+        let oem = Profile {
+            name: "TG2480H OEM".into(),
+            partial_cut: true,
+            ..Profile::for_model(Model::Tg2480h)
+        };
+        printer.detect_profile(std::slice::from_ref(&oem)).unwrap();
+        assert_eq!(printer.profile(), Some(oem));
+        drop(printer);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_outcome() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        let outcome = printer.print().cut_paper(CutType::TotalCut).run().unwrap();
+        assert_eq!(outcome.bytes_written, 3);
+        assert_eq!(outcome.job_id, 1);
+
+        printer
+            .print_job(&JobBuilder::new().print().build())
+            .unwrap();
+        let outcome = printer.run_chained().unwrap().print().run().unwrap();
+        assert_eq!(outcome.bytes_written, 1);
+        // every job sent to the printer is numbered
+        assert_eq!(outcome.job_id, 4);
+    }
+
+    #[test]
+    fn test_command_set() {
+        /// CUSTOM ESC/POS cutting by `GS V` only, without the buzzer.
+        struct Legacy;
+
+        impl CommandSet for Legacy {
+            fn name(&self) -> &'static str {
+                "legacy"
+            }
+
+            fn supports(&self, command: &Command) -> bool {
+                !matches!(command, Command::Beep { .. })
+            }
+
+            fn encode(&self, command: &Command, out: &mut Vec<u8>) {
+                match command {
+                    Command::CutPaper(cut_type) => Command::FeedAndCutPaper {
+                        cut_type: *cut_type,
+                        amount: 0,
+                    }
+                    .encode(out),
+                    command => command.encode(out),
+                }
+            }
+        }
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer
+            .set_command_set(Some(Box::new(Legacy)))
+            .print()
+            .cut_paper(CutType::TotalCut);
+        assert_eq!(printer.to_bytes(), [0x0A, 0x1D, 0x56, 0x41, 0]);
+        assert_eq!(printer.run().unwrap().bytes_written, 5);
+
+        printer.beep(1, 100).unwrap();
+        assert!(matches!(
+            printer.run(),
+            Err(Error::Unsupported {
+                command: "beep",
+                printer,
+            }) if printer == "legacy"
+        ));
+        printer.set_command_set(None);
+        printer.run().unwrap();
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer.print().cut_paper(CutType::PartialCut);
+        // nothing is validated without any model
+        printer.run().unwrap();
+
+        printer.set_model(Some(Model::Tg2480h));
+        printer.print().cut_paper(CutType::PartialCut);
+        assert!(matches!(
+            printer.run(),
+            Err(Error::Unsupported {
+                command: "cut_paper",
+                printer
+            }) if printer == "TG2480H"
+        ));
+        // the profile overrides the capabilities of the model
+        printer.set_profile(Some(Profile {
+            partial_cut: true,
+            ..Profile::for_model(Model::Tg2480h)
+        }));
+        printer.run().unwrap();
+        printer.set_profile(None);
+        printer.discard().print().push(Command::PresentTicket(50));
+        assert!(matches!(
+            printer.run(),
+            Err(Error::Unsupported {
+                command: "present_ticket",
+                ..
+            })
+        ));
+
+        // the automatic cut is validated as well
+        printer.discard().set_auto_cut(Some(AutoCut {
+            cut_type: CutType::PartialCut,
+            feed: 0,
+        }));
+        assert!(matches!(
+            printer.print_job(&JobBuilder::new().print().build()),
+            Err(Error::Unsupported {
+                command: "feed_and_cut_paper",
+                ..
+            })
+        ));
+
+        printer.set_model(Some(Model::Tl80)).print().run().unwrap();
+    }
+
+    #[test]
+    fn test_trace() {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        let sink = Arc::clone(&events);
+        printer.set_trace(Some(Box::new(move |event: &TraceEvent| {
+            sink.lock().unwrap().push(match event {
+                TraceEvent::Transmitted { bytes, .. } => format!("transmitted {bytes}"),
+                TraceEvent::Received(response) => format!("received {}", response.len()),
+                TraceEvent::Failed(e) => format!("failed {e}"),
+            });
+        })));
+        printer.print().cut_paper(CutType::TotalCut).run().unwrap();
+        printer.clear_buffer().unwrap();
+        // /dev/null never responds
+        assert!(matches!(printer.status(), Err(Error::Timeout)));
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "transmitted 3",
+                "transmitted 10",
+                "transmitted 3",
+                "failed timed out waiting for the printer"
+            ]
+        );
+
+        let mut printer = CustomPrinter::detached();
+        let sink = Arc::clone(&events);
+        printer.set_trace(Some(Box::new(move |event: &TraceEvent| {
+            sink.lock().unwrap().push(format!("{event:?}"));
+        })));
+        assert!(printer.wake_up().is_err());
+        assert_eq!(events.lock().unwrap().last().unwrap(), "Failed(NoDevice)");
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata};
+
+        /// Subscriber recording the names of the spans and the messages of the events.
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for Recorder {
+            fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0.lock().unwrap().push(format!("{value:?}"));
+                }
+            }
+        }
+
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                self.0.lock().unwrap().push(span.metadata().name().into());
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                event.record(&mut Recorder(Arc::clone(&self.0)));
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Recorder(Arc::clone(&events)), || {
+            let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+            printer.print().cut_paper(CutType::TotalCut).run().unwrap();
+            assert!(printer.status().is_err());
+            let job = JobBuilder::new()
+                .bitmap(8, 1, &[0xFF], BitImageMode::Dots8SingleDensity)
+                .unwrap()
+                .build();
+            assert!(printer.print_job(&job).is_err());
+        });
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "run",
+                "transmitted to the printer",
+                // the status query, /dev/null never responds
+                "transmitted to the printer",
+                "communication with the printer failed",
+                "job built",
+                "print_job",
+                "commands refused"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_finish() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer.print().run().unwrap();
+        printer.finish().unwrap();
+
+        let mut printer = CustomPrinter::detached();
+        printer.print().print();
+        assert!(matches!(
+            printer.finish(),
+            Err(Error::PendingCommands { count: 2 })
+        ));
+
+        let mut printer = CustomPrinter::detached();
+        printer.print();
+        assert_eq!(printer.into_bytes(), &[0x0A]);
+    }
+
+    #[test]
+    fn test_max_size() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer.set_max_size(Some(8));
+        printer
+            .print()
+            .raw(&[0x00; 7])
+            .raw(&[0x00; 2])
+            .print()
+            .cut_paper(CutType::TotalCut);
+        assert_eq!(printer.pending_len(), 2);
+        assert!(matches!(
+            printer.run(),
+            Err(Error::TooLarge { max_size: 8 })
+        ));
+        assert_eq!(printer.pending_len(), 2);
+
+        printer.discard().raw(&[0x00; 7]).print();
+        printer.run().unwrap();
+        // the size is reset after the commands are run
+        printer.raw(&[0x00; 7]).print().run().unwrap();
+
+        printer.set_max_size(None).raw(&[0x00; 16]).run().unwrap();
+
+        // the commands constructed before limiting the size are counted
+        printer
+            .raw(&[0x00; 7])
+            .set_max_size(Some(8))
+            .print()
+            .print();
+        assert_eq!(printer.pending_len(), 2);
+        printer.discard();
+    }
+
+    #[test]
+    fn test_transaction() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer.set_max_size(Some(8));
+        printer.print().begin().raw(&[0x00; 7]).rollback();
+        assert_eq!(printer.pending_len(), 1);
+        // the size is restored by the rollback
+        printer.raw(&[0x00; 7]);
+        assert_eq!(printer.pending_len(), 2);
+
+        assert!(matches!(
+            printer.transaction(|p| Ok(p.print())),
+            Err(Error::TooLarge { max_size: 8 })
+        ));
+        assert_eq!(printer.pending_len(), 2);
+        assert!(matches!(
+            printer.transaction(|p| p.bitmap(16, 1, &[0xFF], BitImageMode::Dots8SingleDensity)),
+            Err(Error::UnsupportedImage(_))
+        ));
+        printer.run().unwrap();
+
+        // running ends the transaction
+        printer.begin().print().run().unwrap();
+        printer.print().rollback();
+        assert_eq!(printer.pending_len(), 1);
+    }
+
+    #[test]
+    fn test_print_jobs() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer.set_model(Some(Model::Tl80));
+        let mut kitchen = printer.job_builder();
+        let mut receipt = printer.job_builder();
+        assert_eq!(kitchen.model(), Some(Model::Tl80));
+        kitchen.print();
+        receipt.print().cut_paper(CutType::TotalCut);
+        printer.print();
+        printer
+            .print_jobs(&[kitchen.build(), receipt.build()])
+            .unwrap();
+        assert_eq!(printer.pending(), &[Command::Print]);
+
+        let unprinted = printer
+            .job_builder()
+            .bitmap(8, 1, &[0xFF], BitImageMode::Dots8SingleDensity)
+            .unwrap()
+            .build();
+        assert!(matches!(
+            printer.print_jobs([&Job::default(), &unprinted]),
+            Err(Error::UnprintedData { index: 0 })
+        ));
+        assert!(matches!(
+            CustomPrinter::detached().print_jobs(&[Job::default()]),
+            Err(Error::NoDevice)
+        ));
+    }
+
+    #[test]
+    fn test_replay() {
+        let path =
+            std::env::temp_dir().join(format!("custom-printer-replay-{}.job", std::process::id()));
+        JobBuilder::new().print().build().save(&path).unwrap();
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer.print();
+        printer.replay(&path).unwrap();
+        assert_eq!(printer.pending(), &[Command::Print]);
+        assert!(matches!(
+            CustomPrinter::detached().replay(&path),
+            Err(Error::NoDevice)
+        ));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(printer.replay(&path), Err(Error::JobFile { .. })));
+    }
+
+    #[test]
+    fn test_page_mode() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(
+                &printer
+                    .enter_page_mode()
+                    .page_area(0, 0, 384, 800)
+                    .page_direction(PageDirection::TopToBottom)
+                    .position(300, 2)
+                    .print_page()
+                    .print_page_and_exit()
+                    .enter_page_mode()
+                    .exit_page_mode()
+                    .cmd
+            ),
+            &[
+                0x1B, 0x4C, 0x1B, 0x57, 0, 0, 0, 0, 0x80, 0x01, 0x20, 0x03, 0x1B, 0x54, 3, 0x1B,
+                0x24, 0x2C, 0x01, 0x1D, 0x24, 0x02, 0x00, 0x1B, 0x0C, 0x0C, 0x1B, 0x4C, 0x1B, 0x53
+            ]
+        );
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(command::encode(&printer.cancel_page_data().cmd), [0x18]);
+    }
+
+    #[test]
+    fn test_feed_and_cut_paper() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.feed_and_cut_paper(CutType::TotalCut, 10).cmd),
+            &[0x1D, 0x56, 0x41, 10]
+        );
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert_eq!(
+            command::encode(&printer.feed_and_cut_paper(CutType::PartialCut, 0).cmd),
+            &[0x1D, 0x56, 0x42, 0]
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn helper_prepare_bitimage() {
+        let converter = |text: &str, inverted: bool, output: &mut File, bank: usize| {
+            let lines: Vec<&str> = text.trim().split('\n').collect();
+            let width = lines[0].len();
+            let banks = lines.len().div_ceil(bank);
+
+            for i in 0..banks {
+                for j in 0..width {
+                    let mut byte: u8 = 0;
+                    for k in 0..bank {
+                        let line_no = i * bank + k;
+                        // padding lines are always 0
+                        if line_no < lines.len() {
+                            let b = lines[line_no].chars().nth(j).unwrap();
+                            if inverted {
+                                if b == '0' {
+                                    byte |= 0x80 >> (k % 8);
+                                }
+                            } else {
+                                if b == '1' {
+                                    byte |= 0x80 >> (k % 8);
+                                }
+                            }
+                        }
+                        if k % 8 == 7 {
+                            output.write_all(&[byte]).ok();
+                            byte = 0;
+                        }
+                    }
+                }
+            }
+        };
+
+        let mut output = File::options()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open("tests/data/thermal.b8")
+            .unwrap();
+        converter(THERMAL_TXT, true, &mut output, 8);
+
+        let mut output = File::options()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open("tests/data/thermal.b24")
+            .unwrap();
+        converter(THERMAL_TXT, true, &mut output, 24);
+    }
+
+    fn convert_text_to_bitmap(text: &str, inverted: bool) -> Vec<u8> {
+        let mut data = Vec::new();
+
+        text.trim().split('\n').for_each(|line| {
+            if line.len() % 8 != 0 {
+                eprintln!("Length of each line of text must be dividable by 8");
+                return;
+            }
+            for i in (0..line.len()).step_by(8) {
+                if let Ok(byte) = u8::from_str_radix(&line[i..i + 8], 2) {
+                    data.extend_from_slice(&[if inverted { !byte } else { byte }]);
+                } else {
+                    eprintln!("text contains characters neither 0 nor 1");
+                    return;
+                }
+            }
+        });
+
+        data
+    }
+
+    #[test]
+    fn test_convert_bitmap_to_bitimage_8dots() {
+        let bitmap = convert_text_to_bitmap(THERMAL_TXT, true);
+        assert_eq!(
+            &builder::convert_bitmap_to_bitimage(
+                THERMAL_WIDTH,
+                THERMAL_HEIGHT,
+                &bitmap,
+                BitImageMode::Dots8SingleDensity
+            ),
+            THERMAL_8DOTS
+        );
+        assert_eq!(
+            &builder::convert_bitmap_to_bitimage(
+                THERMAL_WIDTH,
+                THERMAL_HEIGHT,
+                &bitmap,
+                BitImageMode::Dots8DoubleDensity
+            ),
+            THERMAL_8DOTS
+        );
+    }
+
+    #[test]
+    fn test_convert_bitmap_to_bitimage_24dots() {
+        let bitmap = convert_text_to_bitmap(THERMAL_TXT, true);
+        assert_eq!(
+            &builder::convert_bitmap_to_bitimage(
+                THERMAL_WIDTH,
+                THERMAL_HEIGHT,
+                &bitmap,
+                BitImageMode::Dots24SingleDensity
+            ),
+            THERMAL_24DOTS
+        );
+        assert_eq!(
+            &builder::convert_bitmap_to_bitimage(
+                THERMAL_WIDTH,
+                THERMAL_HEIGHT,
+                &bitmap,
+                BitImageMode::Dots24DoubleDensity
+            ),
+            THERMAL_24DOTS
+        );
+
+        // the last bank of the first 100 lines is padded, the banks before it are the same
+        let bitimage = builder::convert_bitmap_to_bitimage(
+            THERMAL_WIDTH,
+            100,
+            &bitmap,
+            BitImageMode::Dots24SingleDensity,
+        );
+        assert_eq!(bitimage.len(), 5 * THERMAL_WIDTH * 3);
+        assert_eq!(
+            bitimage[..4 * THERMAL_WIDTH * 3],
+            THERMAL_24DOTS[..4 * THERMAL_WIDTH * 3]
+        );
+        assert_ne!(
+            bitimage[4 * THERMAL_WIDTH * 3..],
+            THERMAL_24DOTS[4 * THERMAL_WIDTH * 3..5 * THERMAL_WIDTH * 3]
+        );
+    }
+
+    #[test]
+    fn test_convert_bitmap_to_bitimage_into() {
+        let bitmap = convert_text_to_bitmap(THERMAL_TXT, true);
+        let mode = BitImageMode::Dots24SingleDensity;
+        let len = builder::bitimage_len(THERMAL_WIDTH, THERMAL_HEIGHT, mode);
+        assert_eq!(len, THERMAL_24DOTS.len());
+
+        // the output is cleared before the conversion
+        let mut out = vec![0xFF; len + 1];
+        assert_eq!(
+            builder::convert_bitmap_to_bitimage_into(
+                THERMAL_WIDTH,
+                THERMAL_HEIGHT,
+                &bitmap,
+                mode,
+                &mut out
+            )
+            .unwrap(),
+            len
+        );
+        assert_eq!(&out[..len], THERMAL_24DOTS);
+        assert_eq!(out[len], 0xFF);
+
+        assert!(matches!(
+            builder::convert_bitmap_to_bitimage_into(
+                THERMAL_WIDTH,
+                THERMAL_HEIGHT,
+                &bitmap,
+                mode,
+                &mut out[..len - 1]
+            ),
+            Err(Error::BufferTooSmall { required }) if required == len
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_bit_image() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer
+            .bit_image(THERMAL_PNG_PATH, BitImageMode::Dots8SingleDensity)
+            .unwrap();
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer
+            .bit_image(THERMAL_PNG_PATH, BitImageMode::Dots8DoubleDensity)
+            .unwrap();
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer
+            .bit_image(THERMAL_PNG_PATH, BitImageMode::Dots24SingleDensity)
+            .unwrap();
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer
+            .bit_image(THERMAL_PNG_PATH, BitImageMode::Dots24DoubleDensity)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_printer_info_timeout() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert!(matches!(printer.printer_info(), Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn test_run_checked_timeout() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer.cut_paper(CutType::TotalCut);
+        assert!(matches!(
+            printer.run_checked(Duration::from_millis(20)),
+            Err(Error::Timeout)
+        ));
+        assert!(printer.cmd.is_empty());
+    }
+
+    #[test]
+    fn test_run_checked_missed_reply() {
+        use std::net::TcpListener;
+
+        // miss the reply to the first status query, then answer busy and idle
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            let mut queries = 0;
+            let mut byte = [0];
+            while queries < 3 && stream.read_exact(&mut byte).is_ok() {
+                received.push(byte[0]);
+                if received.ends_with(FULL_STATUS) {
+                    queries += 1;
+                    match queries {
+                        1 => {}
+                        2 => stream.write_all(&[0x10, 0x0F, 0, 0x08, 0, 0]).unwrap(),
+                        _ => stream.write_all(&[0x10, 0x0F, 0, 0, 0, 0]).unwrap(),
+                    }
+                }
+            }
+            queries
+        });
+
+        let mut printer = CustomPrinter::from_stream(TcpStream::connect(addr).unwrap()).unwrap();
+        let status = printer
+            .print()
+            .cut_paper(CutType::TotalCut)
+            .run_checked(Duration::from_secs(5))
+            .unwrap();
+        assert!(status.is_idle());
+        assert_eq!(server.join().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_read_response_timeout() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert!(matches!(
+            printer.read_response(16, Duration::from_millis(20)),
+            Err(Error::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_bitmap() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        let bitmap = convert_text_to_bitmap(THERMAL_TXT, true);
+        printer
+            .bitmap(
+                THERMAL_WIDTH,
+                THERMAL_HEIGHT,
+                &bitmap,
+                BitImageMode::Dots24SingleDensity,
+            )
+            .unwrap();
+        assert_eq!(
+            printer.cmd,
+            [Command::BitImage {
+                mode: BitImageMode::Dots24SingleDensity,
+                width: THERMAL_WIDTH as u16,
+                data: THERMAL_24DOTS.to_vec()
+            }]
+        );
+
+        // the lines are padded to whole bytes
+        let mut printer = CustomPrinter::detached();
+        printer
+            .bitmap(
+                12,
+                2,
+                &[0xFF, 0xF0, 0x80, 0x00],
+                BitImageMode::Dots8DoubleDensity,
+            )
+            .unwrap();
+        assert_eq!(
+            printer.cmd,
+            [Command::BitImage {
+                mode: BitImageMode::Dots8DoubleDensity,
+                width: 12,
+                data: [[0xC0].as_slice(), &[0x80; 11]].concat()
+            }]
+        );
+        for (width, height) in [(0, 8), (8, 0)] {
+            assert!(matches!(
+                printer.bitmap(width, height, &[], BitImageMode::Dots8SingleDensity),
+                Err(Error::UnsupportedImage(_))
+            ));
+        }
+        assert!(matches!(
+            printer.bitmap(16, 2, &[0xFF; 3], BitImageMode::Dots8SingleDensity),
+            Err(Error::UnsupportedImage(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_image_decode_error() {
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        assert!(matches!(
+            printer.bit_image("tests/data/thermal.txt", BitImageMode::Dots8SingleDensity),
+            Err(Error::ImageDecode(_))
+        ));
+    }
+
+    #[test]
+    fn test_raster_images() {
+        let mut printer = CustomPrinter::detached();
+        printer
+            .bitmap(16, 24, &[0xFF; 48], BitImageMode::Dots24DoubleDensity)
+            .unwrap()
+            .print();
+        assert_eq!(
+            mnemonics(&printer.to_bytes()),
+            ["ESC * m=0x21 w=16 [48 bytes]", "LF"]
+        );
+
+        // a single block without the print on the printers whose profile enables them
+        printer.set_model(Some(Model::Tg2480h));
+        assert_eq!(printer.to_bytes()[..2], [0x1B, 0x2A]);
+        printer.set_profile(Some(Profile {
+            raster_images: true,
+            ..Profile::for_model(Model::Tg2480h)
+        }));
+        assert_eq!(
+            mnemonics(&printer.to_bytes()),
+            ["GS v 0 m=0 x=2 y=24 [48 bytes]"]
+        );
+    }
+
+    #[test]
+    fn test_buffer_capacity() {
+        let mut printer = CustomPrinter::new(DEV_NULL)
+            .unwrap()
+            .with_buffer_capacity(THERMAL_8DOTS.len() + 64);
+        assert!(printer.buf.capacity() >= THERMAL_8DOTS.len() + 64);
+
+        let bitmap = [0xFF; THERMAL_WIDTH * THERMAL_HEIGHT / 8];
+        for _ in 0..2 {
+            printer
+                .bitmap(
+                    THERMAL_WIDTH,
+                    THERMAL_HEIGHT,
+                    &bitmap,
+                    BitImageMode::Dots8SingleDensity,
+                )
+                .unwrap();
+            // the buffer of the previous bit image is reused
+            assert!(printer.scratch.is_empty());
+            printer.print().run().unwrap();
+            assert_eq!(printer.scratch.len(), 1);
+        }
+        // reused buffers are zeroed again
+        printer
+            .bitmap(8, 8, &[0; 8], BitImageMode::Dots8SingleDensity)
+            .unwrap();
+        assert_eq!(printer.to_bytes()[5..], [0; 8]);
+
+        printer.discard();
+        assert_eq!(printer.scratch.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_stream_bit_image() {
+        let path =
+            std::env::temp_dir().join(format!("custom-printer-stream-{}.bin", std::process::id()));
+        let auto_cut = Some(AutoCut {
+            cut_type: CutType::TotalCut,
+            feed: 0,
+        });
+        let raster = Profile {
+            raster_images: true,
+            ..Profile::for_model(Model::Tg2480h)
+        };
+        // the pending commands, then 12 banks of 24 lines and the print, or a single raster image
+        for (profile, transmitted) in [
+            (None, 1 + THERMAL_HEIGHT / 24 + 1 + 1),
+            (Some(raster), 1 + 1 + 1),
+        ] {
+            let mut printer = CustomPrinter::from_file(File::create(&path).unwrap());
+            printer.set_auto_cut(auto_cut).set_profile(profile.clone());
+            let count = std::sync::Arc::new(std::sync::Mutex::new(0));
+            printer.set_trace(Some(Box::new({
+                let count = count.clone();
+                move |event: &TraceEvent<'_>| {
+                    if let TraceEvent::Transmitted { .. } = event {
+                        *count.lock().unwrap() += 1;
+                    }
+                }
+            })));
+            printer
+                .initialize()
+                .stream_bit_image(THERMAL_PNG_PATH, BitImageMode::Dots24DoubleDensity)
+                .unwrap()
+                .print()
+                .run()
+                .unwrap();
+            assert_eq!(*count.lock().unwrap(), transmitted);
+
+            // a single cut at the end of the ticket
+            let mut expected = CustomPrinter::detached();
+            expected
+                .set_auto_cut(auto_cut)
+                .set_profile(profile)
+                .initialize()
+                .bit_image(THERMAL_PNG_PATH, BitImageMode::Dots24DoubleDensity)
+                .unwrap()
+                .print()
+                .print();
+            assert_eq!(std::fs::read(&path).unwrap(), expected.to_bytes());
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            CustomPrinter::detached()
+                .stream_bit_image(THERMAL_PNG_PATH, BitImageMode::Dots8SingleDensity),
+            Err(Error::NoDevice)
+        ));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            CustomPrinter::new("/nonexistent/lp0"),
+            Err(Error::DeviceOpen { .. })
+        ));
+        // paths which aren't valid UTF-8 are kept as they are
+        #[cfg(unix)]
+        {
+            use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::PathBuf};
+
+            let path = PathBuf::from("/nonexistent").join(OsStr::from_bytes(b"lp\xFF"));
+            assert!(matches!(
+                CustomPrinter::new(&path),
+                Err(Error::DeviceOpen { path: error_path, .. }) if error_path == path
+            ));
+        }
+
+        let mut printer = CustomPrinter::new(DEV_NULL).unwrap();
+        printer
+            .bitmap(8, 8, &[0xFF; 8], BitImageMode::Dots8SingleDensity)
+            .unwrap()
+            .cut_paper(CutType::TotalCut);
+        assert!(matches!(
+            printer.run(),
+            Err(Error::UnprintedData { index: 0 })
+        ));
+        // the constructed commands are kept for fixing
+        assert_eq!(printer.pending_len(), 2);
+    }
+
+    #[test]
+    fn test_multiple_run() {}
+}
@@ -0,0 +1,574 @@
+//! Capabilities of the printers consulted when constructing and checking commands.
+
+#[cfg(feature = "json")]
+use alloc::string::ToString;
+use alloc::{borrow::Cow, format, string::String};
+#[cfg(all(feature = "std", feature = "json"))]
+use std::{fs, path::Path};
+
+#[cfg(feature = "json")]
+use crate::{
+    json::{self, Value},
+    Error,
+};
+use crate::{Command, CutType, FeedUnit, Model};
+
+/// Description of the print head, cutter and optional firmware features of a printer, given by
+/// [`for_model()`](Profile::for_model()) for a supported [`Model`] and returned by
+/// [`CommandBuilder::profile()`](crate::CommandBuilder::profile()).
+///
+/// Printers which aren't supported by the crate, e.g. OEM-rebadged CUSTOM models, are described by a profile set by
+/// [`CustomPrinter::set_profile()`](crate::CustomPrinter::set_profile()) or
+/// [`JobBuilder::set_profile()`](crate::JobBuilder::set_profile()).
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Model, Profile};
+/// let profile = Profile {
+///     name: "TG2480H OEM".into(),
+///     partial_cut: true,
+///     ..Profile::for_model(Model::Tg2480h)
+/// };
+/// assert_eq!(profile.dots_per_line, 576);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// Name of the printer, e.g. `TG2480H`
+    pub name: Cow<'static, str>,
+    /// Number of dots in a printed line
+    pub dots_per_line: u16,
+    /// Resolution of the print head in dots per inch
+    pub dpi: u16,
+    /// Distance between the print head and the cutter in millimeters
+    pub head_to_cutter_mm: f32,
+    /// Whether the cutter supports partial cuts
+    pub partial_cut: bool,
+    /// Whether the firmware prints 1D barcodes
+    pub barcodes: bool,
+    /// Whether the firmware prints 2D codes, e.g. QR codes and PDF417
+    pub codes_2d: bool,
+    /// Whether the printer is equipped with a presenter
+    pub presenter: bool,
+    /// Whether the printer is equipped with a buzzer, see [`CommandBuilder::beep()`](crate::CommandBuilder::beep())
+    pub buzzer: bool,
+    /// Whether the printer feeds the paper backwards, see
+    /// [`CommandBuilder::print_and_reverse_feed_paper()`](crate::CommandBuilder::print_and_reverse_feed_paper())
+    pub reverse_feed: bool,
+    /// Whether the printer is equipped with a black mark sensor, see
+    /// [`CommandBuilder::feed_to_mark()`](crate::CommandBuilder::feed_to_mark())
+    pub black_mark: bool,
+    /// Whether the firmware prints raster images by `GS v 0`, so each bit image of the 24 dot modes followed by a
+    /// print is sent by [`CustomPrinter`](crate::CustomPrinter) as a single block instead of a header for each
+    /// bank, without any seams between the banks. Disabled in the built-in profiles, it's enabled in custom
+    /// profiles for the firmwares known to print them.
+    pub raster_images: bool,
+    /// Maximum reverse feed in vertical motion units, see
+    /// [`CommandBuilder::print_and_reverse_feed_paper()`](crate::CommandBuilder::print_and_reverse_feed_paper())
+    pub max_reverse_feed: u8,
+    /// Maximum reverse feed in lines
+    pub max_reverse_feed_lines: u8,
+    /// Maximum feed beyond the cut position in vertical motion units, see
+    /// [`CommandBuilder::feed_and_cut_paper()`](crate::CommandBuilder::feed_and_cut_paper())
+    pub max_cut_feed: u8,
+}
+
+impl Profile {
+    /// The profile of the supported `model`.
+    pub fn for_model(model: Model) -> Self {
+        Self {
+            name: Cow::Borrowed(model.name()),
+            dots_per_line: model.dots_per_line(),
+            dpi: model.dpi(),
+            head_to_cutter_mm: model.head_to_cutter_mm(),
+            partial_cut: matches!(model, Model::Tl60 | Model::Tl80 | Model::Kpm150h),
+            barcodes: true,
+            codes_2d: true,
+            presenter: matches!(model, Model::Vkp80),
+            buzzer: matches!(model, Model::Tl60 | Model::Tl80),
+            reverse_feed: true,
+            black_mark: true,
+            raster_images: false,
+            max_reverse_feed: 48,
+            max_reverse_feed_lines: 2,
+            max_cut_feed: u8::MAX,
+        }
+    }
+
+    /// The profile of the [`Emulator`](crate::Emulator), a 576 dot wide print head at 203 dpi supporting every
+    /// command without any limits, so snapshot tests don't depend on the quirks of any physical model.
+    pub fn emulated() -> Self {
+        Self {
+            name: Cow::Borrowed("Emulated"),
+            dots_per_line: 576,
+            dpi: 203,
+            head_to_cutter_mm: 10.0,
+            partial_cut: true,
+            barcodes: true,
+            codes_2d: true,
+            presenter: true,
+            buzzer: true,
+            reverse_feed: true,
+            black_mark: true,
+            raster_images: false,
+            max_reverse_feed: u8::MAX,
+            max_reverse_feed_lines: u8::MAX,
+            max_cut_feed: u8::MAX,
+        }
+    }
+
+    /// Why a parameter of `command` is beyond the limits of the printer, e.g.
+    /// [`max_reverse_feed`](Profile::max_reverse_feed), if it is.
+    pub(crate) fn exceeded_limit(&self, command: &Command) -> Option<String> {
+        match *command {
+            Command::PrintAndReverseFeed { unit, amount } => {
+                let max = self.max_reverse_feed_of(unit);
+                (amount > max).then(|| format!("reverse feed of {amount} is above {max}"))
+            }
+            Command::FeedAndCutPaper { amount, .. } if amount > self.max_cut_feed => Some(format!(
+                "cut feed of {amount} is above {}",
+                self.max_cut_feed
+            )),
+            _ => None,
+        }
+    }
+
+    /// `command` with its parameters clamped to the limits of the printer, see
+    /// [`LimitPolicy::Clamp`](crate::LimitPolicy::Clamp).
+    pub(crate) fn clamp(&self, command: Command) -> Command {
+        match command {
+            Command::PrintAndReverseFeed { unit, amount } => Command::PrintAndReverseFeed {
+                unit,
+                amount: amount.min(self.max_reverse_feed_of(unit)),
+            },
+            Command::FeedAndCutPaper { cut_type, amount } => Command::FeedAndCutPaper {
+                cut_type,
+                amount: amount.min(self.max_cut_feed),
+            },
+            command => command,
+        }
+    }
+
+    fn max_reverse_feed_of(&self, unit: FeedUnit) -> u8 {
+        match unit {
+            FeedUnit::Inches => self.max_reverse_feed,
+            FeedUnit::Lines => self.max_reverse_feed_lines,
+        }
+    }
+
+    /// Whether `command` is supported by the printer, e.g. partial cuts only if [`partial_cut`](Profile::partial_cut)
+    /// is set, and the presenter commands only if [`presenter`](Profile::presenter) is set.
+    ///
+    /// Commands which aren't specific to any printer, including [`Command::Raw`], are supported by every printer.
+    pub fn supports(&self, command: &Command) -> bool {
+        match command {
+            Command::CutPaper(CutType::PartialCut)
+            | Command::FeedAndCutPaper {
+                cut_type: CutType::PartialCut,
+                ..
+            } => self.partial_cut,
+            Command::PresenterLoop(_)
+            | Command::PresentTicket(_)
+            | Command::EjectTicket
+            | Command::RetractTicket
+            | Command::RetractOnTimeout(_) => self.presenter,
+            Command::Beep { .. } => self.buzzer,
+            Command::PrintAndReverseFeed { .. } => self.reverse_feed,
+            Command::FeedToMark => self.black_mark,
+            Command::Barcode { .. } | Command::BarcodeStyle { .. } => self.barcodes,
+            Command::QrCode { .. } => self.codes_2d,
+            _ => true,
+        }
+    }
+
+    /// Distance between the print head and the cutter in dot lines, rounded up.
+    pub fn head_to_cutter_dots(&self) -> u16 {
+        let dots = self.head_to_cutter_mm * self.dpi as f32 / 25.4;
+        // f32::ceil() isn't available without std
+        let truncated = dots as u16;
+        if (truncated as f32) < dots {
+            truncated + 1
+        } else {
+            truncated
+        }
+    }
+
+    /// Serialize the profile into JSON, read back by [`from_json()`](Profile::from_json()).
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        json::object([
+            ("name", self.name.as_ref().into()),
+            ("dots_per_line", self.dots_per_line.into()),
+            ("dpi", self.dpi.into()),
+            ("head_to_cutter_mm", self.head_to_cutter_mm.into()),
+            ("partial_cut", self.partial_cut.into()),
+            ("barcodes", self.barcodes.into()),
+            ("codes_2d", self.codes_2d.into()),
+            ("presenter", self.presenter.into()),
+            ("buzzer", self.buzzer.into()),
+            ("reverse_feed", self.reverse_feed.into()),
+            ("black_mark", self.black_mark.into()),
+            ("raster_images", self.raster_images.into()),
+            ("max_reverse_feed", self.max_reverse_feed.into()),
+            ("max_reverse_feed_lines", self.max_reverse_feed_lines.into()),
+            ("max_cut_feed", self.max_cut_feed.into()),
+        ])
+        .to_string()
+    }
+
+    /// Deserialize a profile from a JSON object of its fields, e.g. to describe a printer the crate doesn't
+    /// support yet without waiting for a release.
+    ///
+    /// The optional `base` member names a supported [`Model`] whose profile fills in the missing fields, so an
+    /// OEM-rebadged printer only overrides what differs. Without `base`, every field is required except the limits,
+    /// which default to 255, and `buzzer`, `reverse_feed`, `black_mark` and `raster_images`, which default to false.
+    ///
+    /// Returns [`Error::InvalidProfile`] if the JSON is malformed, the base model is unknown or any field is
+    /// missing or invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Model, Profile};
+    /// let profile =
+    ///     Profile::from_json(r#"{"base": "TG2480H", "name": "TG2480H OEM", "partial_cut": true}"#).unwrap();
+    /// assert_eq!(profile.dots_per_line, Profile::for_model(Model::Tg2480h).dots_per_line);
+    /// assert!(profile.partial_cut);
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self, Error> {
+        Self::from_value(json::parse(text).map_err(Error::InvalidProfile)?)
+    }
+
+    /// Deserialize a profile from a TOML table of its fields, like [`from_json()`](Profile::from_json()).
+    ///
+    /// Returns [`Error::InvalidProfile`] if the TOML is malformed, the base model is unknown or any field is
+    /// missing or invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Model, Profile};
+    /// let profile = Profile::from_toml(
+    ///     r#"
+    /// base = "TG2480H"
+    /// name = "TG2480H OEM"
+    /// partial_cut = true
+    /// "#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(profile.dots_per_line, Profile::for_model(Model::Tg2480h).dots_per_line);
+    /// assert!(profile.partial_cut);
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self, Error> {
+        // the fields are read from the JSON value of the table, so both formats are validated alike
+        fn value(toml: toml::Value) -> Value {
+            match toml {
+                toml::Value::String(string) => Value::String(string),
+                toml::Value::Integer(int) => Value::Number(int as f64),
+                toml::Value::Float(float) => Value::Number(float),
+                toml::Value::Boolean(bool) => Value::Bool(bool),
+                toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+                toml::Value::Array(array) => Value::Array(array.into_iter().map(value).collect()),
+                toml::Value::Table(table) => table_value(table),
+            }
+        }
+        fn table_value(table: toml::Table) -> Value {
+            Value::Object(
+                table
+                    .into_iter()
+                    .map(|(key, field)| (key, value(field)))
+                    .collect(),
+            )
+        }
+
+        let table = text
+            .parse::<toml::Table>()
+            .map_err(|e| Error::InvalidProfile(e.message().to_string()))?;
+        Self::from_value(table_value(table))
+    }
+
+    #[cfg(feature = "json")]
+    fn from_value(value: Value) -> Result<Self, Error> {
+        if !matches!(value, Value::Object(_)) {
+            return Err(Error::InvalidProfile("expected an object".to_string()));
+        }
+        let base = match value.get("base") {
+            None | Some(Value::Null) => None,
+            Some(base) => {
+                let name = base
+                    .as_str()
+                    .ok_or_else(|| Error::InvalidProfile("invalid field base".to_string()))?;
+                let model = Model::from_name(name)
+                    .ok_or_else(|| Error::InvalidProfile(format!("unknown base model {name}")))?;
+                Some(Self::for_model(model))
+            }
+        };
+
+        // a field of the object, or of the base profile if it's missing
+        fn field<T>(
+            value: &Value,
+            key: &str,
+            base: Option<T>,
+            parse: impl Fn(&Value) -> Option<T>,
+        ) -> Result<T, Error> {
+            match value.get(key) {
+                Some(field) => parse(field)
+                    .ok_or_else(|| Error::InvalidProfile(format!("invalid field {key}"))),
+                None => base.ok_or_else(|| Error::InvalidProfile(format!("missing field {key}"))),
+            }
+        }
+        let int = |field: &Value| field.as_i64().and_then(|value| u16::try_from(value).ok());
+        let limit = |field: &Value| field.as_i64().and_then(|value| u8::try_from(value).ok());
+        let base = base.as_ref();
+
+        Ok(Self {
+            name: field(
+                &value,
+                "name",
+                base.map(|base| base.name.clone()),
+                |field| field.as_str().map(|name| Cow::Owned(name.to_string())),
+            )?,
+            dots_per_line: field(
+                &value,
+                "dots_per_line",
+                base.map(|base| base.dots_per_line),
+                int,
+            )?,
+            dpi: field(&value, "dpi", base.map(|base| base.dpi), |field| {
+                int(field).filter(|dpi| *dpi > 0)
+            })?,
+            head_to_cutter_mm: field(
+                &value,
+                "head_to_cutter_mm",
+                base.map(|base| base.head_to_cutter_mm),
+                |field| field.as_f64().filter(|mm| *mm >= 0.0).map(|mm| mm as f32),
+            )?,
+            partial_cut: field(
+                &value,
+                "partial_cut",
+                base.map(|base| base.partial_cut),
+                Value::as_bool,
+            )?,
+            barcodes: field(
+                &value,
+                "barcodes",
+                base.map(|base| base.barcodes),
+                Value::as_bool,
+            )?,
+            codes_2d: field(
+                &value,
+                "codes_2d",
+                base.map(|base| base.codes_2d),
+                Value::as_bool,
+            )?,
+            presenter: field(
+                &value,
+                "presenter",
+                base.map(|base| base.presenter),
+                Value::as_bool,
+            )?,
+            buzzer: field(
+                &value,
+                "buzzer",
+                Some(base.is_some_and(|base| base.buzzer)),
+                Value::as_bool,
+            )?,
+            reverse_feed: field(
+                &value,
+                "reverse_feed",
+                Some(base.is_some_and(|base| base.reverse_feed)),
+                Value::as_bool,
+            )?,
+            black_mark: field(
+                &value,
+                "black_mark",
+                Some(base.is_some_and(|base| base.black_mark)),
+                Value::as_bool,
+            )?,
+            raster_images: field(
+                &value,
+                "raster_images",
+                Some(base.is_some_and(|base| base.raster_images)),
+                Value::as_bool,
+            )?,
+            max_reverse_feed: field(
+                &value,
+                "max_reverse_feed",
+                Some(base.map_or(u8::MAX, |base| base.max_reverse_feed)),
+                limit,
+            )?,
+            max_reverse_feed_lines: field(
+                &value,
+                "max_reverse_feed_lines",
+                Some(base.map_or(u8::MAX, |base| base.max_reverse_feed_lines)),
+                limit,
+            )?,
+            max_cut_feed: field(
+                &value,
+                "max_cut_feed",
+                Some(base.map_or(u8::MAX, |base| base.max_cut_feed)),
+                limit,
+            )?,
+        })
+    }
+
+    /// Load a profile from the JSON file at `path`, see [`from_json()`](Profile::from_json()), or with the
+    /// `toml` feature, from the TOML file if `path` has the `toml` extension, see `from_toml()`.
+    ///
+    /// Returns [`Error::ProfileFile`] if the file can't be read.
+    #[cfg(all(feature = "std", feature = "json"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| Error::ProfileFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        #[cfg(feature = "toml")]
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            return Self::from_toml(&text);
+        }
+
+        Self::from_json(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_model() {
+        let profile = Profile::for_model(Model::Vkp80);
+        assert_eq!(profile.name, "VKP80");
+        assert_eq!(profile.dpi, 200);
+        assert!(profile.presenter);
+        assert!(!profile.partial_cut);
+        assert!(Profile::for_model(Model::Tl60).partial_cut);
+
+        let oem = Profile {
+            partial_cut: true,
+            ..profile
+        };
+        assert!(oem.supports(&Command::CutPaper(CutType::PartialCut)));
+        assert!(oem.supports(&Command::EjectTicket));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json() {
+        let profile = Profile {
+            name: "TG2480H OEM".into(),
+            head_to_cutter_mm: 12.3,
+            partial_cut: true,
+            ..Profile::for_model(Model::Tg2480h)
+        };
+        let json = profile.to_json();
+        assert_eq!(
+            json,
+            r#"{"name":"TG2480H OEM","dots_per_line":576,"dpi":203,"head_to_cutter_mm":12.3,"partial_cut":true,"barcodes":true,"codes_2d":true,"presenter":false,"buzzer":false,"reverse_feed":true,"black_mark":true,"raster_images":false,"max_reverse_feed":48,"max_reverse_feed_lines":2,"max_cut_feed":255}"#
+        );
+        assert_eq!(Profile::from_json(&json).unwrap(), profile);
+        assert_eq!(
+            Profile::from_json(r#"{"base":"tg2480-h","name":"TG2480H OEM","head_to_cutter_mm":12.3,"partial_cut":true}"#)
+                .unwrap(),
+            profile
+        );
+
+        // the raster images are only used if the firmware is known to print them
+        let json = r#"{"name":"X","dots_per_line":384,"dpi":203,"head_to_cutter_mm":10,"partial_cut":false,"barcodes":false,"codes_2d":false,"presenter":false}"#;
+        assert!(!Profile::from_json(json).unwrap().raster_images);
+        assert!(
+            Profile::from_json(r#"{"base":"tg2480-h","raster_images":true}"#)
+                .unwrap()
+                .raster_images
+        );
+
+        assert!(matches!(
+            Profile::from_json(r#"{"name":"X"}"#),
+            Err(Error::InvalidProfile(message)) if message == "missing field dots_per_line"
+        ));
+        assert!(matches!(
+            Profile::from_json(r#"{"base":"TG2480H","dpi":0}"#),
+            Err(Error::InvalidProfile(message)) if message == "invalid field dpi"
+        ));
+        assert!(matches!(
+            Profile::from_json(r#"{"base":"KPM180H"}"#),
+            Err(Error::InvalidProfile(message)) if message == "unknown base model KPM180H"
+        ));
+        assert!(matches!(
+            Profile::from_json("[]"),
+            Err(Error::InvalidProfile(_))
+        ));
+        #[cfg(feature = "std")]
+        assert!(matches!(
+            Profile::load("/nonexistent/profile.json"),
+            Err(Error::ProfileFile { .. })
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml() {
+        let profile = Profile {
+            name: "TG2480H OEM".into(),
+            partial_cut: true,
+            max_cut_feed: 12,
+            ..Profile::for_model(Model::Tg2480h)
+        };
+        let toml = r#"
+base = "tg2480-h"
+name = "TG2480H OEM"
+partial_cut = true
+max_cut_feed = 12
+"#;
+        assert_eq!(Profile::from_toml(toml).unwrap(), profile);
+        let toml = r#"
+name = "X"
+dots_per_line = 384
+dpi = 203
+head_to_cutter_mm = 10.5
+partial_cut = false
+barcodes = false
+codes_2d = false
+presenter = false
+"#;
+        let custom = Profile::from_toml(toml).unwrap();
+        assert_eq!(custom.head_to_cutter_mm, 10.5);
+        assert_eq!(custom.max_reverse_feed, u8::MAX);
+
+        assert!(matches!(
+            Profile::from_toml(r#"name = "X""#),
+            Err(Error::InvalidProfile(e)) if e == "missing field dots_per_line"
+        ));
+        assert!(matches!(
+            Profile::from_toml("base = \"TG2480H\"\nmax_cut_feed = 256"),
+            Err(Error::InvalidProfile(e)) if e == "invalid field max_cut_feed"
+        ));
+        assert!(matches!(
+            Profile::from_toml("base = "),
+            Err(Error::InvalidProfile(_))
+        ));
+
+        #[cfg(feature = "std")]
+        {
+            let path = std::env::temp_dir().join(format!(
+                "custom-printer-profile-{}.toml",
+                std::process::id()
+            ));
+            std::fs::write(
+                &path,
+                "base = \"TG2480H\"\nname = \"TG2480H OEM\"\npartial_cut = true\nmax_cut_feed = 12",
+            )
+            .unwrap();
+            let loaded = Profile::load(&path);
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.unwrap(), profile);
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! Python module `custom_printer` of the printer for kiosk applications written in Python, built on PyO3.
+//!
+//! The constructing methods of `Printer` return the printer, so they are chained like [`CommandBuilder`]:
+//!
+//! ```python
+//! from custom_printer import Justification, Printer
+//!
+//! with Printer("/dev/usb/lp0", model="TG2480H") as printer:
+//!     printer.justification(Justification.Center).text("Hello").print().cut().run()
+//! ```
+//!
+//! The errors of the crate are raised as `PrinterError`, and invalid arguments, e.g. an unknown model, as
+//! `ValueError`.
+
+use std::path::PathBuf;
+
+use pyo3::{create_exception, exceptions::PyException, exceptions::PyValueError, prelude::*};
+
+use crate::{CommandBuilder, CustomPrinter, Error, FeedUnit, Model};
+
+create_exception!(
+    custom_printer,
+    PrinterError,
+    PyException,
+    "Error of the printer or of the commands, with the message of the crate."
+);
+
+fn printer_error(error: Error) -> PyErr {
+    PrinterError::new_err(error.to_string())
+}
+
+fn closed() -> PyErr {
+    PrinterError::new_err("the printer is closed")
+}
+
+/// Python enums of the parameters, converted into the enums of the crate of the same names.
+macro_rules! enums {
+    ($($(#[$attr:meta])* $name:ident { $($variant:ident),+ $(,)? })+) => {
+        $(
+            $(#[$attr])*
+            #[pyclass(module = "custom_printer", eq, eq_int, frozen, from_py_object)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub enum $name {
+                $(
+                    #[allow(missing_docs)]
+                    $variant
+                ),+
+            }
+
+            impl From<$name> for crate::$name {
+                fn from(value: $name) -> Self {
+                    match value {
+                        $($name::$variant => Self::$variant),+
+                    }
+                }
+            }
+        )+
+    };
+}
+
+enums! {
+    /// Density of bit images, see [`crate::BitImageMode`]
+    BitImageMode {
+        Dots8SingleDensity,
+        Dots8DoubleDensity,
+        Dots24SingleDensity,
+        Dots24DoubleDensity,
+    }
+    /// Alignment of the printed data, see [`crate::Justification`]
+    Justification { Left, Center, Right }
+    /// Symbology of barcodes, see [`crate::BarcodeSystem`]
+    BarcodeSystem { UpcA, UpcE, Ean13, Ean8, Code39, Itf, Codabar, Code93, Code128 }
+    /// Error correction level of QR codes, see [`crate::QrErrorCorrection`]
+    QrErrorCorrection { Low, Medium, Quartile, High }
+    /// Pin of the drawer kick-out connector, see [`crate::DrawerPin`]
+    DrawerPin { Pin2, Pin5 }
+}
+
+/// Status of the printer returned by `Printer.status()`, see [`crate::Status`].
+#[pyclass(module = "custom_printer", get_all, frozen, skip_from_py_object)]
+#[derive(Debug, Clone, Copy)]
+pub struct Status {
+    /// The paper has run out
+    pub paper_end: bool,
+    /// The paper is near its end
+    pub paper_near_end: bool,
+    /// A ticket is present in the output
+    pub ticket_present: bool,
+    /// The cover is open
+    pub cover_open: bool,
+    /// The print head is overheated
+    pub head_overheated: bool,
+    /// The autocutter has an error
+    pub cutter_error: bool,
+    /// The printer is idle
+    pub idle: bool,
+}
+
+/// Printer connected to a device node, or without any device if `dev` is `None`, see [`CustomPrinter`].
+///
+/// The printer is used as a context manager closing it on exit. Its methods raise `PrinterError` once it's
+/// closed.
+#[pyclass(module = "custom_printer", unsendable)]
+pub struct Printer {
+    printer: Option<CustomPrinter>,
+}
+
+type Chained<'py> = PyResult<PyRefMut<'py, Printer>>;
+
+impl Printer {
+    fn printer(&mut self) -> PyResult<&mut CustomPrinter> {
+        self.printer.as_mut().ok_or_else(closed)
+    }
+
+    /// Construct commands by `f` and return the printer to chain the next ones.
+    fn chain<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        f: impl FnOnce(&mut CustomPrinter) -> Result<(), Error>,
+    ) -> Chained<'py> {
+        f(slf.printer()?).map_err(printer_error)?;
+        Ok(slf)
+    }
+}
+
+#[pymethods]
+impl Printer {
+    /// Open the printer connected to `dev`, of the `model` named like `"TG2480H"` if given, which enables
+    /// the checks of the commands it lacks.
+    #[new]
+    #[pyo3(signature = (dev = None, model = None))]
+    fn new(dev: Option<PathBuf>, model: Option<&str>) -> PyResult<Self> {
+        let model = model
+            .map(|name| {
+                Model::from_name(name)
+                    .ok_or_else(|| PyValueError::new_err(format!("unknown model {name}")))
+            })
+            .transpose()?;
+        let mut printer = match dev {
+            Some(dev) => CustomPrinter::new(dev).map_err(printer_error)?,
+            None => CustomPrinter::detached(),
+        };
+        printer.set_model(model);
+
+        Ok(Self {
+            printer: Some(printer),
+        })
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_exc))]
+    fn __exit__(&mut self, _exc: &Bound<'_, PyAny>) {
+        self.close();
+    }
+
+    /// Close the printer, discarding the pending commands.
+    fn close(&mut self) {
+        if let Some(mut printer) = self.printer.take() {
+            printer.discard();
+        }
+    }
+
+    /// Reset the printer to its power-on settings.
+    fn initialize(slf: PyRefMut<'_, Self>) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            printer.initialize();
+            Ok(())
+        })
+    }
+
+    /// Append ASCII text, or raise `ValueError` if it isn't ASCII, see `raw()` for other code pages.
+    fn text<'py>(slf: PyRefMut<'py, Self>, text: &str) -> Chained<'py> {
+        if !text.is_ascii() {
+            return Err(PyValueError::new_err("text isn't ASCII"));
+        }
+        Self::chain(slf, |printer| {
+            printer.raw(text.as_bytes());
+            Ok(())
+        })
+    }
+
+    /// Append bytes as they are, e.g. text encoded in the code page of the printer.
+    fn raw<'py>(slf: PyRefMut<'py, Self>, bytes: &[u8]) -> Chained<'py> {
+        Self::chain(slf, |printer| {
+            printer.raw(bytes);
+            Ok(())
+        })
+    }
+
+    /// Print the data in the print buffer and feed the paper by `lines` lines, or by one line if `lines` is 0.
+    #[pyo3(signature = (lines = 0))]
+    fn print(slf: PyRefMut<'_, Self>, lines: u8) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            match lines {
+                0 => printer.print(),
+                lines => printer.print_and_feed_paper(FeedUnit::Lines, lines),
+            };
+            Ok(())
+        })
+    }
+
+    /// Set the bold text.
+    fn bold(slf: PyRefMut<'_, Self>, enabled: bool) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            printer.bold(enabled);
+            Ok(())
+        })
+    }
+
+    /// Set the underlined text.
+    fn underline(slf: PyRefMut<'_, Self>, enabled: bool) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            printer.underline(enabled);
+            Ok(())
+        })
+    }
+
+    /// Set the width and height multipliers of the characters, see [`CommandBuilder::character_size()`].
+    fn character_size(slf: PyRefMut<'_, Self>, width: u8, height: u8) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            printer.character_size(width, height);
+            Ok(())
+        })
+    }
+
+    /// Set the alignment of the printed data.
+    fn justification(slf: PyRefMut<'_, Self>, justification: Justification) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            printer.justification(justification.into());
+            Ok(())
+        })
+    }
+
+    /// Select the code page of the text.
+    fn code_page(slf: PyRefMut<'_, Self>, page: u8) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            printer.code_page(page);
+            Ok(())
+        })
+    }
+
+    /// Append a barcode of `data`, see [`CommandBuilder::barcode()`].
+    fn barcode<'py>(slf: PyRefMut<'py, Self>, system: BarcodeSystem, data: &[u8]) -> Chained<'py> {
+        Self::chain(slf, |printer| {
+            printer.barcode(system.into(), data).map(drop)
+        })
+    }
+
+    /// Append a QR code of `data`, see [`CommandBuilder::qr_code()`].
+    #[pyo3(signature = (data, module_size = 6, error_correction = QrErrorCorrection::Medium))]
+    fn qr_code<'py>(
+        slf: PyRefMut<'py, Self>,
+        data: &[u8],
+        module_size: u8,
+        error_correction: QrErrorCorrection,
+    ) -> Chained<'py> {
+        Self::chain(slf, |printer| {
+            printer
+                .qr_code(data, module_size, error_correction.into())
+                .map(drop)
+        })
+    }
+
+    /// Append the bit image of the 1 bpp `bitmap` of `width` x `height` dots, see [`CommandBuilder::bitmap()`].
+    #[pyo3(signature = (width, height, bitmap, mode = BitImageMode::Dots24DoubleDensity))]
+    fn bitmap<'py>(
+        slf: PyRefMut<'py, Self>,
+        width: usize,
+        height: usize,
+        bitmap: &[u8],
+        mode: BitImageMode,
+    ) -> Chained<'py> {
+        Self::chain(slf, |printer| {
+            printer.bitmap(width, height, bitmap, mode.into()).map(drop)
+        })
+    }
+
+    /// Append the bit image from the image file `path`, see [`CommandBuilder::bit_image()`].
+    #[cfg(feature = "image")]
+    #[pyo3(signature = (path, mode = BitImageMode::Dots24DoubleDensity))]
+    fn image(slf: PyRefMut<'_, Self>, path: PathBuf, mode: BitImageMode) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            printer.bit_image(path, mode.into()).map(drop)
+        })
+    }
+
+    /// Feed the paper by `feed` units past the cut position and cut it, partially if `partial` is true.
+    #[pyo3(signature = (partial = false, feed = 0))]
+    fn cut(slf: PyRefMut<'_, Self>, partial: bool, feed: u8) -> Chained<'_> {
+        let cut_type = match partial {
+            false => crate::CutType::TotalCut,
+            true => crate::CutType::PartialCut,
+        };
+        Self::chain(slf, |printer| {
+            printer.feed_and_cut_paper(cut_type, feed);
+            Ok(())
+        })
+    }
+
+    /// Pulse the pin of the drawer kick-out connector, see [`CommandBuilder::open_drawer()`].
+    #[pyo3(signature = (pin = DrawerPin::Pin2, on_ms = 100, off_ms = 100))]
+    fn open_drawer(
+        slf: PyRefMut<'_, Self>,
+        pin: DrawerPin,
+        on_ms: u16,
+        off_ms: u16,
+    ) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            printer.open_drawer(pin.into(), on_ms, off_ms);
+            Ok(())
+        })
+    }
+
+    /// Sound the buzzer `count` times, see [`CommandBuilder::beep()`].
+    fn beep(slf: PyRefMut<'_, Self>, count: u8, duration_ms: u16) -> Chained<'_> {
+        Self::chain(slf, |printer| printer.beep(count, duration_ms).map(drop))
+    }
+
+    /// Send the pending commands to the printer, see [`CustomPrinter::run()`].
+    fn run(slf: PyRefMut<'_, Self>) -> Chained<'_> {
+        Self::chain(slf, |printer| printer.run().map(drop))
+    }
+
+    /// Discard the pending commands.
+    fn discard(slf: PyRefMut<'_, Self>) -> Chained<'_> {
+        Self::chain(slf, |printer| {
+            printer.discard();
+            Ok(())
+        })
+    }
+
+    /// The encoded pending commands, e.g. to send them by other means.
+    fn to_bytes(&self) -> PyResult<Vec<u8>> {
+        Ok(self.printer.as_ref().ok_or_else(closed)?.to_bytes())
+    }
+
+    /// Query the status of the printer, see [`CustomPrinter::status()`].
+    fn status(&mut self) -> PyResult<Status> {
+        let status = self.printer()?.status().map_err(printer_error)?;
+        Ok(Status {
+            paper_end: status.paper_end(),
+            paper_near_end: status.paper_near_end(),
+            ticket_present: status.ticket_present(),
+            cover_open: status.cover_open(),
+            head_overheated: status.head_overheated(),
+            cutter_error: status.cutter_error(),
+            idle: status.is_idle(),
+        })
+    }
+}
+
+/// The `custom_printer` module.
+#[pymodule]
+fn custom_printer(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("PrinterError", m.py().get_type::<PrinterError>())?;
+    m.add_class::<Printer>()?;
+    m.add_class::<Status>()?;
+    m.add_class::<BitImageMode>()?;
+    m.add_class::<Justification>()?;
+    m.add_class::<BarcodeSystem>()?;
+    m.add_class::<QrErrorCorrection>()?;
+    m.add_class::<DrawerPin>()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::PyDict;
+
+    use super::*;
+
+    #[test]
+    fn test_python() {
+        Python::initialize();
+        Python::attach(|py| {
+            py.import("sys")
+                .and_then(|sys| sys.getattr("modules"))
+                .and_then(|modules| {
+                    modules.set_item("custom_printer", pyo3::wrap_pymodule!(custom_printer)(py))
+                })
+                .unwrap();
+            py.run(
+                cr#"
+from custom_printer import BitImageMode, Justification, Printer, PrinterError
+
+printer = Printer()
+printer.justification(Justification.Center).bold(True).text("Hi").print(3).cut(partial=True)
+assert printer.to_bytes() == b"\x1ba\x01\x1bE\x01Hi\x1bd\x03\x1dVB\x00", printer.to_bytes()
+printer.discard().bitmap(8, 8, bytes([0xFF] * 8), BitImageMode.Dots8SingleDensity)
+try:
+    # the bit image isn't printed
+    printer.run()
+    raise AssertionError("run() succeeded")
+except PrinterError:
+    pass
+try:
+    printer.text("é")
+    raise AssertionError("text() succeeded")
+except ValueError:
+    pass
+try:
+    printer.status()
+    raise AssertionError("status() succeeded")
+except PrinterError:
+    pass
+printer.close()
+
+with Printer("/dev/null", model="TG2480H") as printer:
+    printer.text("Hi").print().cut().run()
+    try:
+        printer.beep(1, 100)
+        raise AssertionError("the TG2480H beeped")
+    except PrinterError:
+        pass
+try:
+    printer.print()
+    raise AssertionError("the printer isn't closed")
+except PrinterError:
+    pass
+try:
+    Printer(model="TG0")
+    raise AssertionError("unknown model")
+except ValueError:
+    pass
+"#,
+                None,
+                Some(&PyDict::new(py)),
+            )
+            .unwrap();
+        });
+    }
+}
@@ -0,0 +1,155 @@
+//! Queue of jobs waiting to be printed.
+
+use alloc::collections::BinaryHeap;
+use core::cmp::{Ordering, Reverse};
+
+use crate::Job;
+
+/// Priorities of the jobs in a [`JobQueue`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Low priority, e.g. marketing coupons
+    Low,
+    /// Normal priority
+    #[default]
+    Normal,
+    /// High priority, e.g. payment receipts
+    High,
+}
+
+/// Queue of [`Job`]s waiting to be printed, taken by their [`Priority`] and in the order they were pushed within
+/// the same priority.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{CommandBuilder, Job, JobBuilder, JobQueue, Priority};
+/// let coupon = JobBuilder::new().raw(b"10% off").print().build();
+/// let receipt = JobBuilder::new().raw(b"Paid 4.30").print().build();
+///
+/// let mut queue = JobQueue::new();
+/// queue.push(coupon, Priority::Low);
+/// queue.push(receipt, Priority::High);
+///
+/// # #[cfg(feature = "std")]
+/// # {
+/// # use custom_printer::CustomPrinter;
+/// let mut printer = CustomPrinter::new("/dev/null").unwrap();
+/// while let Some(job) = queue.pop() {
+///     // the receipt is printed before the coupon
+///     printer.print_job(&job).unwrap();
+/// }
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct JobQueue {
+    entries: BinaryHeap<Entry>,
+    // sequence number of the next pushed job, for the order within a priority
+    next: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    priority: Priority,
+    seq: Reverse<u64>,
+    job: Job,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.priority, self.seq).cmp(&(other.priority, other.seq))
+    }
+}
+
+impl JobQueue {
+    /// Create an empty [`JobQueue`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Push `job` with `priority` to the queue, behind the jobs of the same priority.
+    pub fn push(&mut self, job: Job, priority: Priority) -> &mut Self {
+        self.entries.push(Entry {
+            priority,
+            seq: Reverse(self.next),
+            job,
+        });
+        self.next += 1;
+
+        self
+    }
+
+    /// Take the next job to be printed, i.e. the first pushed job of the highest priority.
+    pub fn pop(&mut self) -> Option<Job> {
+        self.entries.pop().map(|entry| entry.job)
+    }
+
+    /// The next job to be printed and its priority, without taking it, see [`pop()`](JobQueue::pop()).
+    pub fn peek(&self) -> Option<(&Job, Priority)> {
+        self.entries
+            .peek()
+            .map(|entry| (&entry.job, entry.priority))
+    }
+
+    /// Number of the jobs in the queue.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the queue has no jobs.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all the jobs of the queue, e.g. when the shop is closing.
+    pub fn clear(&mut self) -> &mut Self {
+        self.entries.clear();
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandBuilder, JobBuilder};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_priority() {
+        let job = |n: u8| JobBuilder::new().raw(&[n]).build();
+        let mut queue = JobQueue::new();
+        queue
+            .push(job(1), Priority::Low)
+            .push(job(2), Priority::Normal)
+            .push(job(3), Priority::High)
+            .push(job(4), Priority::Normal)
+            .push(job(5), Priority::Low)
+            .push(job(6), Priority::High);
+        assert_eq!(queue.len(), 6);
+        assert_eq!(
+            queue.peek().map(|(_, priority)| priority),
+            Some(Priority::High)
+        );
+
+        let order: Vec<_> = core::iter::from_fn(|| queue.pop())
+            .map(|job| job.to_bytes()[0])
+            .collect();
+        assert_eq!(order, [3, 6, 2, 4, 1, 5]);
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,1397 @@
+//! Receipts described declaratively by the [`receipt!`](crate::receipt!) macro, or composed of the [`Element`]s of a
+//! [`Receipt`].
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+mod barcode;
+mod canvas;
+mod date;
+mod html;
+#[cfg(feature = "json")]
+mod json;
+mod layout;
+mod list;
+mod markdown;
+mod money;
+mod paragraph;
+mod payment;
+mod sale;
+mod separator;
+mod style;
+mod table;
+mod text;
+mod text_box;
+mod watermark;
+
+pub use canvas::{Canvas, Region};
+pub use date::{DateFormat, DateHeader};
+pub use html::render_html;
+#[cfg(feature = "json")]
+pub use json::RECEIPT_FORMAT_VERSION;
+pub use layout::{Layout, LayoutMode, LayoutNode, Track};
+pub use list::{List, ListItem, ListStyle};
+pub use markdown::render_markdown;
+pub use money::{MoneyFormat, SymbolPlacement};
+pub use paragraph::Paragraph;
+pub use sale::{SaleItem, SaleLabels, TaxLine, Totals};
+pub use separator::{Separator, SeparatorStyle};
+pub use style::RenderStyle;
+pub use table::{Column, ColumnWidth, HeaderStyle, Table, TableMode, TwoColumns};
+pub use text::{Span, TextStyle};
+pub use text_box::{BorderStyle, TextBox};
+pub use watermark::{Watermark, WatermarkImage};
+
+use crate::{
+    BarcodeSystem, BitImageMode, Command, CommandBuilder, CutType, Error, FeedUnit, HriFont,
+    HriPosition, Job, JobBuilder, Justification, Length, Profile, QrErrorCorrection, Sequence,
+};
+
+/// Width of the characters of font A in dots.
+pub(crate) const FONT_A_WIDTH: u16 = 12;
+
+/// Space left blank for the signatures of [`Receipt::signature()`].
+const SIGNATURE_SPACE: Length = Length::mm(15.0);
+
+/// Describe a receipt declaratively and construct its commands on a [`CommandBuilder`](crate::CommandBuilder),
+/// e.g. a [`CustomPrinter`](crate::CustomPrinter) or a [`JobBuilder`](crate::JobBuilder).
+///
+/// The receipt is a list of items, each terminated by `;`:
+///
+/// * `initialize;` initializes the printer
+/// * `header text;` prints the line `text` followed by a blank line
+/// * `line text;` prints the line `text`
+/// * `lines texts;` prints each line of `texts`, any iterator of strings
+/// * `image path, mode;` prints the bit image from the image file `path` (`image` feature)
+/// * `bitmap width, height, bitmap, mode;` prints the bit image of a 1 bpp `bitmap`
+/// * `barcode system, data;` prints `data` as a barcode of `system`
+/// * `feed lines;` prints and feeds the paper by `lines`
+/// * `raw bytes;` appends raw bytes
+/// * `cut;` or `cut partial;` feeds the paper to the cut position and cuts it totally or partially
+///
+/// Text is sent as the bytes of the string, so it should be in the code page of the printer, e.g. ASCII.
+///
+/// The macro evaluates to `Result<&mut B, Error>` of the builder, as printing images and barcodes may fail.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{receipt, JobBuilder};
+/// let items = ["Coffee      2.50", "Croissant   1.80"];
+/// let mut builder = JobBuilder::new();
+/// receipt!(&mut builder => {
+///     initialize;
+///     header "CAFE ROMA";
+///     lines items;
+///     line format!("Total       {:.2}", 4.30);
+///     cut partial;
+/// })
+/// .unwrap();
+/// let job = builder.build();
+/// assert_eq!(job.commands().len(), 11);
+/// ```
+#[macro_export]
+macro_rules! receipt {
+    ($builder:expr => { $($items:tt)* }) => {{
+        let builder = $builder;
+        let result = (|| -> ::core::result::Result<(), $crate::Error> {
+            $crate::receipt!(@items builder; $($items)*);
+            Ok(())
+        })();
+
+        result.map(|()| builder)
+    }};
+
+    (@items $b:ident;) => {};
+    (@items $b:ident; initialize; $($rest:tt)*) => {
+        $crate::CommandBuilder::initialize($b);
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+    (@items $b:ident; header $text:expr; $($rest:tt)*) => {
+        $crate::receipt!(@items $b; line $text; feed 1; $($rest)*);
+    };
+    (@items $b:ident; line $text:expr; $($rest:tt)*) => {
+        $crate::CommandBuilder::raw($b, ::core::convert::AsRef::<str>::as_ref(&$text).as_bytes());
+        $crate::CommandBuilder::print($b);
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+    (@items $b:ident; lines $texts:expr; $($rest:tt)*) => {
+        for text in $texts {
+            $crate::receipt!(@items $b; line text;);
+        }
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+    (@items $b:ident; image $path:expr, $mode:expr; $($rest:tt)*) => {
+        $crate::CommandBuilder::bit_image($b, $path, $mode)?;
+        $crate::CommandBuilder::print($b);
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+    (@items $b:ident; bitmap $width:expr, $height:expr, $bitmap:expr, $mode:expr; $($rest:tt)*) => {
+        $crate::CommandBuilder::bitmap($b, $width, $height, $bitmap, $mode)?;
+        $crate::CommandBuilder::print($b);
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+    (@items $b:ident; barcode $system:expr, $data:expr; $($rest:tt)*) => {
+        $crate::CommandBuilder::barcode($b, $system, $data)?;
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+    (@items $b:ident; feed $lines:expr; $($rest:tt)*) => {
+        $crate::CommandBuilder::print_and_feed_paper($b, $crate::FeedUnit::Lines, $lines);
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+    (@items $b:ident; raw $bytes:expr; $($rest:tt)*) => {
+        $crate::CommandBuilder::raw($b, $bytes);
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+    (@items $b:ident; cut; $($rest:tt)*) => {
+        $crate::CommandBuilder::feed_and_cut_paper($b, $crate::CutType::TotalCut, 0);
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+    (@items $b:ident; cut partial; $($rest:tt)*) => {
+        $crate::CommandBuilder::feed_and_cut_paper($b, $crate::CutType::PartialCut, 0);
+        $crate::receipt!(@items $b; $($rest)*);
+    };
+}
+
+/// Alignment of text in its line or column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    /// Aligned on the left
+    #[default]
+    Left,
+    /// Centered
+    Center,
+    /// Aligned on the right
+    Right,
+}
+
+/// An element of a [`Receipt`].
+///
+/// Text is laid out in the characters of font A fitting in the printable line of the
+/// [`config()`](CommandBuilder::config()), e.g. 48 characters on 80 mm paper, see [`Receipt::render()`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Element {
+    /// Centered text at the top of the receipt, e.g. the name of the store, followed by a blank line
+    Header(String),
+    /// Text wrapped at the width of the line
+    Line(String),
+    /// A heading of `level` 1 to 3 in bold, enlarged twice for level 1 and twice as high for level 2
+    Heading {
+        /// Level of the heading, the levels above 3 being printed like 3
+        level: u8,
+        /// Text of the heading
+        text: String,
+        /// Alignment of the heading in the line
+        align: Align,
+    },
+    /// Styled text wrapped at the width of the line, e.g. a paragraph with bold words or an item of a list
+    Text {
+        /// Runs of text in their style
+        spans: Vec<Span>,
+        /// Number of spaces the lines after the first are indented by, e.g. the width of the bullet of a list item
+        indent: u16,
+        /// Alignment of the lines, including their indent
+        align: Align,
+    },
+    /// Lines printed as they are, without wrapping, e.g. a code block, the characters beyond the width of the line
+    /// being left out
+    Preformatted(String),
+    /// A line across the paper drawn by a character, e.g. `'-'`
+    Rule(char),
+    /// A styled line across the paper, see [`Separator`]
+    Separator(Separator),
+    /// An item of the receipt, with its name on the left and its amount on the right of the same line. A name too long
+    /// for the line is wrapped, and the amount is printed on the right of its last line.
+    Item {
+        /// Name of the item, e.g. `"Coffee x2"`
+        name: String,
+        /// Amount of the item, e.g. `"5.00"`
+        amount: String,
+    },
+    /// A label on the left and its value on the right of the same line, the space between them being filled by
+    /// `fill` characters, e.g. `Subtotal ........ 12.40`. A label too long for the line is wrapped like the name of
+    /// an [`Element::Item`].
+    KeyValue {
+        /// Label, e.g. `"Subtotal"`
+        label: String,
+        /// Value, e.g. `"12.40"`
+        value: String,
+        /// Character filling the space, e.g. `'.'`, kept a space apart from the label and the value unless it's a
+        /// space
+        fill: char,
+    },
+    /// A 1 bpp bitmap, e.g. a logo, see [`CommandBuilder::bitmap()`]
+    Bitmap {
+        /// Width in pixels
+        width: usize,
+        /// Height in pixels
+        height: usize,
+        /// Rows of pixels, each padded to whole bytes
+        bitmap: Vec<u8>,
+        /// Mode of the bit image
+        mode: BitImageMode,
+    },
+    /// An image file, e.g. a logo, see [`CommandBuilder::bit_image()`] (`image` feature)
+    #[cfg(feature = "image")]
+    Image {
+        /// Path of the image file
+        path: std::path::PathBuf,
+        /// Mode of the bit image
+        mode: BitImageMode,
+    },
+    /// A barcode, see [`CommandBuilder::barcode()`]
+    Barcode {
+        /// Barcode system
+        system: BarcodeSystem,
+        /// Data of the barcode
+        data: Vec<u8>,
+    },
+    /// A barcode centered in the line, fitted in `width` dots: it's printed with the widest modules up to 3 dots
+    /// fitting the barcode and its quiet zone of 10 modules on each side, and its text below it in font A if it
+    /// fits, or else in font B, or else without text, see [`CommandBuilder::barcode_style()`]. The style of the
+    /// printer is restored after it, i.e. modules of 3 dots without text.
+    FittedBarcode {
+        /// Barcode system
+        system: BarcodeSystem,
+        /// Data of the barcode
+        data: Vec<u8>,
+        /// Width the barcode and its text are fitted in, capped by the line
+        width: u16,
+    },
+    /// A QR code centered in the line, see [`CommandBuilder::qr_code()`]. It's printed with the largest modules up to
+    /// `module_size` fitting the code and its quiet zone of 4 modules on each side in the line.
+    QrCode {
+        /// Data of the QR code, e.g. a URL
+        data: Vec<u8>,
+        /// Error correction level
+        error_correction: QrErrorCorrection,
+        /// Largest size of the modules in dots
+        module_size: u8,
+    },
+    /// Centered text at the bottom of the receipt, e.g. a greeting, preceded by a blank line
+    Footer(String),
+    /// Blank lines
+    Feed(u8),
+    /// A blank gap of a physical length, the same on print heads of any resolution, e.g. room for a stamp
+    Space(Length),
+    /// A line to sign on, e.g. on delivery slips or the fallback receipts of card-present payments: `space` left
+    /// blank for the signature above a rule inset by 2 characters, and the `caption` centered below the rule
+    Signature {
+        /// Caption of the line, e.g. `"Customer signature"`
+        caption: String,
+        /// Blank space above the line
+        space: Length,
+    },
+    /// A table of text cells, see [`Table`]
+    Table(Table),
+    /// Two blocks of text side by side, see [`TwoColumns`]
+    TwoColumns(TwoColumns),
+    /// Text framed by a border, see [`TextBox`]
+    Box(TextBox),
+    /// Items marked by bullets or numbers, possibly nested, see [`List`]
+    List(List),
+    /// Long text justified or wrapped rag-right, optionally hyphenated, see [`Paragraph`]
+    Paragraph(Paragraph),
+    /// Regions placed at absolute positions and rotated independently in page mode, see [`Canvas`]
+    Canvas(Canvas),
+    /// Rows and columns of fixed and flexible tracks laid out in dots, see [`Layout`]
+    Layout(Layout),
+    /// Elements printed over a faint mark in page mode, see [`Watermark`]
+    Watermarked {
+        /// Mark behind the elements
+        watermark: Watermark,
+        /// Elements printed over the mark
+        content: Receipt,
+    },
+    /// Feed the paper to the cut position and cut it
+    Cut(CutType),
+}
+
+/// A receipt composed of [`Element`]s, giving applications a semantic layer above the commands: the elements are
+/// laid out and rendered to commands for the printer by [`render()`](Receipt::render()) or
+/// [`to_job()`](Receipt::to_job()).
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{CutType, Emulator, Model, Profile, Receipt};
+/// let receipt = Receipt::new()
+///     .header("CAFE ROMA")
+///     .item("Coffee x2", "5.00")
+///     .item("Croissant", "1.80")
+///     .footer("Thank you!")
+///     .cut(CutType::TotalCut);
+///
+/// let job = receipt.to_job(&Profile::for_model(Model::Tg2480h)).unwrap();
+/// assert!(!job.is_empty());
+///
+/// let mut emulator = Emulator::new();
+/// emulator.print_job(&job);
+/// let paper = emulator.render();
+/// let lines: Vec<_> = paper.lines().collect();
+/// assert_eq!(lines[0].trim(), "CAFE ROMA");
+/// assert_eq!(lines[1], "");
+/// assert_eq!(lines[2], format!("Coffee x2{}5.00", " ".repeat(35)));
+/// assert_eq!(lines[6], "--- cut ---");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Receipt {
+    elements: Vec<Element>,
+    money: MoneyFormat,
+    // boxed as the labels are rarely set, so the receipts nested in elements stay small
+    labels: Box<SaleLabels>,
+}
+
+impl Receipt {
+    /// Create an empty [`Receipt`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The elements of the receipt, in order.
+    pub fn elements(&self) -> &[Element] {
+        &self.elements
+    }
+
+    /// Append `element` to the receipt.
+    pub fn push(mut self, element: Element) -> Self {
+        self.elements.push(element);
+
+        self
+    }
+
+    /// Append an [`Element::Header`].
+    pub fn header(self, text: impl Into<String>) -> Self {
+        self.push(Element::Header(text.into()))
+    }
+
+    /// Append an [`Element::Line`].
+    pub fn line(self, text: impl Into<String>) -> Self {
+        self.push(Element::Line(text.into()))
+    }
+
+    /// Append a left aligned [`Element::Heading`].
+    pub fn heading(self, level: u8, text: impl Into<String>) -> Self {
+        self.push(Element::Heading {
+            level,
+            text: text.into(),
+            align: Align::Left,
+        })
+    }
+
+    /// Append a left aligned [`Element::Text`] of `spans`, without indent.
+    pub fn text(self, spans: impl Into<Vec<Span>>) -> Self {
+        self.push(Element::Text {
+            spans: spans.into(),
+            indent: 0,
+            align: Align::Left,
+        })
+    }
+
+    /// Append an [`Element::Preformatted`].
+    pub fn preformatted(self, text: impl Into<String>) -> Self {
+        self.push(Element::Preformatted(text.into()))
+    }
+
+    /// Append an [`Element::Rule`].
+    pub fn rule(self, c: char) -> Self {
+        self.push(Element::Rule(c))
+    }
+
+    /// Append the elements of `markdown`, see [`render_markdown()`].
+    pub fn markdown(mut self, markdown: &str) -> Self {
+        self.elements.extend(render_markdown(markdown).elements);
+
+        self
+    }
+
+    /// Append the elements of `html`, see [`render_html()`].
+    pub fn html(mut self, html: &str) -> Self {
+        self.elements.extend(render_html(html).elements);
+
+        self
+    }
+
+    /// Append a centered level 1 [`Element::Heading`] of `label` followed by the next number of `sequence`, e.g. the
+    /// queue number of an order, returning the error of the sequence if no number can be taken.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Align, Element, Error, Receipt};
+    /// let mut orders = 0;
+    /// let mut sequence = || -> Result<u64, Error> {
+    ///     orders += 1;
+    ///     Ok(orders)
+    /// };
+    /// let receipt = Receipt::new().ticket_number("Order #", &mut sequence).unwrap();
+    /// assert_eq!(
+    ///     receipt.elements(),
+    ///     [Element::Heading { level: 1, text: "Order #1".into(), align: Align::Center }]
+    /// );
+    /// ```
+    pub fn ticket_number(
+        self,
+        label: impl Into<String>,
+        sequence: &mut impl Sequence,
+    ) -> Result<Self, Error> {
+        let mut text = label.into();
+        text.push_str(&sequence.next()?.to_string());
+
+        Ok(self.push(Element::Heading {
+            level: 1,
+            text,
+            align: Align::Center,
+        }))
+    }
+
+    /// Append the header of a store at the Unix `timestamp`: the centered store name of `header` in a level 1
+    /// [`Element::Heading`], the centered lines of its address, and the date and time formatted by its
+    /// [`DateFormat`] in an [`Element::Header`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{DateFormat, DateHeader, Emulator, Receipt};
+    /// let header = DateHeader::new("CAFE ROMA")
+    ///     .address_line("Via Roma 1, Milano")
+    ///     .format(DateFormat::default().pattern("%d/%m/%Y %H:%M").utc_offset(60));
+    /// let receipt = Receipt::new().date_header(&header, 1_773_518_709).item("Espresso", "1.20");
+    ///
+    /// let mut emulator = Emulator::new();
+    /// receipt.render(&mut emulator).unwrap();
+    /// assert_eq!(
+    ///     emulator.render(),
+    ///     "       CAFE ROMA\n\
+    ///      \x20              Via Roma 1, Milano\n\
+    ///      \x20               14/03/2026 21:05\n\
+    ///      \n\
+    ///      Espresso                                    1.20\n"
+    /// );
+    /// ```
+    pub fn date_header(mut self, header: &DateHeader, timestamp: i64) -> Self {
+        self.elements
+            .extend(date::header_elements(header, timestamp));
+
+        self
+    }
+
+    /// Append an [`Element::Separator`].
+    pub fn separator(self, separator: Separator) -> Self {
+        self.push(Element::Separator(separator))
+    }
+
+    /// Append an [`Element::Item`].
+    pub fn item(self, name: impl Into<String>, amount: impl Into<String>) -> Self {
+        self.push(Element::Item {
+            name: name.into(),
+            amount: amount.into(),
+        })
+    }
+
+    /// Format the amounts of the following [`priced_item()`](Receipt::priced_item()) and
+    /// [`amount_line()`](Receipt::amount_line()) by `format`, so they are consistent across the receipt.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Element, MoneyFormat, Receipt};
+    /// let receipt = Receipt::new()
+    ///     .money_format(MoneyFormat::de_de())
+    ///     .priced_item("Kaffee", 250)
+    ///     .amount_line("Summe", 123450);
+    /// assert_eq!(
+    ///     receipt.elements(),
+    ///     [
+    ///         Element::Item { name: "Kaffee".into(), amount: "2,50 EUR".into() },
+    ///         Element::KeyValue { label: "Summe".into(), value: "1.234,50 EUR".into(), fill: '.' },
+    ///     ]
+    /// );
+    /// ```
+    pub fn money_format(self, format: MoneyFormat) -> Self {
+        Self {
+            money: format,
+            ..self
+        }
+    }
+
+    /// Append an [`Element::Item`] of an amount of `minor` units, e.g. cents, formatted by the
+    /// [`money_format()`](Receipt::money_format()).
+    pub fn priced_item(self, name: impl Into<String>, minor: i64) -> Self {
+        let amount = self.money.format(minor);
+        self.item(name, amount)
+    }
+
+    /// Append an [`Element::KeyValue`] filled by dots of an amount of `minor` units, e.g. a total, formatted by the
+    /// [`money_format()`](Receipt::money_format()).
+    pub fn amount_line(self, label: impl Into<String>, minor: i64) -> Self {
+        let value = self.money.format(minor);
+        self.kv_line(label, value)
+    }
+
+    /// Label the following [`sale_items()`](Receipt::sale_items()), [`totals()`](Receipt::totals()) and
+    /// [`tax_summary()`](Receipt::tax_summary()) by `labels`, e.g. in the language of the
+    /// [`money_format()`](Receipt::money_format()).
+    pub fn sale_labels(self, labels: SaleLabels) -> Self {
+        Self {
+            labels: Box::new(labels),
+            ..self
+        }
+    }
+
+    /// Append an [`Element::Table`] of the itemized sale of `items`, their quantity, name, unit price and amount
+    /// under a bold header, formatted by the [`money_format()`](Receipt::money_format()).
+    ///
+    /// The names too long for their column are wrapped, the other columns being as wide as their longest cell.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Emulator, JobBuilder, MoneyFormat, PrinterConfig, Receipt, SaleItem, TaxLine, Totals};
+    /// let receipt = Receipt::new()
+    ///     .money_format(MoneyFormat::en_us())
+    ///     .sale_items([SaleItem::new(2, "Cappuccino", 350), SaleItem::new(1, "Pain au chocolat", 210)])
+    ///     .totals(Totals::new(910, 73, 983))
+    ///     .tax_summary([TaxLine::new(800, 910, 73)]);
+    ///
+    /// let mut builder = JobBuilder::new();
+    /// builder.set_config(Some(PrinterConfig { dots_per_line: 384, ..Default::default() }));
+    /// receipt.render(&mut builder).unwrap();
+    ///
+    /// let mut emulator = Emulator::new();
+    /// emulator.print_job(&builder.build());
+    /// assert_eq!(
+    ///     emulator.render(),
+    ///     "Qty Item             Unit Amount\n\
+    ///      --------------------------------\n\
+    ///      \x20 2 Cappuccino       3.50  $7.00\n\
+    ///      \x20 1 Pain au chocolat 2.10  $2.10\n\
+    ///      Subtotal ................. $9.10\n\
+    ///      Tax ...................... $0.73\n\
+    ///      TOTAL                      $9.83\n\
+    ///      Rate              Net  Tax Gross\n\
+    ///      --------------------------------\n\
+    ///      8%               9.10 0.73  9.83\n"
+    /// );
+    /// ```
+    pub fn sale_items(self, items: impl IntoIterator<Item = SaleItem>) -> Self {
+        let items: Vec<SaleItem> = items.into_iter().collect();
+        let table = sale::items_table(&items, &self.money, &self.labels);
+        self.table(table)
+    }
+
+    /// Append the [`Element::KeyValue`]s of the subtotal, the discount unless it's 0 and the tax of `totals`,
+    /// followed by the total in bold, formatted by the [`money_format()`](Receipt::money_format()).
+    pub fn totals(mut self, totals: Totals) -> Self {
+        let elements = sale::totals_elements(&totals, &self.money, &self.labels);
+        self.elements.extend(elements);
+
+        self
+    }
+
+    /// Append an [`Element::Table`] of the tax summary of `lines`, their rate, net amount, tax and gross amount
+    /// under a bold header, formatted as numbers by the [`money_format()`](Receipt::money_format()).
+    pub fn tax_summary(self, lines: impl IntoIterator<Item = TaxLine>) -> Self {
+        let lines: Vec<TaxLine> = lines.into_iter().collect();
+        let table = sale::tax_table(&lines, &self.money, &self.labels);
+        self.table(table)
+    }
+
+    /// Append an [`Element::KeyValue`] filled by dots, the most common line of the receipts.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Emulator, Receipt};
+    /// let mut emulator = Emulator::new();
+    /// Receipt::new().kv_line("Subtotal", "12.40").render(&mut emulator).unwrap();
+    /// assert_eq!(emulator.render(), format!("Subtotal {} 12.40\n", ".".repeat(33)));
+    /// ```
+    pub fn kv_line(self, label: impl Into<String>, value: impl Into<String>) -> Self {
+        self.kv_line_filled(label, value, '.')
+    }
+
+    /// Append an [`Element::KeyValue`] filled by `fill`, e.g. `' '` or `'_'`.
+    pub fn kv_line_filled(
+        self,
+        label: impl Into<String>,
+        value: impl Into<String>,
+        fill: char,
+    ) -> Self {
+        self.push(Element::KeyValue {
+            label: label.into(),
+            value: value.into(),
+            fill,
+        })
+    }
+
+    /// Append an [`Element::Bitmap`].
+    pub fn bitmap(
+        self,
+        width: usize,
+        height: usize,
+        bitmap: impl Into<Vec<u8>>,
+        mode: BitImageMode,
+    ) -> Self {
+        self.push(Element::Bitmap {
+            width,
+            height,
+            bitmap: bitmap.into(),
+            mode,
+        })
+    }
+
+    /// Append an [`Element::Image`].
+    #[cfg(feature = "image")]
+    pub fn image(self, path: impl Into<std::path::PathBuf>, mode: BitImageMode) -> Self {
+        self.push(Element::Image {
+            path: path.into(),
+            mode,
+        })
+    }
+
+    /// Append an [`Element::Barcode`].
+    pub fn barcode(self, system: BarcodeSystem, data: impl Into<Vec<u8>>) -> Self {
+        self.push(Element::Barcode {
+            system,
+            data: data.into(),
+        })
+    }
+
+    /// Append an [`Element::FittedBarcode`] fitted in `width` dots, e.g. [`u16::MAX`] for the whole line.
+    ///
+    /// Rendering the receipt returns [`Error::InvalidParameter`] if the data isn't valid for `system` or the barcode
+    /// doesn't fit even with modules of 1 dot.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{BarcodeSystem, Command, HriFont, HriPosition, JobBuilder, Receipt};
+    /// let mut builder = JobBuilder::new();
+    /// Receipt::new()
+    ///     .fitted_barcode(BarcodeSystem::Ean13, "400638133393", 240)
+    ///     .render(&mut builder)
+    ///     .unwrap();
+    /// // 95 modules and the quiet zones of 20 modules fit in 240 dots by modules of 2 dots
+    /// assert_eq!(
+    ///     builder.build().commands()[1],
+    ///     Command::BarcodeStyle { module_width: 2, hri: HriPosition::Below, hri_font: HriFont::A }
+    /// );
+    ///
+    /// let receipt = Receipt::new().fitted_barcode(BarcodeSystem::Ean13, "400638133393", 100);
+    /// assert!(receipt.render(&mut JobBuilder::new()).is_err());
+    /// ```
+    pub fn fitted_barcode(
+        self,
+        system: BarcodeSystem,
+        data: impl Into<Vec<u8>>,
+        width: u16,
+    ) -> Self {
+        self.push(Element::FittedBarcode {
+            system,
+            data: data.into(),
+            width,
+        })
+    }
+
+    /// Append an [`Element::QrCode`] at [`QrErrorCorrection::Medium`], its modules up to 8 dots, i.e. 1 mm at
+    /// 203 dpi.
+    pub fn qr_code(self, data: impl Into<Vec<u8>>) -> Self {
+        self.push(Element::QrCode {
+            data: data.into(),
+            error_correction: QrErrorCorrection::Medium,
+            module_size: payment::MAX_MODULE_SIZE,
+        })
+    }
+
+    /// Append a payment block: a centered [`Element::QrCode`] of `payload`, e.g. the URL of a payment page or an
+    /// EPC transfer, like [`qr_code()`](Receipt::qr_code()), followed by `amount` in minor units formatted by the
+    /// [`money_format()`](Receipt::money_format()) in a centered level 2 [`Element::Heading`], and the centered
+    /// `instructions`, left out if empty.
+    ///
+    /// The code is printed by the firmware of the printer, so rendering the receipt returns
+    /// [`Error::Unsupported`] for a profile without 2D codes, and [`Error::InvalidParameter`] if it doesn't fit in
+    /// the line. The code is only centered in standard mode, e.g. not in the regions of a [`Canvas`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Emulator, MoneyFormat, Receipt};
+    /// let receipt = Receipt::new()
+    ///     .money_format(MoneyFormat::en_us())
+    ///     .qr_payment("https://pay.example.com/i/4711", 1250, "Scan to pay by phone");
+    ///
+    /// let mut emulator = Emulator::new();
+    /// receipt.render(&mut emulator).unwrap();
+    /// assert_eq!(
+    ///     emulator.render(),
+    ///     "[QR https://pay.example.com/i/4711]\n\
+    ///      \x20                    $12.50\n\
+    ///      \x20             Scan to pay by phone\n"
+    /// );
+    /// ```
+    pub fn qr_payment(
+        mut self,
+        payload: impl Into<Vec<u8>>,
+        amount: i64,
+        instructions: impl Into<String>,
+    ) -> Self {
+        let elements =
+            payment::payment_elements(payload.into(), amount, instructions.into(), &self.money);
+        self.elements.extend(elements);
+
+        self
+    }
+
+    /// Append an [`Element::Footer`].
+    pub fn footer(self, text: impl Into<String>) -> Self {
+        self.push(Element::Footer(text.into()))
+    }
+
+    /// Append an [`Element::Feed`].
+    pub fn feed(self, lines: u8) -> Self {
+        self.push(Element::Feed(lines))
+    }
+
+    /// Append an [`Element::Space`].
+    pub fn space(self, length: Length) -> Self {
+        self.push(Element::Space(length))
+    }
+
+    /// Append an [`Element::Signature`] of `caption`, leaving 15 mm blank for the signature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Emulator, JobBuilder, PrinterConfig, Receipt};
+    /// let receipt = Receipt::new().signature("Received by");
+    ///
+    /// let mut builder = JobBuilder::new();
+    /// builder.set_config(Some(PrinterConfig { dots_per_line: 144, ..Default::default() }));
+    /// receipt.render(&mut builder).unwrap();
+    ///
+    /// let mut emulator = Emulator::new();
+    /// emulator.print_job(&builder.build());
+    /// let paper = emulator.render();
+    /// let lines: Vec<_> = paper.lines().collect();
+    /// // 15 mm are about 4 lines of text
+    /// assert_eq!(lines[..4], ["", "", "", ""]);
+    /// assert_eq!(lines[4], format!("{}{}", " ".repeat(24), "#".repeat(96)));
+    /// assert_eq!(lines.last(), Some(&"Received by"));
+    /// ```
+    pub fn signature(self, caption: impl Into<String>) -> Self {
+        self.push(Element::Signature {
+            caption: caption.into(),
+            space: SIGNATURE_SPACE,
+        })
+    }
+
+    /// Append an [`Element::Table`].
+    pub fn table(self, table: Table) -> Self {
+        self.push(Element::Table(table))
+    }
+
+    /// Append an [`Element::TwoColumns`].
+    pub fn two_columns(self, columns: TwoColumns) -> Self {
+        self.push(Element::TwoColumns(columns))
+    }
+
+    /// Append an [`Element::Box`].
+    pub fn text_box(self, text_box: TextBox) -> Self {
+        self.push(Element::Box(text_box))
+    }
+
+    /// Append an [`Element::List`].
+    pub fn list(self, list: List) -> Self {
+        self.push(Element::List(list))
+    }
+
+    /// Append an [`Element::Paragraph`].
+    pub fn paragraph(self, paragraph: Paragraph) -> Self {
+        self.push(Element::Paragraph(paragraph))
+    }
+
+    /// Append an [`Element::Canvas`].
+    pub fn canvas(self, canvas: Canvas) -> Self {
+        self.push(Element::Canvas(canvas))
+    }
+
+    /// Append an [`Element::Layout`].
+    pub fn layout(self, layout: Layout) -> Self {
+        self.push(Element::Layout(layout))
+    }
+
+    /// Append an [`Element::Watermarked`] of `content` over `watermark`, e.g. `COPY` on a duplicate receipt.
+    pub fn watermarked(self, watermark: Watermark, content: Receipt) -> Self {
+        self.push(Element::Watermarked { watermark, content })
+    }
+
+    /// Append an [`Element::Cut`].
+    pub fn cut(self, cut_type: CutType) -> Self {
+        self.push(Element::Cut(cut_type))
+    }
+
+    /// Render the elements to commands on `builder`, laid out for the printable line of its
+    /// [`config()`](CommandBuilder::config()).
+    ///
+    /// The commands are checked against the [`profile()`](CommandBuilder::profile()) of `builder`, an error is
+    /// returned for the first element which can't be printed, e.g. [`Error::Unsupported`] for a barcode on a
+    /// profile without barcodes, or [`Error::UnsupportedImage`] for a bitmap of an invalid size. The commands of
+    /// the elements before it are left on `builder`.
+    pub fn render<'a, B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &'a mut B,
+    ) -> Result<&'a mut B, Error> {
+        self.render_styled(builder, &RenderStyle::default())
+    }
+
+    /// Render the elements to commands on `builder` in `style`, e.g. an order as a
+    /// [`kitchen_ticket()`](RenderStyle::kitchen_ticket()), see [`render()`](Receipt::render()).
+    pub fn render_styled<'a, B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &'a mut B,
+        style: &RenderStyle,
+    ) -> Result<&'a mut B, Error> {
+        let dots = builder.config().printable_dots();
+        style.begin(builder);
+        self.render_in(builder, dots, style)?;
+        style.end(builder);
+        let cut = matches!(self.elements.last(), Some(Element::Cut(_)));
+        style.finish(builder, cut)?;
+
+        Ok(builder)
+    }
+
+    /// Render the elements to commands on `builder` in `style`, laid out for a line of `dots`, see
+    /// [`render()`](Receipt::render()).
+    pub(crate) fn render_in<B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &mut B,
+        dots: u16,
+        style: &RenderStyle,
+    ) -> Result<(), Error> {
+        let columns = columns(dots);
+
+        for element in &self.elements {
+            match element {
+                Element::Header(text) => {
+                    print_lines(
+                        builder,
+                        wrap(text, columns).iter().map(|line| center(line, columns)),
+                    );
+                    builder.print_and_feed_paper(FeedUnit::Lines, 1);
+                }
+                Element::Line(text) => print_lines(builder, wrap(text, columns)),
+                Element::Heading { level, text, align } => {
+                    let (width, height) = match level {
+                        1 => (2, 2),
+                        2 => (1, 2),
+                        _ => (1, 1),
+                    };
+                    let (width, height) = (width, (height * style.height()).min(8));
+                    builder.bold(true);
+                    if (width, height) != (1, style.height()) {
+                        builder.character_size(width, height);
+                    }
+                    let columns = (columns / width as usize).max(1);
+                    print_lines(
+                        builder,
+                        wrap(text, columns)
+                            .iter()
+                            .map(|line| pad(line, columns, *align).trim_end().to_string()),
+                    );
+                    if (width, height) != (1, style.height()) {
+                        builder.character_size(1, style.height());
+                    }
+                    builder.bold(false);
+                }
+                Element::Text {
+                    spans,
+                    indent,
+                    align,
+                } => {
+                    let mut lines = text::wrap_spans(spans, columns, *indent as usize);
+                    text::align_spans(&mut lines, columns, *align);
+                    text::print_spans(builder, &lines);
+                }
+                Element::Preformatted(text) => print_lines(
+                    builder,
+                    text.lines()
+                        .map(|line| line.chars().take(columns).collect::<String>()),
+                ),
+                Element::Rule(c) => {
+                    print_lines(builder, [String::from(*c).repeat(columns)]);
+                }
+                Element::Separator(separator) => separator.render(builder, dots)?,
+                Element::Item { name, amount } => {
+                    print_lines(builder, spread(name, amount, ' ', columns));
+                }
+                Element::KeyValue { label, value, fill } => {
+                    print_lines(builder, spread(label, value, *fill, columns));
+                }
+                Element::Bitmap { .. } if !style.images => {}
+                #[cfg(feature = "image")]
+                Element::Image { .. } if !style.images => {}
+                Element::Bitmap {
+                    width,
+                    height,
+                    bitmap,
+                    mode,
+                } => {
+                    builder.bitmap(*width, *height, bitmap, *mode)?.print();
+                }
+                #[cfg(feature = "image")]
+                Element::Image { path, mode } => {
+                    builder.bit_image(path, *mode)?.print();
+                }
+                Element::Barcode { system, data } => {
+                    builder.barcode(*system, data)?;
+                }
+                Element::FittedBarcode {
+                    system,
+                    data,
+                    width,
+                } => {
+                    let (module_width, hri, hri_font) =
+                        barcode::fit(*system, data, (*width).min(dots))?;
+                    builder.justification(Justification::Center);
+                    let printed = builder
+                        .barcode_style(module_width, hri, hri_font)
+                        .and_then(|builder| builder.barcode(*system, data))
+                        .map(|_| ());
+                    builder.justification(Justification::Left);
+                    printed?;
+                    builder.barcode_style(
+                        barcode::DEFAULT_MODULE_WIDTH,
+                        HriPosition::None,
+                        HriFont::A,
+                    )?;
+                }
+                Element::QrCode {
+                    data,
+                    error_correction,
+                    module_size,
+                } => {
+                    let size =
+                        payment::module_size(data.len(), *error_correction, *module_size, dots)?;
+                    // the following elements are aligned by padding, so the justification is reset even on errors
+                    builder.justification(Justification::Center);
+                    let printed = builder.qr_code(data, size, *error_correction).map(|_| ());
+                    builder.justification(Justification::Left);
+                    printed?;
+                }
+                Element::Footer(text) => {
+                    builder.print_and_feed_paper(FeedUnit::Lines, 1);
+                    print_lines(
+                        builder,
+                        wrap(text, columns).iter().map(|line| center(line, columns)),
+                    );
+                }
+                Element::Feed(lines) => {
+                    builder.print_and_feed_paper(FeedUnit::Lines, *lines);
+                }
+                Element::Space(length) => {
+                    builder.feed(*length);
+                }
+                Element::Signature { caption, space } => {
+                    builder.feed(*space);
+                    Separator::new(SeparatorStyle::Solid)
+                        .inset(2)
+                        .render(builder, dots)?;
+                    print_lines(
+                        builder,
+                        wrap(caption, columns)
+                            .iter()
+                            .map(|line| center(line, columns)),
+                    );
+                }
+                Element::Table(table) => table.render(builder, dots)?,
+                Element::TwoColumns(columns) => columns.render(builder, dots)?,
+                Element::Box(text_box) => text_box.render(builder, dots)?,
+                Element::List(list) => list.render(builder, columns, style.bold_modifiers),
+                Element::Paragraph(paragraph) => paragraph.render(builder, columns),
+                Element::Cut(cut_type) => {
+                    builder.try_push(Command::FeedAndCutPaper {
+                        cut_type: style.cut.unwrap_or(*cut_type),
+                        amount: 0,
+                    })?;
+                }
+                // the canvases, layouts and watermarks are rendered in their own style
+                Element::Canvas(canvas) => {
+                    style.end(builder);
+                    canvas.render(builder)?;
+                    style.begin(builder);
+                }
+                Element::Layout(layout) => {
+                    style.end(builder);
+                    layout.render(builder, dots)?;
+                    style.begin(builder);
+                }
+                Element::Watermarked { watermark, content } => {
+                    style.end(builder);
+                    watermark::render(watermark, content, builder, dots)?;
+                    style.begin(builder);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render the receipt to a PNG image of the paper printed by a printer of `config`, one pixel for each dot at
+    /// the resolution of [`PrinterConfig::dpi`](crate::PrinterConfig::dpi), e.g. for design reviews and web previews without any printer.
+    ///
+    /// The margins of `config` are applied like [`CommandBuilder::apply_config()`], then the paper is drawn as
+    /// the printer would print it: text by the glyphs of font A in its styles and sizes, bit images and rules by
+    /// their dots, barcodes by their system and data in brackets, the regions of canvases rotated, and cuts by
+    /// dashed lines. Returns the error of [`render()`](Receipt::render()) if an element can't be rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{PrinterConfig, Receipt};
+    /// let receipt = Receipt::new().header("CAFE ROMA").item("Coffee", "2.50");
+    /// let png = receipt.to_png(&PrinterConfig::default()).unwrap();
+    /// assert_eq!(png[..8], *b"\x89PNG\r\n\x1a\n");
+    /// // 384 dots wide, 3 lines of 34 dots high
+    /// assert_eq!(png[16..24], [0, 0, 1, 128, 0, 0, 0, 102]);
+    /// ```
+    #[cfg(feature = "image")]
+    pub fn to_png(&self, config: &crate::PrinterConfig) -> Result<Vec<u8>, Error> {
+        let mut builder = JobBuilder::new();
+        builder.set_config(Some(*config)).apply_config();
+        self.render(&mut builder)?;
+        let raster = crate::preview::rasterize(builder.build().commands(), config.dots_per_line);
+
+        crate::preview::png(&raster, config.dpi)
+    }
+
+    /// Render the elements to a [`Job`] for the printer of `profile`, see [`render()`](Receipt::render()).
+    pub fn to_job(&self, profile: &Profile) -> Result<Job, Error> {
+        let mut builder = JobBuilder::new();
+        builder.set_profile(Some(profile.clone()));
+
+        Ok(self.render(&mut builder)?.build())
+    }
+}
+
+/// Number of characters of font A in a line of `dots`, at least 1.
+pub(crate) fn columns(dots: u16) -> usize {
+    (dots / FONT_A_WIDTH).max(1) as usize
+}
+
+/// Wrap `text` at the spaces into lines of at most `columns` characters, splitting the words longer than a line.
+///
+/// Every line of `text` is wrapped on its own, an empty line being kept as it is.
+pub(crate) fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut len = 0;
+        for word in paragraph.split_whitespace() {
+            let mut chars: Vec<char> = word.chars().collect();
+            if len > 0 && len + 1 + chars.len() <= columns {
+                line.push(' ');
+                len += 1;
+            } else if len > 0 {
+                lines.push(core::mem::take(&mut line));
+                len = 0;
+            }
+            while chars.len() > columns - len {
+                let rest = chars.split_off(columns - len);
+                line.extend(chars);
+                lines.push(core::mem::take(&mut line));
+                len = 0;
+                chars = rest;
+            }
+            len += chars.len();
+            line.extend(chars);
+        }
+        if len > 0 || paragraph.trim().is_empty() {
+            lines.push(line);
+        }
+    }
+
+    lines
+}
+
+/// `text` centered in a line of `columns` characters, without trailing spaces.
+pub(crate) fn center(text: &str, columns: usize) -> String {
+    let padding = columns.saturating_sub(text.chars().count()) / 2;
+
+    " ".repeat(padding) + text
+}
+
+/// `text` right aligned in a line of `columns` characters.
+pub(crate) fn right(text: &str, columns: usize) -> String {
+    let padding = columns.saturating_sub(text.chars().count());
+
+    " ".repeat(padding) + text
+}
+
+/// `text` aligned by `align` and padded to `columns` characters.
+pub(crate) fn pad(text: &str, columns: usize, align: Align) -> String {
+    let padding = columns.saturating_sub(text.chars().count());
+    let left = match align {
+        Align::Left => 0,
+        Align::Center => padding / 2,
+        Align::Right => padding,
+    };
+
+    " ".repeat(left) + text + &" ".repeat(padding - left)
+}
+
+/// Lines of `label` wrapped in `columns` characters, the last one ending with `value` aligned on the right, the
+/// space between them being filled by `fill` a space apart from both. `value` is printed on its own line if it
+/// doesn't fit on the last line of `label`.
+pub(crate) fn spread(label: &str, value: &str, fill: char, columns: usize) -> Vec<String> {
+    let mut lines = wrap(label, columns);
+    let last = lines.pop().unwrap_or_default();
+    let used = last.chars().count() + value.chars().count();
+
+    if used < columns {
+        let padding = columns - used;
+        let gap = if fill == ' ' || padding < 3 || last.is_empty() {
+            " ".repeat(padding)
+        } else {
+            String::from(" ") + &String::from(fill).repeat(padding - 2) + " "
+        };
+        lines.push(last + &gap + value);
+    } else {
+        lines.push(last);
+        lines.push(right(value, columns));
+    }
+
+    lines
+}
+
+/// Print each line of `lines` as text.
+fn print_lines<B: CommandBuilder + ?Sized, S: AsRef<str>>(
+    builder: &mut B,
+    lines: impl IntoIterator<Item = S>,
+) {
+    for line in lines {
+        builder.raw(line.as_ref().as_bytes()).print();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Emulator, PrinterConfig};
+    use alloc::vec;
+
+    #[test]
+    fn test_receipt() {
+        let total = String::from("Total 4.30");
+        let mut builder = JobBuilder::new();
+        receipt!(&mut builder => {
+            initialize;
+            header "CAFE";
+            lines ["A", "B"];
+            bitmap 8, 1, &[0xFF], BitImageMode::Dots8SingleDensity;
+            line total;
+            raw &[0x1B, 0x40];
+            cut;
+        })
+        .unwrap();
+        assert_eq!(
+            builder.build().commands(),
+            &[
+                Command::Initialize,
+                Command::Raw(b"CAFE".to_vec()),
+                Command::Print,
+                Command::PrintAndFeed {
+                    unit: FeedUnit::Lines,
+                    amount: 1
+                },
+                Command::Raw(b"A".to_vec()),
+                Command::Print,
+                Command::Raw(b"B".to_vec()),
+                Command::Print,
+                Command::BitImage {
+                    mode: BitImageMode::Dots8SingleDensity,
+                    width: 8,
+                    data: vec![0x80; 8]
+                },
+                Command::Print,
+                Command::Raw(b"Total 4.30".to_vec()),
+                Command::Print,
+                Command::Raw(vec![0x1B, 0x40]),
+                Command::FeedAndCutPaper {
+                    cut_type: CutType::TotalCut,
+                    amount: 0
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let receipt = Receipt::new()
+            .header("CAFE")
+            .line("a long line wrapped at the spaces")
+            .item("Coffee", "2.50")
+            .item("Espresso macchiato", "12.50")
+            .barcode(BarcodeSystem::Ean8, "1234567")
+            .footer("Thanks")
+            .cut(CutType::PartialCut);
+
+        let mut builder = JobBuilder::new();
+        builder.set_config(Some(PrinterConfig {
+            dots_per_line: 240,
+            ..Default::default()
+        }));
+        receipt.render(&mut builder).unwrap();
+        let mut emulator = Emulator::new();
+        emulator.print_job(&builder.build());
+        assert_eq!(
+            emulator.render(),
+            "        CAFE\n\
+             \n\
+             a long line wrapped\n\
+             at the spaces\n\
+             Coffee          2.50\n\
+             Espresso macchiato\n\
+             \x20              12.50\n\
+             [Ean8 1234567]\n\
+             \n\
+             \x20      Thanks\n\
+             --- partial cut ---\n"
+        );
+
+        // the barcode is refused by the profile, after the elements before it
+        let mut profile = Profile::emulated();
+        profile.barcodes = false;
+        assert!(matches!(
+            receipt.to_job(&profile),
+            Err(Error::Unsupported {
+                command: "barcode",
+                ..
+            })
+        ));
+
+        let receipt = Receipt::new().qr_code("https://example.com");
+        let mut builder = JobBuilder::new();
+        builder.set_config(Some(PrinterConfig {
+            dots_per_line: 240,
+            ..Default::default()
+        }));
+        receipt.render(&mut builder).unwrap();
+        // version 2 of 25 modules and its quiet zone in 240 dots
+        assert_eq!(
+            builder.build().commands(),
+            [
+                Command::Justification(Justification::Center),
+                Command::QrCode {
+                    data: b"https://example.com".to_vec(),
+                    module_size: 7,
+                    error_correction: QrErrorCorrection::Medium,
+                },
+                Command::Justification(Justification::Left),
+            ]
+        );
+        profile.codes_2d = false;
+        assert!(matches!(
+            receipt.to_job(&profile),
+            Err(Error::Unsupported {
+                command: "qr_code",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_render_styled() {
+        let order = Receipt::new()
+            .bitmap(8, 1, vec![0xFF], BitImageMode::Dots8SingleDensity)
+            .heading(2, "Table 4")
+            .line("Burger")
+            .cut(CutType::TotalCut);
+
+        let mut builder = JobBuilder::new();
+        order
+            .render_styled(&mut builder, &RenderStyle::kitchen_ticket())
+            .unwrap();
+        // the logo left out, the heading twice as high and the cut of the receipt made partial
+        assert_eq!(
+            builder.build().commands(),
+            [
+                Command::CharacterSize {
+                    width: 1,
+                    height: 2
+                },
+                Command::Bold(true),
+                Command::CharacterSize {
+                    width: 1,
+                    height: 4
+                },
+                Command::Raw(b"Table 4".to_vec()),
+                Command::Print,
+                Command::CharacterSize {
+                    width: 1,
+                    height: 2
+                },
+                Command::Bold(false),
+                Command::Raw(b"Burger".to_vec()),
+                Command::Print,
+                Command::FeedAndCutPaper {
+                    cut_type: CutType::PartialCut,
+                    amount: 0
+                },
+                Command::CharacterSize {
+                    width: 1,
+                    height: 1
+                },
+                Command::Beep {
+                    count: 2,
+                    duration_ms: 200
+                },
+            ]
+        );
+
+        // the customer receipt is rendered as it is
+        let mut styled = JobBuilder::new();
+        order
+            .render_styled(&mut styled, &RenderStyle::customer_receipt())
+            .unwrap();
+        let mut builder = JobBuilder::new();
+        order.render(&mut builder).unwrap();
+        assert_eq!(styled.build(), builder.build());
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("", 5), [""]);
+        assert_eq!(wrap("ab cd ef", 5), ["ab cd", "ef"]);
+        assert_eq!(wrap("abcdefgh ij", 5), ["abcde", "fgh", "ij"]);
+        assert_eq!(wrap("a\n\nb", 5), ["a", "", "b"]);
+        assert_eq!(center("ab", 7), "  ab");
+
+        assert_eq!(spread("Tax", "1.00", '.', 12), ["Tax ... 1.00"]);
+        assert_eq!(spread("Tax", "1.00", '.', 9), ["Tax  1.00"]);
+        assert_eq!(spread("Tax", "1.00", '_', 7), ["Tax", "   1.00"]);
+        assert_eq!(
+            spread("Total due", "9.90", ' ', 12),
+            ["Total due", "        9.90"]
+        );
+    }
+
+    #[test]
+    fn test_receipt_error() {
+        let mut builder = JobBuilder::new();
+        let result = receipt!(&mut builder => {
+            line "before";
+            bitmap 16, 1, &[0xFF], BitImageMode::Dots8SingleDensity;
+            cut partial;
+        });
+        assert!(matches!(result, Err(Error::UnsupportedImage(_))));
+        // items after the failing one aren't constructed
+        assert_eq!(builder.commands().len(), 2);
+    }
+}
@@ -0,0 +1,88 @@
+//! Barcodes of the receipts fitted in a width, their modules and text sized for it.
+
+use alloc::format;
+
+use crate::{BarcodeSystem, Error, HriFont, HriPosition};
+
+/// Module width of the printers after initialization in dots, restored after the fitted barcodes.
+pub(super) const DEFAULT_MODULE_WIDTH: u8 = 3;
+
+/// Largest module width of the fitted barcodes in dots, readable by any scanner without wasting paper.
+const MAX_MODULE_WIDTH: u8 = DEFAULT_MODULE_WIDTH;
+
+/// Number of modules of the quiet zone left blank on each side of a barcode.
+const QUIET_ZONE: u32 = 10;
+
+/// The style of the barcode of `data` in `system` fitting in `dots`: the widest modules up to
+/// [`MAX_MODULE_WIDTH`] fitting the barcode and its quiet zones, and its text below it in font A if it fits, or
+/// else in font B, or else left out.
+///
+/// Returns [`Error::InvalidParameter`] if the data isn't valid, or if even modules of 1 dot don't fit.
+pub(super) fn fit(
+    system: BarcodeSystem,
+    data: &[u8],
+    dots: u16,
+) -> Result<(u8, HriPosition, HriFont), Error> {
+    let modules = system
+        .modules(data)
+        .ok_or_else(|| Error::InvalidParameter(system.invalid_data(data).unwrap_or_default()))?;
+    let fitting = dots as u32 / (modules + 2 * QUIET_ZONE);
+    if fitting == 0 {
+        return Err(Error::InvalidParameter(format!(
+            "{system:?} barcode of {modules} modules is wider than {dots} dots"
+        )));
+    }
+
+    let chars = system.hri_chars(data);
+    let hri = [HriFont::A, HriFont::B]
+        .into_iter()
+        .find(|font| chars * font.width() as usize <= dots as usize);
+
+    Ok((
+        fitting.min(MAX_MODULE_WIDTH as u32) as u8,
+        hri.map_or(HriPosition::None, |_| HriPosition::Below),
+        hri.unwrap_or_default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        // 95 modules and the quiet zones of 20 modules
+        assert!(matches!(
+            fit(BarcodeSystem::Ean13, b"400638133393", 576),
+            Ok((3, HriPosition::Below, HriFont::A))
+        ));
+        assert!(matches!(
+            fit(BarcodeSystem::Ean13, b"400638133393", 240),
+            Ok((2, HriPosition::Below, HriFont::A))
+        ));
+        // 13 digits of font A are 156 dots wide
+        assert!(matches!(
+            fit(BarcodeSystem::Ean13, b"400638133393", 150),
+            Ok((1, HriPosition::Below, HriFont::B))
+        ));
+        assert!(matches!(
+            fit(BarcodeSystem::Ean13, b"400638133393", 100),
+            Err(Error::InvalidParameter(_))
+        ));
+
+        // 20 pairs of digits in code set C, 20 symbols of 11 modules printing 40 digits
+        let data = [b"{C".as_slice(), &[12; 20]].concat();
+        assert!(matches!(
+            fit(BarcodeSystem::Code128, &data, 300),
+            Ok((1, HriPosition::None, HriFont::A))
+        ));
+        assert!(matches!(
+            fit(BarcodeSystem::Code128, b"{Babc", 576),
+            Ok((3, HriPosition::Below, HriFont::A))
+        ));
+        assert!(matches!(
+            fit(BarcodeSystem::Ean13, b"ABC", 576),
+            Err(Error::InvalidParameter(reason)) if reason.contains("Ean13")
+        ));
+    }
+}
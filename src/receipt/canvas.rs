@@ -0,0 +1,189 @@
+//! Page mode layouts of the receipts.
+
+use alloc::{format, vec::Vec};
+
+use super::{layout::nested_page, Receipt, RenderStyle};
+use crate::{CommandBuilder, Error, PageDirection};
+
+/// A region of a [`Canvas`], the elements of its receipt laid out in its area in the print direction.
+///
+/// The area is in horizontal and vertical motion units from the upper left corner of the page, each of them one dot
+/// unless changed by [`CommandBuilder::motion_units()`]. The lines of the elements run along the width of the area
+/// for [`PageDirection::LeftToRight`] and [`PageDirection::RightToLeft`], and along its height, i.e. sideways, for
+/// [`PageDirection::BottomToTop`] and [`PageDirection::TopToBottom`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    /// Horizontal origin of the area
+    pub x: u16,
+    /// Vertical origin of the area
+    pub y: u16,
+    /// Width of the area
+    pub width: u16,
+    /// Height of the area
+    pub height: u16,
+    /// Print direction in the area, i.e. its rotation
+    pub direction: PageDirection,
+    /// Elements printed in the area
+    pub content: Receipt,
+}
+
+impl Region {
+    /// A region of `content` in the area of `width` x `height` at (`x`, `y`), printed upright.
+    pub fn new(x: u16, y: u16, width: u16, height: u16, content: Receipt) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            direction: PageDirection::LeftToRight,
+            content,
+        }
+    }
+
+    /// The region printed in `direction`, e.g. [`PageDirection::BottomToTop`] for a sideways barcode.
+    pub fn direction(self, direction: PageDirection) -> Self {
+        Self { direction, ..self }
+    }
+
+    /// Length of the lines of the region, in dots.
+    fn line_dots(&self) -> u16 {
+        match self.direction {
+            PageDirection::LeftToRight | PageDirection::RightToLeft => self.width,
+            PageDirection::BottomToTop | PageDirection::TopToBottom => self.height,
+        }
+    }
+}
+
+/// A page of regions at absolute positions, each rotated independently, e.g. a coupon of upright text next to a
+/// sideways barcode.
+///
+/// The canvas is composed in page mode and printed at once. The regions can't contain a
+/// [`Element::Canvas`](super::Element::Canvas), a [`Element::Layout`](super::Element::Layout), a
+/// [`Element::Watermarked`](super::Element::Watermarked) or a [`Element::Cut`](super::Element::Cut), which are left
+/// out of page mode.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{mnemonics, BarcodeSystem, Canvas, CutType, JobBuilder, PageDirection, Receipt, Region};
+/// let coupon = Canvas::new()
+///     .region(Region::new(0, 0, 384, 200, Receipt::new().heading(1, "20% OFF").line("on your next coffee")))
+///     .region(
+///         Region::new(400, 0, 176, 200, Receipt::new().barcode(BarcodeSystem::Ean13, "400638133393"))
+///             .direction(PageDirection::BottomToTop),
+///     );
+///
+/// let mut builder = JobBuilder::new();
+/// Receipt::new().canvas(coupon).cut(CutType::TotalCut).render(&mut builder).unwrap();
+/// let mnemonics = mnemonics(&builder.build().to_bytes());
+/// assert_eq!(mnemonics[..3], ["ESC L", "ESC W x=0 y=0 w=384 h=200", "ESC T n=0"]);
+/// // the barcode is printed sideways in the area at the right
+/// assert!(mnemonics.iter().any(|m| m == "ESC W x=400 y=0 w=176 h=200"));
+/// assert!(mnemonics.iter().any(|m| m == "ESC T n=1"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Canvas {
+    /// Regions of the canvas, in the order they are composed
+    pub regions: Vec<Region>,
+}
+
+impl Canvas {
+    /// Create an empty [`Canvas`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `region` to the canvas.
+    pub fn region(mut self, region: Region) -> Self {
+        self.regions.push(region);
+
+        self
+    }
+
+    /// Render the canvas to page mode commands on `builder`.
+    ///
+    /// Returns [`Error::InvalidParameter`] if a region contains a canvas, a layout, a watermark or a cut.
+    pub(crate) fn render<B: CommandBuilder + ?Sized>(&self, builder: &mut B) -> Result<(), Error> {
+        for (i, region) in self.regions.iter().enumerate() {
+            if let Some(kind) = nested_page(&region.content) {
+                return Err(Error::InvalidParameter(format!(
+                    "region {i} of the canvas contains a {kind}"
+                )));
+            }
+        }
+        if self.regions.is_empty() {
+            return Ok(());
+        }
+
+        builder.enter_page_mode();
+        for region in &self.regions {
+            builder
+                .page_area(region.x, region.y, region.width, region.height)
+                .page_direction(region.direction);
+            region
+                .content
+                .render_in(builder, region.line_dots(), &RenderStyle::default())?;
+        }
+        builder.print_page_and_exit();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, CutType, JobBuilder};
+    use alloc::vec;
+
+    #[test]
+    fn test_render() {
+        let canvas = Canvas::new()
+            .region(Region::new(0, 0, 120, 48, Receipt::new().line("upright")))
+            .region(
+                Region::new(120, 0, 48, 96, Receipt::new().line("sideways"))
+                    .direction(PageDirection::TopToBottom),
+            );
+        let mut builder = JobBuilder::new();
+        canvas.render(&mut builder).unwrap();
+        let commands = builder.build().commands().to_vec();
+        assert_eq!(
+            commands,
+            vec![
+                Command::EnterPageMode,
+                Command::PageArea {
+                    x: 0,
+                    y: 0,
+                    width: 120,
+                    height: 48
+                },
+                Command::PageDirection(PageDirection::LeftToRight),
+                Command::Raw(b"upright".to_vec()),
+                Command::Print,
+                Command::PageArea {
+                    x: 120,
+                    y: 0,
+                    width: 48,
+                    height: 96
+                },
+                Command::PageDirection(PageDirection::TopToBottom),
+                // the lines run along the height of the area
+                Command::Raw(b"sideways".to_vec()),
+                Command::Print,
+                Command::PrintPageAndExit,
+            ]
+        );
+
+        let cut = Canvas::new().region(Region::new(
+            0,
+            0,
+            8,
+            8,
+            Receipt::new().cut(CutType::TotalCut),
+        ));
+        assert!(matches!(
+            cut.render(&mut JobBuilder::new()),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+}
@@ -0,0 +1,332 @@
+//! Dates and times of the receipts, and the headers of the stores printing them.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use super::{Align, Element, Span};
+
+/// Format of the dates and times of the receipts, following the conventions of a locale in a time zone, e.g.
+/// `03/14/2026 09:05 PM` in the United States or `14.03.2026 21:05` in Germany.
+///
+/// The `pattern` is copied to the text but for its fields:
+///
+/// * `%Y` the year, `%y` its last 2 digits
+/// * `%m` the month from `01`, `%B` its name and `%b` its first 3 characters
+/// * `%d` the day of the month from `01`, `%A` the name of the day of the week and `%a` its first 3 characters
+/// * `%H` the hour from `00` to `23`, `%I` from `01` to `12` and `%p` `AM` or `PM`
+/// * `%M` the minutes and `%S` the seconds
+/// * `%z` the offset from UTC, e.g. `+0100`, and `%%` a `%`
+///
+/// The times are Unix timestamps, shifted by a fixed offset from UTC, as the crate has no database of the time
+/// zones: the offset in effect at the time, e.g. of the daylight saving time, should be set by the caller. Like the
+/// currency symbols of [`MoneyFormat`](super::MoneyFormat), the names of the presets are in ASCII.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::DateFormat;
+/// // 2026-03-14 20:05:09 UTC
+/// let time = 1_773_518_709;
+/// assert_eq!(DateFormat::default().format(time), "2026-03-14 20:05");
+/// assert_eq!(DateFormat::en_us().utc_offset(-4 * 60).format(time), "03/14/2026 04:05 PM");
+/// assert_eq!(DateFormat::de_de().utc_offset(60).format(time), "14.03.2026 21:05");
+/// assert_eq!(
+///     DateFormat::fr_fr().pattern("%A %d %B %Y, %Hh%M").utc_offset(60).format(time),
+///     "samedi 14 mars 2026, 21h05"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateFormat {
+    /// Pattern of the text, see above
+    pub pattern: String,
+    /// Offset of the time zone from UTC in minutes, e.g. 60 for the Central European Time
+    pub utc_offset: i16,
+    /// Names of the months from January
+    pub months: [String; 12],
+    /// Names of the days of the week from Monday
+    pub weekdays: [String; 7],
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        Self::new(
+            "%Y-%m-%d %H:%M",
+            [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ],
+            [
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+                "Sunday",
+            ],
+        )
+    }
+}
+
+impl DateFormat {
+    fn new(pattern: &str, months: [&str; 12], weekdays: [&str; 7]) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            utc_offset: 0,
+            months: months.map(str::to_string),
+            weekdays: weekdays.map(str::to_string),
+        }
+    }
+
+    /// The United States, e.g. `03/14/2026 09:05 PM`.
+    pub fn en_us() -> Self {
+        Self::default().pattern("%m/%d/%Y %I:%M %p")
+    }
+
+    /// Germany, e.g. `14.03.2026 21:05`.
+    pub fn de_de() -> Self {
+        Self::new(
+            "%d.%m.%Y %H:%M",
+            [
+                "Januar",
+                "Februar",
+                "Maerz",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ],
+            [
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+                "Sonntag",
+            ],
+        )
+    }
+
+    /// France, e.g. `14/03/2026 21:05`.
+    pub fn fr_fr() -> Self {
+        Self::new(
+            "%d/%m/%Y %H:%M",
+            [
+                "janvier",
+                "fevrier",
+                "mars",
+                "avril",
+                "mai",
+                "juin",
+                "juillet",
+                "aout",
+                "septembre",
+                "octobre",
+                "novembre",
+                "decembre",
+            ],
+            [
+                "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+            ],
+        )
+    }
+
+    /// The format of `pattern`.
+    pub fn pattern(self, pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ..self
+        }
+    }
+
+    /// The format in the time zone `utc_offset` minutes from UTC.
+    pub fn utc_offset(self, utc_offset: i16) -> Self {
+        Self { utc_offset, ..self }
+    }
+
+    /// Format the Unix `timestamp`, the seconds since 1970-01-01 00:00:00 UTC, in the time zone of the format.
+    pub fn format(&self, timestamp: i64) -> String {
+        let local = timestamp.saturating_add(self.utc_offset as i64 * 60);
+        let (days, seconds) = (local.div_euclid(86_400), local.rem_euclid(86_400));
+        let (year, month, day) = civil(days);
+        // 1970-01-01 was a Thursday
+        let weekday = (days + 3).rem_euclid(7) as usize;
+        let (hour, minute, second) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+        let month_name = &self.months[month as usize - 1];
+        let weekday_name = &self.weekdays[weekday];
+
+        let mut text = String::new();
+        let mut chars = self.pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+            // writing to a string never fails
+            let _ = match chars.next() {
+                Some('Y') => write!(text, "{year}"),
+                Some('y') => write!(text, "{:02}", year.rem_euclid(100)),
+                Some('m') => write!(text, "{month:02}"),
+                Some('B') => write!(text, "{month_name}"),
+                Some('b') => write!(text, "{}", abbreviation(month_name)),
+                Some('d') => write!(text, "{day:02}"),
+                Some('A') => write!(text, "{weekday_name}"),
+                Some('a') => write!(text, "{}", abbreviation(weekday_name)),
+                Some('H') => write!(text, "{hour:02}"),
+                Some('I') => write!(text, "{:02}", (hour + 11) % 12 + 1),
+                Some('p') => write!(text, "{}", if hour < 12 { "AM" } else { "PM" }),
+                Some('M') => write!(text, "{minute:02}"),
+                Some('S') => write!(text, "{second:02}"),
+                Some('z') => {
+                    let sign = if self.utc_offset < 0 { '-' } else { '+' };
+                    let offset = self.utc_offset.unsigned_abs();
+                    write!(text, "{sign}{:02}{:02}", offset / 60, offset % 60)
+                }
+                Some('%') | None => write!(text, "%"),
+                Some(other) => write!(text, "%{other}"),
+            };
+        }
+
+        text
+    }
+}
+
+/// The first 3 characters of `name`.
+fn abbreviation(name: &str) -> &str {
+    name.char_indices()
+        .nth(3)
+        .map_or(name, |(end, _)| &name[..end])
+}
+
+/// The year, month and day of the `days` since 1970-01-01 in the proleptic Gregorian calendar.
+fn civil(days: i64) -> (i64, u32, u32) {
+    // days since 0000-03-01, so the leap days end the years of 400 years
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    (year, month, day)
+}
+
+/// The header of the receipts of a store, its name and address above the date and time of each receipt, see
+/// [`Receipt::date_header()`](super::Receipt::date_header()).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DateHeader {
+    /// Name of the store, left out if empty
+    pub store: String,
+    /// Lines of the address of the store
+    pub address: Vec<String>,
+    /// Format of the date and time
+    pub format: DateFormat,
+}
+
+impl DateHeader {
+    /// The header of the store `store`, without address, the time formatted by the default [`DateFormat`].
+    pub fn new(store: impl Into<String>) -> Self {
+        Self {
+            store: store.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The header with the line `line` appended to the address.
+    pub fn address_line(mut self, line: impl Into<String>) -> Self {
+        self.address.push(line.into());
+
+        self
+    }
+
+    /// The header formatting the time by `format`.
+    pub fn format(self, format: DateFormat) -> Self {
+        Self { format, ..self }
+    }
+}
+
+/// The elements of `header` at the Unix `timestamp`: the store in a level 1 heading, the lines of its address and
+/// the date and time followed by a blank line, all centered.
+pub(super) fn header_elements(header: &DateHeader, timestamp: i64) -> Vec<Element> {
+    let mut elements = Vec::new();
+    if !header.store.is_empty() {
+        elements.push(Element::Heading {
+            level: 1,
+            text: header.store.clone(),
+            align: Align::Center,
+        });
+    }
+    elements.extend(header.address.iter().map(|line| Element::Text {
+        spans: Vec::from([Span::plain(line.as_str())]),
+        indent: 0,
+        align: Align::Center,
+    }));
+    elements.push(Element::Header(header.format.format(timestamp)));
+
+    elements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil() {
+        assert_eq!(civil(0), (1970, 1, 1));
+        assert_eq!(civil(-1), (1969, 12, 31));
+        // the leap days
+        assert_eq!(civil(11_016), (2000, 2, 29));
+        assert_eq!(civil(19_782), (2024, 2, 29));
+        assert_eq!(civil(-25_508), (1900, 3, 1));
+    }
+
+    #[test]
+    fn test_format() {
+        let format = DateFormat::default().pattern("%a %d %b %y %I:%M:%S %p %z %% %q");
+        assert_eq!(format.format(0), "Thu 01 Jan 70 12:00:00 AM +0000 % %q");
+        // the offset moves the date back across the new year
+        assert_eq!(
+            format.utc_offset(-90).format(0),
+            "Wed 31 Dec 69 10:30:00 PM -0130 % %q"
+        );
+    }
+
+    #[test]
+    fn test_header_elements() {
+        let header = DateHeader::new("").address_line("Via Roma 1");
+        assert_eq!(
+            header_elements(&header, 86_400),
+            [
+                Element::Text {
+                    spans: Vec::from([Span::plain("Via Roma 1")]),
+                    indent: 0,
+                    align: Align::Center,
+                },
+                Element::Header("1970-01-02 00:00".to_string()),
+            ]
+        );
+    }
+}
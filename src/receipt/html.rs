@@ -0,0 +1,544 @@
+//! Rendering of a subset of HTML into receipts.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{Align, Column, Element, HeaderStyle, Receipt, Span, Table, TextStyle};
+
+/// Render `html` into a [`Receipt`], easing the migration of receipts printed by browsers.
+///
+/// The subset of HTML is mapped to the printer styles as follows:
+///
+/// * `<p>` and `<div>`: [`Element::Text`] of their text, the whitespace being collapsed. `<b>` and `<strong>` are
+///   printed in bold, `<u>`, `<i>` and `<em>` underlined as thermal printers have no italics, and `<br>` breaks
+///   the line.
+/// * `<h1>`, `<h2>` and `<h3>`: [`Element::Heading`]s of their level
+/// * `<center>`: its text and headings centered. The `align` attribute of `<p>`, `<div>` and the headings, e.g.
+///   `align="right"`, aligns them likewise.
+/// * `<table>`: an [`Element::Table`] of columns sharing the width of the line, its first row of `<th>` cells being
+///   the bold header. The `align` attribute of the first cell of a column aligns the column.
+/// * `<img>`: an [`Element::Image`] of the image file of its `src` attribute (`image` feature), or its `alt`
+///   text without the `image` feature
+/// * `<hr>`: an [`Element::Rule`] of `-`
+///
+/// Comments and the content of `<head>`, `<style>` and `<script>` are left out, and the other tags are ignored,
+/// their content being printed as text. The entities `&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`, `&nbsp;` and
+/// the numeric ones are decoded.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{render_html, Emulator};
+/// let receipt = render_html(
+///     "<center><h3>CAFE ROMA</h3></center>
+///      <table>
+///        <tr><th>Item</th><th align=\"right\">Price</th></tr>
+///        <tr><td>Coffee</td><td>2.50</td></tr>
+///      </table>
+///      <hr>
+///      <p>Paid by <b>card</b></p>",
+/// );
+///
+/// let mut emulator = Emulator::new();
+/// receipt.render(&mut emulator).unwrap();
+/// let paper = emulator.render();
+/// let lines: Vec<_> = paper.lines().collect();
+/// assert_eq!(lines[0], format!("{}CAFE ROMA", " ".repeat(19)));
+/// assert_eq!(lines[1], format!("Item{}Price", " ".repeat(38)));
+/// assert_eq!(lines[2], format!("Coffee{}2.50", " ".repeat(37)));
+/// assert_eq!(lines[4], "Paid by card");
+/// ```
+pub fn render_html(html: &str) -> Receipt {
+    let mut parser = Parser::default();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        parser.text(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let is_tag = rest[1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!');
+        let Some(end) = rest.find('>').filter(|_| is_tag) else {
+            parser.text("<");
+            rest = &rest[1..];
+            continue;
+        };
+        let tag = Tag::parse(&rest[1..end]);
+        rest = &rest[end + 1..];
+
+        if !tag.closing && matches!(tag.name.as_str(), "head" | "style" | "script") {
+            // skip the content up to the closing tag
+            let closing = ["</", &tag.name].concat();
+            rest = find_ignore_case(rest, &closing).map_or("", |start| {
+                rest[start..]
+                    .find('>')
+                    .map_or("", |end| &rest[start + end + 1..])
+            });
+            continue;
+        }
+        parser.tag(&tag);
+    }
+    parser.text(rest);
+    parser.flush();
+
+    parser.receipt
+}
+
+/// An opening or closing tag.
+struct Tag {
+    /// Name of the tag in lowercase
+    name: String,
+    closing: bool,
+    attributes: Vec<(String, String)>,
+}
+
+impl Tag {
+    /// Parse the text of a tag between `<` and `>`.
+    fn parse(text: &str) -> Self {
+        let text = text.trim_end_matches('/');
+        let (closing, text) = match text.strip_prefix('/') {
+            Some(text) => (true, text),
+            None => (false, text),
+        };
+        let name_len = text.find(|c: char| c.is_whitespace()).unwrap_or(text.len());
+
+        let mut attributes = Vec::new();
+        let mut rest = text[name_len..].trim_start();
+        while !rest.is_empty() {
+            let key_len = rest
+                .find(|c: char| c == '=' || c.is_whitespace())
+                .unwrap_or(rest.len());
+            let key = rest[..key_len].to_ascii_lowercase();
+            rest = rest[key_len..].trim_start();
+            let value = match rest.strip_prefix('=').map(str::trim_start) {
+                Some(value) => {
+                    let (value, after) = match value.chars().next() {
+                        Some(quote @ ('"' | '\'')) => {
+                            let value = &value[1..];
+                            let end = value.find(quote).unwrap_or(value.len());
+                            (&value[..end], value.get(end + 1..).unwrap_or(""))
+                        }
+                        _ => {
+                            let end = value
+                                .find(|c: char| c.is_whitespace())
+                                .unwrap_or(value.len());
+                            (&value[..end], &value[end..])
+                        }
+                    };
+                    rest = after.trim_start();
+                    decode_entities(value)
+                }
+                None => String::new(),
+            };
+            attributes.push((key, value));
+        }
+
+        Self {
+            name: text[..name_len].to_ascii_lowercase(),
+            closing,
+            attributes,
+        }
+    }
+
+    fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Alignment of the `align` attribute.
+    fn align(&self) -> Option<Align> {
+        match self.attribute("align")?.to_ascii_lowercase().as_str() {
+            "left" => Some(Align::Left),
+            "center" => Some(Align::Center),
+            "right" => Some(Align::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Cells of the table being parsed, each with its text, whether it's a `<th>` and its alignment.
+type Row = Vec<(String, bool, Option<Align>)>;
+
+#[derive(Default)]
+struct Parser {
+    receipt: Receipt,
+    /// Text of the block being parsed
+    spans: Vec<Span>,
+    /// Level of the heading being parsed
+    heading: Option<u8>,
+    /// Alignment of the block being parsed by its `align` attribute
+    align: Option<Align>,
+    // depths of the nested tags
+    bold: usize,
+    underline: usize,
+    center: usize,
+    /// Rows of the table being parsed, and whether a cell is open
+    table: Option<(Vec<Row>, bool)>,
+}
+
+impl Parser {
+    fn tag(&mut self, tag: &Tag) {
+        match (tag.name.as_str(), tag.closing) {
+            ("b" | "strong", closing) => self.bold = depth(self.bold, closing),
+            ("u" | "i" | "em", closing) => self.underline = depth(self.underline, closing),
+            ("center", closing) => {
+                self.flush();
+                self.center = depth(self.center, closing);
+            }
+            ("p" | "div", closing) => {
+                self.flush();
+                if !closing {
+                    self.align = tag.align();
+                }
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                self.flush();
+                self.heading = Some(tag.name.as_bytes()[1] - b'0');
+                self.align = tag.align();
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => self.flush(),
+            ("br", _) => match &mut self.table {
+                Some((rows, true)) => push_text(&mut rows_cell(rows).0, " "),
+                _ if self.heading.is_some() => self.text(" "),
+                _ => {
+                    let spans = core::mem::take(&mut self.spans);
+                    self.push_text(spans);
+                }
+            },
+            ("hr", _) => {
+                self.flush();
+                self.receipt.elements.push(Element::Rule('-'));
+            }
+            ("img", _) => {
+                self.flush();
+                #[cfg(feature = "image")]
+                if let Some(src) = tag.attribute("src").filter(|src| !src.is_empty()) {
+                    self.receipt.elements.push(Element::Image {
+                        path: src.into(),
+                        mode: crate::BitImageMode::Dots24DoubleDensity,
+                    });
+                }
+                #[cfg(not(feature = "image"))]
+                if let Some(alt) = tag.attribute("alt") {
+                    self.text(alt);
+                    self.flush();
+                }
+            }
+            ("table", false) => {
+                self.flush();
+                self.table = Some((Vec::new(), false));
+            }
+            ("table", true) => {
+                if let Some((rows, _)) = self.table.take() {
+                    self.push_table(rows);
+                }
+            }
+            ("tr", false) => {
+                if let Some((rows, open)) = &mut self.table {
+                    rows.push(Vec::new());
+                    *open = false;
+                }
+            }
+            ("th" | "td", false) => {
+                if let Some((rows, open)) = &mut self.table {
+                    if rows.is_empty() {
+                        rows.push(Vec::new());
+                    }
+                    let row = rows.last_mut().unwrap();
+                    row.push((String::new(), tag.name == "th", tag.align()));
+                    *open = true;
+                }
+            }
+            ("tr" | "th" | "td", true) => {
+                if let Some((_, open)) = &mut self.table {
+                    *open = false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Append the text of a text node, collapsing its whitespace.
+    fn text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let text = decode_entities(text);
+        match &mut self.table {
+            Some((rows, true)) => push_text(&mut rows_cell(rows).0, &text),
+            // the text between the cells is left out
+            Some((_, false)) => {}
+            None => {
+                let style = TextStyle {
+                    bold: self.bold > 0,
+                    underline: self.underline > 0,
+                };
+                let spaced = self.spans.iter().all(|span| span.text.is_empty())
+                    || self.spans.iter().any(|span| span.text.ends_with(' '));
+                match self.spans.last_mut() {
+                    Some(span) if span.style == style => push_text(&mut span.text, &text),
+                    _ => {
+                        let mut span = Span::new(String::new(), style);
+                        // keep a space between the spans, unless the block is still empty
+                        if !spaced && text.starts_with(char::is_whitespace) {
+                            span.text.push(' ');
+                        }
+                        push_text(&mut span.text, &text);
+                        self.spans.push(span);
+                    }
+                }
+            }
+        }
+    }
+
+    fn block_align(&self) -> Align {
+        match self.align {
+            Some(align) => align,
+            None if self.center > 0 => Align::Center,
+            None => Align::Left,
+        }
+    }
+
+    /// Append the block being parsed, if it has any text.
+    fn flush(&mut self) {
+        let spans = core::mem::take(&mut self.spans);
+        let align = self.block_align();
+        if let Some(level) = self.heading.take() {
+            let text: String = spans.iter().map(|span| span.text.as_str()).collect();
+            if !text.trim().is_empty() {
+                self.receipt.elements.push(Element::Heading {
+                    level,
+                    text: text.trim().to_string(),
+                    align,
+                });
+            }
+        } else if spans.iter().any(|span| !span.text.trim().is_empty()) {
+            self.push_text(spans);
+        }
+        self.align = None;
+    }
+
+    /// Append a line of `spans`, even if it's empty, without the whitespace around it.
+    fn push_text(&mut self, mut spans: Vec<Span>) {
+        if let Some(span) = spans.first_mut() {
+            span.text = span.text.trim_start().to_string();
+        }
+        if let Some(span) = spans.last_mut() {
+            span.text = span.text.trim_end().to_string();
+        }
+        spans.retain(|span| !span.text.is_empty());
+        let align = self.block_align();
+        self.receipt.elements.push(Element::Text {
+            spans,
+            indent: 0,
+            align,
+        });
+    }
+
+    fn push_table(&mut self, rows: Vec<Row>) {
+        let mut rows: Vec<Row> = rows.into_iter().filter(|row| !row.is_empty()).collect();
+        let count = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if count == 0 {
+            return;
+        }
+        for row in &mut rows {
+            row.resize(count, (String::new(), false, None));
+        }
+
+        let columns: Vec<Column> = (0..count)
+            .map(|i| {
+                let align = rows.iter().find_map(|row| row[i].2).unwrap_or_default();
+                Column::weight(1).align(align)
+            })
+            .collect();
+        let mut table = Table::new(columns);
+        let cells = |row: Row| row.into_iter().map(|(text, _, _)| text.trim().to_string());
+        if rows[0].iter().all(|(_, th, _)| *th) {
+            table = table
+                .header(cells(rows.remove(0)))
+                .header_style(HeaderStyle {
+                    bold: true,
+                    ..Default::default()
+                });
+        }
+        for row in rows {
+            table = table.row(cells(row));
+        }
+        self.receipt.elements.push(Element::Table(table));
+    }
+}
+
+/// Depth of a nested tag after its opening or closing tag.
+fn depth(depth: usize, closing: bool) -> usize {
+    if closing {
+        depth.saturating_sub(1)
+    } else {
+        depth + 1
+    }
+}
+
+/// The last cell of the table being parsed.
+fn rows_cell(rows: &mut [Row]) -> &mut (String, bool, Option<Align>) {
+    let row = rows.last_mut().expect("an open cell is in a row");
+
+    row.last_mut().expect("an open cell is in its row")
+}
+
+/// Append `text` to `out`, collapsing the whitespace into single spaces.
+fn push_text(out: &mut String, text: &str) {
+    for c in text.chars() {
+        if !c.is_whitespace() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with(' ') {
+            out.push(' ');
+        }
+    }
+}
+
+/// `text` with its entities decoded.
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|end| *end <= 10).and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                entity => {
+                    let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
+
+/// Position of `needle`, in lowercase ASCII, in `haystack` ignoring the case.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_render_html() {
+        let receipt = render_html(
+            "<html><head><title>Receipt</title><style>p { margin: 0 }</style></head>
+             <body>
+             <!-- the store -->
+             <CENTER><H1>Cafe</H1>Via <i>Roma</i>, 1<br>Open 7&ndash;22 &amp; &#x2A;</center>
+             <p align=right>Table <b>3</b><br><br>x &lt; y</p>
+             <table><tr><td>Coffee</td><td align='right'>2.50</td></tr><tr><td>Tea</td></tr></table>
+             <hr/>
+             <img alt=\"logo\" src=\"logo.png\">
+             </body></html>",
+        );
+        let underline = TextStyle {
+            underline: true,
+            ..Default::default()
+        };
+        let mut expected = vec![
+            Element::Heading {
+                level: 1,
+                text: "Cafe".into(),
+                align: Align::Center,
+            },
+            Element::Text {
+                spans: vec![
+                    Span::plain("Via "),
+                    Span::new("Roma", underline),
+                    Span::plain(", 1"),
+                ],
+                indent: 0,
+                align: Align::Center,
+            },
+            Element::Text {
+                spans: vec![Span::plain("Open 7&ndash;22 & *")],
+                indent: 0,
+                align: Align::Center,
+            },
+            Element::Text {
+                spans: vec![Span::plain("Table "), Span::bold("3")],
+                indent: 0,
+                align: Align::Right,
+            },
+            Element::Text {
+                spans: vec![],
+                indent: 0,
+                align: Align::Right,
+            },
+            Element::Text {
+                spans: vec![Span::plain("x < y")],
+                indent: 0,
+                align: Align::Right,
+            },
+            Element::Table(
+                Table::new([Column::weight(1), Column::weight(1).align(Align::Right)])
+                    .row(["Coffee", "2.50"])
+                    .row(["Tea", ""]),
+            ),
+            Element::Rule('-'),
+        ];
+        #[cfg(feature = "image")]
+        expected.push(Element::Image {
+            path: "logo.png".into(),
+            mode: crate::BitImageMode::Dots24DoubleDensity,
+        });
+        #[cfg(not(feature = "image"))]
+        expected.push(Element::Text {
+            spans: vec![Span::plain("logo")],
+            indent: 0,
+            align: Align::Left,
+        });
+        assert_eq!(receipt.elements(), expected);
+
+        assert_eq!(
+            render_html("a < b <c>d").elements(),
+            [Element::Text {
+                spans: vec![Span::plain("a < b d")],
+                indent: 0,
+                align: Align::Left
+            }]
+        );
+    }
+}
@@ -0,0 +1,1012 @@
+//! Versioned JSON schema of the receipts.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{
+    Align, BorderStyle, Canvas, Column, ColumnWidth, Element, HeaderStyle, Layout, LayoutMode,
+    LayoutNode, List, ListItem, ListStyle, Paragraph, Receipt, Region, Separator, SeparatorStyle,
+    Span, Table, TableMode, TextBox, TextStyle, Track, TwoColumns, Watermark, WatermarkImage,
+};
+use crate::{
+    command::json::{named, Fields, Named},
+    json::{self, Value},
+    Error, Length,
+};
+
+/// Version of the JSON schema of receipts written by [`Receipt::to_json()`].
+pub const RECEIPT_FORMAT_VERSION: u32 = 1;
+
+named!(Align { Left => "left", Center => "center", Right => "right" });
+named!(TableMode { Text => "text", BitImage => "bit_image" });
+named!(BorderStyle { Line => "line", Ascii => "ascii" });
+named!(LayoutMode { PageMode => "page_mode", BitImage => "bit_image" });
+
+/// An object of the element `kind` with its `fields`.
+fn tagged<const N: usize>(kind: &str, fields: [(&str, Value); N]) -> Value {
+    let mut members = Vec::from([("type".to_string(), Value::from(kind))]);
+    members.extend(
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value)),
+    );
+
+    Value::Object(members)
+}
+
+/// The field `key` of `fields`, unless it's missing or null.
+fn optional<'a>(fields: &Fields<'a>, key: &str) -> Option<&'a Value> {
+    fields.0.get(key).filter(|value| **value != Value::Null)
+}
+
+fn align_or_left(fields: &Fields, key: &str) -> Result<Align, String> {
+    match optional(fields, key) {
+        Some(_) => fields.named(key),
+        None => Ok(Align::Left),
+    }
+}
+
+fn bool_or_false(fields: &Fields, key: &str) -> Result<bool, String> {
+    match optional(fields, key) {
+        Some(_) => fields.bool(key),
+        None => Ok(false),
+    }
+}
+
+fn char(fields: &Fields, key: &str) -> Result<char, String> {
+    let mut chars = fields.str(key)?.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(Fields::invalid(key)),
+    }
+}
+
+fn strings(fields: &Fields, key: &str) -> Result<Vec<String>, String> {
+    fields
+        .get(key)?
+        .as_array()
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|value| value.as_str().map(str::to_string))
+                .collect()
+        })
+        .ok_or_else(|| Fields::invalid(key))
+}
+
+fn strings_to_json(strings: &[String]) -> Value {
+    Value::Array(
+        strings
+            .iter()
+            .map(|string| string.as_str().into())
+            .collect(),
+    )
+}
+
+fn span_to_json(span: &Span) -> Value {
+    json::object([
+        ("text", span.text.as_str().into()),
+        ("bold", span.style.bold.into()),
+        ("underline", span.style.underline.into()),
+    ])
+}
+
+fn span_from_json(value: &Value) -> Result<Span, String> {
+    let fields = Fields(value);
+
+    Ok(Span::new(
+        fields.str("text")?,
+        TextStyle {
+            bold: bool_or_false(&fields, "bold")?,
+            underline: bool_or_false(&fields, "underline")?,
+        },
+    ))
+}
+
+fn column_to_json(column: &Column) -> Value {
+    let (key, value) = match column.width {
+        ColumnWidth::Chars(chars) => ("chars", chars),
+        ColumnWidth::Dots(dots) => ("dots", dots),
+        ColumnWidth::Weight(weight) => ("weight", weight),
+    };
+
+    json::object([(key, value.into()), ("align", column.align.name().into())])
+}
+
+fn column_from_json(value: &Value) -> Result<Column, String> {
+    let fields = Fields(value);
+    let width = if optional(&fields, "chars").is_some() {
+        ColumnWidth::Chars(fields.int("chars")?)
+    } else if optional(&fields, "dots").is_some() {
+        ColumnWidth::Dots(fields.int("dots")?)
+    } else {
+        ColumnWidth::Weight(fields.int("weight")?)
+    };
+
+    Ok(Column {
+        width,
+        align: align_or_left(&fields, "align")?,
+    })
+}
+
+fn table_to_json(table: &Table) -> Value {
+    let style = table.header_style;
+
+    json::object([
+        (
+            "columns",
+            Value::Array(table.columns.iter().map(column_to_json).collect()),
+        ),
+        (
+            "header",
+            table.header.as_deref().map_or(Value::Null, strings_to_json),
+        ),
+        (
+            "header_style",
+            json::object([
+                ("bold", style.bold.into()),
+                ("underline", style.underline.into()),
+                ("rule", style.rule.into()),
+            ]),
+        ),
+        (
+            "rows",
+            Value::Array(table.rows.iter().map(|row| strings_to_json(row)).collect()),
+        ),
+        ("mode", table.mode.name().into()),
+    ])
+}
+
+fn table_from_json(fields: &Fields) -> Result<Table, String> {
+    let columns = fields
+        .get("columns")?
+        .as_array()
+        .ok_or_else(|| Fields::invalid("columns"))?
+        .iter()
+        .map(column_from_json)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut table = Table::new(columns);
+    if optional(fields, "header").is_some() {
+        table = table.header(strings(fields, "header")?);
+    }
+    if let Some(style) = optional(fields, "header_style") {
+        let style = Fields(style);
+        table = table.header_style(HeaderStyle {
+            bold: bool_or_false(&style, "bold")?,
+            underline: bool_or_false(&style, "underline")?,
+            rule: bool_or_false(&style, "rule")?,
+        });
+    }
+    if optional(fields, "mode").is_some() {
+        table = table.mode(fields.named("mode")?);
+    }
+    for row in fields
+        .get("rows")?
+        .as_array()
+        .ok_or_else(|| Fields::invalid("rows"))?
+    {
+        let cells = row
+            .as_array()
+            .and_then(|cells| cells.iter().map(Value::as_str).collect::<Option<Vec<_>>>())
+            .ok_or_else(|| Fields::invalid("rows"))?;
+        table = table.row(cells);
+    }
+
+    Ok(table)
+}
+
+fn list_to_json(list: &List) -> Value {
+    let (style, marker) = match list.style {
+        ListStyle::Bullet(bullet) => ("bullet", ("bullet", bullet.to_string().as_str().into())),
+        ListStyle::Numbered(start) => ("numbered", ("start", start.into())),
+    };
+    let items = list.items.iter().map(|item| {
+        json::object([
+            ("text", item.text.as_str().into()),
+            (
+                "list",
+                item.sublist.as_ref().map_or(Value::Null, list_to_json),
+            ),
+        ])
+    });
+
+    json::object([
+        ("style", style.into()),
+        marker,
+        ("indent", list.indent.into()),
+        ("items", Value::Array(items.collect())),
+    ])
+}
+
+fn list_from_json(fields: &Fields) -> Result<List, String> {
+    let style = match fields.str("style")? {
+        "bullet" => ListStyle::Bullet(char(fields, "bullet")?),
+        "numbered" => ListStyle::Numbered(fields.int("start")?),
+        _ => return Err(Fields::invalid("style")),
+    };
+    let mut list = List::new(style);
+    if optional(fields, "indent").is_some() {
+        list = list.indent(fields.int("indent")?);
+    }
+    for item in fields
+        .get("items")?
+        .as_array()
+        .ok_or_else(|| Fields::invalid("items"))?
+    {
+        let item = Fields(item);
+        list.items.push(ListItem {
+            text: item.str("text")?.to_string(),
+            sublist: optional(&item, "list")
+                .map(|list| list_from_json(&Fields(list)))
+                .transpose()?,
+        });
+    }
+
+    Ok(list)
+}
+
+fn elements_to_json(content: &Receipt) -> Value {
+    Value::Array(content.elements.iter().map(Element::to_json).collect())
+}
+
+/// The receipt of the `elements` of `fields`, e.g. the content of a region.
+fn elements_from_json(fields: &Fields) -> Result<Receipt, String> {
+    let elements = fields
+        .get("elements")?
+        .as_array()
+        .ok_or_else(|| Fields::invalid("elements"))?
+        .iter()
+        .enumerate()
+        .map(|(index, element)| {
+            Element::from_json(element).map_err(|message| format!("element {index}: {message}"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Receipt {
+        elements,
+        ..Default::default()
+    })
+}
+
+fn watermark_to_json(watermark: &Watermark) -> Value {
+    let shade = ("shade", watermark.shade.into());
+    match &watermark.image {
+        WatermarkImage::Text(text) => json::object([("text", text.as_str().into()), shade]),
+        WatermarkImage::Bitmap {
+            width,
+            height,
+            bitmap,
+        } => json::object([
+            ("width", (*width).into()),
+            ("height", (*height).into()),
+            ("bitmap", json::to_hex(bitmap).into()),
+            shade,
+        ]),
+    }
+}
+
+fn watermark_from_json(fields: &Fields) -> Result<Watermark, String> {
+    let watermark = match optional(fields, "text") {
+        Some(_) => Watermark::text(fields.str("text")?),
+        None => Watermark::bitmap(
+            fields.int("width")?,
+            fields.int("height")?,
+            fields.hex("bitmap")?,
+        ),
+    };
+
+    Ok(watermark.shade(fields.int("shade")?))
+}
+
+fn region_to_json(region: &Region) -> Value {
+    json::object([
+        ("x", region.x.into()),
+        ("y", region.y.into()),
+        ("width", region.width.into()),
+        ("height", region.height.into()),
+        ("direction", region.direction.name().into()),
+        ("elements", elements_to_json(&region.content)),
+    ])
+}
+
+fn region_from_json(value: &Value) -> Result<Region, String> {
+    let fields = Fields(value);
+    let mut region = Region::new(
+        fields.int("x")?,
+        fields.int("y")?,
+        fields.int("width")?,
+        fields.int("height")?,
+        elements_from_json(&fields)?,
+    );
+    if optional(&fields, "direction").is_some() {
+        region = region.direction(fields.named("direction")?);
+    }
+
+    Ok(region)
+}
+
+fn node_to_json(node: &LayoutNode) -> Value {
+    let cells = |cells: &[(Track, LayoutNode)]| {
+        Value::Array(
+            cells
+                .iter()
+                .map(|(track, node)| {
+                    let (key, value) = match track {
+                        Track::Fixed(dots) => ("fixed", dots),
+                        Track::Flex(weight) => ("flex", weight),
+                    };
+                    json::object([(key, (*value).into()), ("node", node_to_json(node))])
+                })
+                .collect(),
+        )
+    };
+
+    match node {
+        LayoutNode::Content(content) => json::object([("elements", elements_to_json(content))]),
+        LayoutNode::Row(row) => json::object([("row", cells(row))]),
+        LayoutNode::Column(column) => json::object([("column", cells(column))]),
+    }
+}
+
+fn node_from_json(value: &Value) -> Result<LayoutNode, String> {
+    let fields = Fields(value);
+    let cells = |key: &str| -> Result<Vec<(Track, LayoutNode)>, String> {
+        fields
+            .get(key)?
+            .as_array()
+            .ok_or_else(|| Fields::invalid(key))?
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| {
+                let cell = Fields(cell);
+                let track = if optional(&cell, "fixed").is_some() {
+                    Track::Fixed(cell.int("fixed")?)
+                } else {
+                    Track::Flex(cell.int("flex")?)
+                };
+                node_from_json(cell.get("node")?)
+                    .map(|node| (track, node))
+                    .map_err(|message| format!("cell {index}: {message}"))
+            })
+            .collect()
+    };
+
+    if optional(&fields, "row").is_some() {
+        Ok(LayoutNode::Row(cells("row")?))
+    } else if optional(&fields, "column").is_some() {
+        Ok(LayoutNode::Column(cells("column")?))
+    } else {
+        Ok(LayoutNode::Content(elements_from_json(&fields)?))
+    }
+}
+
+/// The field of the data of a barcode or a QR code, `data` as a string if it's UTF-8, or `hex` in hexadecimal.
+fn data_to_json(data: &[u8]) -> (&'static str, Value) {
+    match core::str::from_utf8(data) {
+        Ok(text) => ("data", text.into()),
+        Err(_) => ("hex", json::to_hex(data).into()),
+    }
+}
+
+fn data_from_json(fields: &Fields) -> Result<Vec<u8>, String> {
+    match optional(fields, "hex") {
+        Some(_) => fields.hex("hex"),
+        None => Ok(fields.str("data")?.as_bytes().to_vec()),
+    }
+}
+
+impl Element {
+    /// The JSON object of the element, tagged by its name in `type`.
+    fn to_json(&self) -> Value {
+        match self {
+            Self::Header(text) => tagged("header", [("text", text.as_str().into())]),
+            Self::Line(text) => tagged("line", [("text", text.as_str().into())]),
+            Self::Heading { level, text, align } => tagged(
+                "heading",
+                [
+                    ("level", (*level).into()),
+                    ("text", text.as_str().into()),
+                    ("align", align.name().into()),
+                ],
+            ),
+            Self::Text {
+                spans,
+                indent,
+                align,
+            } => tagged(
+                "text",
+                [
+                    (
+                        "spans",
+                        Value::Array(spans.iter().map(span_to_json).collect()),
+                    ),
+                    ("indent", (*indent).into()),
+                    ("align", align.name().into()),
+                ],
+            ),
+            Self::Preformatted(text) => tagged("preformatted", [("text", text.as_str().into())]),
+            Self::Rule(c) => tagged("rule", [("char", c.to_string().into())]),
+            Self::Separator(separator) => {
+                let (style, c) = match separator.style {
+                    SeparatorStyle::Solid => ("solid", Value::Null),
+                    SeparatorStyle::Dashed => ("dashed", Value::Null),
+                    SeparatorStyle::Double => ("double", Value::Null),
+                    SeparatorStyle::Char(c) => ("char", c.to_string().into()),
+                };
+                tagged(
+                    "separator",
+                    [
+                        ("style", style.into()),
+                        ("char", c),
+                        ("thickness", separator.thickness.into()),
+                        ("inset", separator.inset.into()),
+                    ],
+                )
+            }
+            Self::Item { name, amount } => tagged(
+                "item",
+                [
+                    ("name", name.as_str().into()),
+                    ("amount", amount.as_str().into()),
+                ],
+            ),
+            Self::KeyValue { label, value, fill } => tagged(
+                "key_value",
+                [
+                    ("label", label.as_str().into()),
+                    ("value", value.as_str().into()),
+                    ("fill", fill.to_string().into()),
+                ],
+            ),
+            Self::Bitmap {
+                width,
+                height,
+                bitmap,
+                mode,
+            } => tagged(
+                "bitmap",
+                [
+                    ("width", (*width).into()),
+                    ("height", (*height).into()),
+                    ("bitmap", json::to_hex(bitmap).into()),
+                    ("mode", mode.name().into()),
+                ],
+            ),
+            #[cfg(feature = "image")]
+            Self::Image { path, mode } => tagged(
+                "image",
+                [
+                    ("path", path.to_string_lossy().as_ref().into()),
+                    ("mode", mode.name().into()),
+                ],
+            ),
+            Self::Barcode { system, data } => tagged(
+                "barcode",
+                [("system", system.name().into()), data_to_json(data)],
+            ),
+            Self::FittedBarcode {
+                system,
+                data,
+                width,
+            } => tagged(
+                "fitted_barcode",
+                [
+                    ("system", system.name().into()),
+                    data_to_json(data),
+                    ("width", (*width).into()),
+                ],
+            ),
+            Self::QrCode {
+                data,
+                error_correction,
+                module_size,
+            } => tagged(
+                "qr_code",
+                [
+                    data_to_json(data),
+                    ("error_correction", error_correction.name().into()),
+                    ("module_size", (*module_size).into()),
+                ],
+            ),
+            Self::Footer(text) => tagged("footer", [("text", text.as_str().into())]),
+            Self::Feed(lines) => tagged("feed", [("lines", (*lines).into())]),
+            Self::Space(length) => tagged("space", [("length", length.to_json())]),
+            Self::Signature { caption, space } => tagged(
+                "signature",
+                [
+                    ("caption", caption.as_str().into()),
+                    ("space", space.to_json()),
+                ],
+            ),
+            Self::Table(table) => tagged("table", [("table", table_to_json(table))]),
+            Self::TwoColumns(columns) => tagged(
+                "two_columns",
+                [
+                    ("left", columns.left.as_str().into()),
+                    ("right", columns.right.as_str().into()),
+                    (
+                        "columns",
+                        Value::Array(columns.columns.iter().map(column_to_json).collect()),
+                    ),
+                ],
+            ),
+            Self::Box(text_box) => tagged(
+                "box",
+                [
+                    ("text", text_box.text.as_str().into()),
+                    ("border", text_box.border.name().into()),
+                    ("padding", text_box.padding.into()),
+                    ("vertical_padding", text_box.vertical_padding.into()),
+                    ("align", text_box.align.name().into()),
+                ],
+            ),
+            Self::List(list) => tagged("list", [("list", list_to_json(list))]),
+            Self::Paragraph(paragraph) => tagged(
+                "paragraph",
+                [
+                    ("text", paragraph.text.as_str().into()),
+                    ("justify", paragraph.justify.into()),
+                    ("hyphenate", paragraph.hyphenate.into()),
+                ],
+            ),
+            Self::Watermarked { watermark, content } => tagged(
+                "watermarked",
+                [
+                    ("watermark", watermark_to_json(watermark)),
+                    ("elements", elements_to_json(content)),
+                ],
+            ),
+            Self::Canvas(canvas) => tagged(
+                "canvas",
+                [(
+                    "regions",
+                    Value::Array(canvas.regions.iter().map(region_to_json).collect()),
+                )],
+            ),
+            Self::Layout(layout) => tagged(
+                "layout",
+                [
+                    ("height", layout.height.into()),
+                    ("mode", layout.mode.name().into()),
+                    ("root", node_to_json(&layout.root)),
+                ],
+            ),
+            Self::Cut(cut_type) => tagged("cut", [("cut_type", cut_type.name().into())]),
+        }
+    }
+
+    /// Parse an element from its JSON object written by [`to_json()`](Element::to_json()), the optional fields,
+    /// e.g. `align`, taking their default values.
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let fields = Fields(value);
+
+        Ok(match fields.str("type")? {
+            "header" => Self::Header(fields.str("text")?.to_string()),
+            "line" => Self::Line(fields.str("text")?.to_string()),
+            "heading" => Self::Heading {
+                level: fields.int("level")?,
+                text: fields.str("text")?.to_string(),
+                align: align_or_left(&fields, "align")?,
+            },
+            "text" => Self::Text {
+                spans: fields
+                    .get("spans")?
+                    .as_array()
+                    .ok_or_else(|| Fields::invalid("spans"))?
+                    .iter()
+                    .map(span_from_json)
+                    .collect::<Result<_, _>>()?,
+                indent: match optional(&fields, "indent") {
+                    Some(_) => fields.int("indent")?,
+                    None => 0,
+                },
+                align: align_or_left(&fields, "align")?,
+            },
+            "preformatted" => Self::Preformatted(fields.str("text")?.to_string()),
+            "rule" => Self::Rule(char(&fields, "char")?),
+            "separator" => {
+                let style = match fields.str("style")? {
+                    "solid" => SeparatorStyle::Solid,
+                    "dashed" => SeparatorStyle::Dashed,
+                    "double" => SeparatorStyle::Double,
+                    "char" => SeparatorStyle::Char(char(&fields, "char")?),
+                    _ => return Err(Fields::invalid("style")),
+                };
+                let mut separator = Separator::new(style);
+                if optional(&fields, "thickness").is_some() {
+                    separator = separator.thickness(fields.int("thickness")?);
+                }
+                if optional(&fields, "inset").is_some() {
+                    separator = separator.inset(fields.int("inset")?);
+                }
+                Self::Separator(separator)
+            }
+            "item" => Self::Item {
+                name: fields.str("name")?.to_string(),
+                amount: fields.str("amount")?.to_string(),
+            },
+            "key_value" => Self::KeyValue {
+                label: fields.str("label")?.to_string(),
+                value: fields.str("value")?.to_string(),
+                fill: match optional(&fields, "fill") {
+                    Some(_) => char(&fields, "fill")?,
+                    None => '.',
+                },
+            },
+            "bitmap" => Self::Bitmap {
+                width: fields.int("width")?,
+                height: fields.int("height")?,
+                bitmap: fields.hex("bitmap")?,
+                mode: fields.named("mode")?,
+            },
+            #[cfg(feature = "image")]
+            "image" => Self::Image {
+                path: fields.str("path")?.into(),
+                mode: fields.named("mode")?,
+            },
+            "barcode" => Self::Barcode {
+                system: fields.named("system")?,
+                data: data_from_json(&fields)?,
+            },
+            "fitted_barcode" => Self::FittedBarcode {
+                system: fields.named("system")?,
+                data: data_from_json(&fields)?,
+                width: fields.int("width")?,
+            },
+            "qr_code" => Self::QrCode {
+                data: data_from_json(&fields)?,
+                error_correction: fields.named("error_correction")?,
+                module_size: fields.int("module_size")?,
+            },
+            "footer" => Self::Footer(fields.str("text")?.to_string()),
+            "feed" => Self::Feed(fields.int("lines")?),
+            "space" => Self::Space(Length::from_json(fields.get("length")?)?),
+            "signature" => Self::Signature {
+                caption: fields.str("caption")?.to_string(),
+                space: Length::from_json(fields.get("space")?)?,
+            },
+            "table" => Self::Table(table_from_json(&Fields(fields.get("table")?))?),
+            "two_columns" => {
+                let mut columns = TwoColumns::new(fields.str("left")?, fields.str("right")?);
+                if optional(&fields, "columns").is_some() {
+                    match fields.get("columns")?.as_array() {
+                        Some([left, right]) => {
+                            columns =
+                                columns.columns(column_from_json(left)?, column_from_json(right)?);
+                        }
+                        _ => return Err(Fields::invalid("columns")),
+                    }
+                }
+                Self::TwoColumns(columns)
+            }
+            "box" => {
+                let mut text_box =
+                    TextBox::new(fields.str("text")?).align(align_or_left(&fields, "align")?);
+                if optional(&fields, "border").is_some() {
+                    text_box = text_box.border(fields.named("border")?);
+                }
+                if optional(&fields, "padding").is_some() {
+                    text_box.padding = fields.int("padding")?;
+                }
+                if optional(&fields, "vertical_padding").is_some() {
+                    text_box.vertical_padding = fields.int("vertical_padding")?;
+                }
+                Self::Box(text_box)
+            }
+            "list" => Self::List(list_from_json(&Fields(fields.get("list")?))?),
+            "paragraph" => Self::Paragraph(
+                Paragraph::new(fields.str("text")?)
+                    .justify(bool_or_false(&fields, "justify")?)
+                    .hyphenate(bool_or_false(&fields, "hyphenate")?),
+            ),
+            "watermarked" => Self::Watermarked {
+                watermark: watermark_from_json(&Fields(fields.get("watermark")?))?,
+                content: elements_from_json(&fields)?,
+            },
+            "canvas" => {
+                let regions = fields
+                    .get("regions")?
+                    .as_array()
+                    .ok_or_else(|| Fields::invalid("regions"))?
+                    .iter()
+                    .enumerate()
+                    .map(|(index, region)| {
+                        region_from_json(region)
+                            .map_err(|message| format!("region {index}: {message}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Self::Canvas(Canvas { regions })
+            }
+            "layout" => {
+                let mut layout =
+                    Layout::new(fields.int("height")?, node_from_json(fields.get("root")?)?);
+                if optional(&fields, "mode").is_some() {
+                    layout = layout.mode(fields.named("mode")?);
+                }
+                Self::Layout(layout)
+            }
+            "cut" => Self::Cut(fields.named("cut_type")?),
+            kind => return Err(format!("unknown element {kind}")),
+        })
+    }
+}
+
+impl Receipt {
+    /// Serialize the receipt to its versioned JSON document, e.g. to be stored or sent to another service.
+    ///
+    /// The document is an object of the [`RECEIPT_FORMAT_VERSION`] in `version` and the `elements` in order, each
+    /// being an object tagged by its `type` in snake case, e.g. `{"type":"item","name":"Coffee","amount":"2.50"}`.
+    /// The fields are named like those of the [`Element`]s, with the following representations:
+    ///
+    /// * Alignments, bit image modes, barcode systems, table modes, page directions and cut types: their names in
+    ///   snake case, e.g. `"center"`, `"dots24_double_density"`, `"ean13"`, `"bit_image"`, `"bottom_to_top"` or
+    ///   `"partial"`
+    /// * [`Element::Text`]: `spans` as objects of `text`, `bold` and `underline`
+    /// * [`Element::Rule`] and the `fill` of [`Element::KeyValue`]: a string of one character
+    /// * [`Element::Separator`]: its `style` of `"solid"`, `"dashed"`, `"double"` or `"char"` and its `char`, or
+    ///   `null`, `thickness` and `inset`
+    /// * [`Element::Bitmap`]: the bytes of `bitmap` in hexadecimal
+    /// * [`Element::Barcode`] and [`Element::FittedBarcode`]: `data` as a string, or `hex` in hexadecimal if it
+    ///   isn't UTF-8, e.g. Shift-JIS text
+    /// * [`Element::QrCode`]: `data` or `hex` like barcodes, and its `error_correction` of `"low"`, `"medium"`, `"quartile"`
+    ///   or `"high"`
+    /// * [`Element::Space`] and the `space` of [`Element::Signature`]: an object of the length in `mm`, `inches` or
+    ///   `dots`, e.g. `{"mm":15}`
+    /// * [`Element::Table`]: a `table` object of `columns`, each of `chars`, `dots` or `weight` and `align`, the
+    ///   `header` cells or `null`, its `header_style` of `bold`, `underline` and `rule`, `rows` of arrays of cells,
+    ///   and `mode`
+    /// * [`Element::TwoColumns`]: its `left` and `right` text, and its 2 `columns` like those of the tables
+    /// * [`Element::Box`]: its `text`, `border` of `"line"` or `"ascii"`, `padding`, `vertical_padding` and `align`
+    /// * [`Element::List`]: a `list` object of its `style` of `"bullet"` and its `bullet`, or `"numbered"` and its
+    ///   `start`, its `indent` and `items`, each of `text` and a nested `list`, or `null`
+    /// * [`Element::Canvas`]: its `regions`, each of `x`, `y`, `width`, `height`, `direction` and the `elements` of
+    ///   its content
+    /// * [`Element::Watermarked`]: its `watermark`, an object of its `text`, or of the `width`, `height` and
+    ///   hexadecimal `bitmap` of an image, and its `shade`, and the `elements` of its content
+    /// * [`Element::Layout`]: its `height`, `mode` of `"page_mode"` or `"bit_image"` and `root` node, an object of
+    ///   the cells of a `row` or a `column`, each of `fixed` dots or a `flex` weight and its `node`, or of the
+    ///   `elements` of a content node
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{CutType, Receipt};
+    /// let receipt = Receipt::new().item("Coffee", "2.50").cut(CutType::PartialCut);
+    /// assert_eq!(
+    ///     receipt.to_json(),
+    ///     r#"{"version":1,"elements":[{"type":"item","name":"Coffee","amount":"2.50"},{"type":"cut","cut_type":"partial"}]}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        json::object([
+            ("version", RECEIPT_FORMAT_VERSION.into()),
+            (
+                "elements",
+                Value::Array(self.elements.iter().map(Element::to_json).collect()),
+            ),
+        ])
+        .to_string()
+    }
+
+    /// Interpret a JSON document of the schema of [`to_json()`](Receipt::to_json()) as a receipt, so backends in
+    /// other languages can produce the receipts printed by this crate, e.g. by [`to_job()`](Receipt::to_job()).
+    ///
+    /// The optional fields take their default values: `align`, `indent`, `fill` (`.`), the `bold` and `underline`
+    /// of the spans, the `thickness` and `inset` of separators, the `header`, `header_style` and `mode` of tables,
+    /// the `columns` of two columns, the `border`, `padding` and `vertical_padding` of boxes, the `indent` and
+    /// nested `list` of lists, the `justify` and `hyphenate` of paragraphs, the `direction` of the regions of
+    /// canvases and the `mode` of layouts. Returns [`Error::InvalidFormat`] if the JSON is malformed, its version
+    /// isn't supported or any element is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Emulator, Model, Profile, Receipt};
+    /// let receipt = Receipt::from_json(
+    ///     r#"{
+    ///         "version": 1,
+    ///         "elements": [
+    ///             {"type": "heading", "level": 3, "text": "CAFE ROMA", "align": "center"},
+    ///             {"type": "text", "spans": [{"text": "Table "}, {"text": "3", "bold": true}]},
+    ///             {"type": "key_value", "label": "Total", "value": "4.30"},
+    ///             {"type": "cut", "cut_type": "partial"}
+    ///         ]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    /// let job = receipt.to_job(&Profile::for_model(Model::Tl80)).unwrap();
+    ///
+    /// let mut emulator = Emulator::new();
+    /// emulator.print_job(&job);
+    /// assert!(emulator.render().contains("Table 3\nTotal ....."));
+    /// ```
+    pub fn from_json(text: &str) -> Result<Self, Error> {
+        let value = json::parse(text).map_err(Error::InvalidFormat)?;
+        match value.get("version").and_then(Value::as_i64) {
+            Some(version) if version == RECEIPT_FORMAT_VERSION as i64 => {}
+            Some(version) => {
+                return Err(Error::InvalidFormat(format!(
+                    "unsupported version {version}"
+                )))
+            }
+            None => return Err(Error::InvalidFormat("missing version".to_string())),
+        }
+
+        let elements = value
+            .get("elements")
+            .and_then(Value::as_array)
+            .ok_or_else(|| Error::InvalidFormat("missing elements".to_string()))?
+            .iter()
+            .enumerate()
+            .map(|(index, element)| {
+                Element::from_json(element)
+                    .map_err(|message| Error::InvalidFormat(format!("element {index}: {message}")))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            elements,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BarcodeSystem, BitImageMode, CutType, PageDirection};
+
+    #[test]
+    fn test_round_trip() {
+        let receipt = Receipt::new()
+            .header("CAFE")
+            .line("Via Roma")
+            .push(Element::Heading {
+                level: 2,
+                text: "Order".into(),
+                align: Align::Right,
+            })
+            .push(Element::Text {
+                spans: Vec::from([Span::plain("- "), Span::bold("a \"b\"")]),
+                indent: 2,
+                align: Align::Center,
+            })
+            .preformatted("x = 1\ny = 2")
+            .rule('=')
+            .separator(Separator::new(SeparatorStyle::Double).inset(2))
+            .separator(Separator::new(SeparatorStyle::Char('~')).thickness(3))
+            .item("Coffee", "2.50")
+            .kv_line_filled("Tax", "0.20", '_')
+            .bitmap(8, 1, [0x81], BitImageMode::Dots8SingleDensity)
+            .barcode(BarcodeSystem::Ean8, "1234567")
+            .fitted_barcode(BarcodeSystem::Code128, "{BA-42", 200)
+            .qr_payment("https://pay.example.com", 430, "Scan to pay")
+            .footer("Thanks")
+            .feed(2)
+            .space(Length::dots(40))
+            .signature("Received by")
+            .push(Element::Signature {
+                caption: "Cardholder".into(),
+                space: Length::inches(0.5),
+            })
+            .table(
+                Table::new([
+                    Column::chars(3),
+                    Column::dots(50).align(Align::Right),
+                    Column::weight(2),
+                ])
+                .header(["a", "b", "c"])
+                .header_style(HeaderStyle {
+                    rule: true,
+                    ..Default::default()
+                })
+                .row(["1", "2", "3"])
+                .mode(TableMode::BitImage),
+            )
+            .table(Table::new([Column::weight(1)]).row(["only"]))
+            .two_columns(TwoColumns::new("a\nb", "c").columns(Column::chars(4), Column::weight(1)))
+            .text_box(TextBox::new("Warranty").padding(0, 2))
+            .text_box(
+                TextBox::new("Terms")
+                    .border(BorderStyle::Ascii)
+                    .align(Align::Center),
+            )
+            .list(
+                List::new(ListStyle::Numbered(3))
+                    .nested(
+                        "Burger",
+                        List::new(ListStyle::Bullet('-'))
+                            .item("no onions")
+                            .indent(4),
+                    )
+                    .item("Fries"),
+            )
+            .paragraph(Paragraph::new("Terms of sale").hyphenate(true))
+            .paragraph(Paragraph::new("Void\nwhere prohibited").justify(true))
+            .watermarked(Watermark::text("COPY"), Receipt::new().line("duplicate"))
+            .watermarked(
+                Watermark::bitmap(8, 2, [0x18, 0x3C]).shade(8),
+                Receipt::new().item("Tea", "1.90"),
+            )
+            .canvas(
+                Canvas::new()
+                    .region(Region::new(0, 0, 200, 100, Receipt::new().line("upright")))
+                    .region(
+                        Region::new(200, 0, 100, 300, Receipt::new().rule('='))
+                            .direction(PageDirection::BottomToTop),
+                    ),
+            )
+            .layout(
+                Layout::new(
+                    120,
+                    LayoutNode::row([
+                        (
+                            Track::Fixed(96),
+                            LayoutNode::content(Receipt::new().line("A7")),
+                        ),
+                        (
+                            Track::Flex(1),
+                            LayoutNode::column([(
+                                Track::Flex(2),
+                                LayoutNode::content(Receipt::new()),
+                            )]),
+                        ),
+                    ]),
+                )
+                .mode(LayoutMode::BitImage),
+            )
+            .cut(CutType::TotalCut);
+        #[cfg(feature = "image")]
+        let receipt = receipt.image("logo.png", BitImageMode::Dots24DoubleDensity);
+
+        assert_eq!(Receipt::from_json(&receipt.to_json()).unwrap(), receipt);
+    }
+
+    #[test]
+    fn test_binary_data() {
+        // Shift-JIS text and binary data aren't UTF-8
+        let receipt = Receipt::new()
+            .qr_code([0x93, 0xFA, 0x96, 0x7B])
+            .barcode(BarcodeSystem::Code128, [0x7B, 0x42, 0xFF])
+            .fitted_barcode(BarcodeSystem::Code128, [0xFF], 200)
+            .qr_code("https://example.com");
+        let json = receipt.to_json();
+        assert!(json.contains(r#""hex":"93fa967b""#));
+        assert!(json.contains(r#""data":"https://example.com""#));
+        assert_eq!(Receipt::from_json(&json).unwrap(), receipt);
+        assert!(Receipt::from_json(
+            r#"{"version":1,"elements":[{"type":"qr_code","hex":"9","error_correction":"medium","module_size":6}]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let error = |text: &str| match Receipt::from_json(text) {
+            Err(Error::InvalidFormat(message)) => message,
+            result => panic!("unexpected {result:?}"),
+        };
+        assert_eq!(
+            error(r#"{"version":2,"elements":[]}"#),
+            "unsupported version 2"
+        );
+        assert_eq!(error(r#"{"elements":[]}"#), "missing version");
+        assert_eq!(
+            error(r#"{"version":1,"elements":[{"type":"line","text":"a"},{"type":"fly"}]}"#),
+            "element 1: unknown element fly"
+        );
+        assert_eq!(
+            error(r#"{"version":1,"elements":[{"type":"rule","char":"--"}]}"#),
+            "element 0: invalid field char"
+        );
+        assert_eq!(
+            error(
+                r#"{"version":1,"elements":[{"type":"heading","text":"a","level":1,"align":"up"}]}"#
+            ),
+            "element 0: invalid field align"
+        );
+    }
+}
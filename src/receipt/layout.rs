@@ -0,0 +1,352 @@
+//! Flexible layouts of the receipts in rows and columns of tracks.
+
+use alloc::{format, vec::Vec};
+
+use super::{Canvas, Element, Receipt, Region};
+use crate::{preview, BitImageMode, CommandBuilder, Error, JobBuilder};
+
+/// Size of a track of a [`LayoutNode::Row`] or [`LayoutNode::Column`], along the axis of the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Track {
+    /// Number of dots
+    Fixed(u16),
+    /// Share of the dots left by the fixed tracks, in proportion to the weights of the flexible tracks
+    Flex(u16),
+}
+
+/// A node of a [`Layout`], dividing its box into tracks or laying out elements in it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutNode {
+    /// Elements laid out in the box of the node, their lines running along its width
+    Content(Receipt),
+    /// Nodes side by side from left to right, each as wide as its track
+    Row(Vec<(Track, LayoutNode)>),
+    /// Nodes stacked from top to bottom, each as tall as its track
+    Column(Vec<(Track, LayoutNode)>),
+}
+
+impl LayoutNode {
+    /// A node laying out the elements of `content` in its box.
+    pub fn content(content: Receipt) -> Self {
+        Self::Content(content)
+    }
+
+    /// A node of the `cells` side by side.
+    pub fn row(cells: impl IntoIterator<Item = (Track, LayoutNode)>) -> Self {
+        Self::Row(cells.into_iter().collect())
+    }
+
+    /// A node of the `cells` stacked.
+    pub fn column(cells: impl IntoIterator<Item = (Track, LayoutNode)>) -> Self {
+        Self::Column(cells.into_iter().collect())
+    }
+
+    /// Append the regions of the content nodes below the node in the box of `width` x `height` at (`x`, `y`).
+    fn regions(
+        &self,
+        (x, y, width, height): (u16, u16, u16, u16),
+        regions: &mut Vec<Region>,
+    ) -> Result<(), Error> {
+        match self {
+            Self::Content(content) => {
+                if !content.elements().is_empty() && width > 0 && height > 0 {
+                    regions.push(Region::new(x, y, width, height, content.clone()));
+                }
+            }
+            Self::Row(cells) => {
+                let tracks: Vec<Track> = cells.iter().map(|(track, _)| *track).collect();
+                let mut left = x;
+                for ((_, node), size) in cells.iter().zip(sizes(&tracks, width)?) {
+                    node.regions((left, y, size, height), regions)?;
+                    left += size;
+                }
+            }
+            Self::Column(cells) => {
+                let tracks: Vec<Track> = cells.iter().map(|(track, _)| *track).collect();
+                let mut top = y;
+                for ((_, node), size) in cells.iter().zip(sizes(&tracks, height)?) {
+                    node.regions((x, top, width, size), regions)?;
+                    top += size;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// How a [`Layout`] is printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LayoutMode {
+    /// A page of page mode, each content node being a print area, see [`Canvas`]
+    #[default]
+    PageMode,
+    /// A bit image of the page drawn by the built-in font, e.g. for printers without page mode
+    BitImage,
+}
+
+/// A block of the width of the line and a fixed height, divided into rows and columns of fixed and flexible tracks,
+/// for pixel-precise designs, e.g. a ticket with a logo beside the event details above a stub.
+///
+/// The positions of the nodes are computed in dots by [`regions()`](Layout::regions()), the flexible tracks sharing
+/// the dots left by the fixed tracks by their weights, the last of them taking the dots left by the rounding. The
+/// content nodes can't contain a [`Element::Canvas`], a [`Element::Layout`], a [`Element::Watermarked`] or a
+/// [`Element::Cut`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Layout, LayoutNode, Receipt, Region, Track};
+/// let ticket = Layout::new(
+///     240,
+///     LayoutNode::row([
+///         (Track::Fixed(96), LayoutNode::content(Receipt::new().heading(1, "A7"))),
+///         (
+///             Track::Flex(1),
+///             LayoutNode::column([
+///                 (Track::Flex(1), LayoutNode::content(Receipt::new().line("ROCK NIGHT"))),
+///                 (Track::Fixed(48), LayoutNode::content(Receipt::new().line("Gate 3"))),
+///             ]),
+///         ),
+///     ]),
+/// );
+///
+/// let regions = ticket.regions(384).unwrap();
+/// let boxes: Vec<_> = regions.iter().map(|region| (region.x, region.y, region.width, region.height)).collect();
+/// assert_eq!(boxes, [(0, 0, 96, 240), (96, 0, 288, 192), (96, 192, 288, 48)]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// Height of the layout in dots
+    pub height: u16,
+    /// Node dividing the whole layout
+    pub root: LayoutNode,
+    /// How the layout is printed
+    pub mode: LayoutMode,
+}
+
+impl Layout {
+    /// A layout of `root` in a block of `height` dots, printed in page mode.
+    pub fn new(height: u16, root: LayoutNode) -> Self {
+        Self {
+            height,
+            root,
+            mode: LayoutMode::PageMode,
+        }
+    }
+
+    /// The layout printed in `mode`.
+    pub fn mode(self, mode: LayoutMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    /// The regions of the content nodes of the layout on a line of `width` dots, from the upper left corner of the
+    /// layout, in the order of the nodes.
+    ///
+    /// Returns [`Error::InvalidParameter`] if the fixed tracks of a node are larger than its box.
+    pub fn regions(&self, width: u16) -> Result<Vec<Region>, Error> {
+        let mut regions = Vec::new();
+        self.root
+            .regions((0, 0, width, self.height), &mut regions)?;
+
+        Ok(regions)
+    }
+
+    /// Render the layout on `builder` for a line of `dots`.
+    ///
+    /// Returns [`Error::InvalidParameter`] if the tracks don't fit or a content node contains a canvas, a layout,
+    /// a watermark or a cut.
+    pub(crate) fn render<B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &mut B,
+        dots: u16,
+    ) -> Result<(), Error> {
+        let canvas = Canvas {
+            regions: self.regions(dots)?,
+        };
+        match self.mode {
+            LayoutMode::PageMode => canvas.render(builder),
+            LayoutMode::BitImage => {
+                let mut page = JobBuilder::new();
+                canvas.render(&mut page)?;
+                let raster = preview::rasterize(page.build().commands(), dots);
+                if raster.height() > 0 {
+                    builder
+                        .bitmap(
+                            raster.width(),
+                            raster.height(),
+                            raster.data(),
+                            BitImageMode::Dots24DoubleDensity,
+                        )?
+                        .print();
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The sizes in dots of `tracks` sharing `total` dots.
+fn sizes(tracks: &[Track], total: u16) -> Result<Vec<u16>, Error> {
+    let fixed: u32 = tracks
+        .iter()
+        .map(|track| match track {
+            Track::Fixed(dots) => *dots as u32,
+            Track::Flex(_) => 0,
+        })
+        .sum();
+    if fixed > total as u32 {
+        return Err(Error::InvalidParameter(format!(
+            "fixed tracks of {fixed} dots exceed the {total} dots of the layout"
+        )));
+    }
+
+    let left = total as u32 - fixed;
+    // flexible tracks all of weight 0 share the dots equally
+    let equal = !tracks.iter().any(|track| matches!(track, Track::Flex(1..)));
+    let weight = |weight: u16| if equal { 1 } else { weight as u32 };
+    let weights: u32 = tracks
+        .iter()
+        .map(|track| match track {
+            Track::Flex(w) => weight(*w),
+            Track::Fixed(_) => 0,
+        })
+        .sum();
+    let last = tracks
+        .iter()
+        .rposition(|track| matches!(track, Track::Flex(w) if weight(*w) > 0));
+    let mut shared = 0;
+
+    Ok(tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| match track {
+            Track::Fixed(dots) => *dots,
+            // the last flexible track takes the dots left by the rounding
+            Track::Flex(_) if Some(i) == last => (left - shared) as u16,
+            Track::Flex(w) => {
+                let size = left * weight(*w) / weights;
+                shared += size;
+                size as u16
+            }
+        })
+        .collect())
+}
+
+/// Check that `content` can be laid out in page mode, returning the kind of the first element which can't, if any.
+pub(crate) fn nested_page(content: &Receipt) -> Option<&'static str> {
+    content.elements().iter().find_map(|element| match element {
+        Element::Canvas(_) => Some("canvas"),
+        Element::Layout(_) => Some("layout"),
+        Element::Watermarked { .. } => Some("watermark"),
+        Element::Cut(_) => Some("cut"),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Command;
+    use alloc::vec;
+
+    #[test]
+    fn test_sizes() {
+        let tracks = [Track::Flex(1), Track::Fixed(100), Track::Flex(2)];
+        assert_eq!(sizes(&tracks, 400).unwrap(), [100, 100, 200]);
+        // the last flexible track takes the dots left by the rounding
+        assert_eq!(sizes(&tracks, 401).unwrap(), [100, 100, 201]);
+        assert_eq!(
+            sizes(&[Track::Flex(1), Track::Flex(0)], 10).unwrap(),
+            [10, 0]
+        );
+        assert_eq!(
+            sizes(&[Track::Flex(0), Track::Fixed(4), Track::Flex(0)], 11).unwrap(),
+            [3, 4, 4]
+        );
+        assert!(matches!(
+            sizes(&[Track::Fixed(300), Track::Fixed(200)], 400),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_render() {
+        let layout = Layout::new(
+            48,
+            LayoutNode::row([
+                (
+                    Track::Flex(1),
+                    LayoutNode::content(Receipt::new().line("L")),
+                ),
+                (Track::Fixed(96), LayoutNode::content(Receipt::new())),
+                (
+                    Track::Flex(1),
+                    LayoutNode::content(Receipt::new().line("R")),
+                ),
+            ]),
+        );
+        let mut builder = JobBuilder::new();
+        layout.render(&mut builder, 384).unwrap();
+        let areas: Vec<Command> = builder
+            .build()
+            .commands()
+            .iter()
+            .filter(|command| matches!(command, Command::PageArea { .. }))
+            .cloned()
+            .collect();
+        // the empty content isn't printed
+        assert_eq!(
+            areas,
+            vec![
+                Command::PageArea {
+                    x: 0,
+                    y: 0,
+                    width: 144,
+                    height: 48
+                },
+                Command::PageArea {
+                    x: 240,
+                    y: 0,
+                    width: 144,
+                    height: 48
+                },
+            ]
+        );
+
+        let mut builder = JobBuilder::new();
+        layout
+            .mode(LayoutMode::BitImage)
+            .render(&mut builder, 384)
+            .unwrap();
+        let commands = builder.build().commands().to_vec();
+        assert!(matches!(
+            commands[..],
+            [Command::BitImage { width: 384, .. }, Command::Print]
+        ));
+
+        // flexible tracks all of weight 0 don't divide by zero
+        let layout = Layout::new(
+            48,
+            LayoutNode::row([
+                (
+                    Track::Flex(0),
+                    LayoutNode::content(Receipt::new().line("L")),
+                ),
+                (
+                    Track::Flex(0),
+                    LayoutNode::content(Receipt::new().line("R")),
+                ),
+            ]),
+        );
+        let mut builder = JobBuilder::new();
+        layout.render(&mut builder, 384).unwrap();
+        assert!(builder.build().commands().contains(&Command::PageArea {
+            x: 192,
+            y: 0,
+            width: 192,
+            height: 48
+        }));
+    }
+}
@@ -0,0 +1,201 @@
+//! Lists of the receipts.
+
+use alloc::{format, string::String, vec::Vec};
+
+use super::{text, Span};
+use crate::CommandBuilder;
+
+/// Marker of the items of a [`List`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListStyle {
+    /// Items marked by a bullet character, e.g. `'-'` or `'*'`
+    Bullet(char),
+    /// Items numbered from the given number, e.g. `1.`
+    Numbered(u32),
+}
+
+/// An item of a [`List`], optionally with a nested list, e.g. the modifiers of an order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListItem {
+    /// Text of the item
+    pub text: String,
+    /// List nested below the item, if any
+    pub sublist: Option<List>,
+}
+
+/// A list of items marked by bullets or numbers, the wrapped lines of an item being indented below its text.
+///
+/// The items of a nested list are indented by the `indent` of the list they are nested in from its markers.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Emulator, JobBuilder, List, ListStyle, PrinterConfig, Receipt};
+/// let order = List::new(ListStyle::Numbered(1))
+///     .indent(3)
+///     .nested("Burger", List::new(ListStyle::Bullet('-')).item("no onions").item("extra cheese, well done"))
+///     .item("Fries");
+///
+/// let mut builder = JobBuilder::new();
+/// builder.set_config(Some(PrinterConfig { dots_per_line: 240, ..Default::default() }));
+/// Receipt::new().list(order).render(&mut builder).unwrap();
+///
+/// let mut emulator = Emulator::new();
+/// emulator.print_job(&builder.build());
+/// assert_eq!(
+///     emulator.render(),
+///     "1. Burger\n\
+///      \x20  - no onions\n\
+///      \x20  - extra cheese,\n\
+///      \x20    well done\n\
+///      2. Fries\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct List {
+    /// Marker of the items
+    pub style: ListStyle,
+    /// Number of spaces the nested lists are indented by, 2 by default
+    pub indent: u16,
+    /// Items of the list
+    pub items: Vec<ListItem>,
+}
+
+impl List {
+    /// Create an empty list of `style`.
+    pub fn new(style: ListStyle) -> Self {
+        Self {
+            style,
+            indent: 2,
+            items: Vec::new(),
+        }
+    }
+
+    /// The list indenting its nested lists by `indent` spaces.
+    pub fn indent(self, indent: u16) -> Self {
+        Self { indent, ..self }
+    }
+
+    /// Append an item of `text`.
+    pub fn item(mut self, text: impl Into<String>) -> Self {
+        self.items.push(ListItem {
+            text: text.into(),
+            sublist: None,
+        });
+
+        self
+    }
+
+    /// Append an item of `text` with the nested `sublist`.
+    pub fn nested(mut self, text: impl Into<String>, sublist: List) -> Self {
+        self.items.push(ListItem {
+            text: text.into(),
+            sublist: Some(sublist),
+        });
+
+        self
+    }
+
+    /// Render the list on `builder` for a line of `columns` characters, the items of the nested lists in bold if
+    /// `bold_nested`.
+    pub(crate) fn render<B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &mut B,
+        columns: usize,
+        bold_nested: bool,
+    ) {
+        let mut lines = Vec::new();
+        self.lines(0, columns, false, bold_nested, &mut lines);
+        text::print_spans(builder, &lines);
+    }
+
+    /// Append the lines of the list, its markers starting after `offset` spaces, wrapped in `columns` characters.
+    ///
+    /// The items are in bold if `bold`, and the items of the nested lists if `bold_nested`.
+    fn lines(
+        &self,
+        offset: usize,
+        columns: usize,
+        bold: bool,
+        bold_nested: bool,
+        lines: &mut Vec<Vec<Span>>,
+    ) {
+        for (i, item) in self.items.iter().enumerate() {
+            let marker = match self.style {
+                ListStyle::Bullet(bullet) => format!("{bullet} "),
+                ListStyle::Numbered(start) => format!("{}. ", start as u64 + i as u64),
+            };
+            // the nested lists don't indent beyond the middle of the line
+            let offset = offset.min(columns / 2);
+            let prefix = " ".repeat(offset) + &marker;
+            let indent = prefix.chars().count();
+            let text = if bold {
+                Span::bold(item.text.as_str())
+            } else {
+                Span::plain(item.text.as_str())
+            };
+            let spans = [Span::plain(prefix), text];
+            lines.extend(text::wrap_spans(&spans, columns, indent));
+
+            if let Some(sublist) = &item.sublist {
+                sublist.lines(
+                    offset + self.indent as usize,
+                    columns,
+                    bold_nested,
+                    bold_nested,
+                    lines,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_lines() {
+        let list = List::new(ListStyle::Numbered(9))
+            .indent(4)
+            .nested(
+                "Pizza margherita",
+                List::new(ListStyle::Bullet('*'))
+                    .nested("half", List::new(ListStyle::Bullet('+')).item("basil")),
+            )
+            .item("Tiramisu");
+        let mut lines = Vec::new();
+        list.lines(0, 14, false, false, &mut lines);
+        let lines: Vec<String> = lines
+            .iter()
+            .map(|line| line.iter().map(|span| span.text.as_str()).collect())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "9. Pizza",
+                "   margherita",
+                "    * half",
+                "      + basil",
+                "10. Tiramisu"
+            ]
+        );
+
+        // the nested lists stop indenting at the middle of the line
+        let mut lines = Vec::new();
+        List::new(ListStyle::Bullet('-'))
+            .indent(20)
+            .nested("a", List::new(ListStyle::Bullet('-')).item("b"))
+            .lines(0, 10, false, false, &mut lines);
+        assert_eq!(lines[1], vec![Span::plain("     - b")]);
+
+        // the modifiers in bold
+        let mut lines = Vec::new();
+        List::new(ListStyle::Bullet('-'))
+            .nested("a", List::new(ListStyle::Bullet('-')).item("b"))
+            .lines(0, 10, false, true, &mut lines);
+        assert_eq!(lines[0], vec![Span::plain("- a")]);
+        assert_eq!(lines[1], vec![Span::plain("  - "), Span::bold("b")]);
+    }
+}
@@ -0,0 +1,290 @@
+//! Rendering of a subset of Markdown into receipts.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{Align, Element, Receipt, Span, TextStyle};
+
+/// Render `markdown` into a [`Receipt`], so simple tickets and release-notes-like content can be written as plain
+/// text.
+///
+/// The subset of Markdown is mapped to the printer styles as follows:
+///
+/// * `#`, `##` and `###` headings: [`Element::Heading`]s of their level
+/// * Paragraphs: [`Element::Text`], the lines of a paragraph being joined. `**bold**` and `__bold__` are printed in
+///   bold, `*italic*` and `_italic_` underlined as thermal printers have no italics, and `` `code` `` as it is.
+///   A `\` escapes the next punctuation character.
+/// * `-`, `*` and `+` list items, and numbered list items like `1.`: [`Element::Text`] of the item after its
+///   bullet, the wrapped lines being indented below the text. Items are nested by 2 spaces.
+/// * `---`, `***` and `___` rules: [`Element::Rule`]s of `-`
+/// * Code blocks fenced by ```` ``` ````: [`Element::Preformatted`]
+///
+/// The blocks separated by blank lines are separated by an [`Element::Feed`] of a line. The other Markdown, e.g.
+/// links, tables or HTML, is printed as text.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{render_markdown, Element, Emulator};
+/// let receipt = render_markdown(
+///     "# Release 2.1\n\nPrinting is **faster**:\n- kitchen tickets\n- receipts\n\n---",
+/// );
+/// assert!(matches!(&receipt.elements()[0], Element::Heading { level: 1, .. }));
+///
+/// let mut emulator = Emulator::new();
+/// receipt.render(&mut emulator).unwrap();
+/// assert_eq!(
+///     emulator.render(),
+///     format!("Release 2.1\n\nPrinting is faster:\n- kitchen tickets\n- receipts\n\n{}\n", "-".repeat(48))
+/// );
+/// ```
+pub fn render_markdown(markdown: &str) -> Receipt {
+    let mut parser = Parser::default();
+    for line in markdown.lines() {
+        parser.line(line);
+    }
+    parser.close();
+
+    parser.receipt
+}
+
+#[derive(Default)]
+struct Parser {
+    receipt: Receipt,
+    /// Paragraph or list item being parsed, with its bullet and indent
+    open: Option<(String, Vec<String>)>,
+    /// Lines of the code block being parsed
+    code: Option<Vec<String>>,
+    /// Whether a blank line separates the next block from the previous one
+    gap: bool,
+}
+
+impl Parser {
+    fn line(&mut self, line: &str) {
+        if let Some(code) = &mut self.code {
+            if line.trim_start().starts_with("```") {
+                let text = code.join("\n");
+                self.code = None;
+                self.receipt.elements.push(Element::Preformatted(text));
+            } else {
+                code.push(line.to_string());
+            }
+            return;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            self.close();
+            self.gap = !self.receipt.elements.is_empty();
+        } else if trimmed.starts_with("```") {
+            self.start();
+            self.code = Some(Vec::new());
+        } else if let Some((level, text)) = heading(trimmed) {
+            self.start();
+            self.receipt = core::mem::take(&mut self.receipt).heading(level, text);
+        } else if is_rule(trimmed) {
+            self.start();
+            self.receipt = core::mem::take(&mut self.receipt).rule('-');
+        } else if let Some((bullet, text)) = list_item(line) {
+            self.start();
+            self.open = Some((bullet, Vec::from([text.to_string()])));
+        } else if let Some((_, lines)) = &mut self.open {
+            lines.push(trimmed.to_string());
+        } else {
+            self.start();
+            self.open = Some((String::new(), Vec::from([trimmed.to_string()])));
+        }
+    }
+
+    /// Close the open block and separate the next one by a blank line if needed.
+    fn start(&mut self) {
+        self.close();
+        if self.gap {
+            self.receipt = core::mem::take(&mut self.receipt).feed(1);
+            self.gap = false;
+        }
+    }
+
+    /// Append the open paragraph or list item, and the unterminated code block.
+    fn close(&mut self) {
+        if let Some((bullet, lines)) = self.open.take() {
+            let indent = bullet.chars().count() as u16;
+            let mut spans = Vec::from([Span::plain(bullet)]);
+            spans.extend(inline(&lines.join(" ")));
+            spans.retain(|span| !span.text.is_empty());
+            self.receipt.elements.push(Element::Text {
+                spans,
+                indent,
+                align: Align::Left,
+            });
+        }
+        if let Some(code) = self.code.take() {
+            self.receipt
+                .elements
+                .push(Element::Preformatted(code.join("\n")));
+        }
+    }
+}
+
+/// The level and text of a heading line.
+fn heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+
+    (1..=6).contains(&level).then(|| (level as u8, text.trim()))
+}
+
+/// Whether `line` is a rule of 3 or more `-`, `*` or `_`.
+fn is_rule(line: &str) -> bool {
+    let mut chars = line.chars().filter(|c| *c != ' ');
+    let Some(first) = chars.next().filter(|c| matches!(c, '-' | '*' | '_')) else {
+        return false;
+    };
+
+    chars.clone().all(|c| c == first) && chars.count() >= 2
+}
+
+/// The bullet, with the indent of its nesting level, and the text of a list item line.
+fn list_item(line: &str) -> Option<(String, &str)> {
+    let spaces = line.chars().take_while(|c| *c == ' ').count();
+    let rest = &line[spaces..];
+    let marker = match rest.as_bytes() {
+        [b'-' | b'*' | b'+', b' ', ..] => 1,
+        _ => {
+            let digits = rest.chars().take_while(char::is_ascii_digit).count();
+            match &rest.as_bytes()[digits..] {
+                [b'.', b' ', ..] if digits > 0 => digits + 1,
+                _ => return None,
+            }
+        }
+    };
+
+    let indent = " ".repeat(spaces / 2 * 2);
+    let bullet = if marker == 1 { "-" } else { &rest[..marker] };
+    Some((indent + bullet + " ", rest[marker + 1..].trim()))
+}
+
+/// The runs of `text` in their style.
+fn inline(text: &str) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut span = Span::plain(String::new());
+    let mut code = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let mut toggle = |span: &mut Span, style: TextStyle| {
+            spans.push(core::mem::replace(span, Span::new(String::new(), style)));
+        };
+
+        match c {
+            '`' => code = !code,
+            _ if code => span.text.push(c),
+            '\\' if next.is_some_and(|c| c.is_ascii_punctuation()) => {
+                span.text.extend(next);
+                i += 1;
+            }
+            '*' | '_' if next == Some(c) => {
+                let style = TextStyle {
+                    bold: !span.style.bold,
+                    ..span.style
+                };
+                toggle(&mut span, style);
+                i += 1;
+            }
+            '*' | '_' if c == '*' || at_boundary(&chars, i, span.style.underline) => {
+                let style = TextStyle {
+                    underline: !span.style.underline,
+                    ..span.style
+                };
+                toggle(&mut span, style);
+            }
+            _ => span.text.push(c),
+        }
+        i += 1;
+    }
+    spans.push(span);
+
+    spans
+}
+
+/// Whether the `_` at `i` opens or closes, if `open`, an underline at the boundary of a word, unlike the `_` in
+/// `snake_case`.
+fn at_boundary(chars: &[char], i: usize, open: bool) -> bool {
+    if open {
+        !chars.get(i + 1).is_some_and(|c| c.is_alphanumeric())
+    } else {
+        !(i > 0 && chars[i - 1].is_alphanumeric())
+            && chars.get(i + 1).is_some_and(|c| !c.is_whitespace())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_render_markdown() {
+        let receipt = render_markdown(
+            "## Order 42
+Table *3*, __no onions__
+and snake_case \\*text\\*
+
+1. Burger
+  - extra `**cheese**`
+2. Fries
+***
+```
+  x  = 1
+```",
+        );
+        let underline = TextStyle {
+            underline: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            receipt.elements(),
+            [
+                Element::Heading {
+                    level: 2,
+                    text: "Order 42".into(),
+                    align: Align::Left
+                },
+                Element::Text {
+                    spans: vec![
+                        Span::plain("Table "),
+                        Span::new("3", underline),
+                        Span::plain(", "),
+                        Span::bold("no onions"),
+                        Span::plain(" and snake_case *text*"),
+                    ],
+                    indent: 0,
+                    align: Align::Left
+                },
+                Element::Feed(1),
+                Element::Text {
+                    spans: vec![Span::plain("1. "), Span::plain("Burger")],
+                    indent: 3,
+                    align: Align::Left
+                },
+                Element::Text {
+                    spans: vec![Span::plain("  - "), Span::plain("extra **cheese**")],
+                    indent: 4,
+                    align: Align::Left
+                },
+                Element::Text {
+                    spans: vec![Span::plain("2. "), Span::plain("Fries")],
+                    indent: 3,
+                    align: Align::Left
+                },
+                Element::Rule('-'),
+                Element::Preformatted("  x  = 1".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Formatting of the amounts of money of the receipts.
+
+use alloc::string::{String, ToString};
+
+/// Placement of the currency symbol of a [`MoneyFormat`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymbolPlacement {
+    /// Before the number, e.g. `$1.50`
+    Before,
+    /// After the number, e.g. `1,50 EUR`
+    #[default]
+    After,
+}
+
+/// Format of the amounts of money and numbers, following the conventions of a locale, e.g. `$1,234.50` in the
+/// United States or `1.234,50 EUR` in Germany.
+///
+/// The amounts are integers in the minor unit of the currency, e.g. cents, so they are exact. The default format
+/// is the number without any currency or thousands separator, e.g. `1234.50`.
+///
+/// The text of the receipts is sent as it is, so the symbol should be in the code page of the printer; the
+/// presets use ASCII, e.g. `EUR` rather than `€`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::MoneyFormat;
+/// assert_eq!(MoneyFormat::en_us().format(123450), "$1,234.50");
+/// assert_eq!(MoneyFormat::de_de().format(-123450), "-1.234,50 EUR");
+/// assert_eq!(MoneyFormat::fr_fr().format_number(1234567), "12 345,67");
+/// assert_eq!(MoneyFormat::default().format(5), "0.05");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoneyFormat {
+    /// Currency symbol, e.g. `"$"` or `"EUR"`, left out if empty
+    pub symbol: String,
+    /// Placement of the symbol
+    pub placement: SymbolPlacement,
+    /// Separate the symbol from the number by a space
+    pub spaced: bool,
+    /// Decimal separator, e.g. `.` or `,`
+    pub decimal: char,
+    /// Separator of the groups of thousands, e.g. `,`, `.` or a space, if any
+    pub thousands: Option<char>,
+    /// Number of decimals of the minor unit, e.g. 2 for cents, at most 18
+    pub decimals: u8,
+}
+
+impl Default for MoneyFormat {
+    fn default() -> Self {
+        Self {
+            symbol: String::new(),
+            placement: SymbolPlacement::After,
+            spaced: true,
+            decimal: '.',
+            thousands: None,
+            decimals: 2,
+        }
+    }
+}
+
+impl MoneyFormat {
+    /// US dollars in the United States, e.g. `$1,234.50`.
+    pub fn en_us() -> Self {
+        Self {
+            symbol: "$".to_string(),
+            placement: SymbolPlacement::Before,
+            spaced: false,
+            thousands: Some(','),
+            ..Default::default()
+        }
+    }
+
+    /// Euros in Germany, e.g. `1.234,50 EUR`.
+    pub fn de_de() -> Self {
+        Self {
+            symbol: "EUR".to_string(),
+            decimal: ',',
+            thousands: Some('.'),
+            ..Default::default()
+        }
+    }
+
+    /// Euros in France, e.g. `1 234,50 EUR`.
+    pub fn fr_fr() -> Self {
+        Self {
+            thousands: Some(' '),
+            ..Self::de_de()
+        }
+    }
+
+    /// Swiss francs in Switzerland, e.g. `CHF 1'234.50`.
+    pub fn de_ch() -> Self {
+        Self {
+            symbol: "CHF".to_string(),
+            placement: SymbolPlacement::Before,
+            thousands: Some('\''),
+            ..Default::default()
+        }
+    }
+
+    /// The format with the currency `symbol`, e.g. `"GBP"` for a format of euros.
+    pub fn symbol(self, symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            ..self
+        }
+    }
+
+    /// Format an amount of `minor` units as a number without the currency, e.g. `1,234.50`.
+    pub fn format_number(&self, minor: i64) -> String {
+        let sign = if minor < 0 { "-" } else { "" };
+
+        sign.to_string() + &self.digits(minor.unsigned_abs())
+    }
+
+    /// Format an amount of `minor` units with the currency, e.g. `$1,234.50`, the sign leading the amount.
+    pub fn format(&self, minor: i64) -> String {
+        let number = self.digits(minor.unsigned_abs());
+        let sign = if minor < 0 { "-" } else { "" };
+        let space = if self.spaced && !self.symbol.is_empty() {
+            " "
+        } else {
+            ""
+        };
+
+        match self.placement {
+            SymbolPlacement::Before => [sign, &self.symbol, space, &number].concat(),
+            SymbolPlacement::After => [sign, &number, space, &self.symbol].concat(),
+        }
+    }
+
+    /// The digits of `minor` units with their separators.
+    fn digits(&self, minor: u64) -> String {
+        let decimals = self.decimals.min(18) as u32;
+        let scale = 10u64.pow(decimals);
+        let (units, fraction) = (minor / scale, minor % scale);
+
+        let mut number = String::new();
+        let digits = units.to_string();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                number.extend(self.thousands);
+            }
+            number.push(digit);
+        }
+        if decimals > 0 {
+            number.push(self.decimal);
+            let fraction = fraction.to_string();
+            number.extend((fraction.len()..decimals as usize).map(|_| '0'));
+            number.push_str(&fraction);
+        }
+
+        number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(MoneyFormat::en_us().format(0), "$0.00");
+        assert_eq!(MoneyFormat::en_us().format(-99), "-$0.99");
+        assert_eq!(
+            MoneyFormat::en_us().format_number(100_000_000),
+            "1,000,000.00"
+        );
+        assert_eq!(MoneyFormat::de_ch().format(123456789), "CHF 1'234'567.89");
+        assert_eq!(MoneyFormat::fr_fr().format(100000), "1 000,00 EUR");
+        assert_eq!(MoneyFormat::de_de().symbol("").format(12), "0,12");
+
+        let yen = MoneyFormat {
+            decimals: 0,
+            ..MoneyFormat::en_us().symbol("JPY ")
+        };
+        assert_eq!(yen.format(1500), "JPY 1,500");
+        assert_eq!(
+            MoneyFormat::en_us().format_number(i64::MIN),
+            "-92,233,720,368,547,758.08"
+        );
+    }
+}
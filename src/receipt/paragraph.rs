@@ -0,0 +1,229 @@
+//! Justified and hyphenated paragraphs of the receipts.
+
+use alloc::{string::String, vec::Vec};
+
+use super::print_lines;
+use crate::CommandBuilder;
+
+/// Soft hyphen, marking where a word may be hyphenated.
+const SOFT_HYPHEN: char = '\u{AD}';
+
+/// Minimum number of characters of a word on either side of an automatic hyphenation.
+const MIN_HEAD: usize = 2;
+const MIN_TAIL: usize = 3;
+
+/// Long text, e.g. legal terms, wrapped rag-right or justified across the paper, optionally hyphenating the words
+/// to save paper on narrow printers.
+///
+/// The words are hyphenated at the soft hyphens (`\u{AD}`) and after the hyphens of the text, and, if
+/// [`hyphenate()`](Paragraph::hyphenate()) is enabled, between the syllables guessed from the vowels of the word,
+/// e.g. `con-di-tions`. A `\n` starts a new paragraph, whose last line is never justified.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Emulator, JobBuilder, Paragraph, PrinterConfig, Receipt};
+/// let terms = Paragraph::new("Refunds are subject to the conditions of sale").justify(true).hyphenate(true);
+///
+/// let mut builder = JobBuilder::new();
+/// builder.set_config(Some(PrinterConfig { dots_per_line: 216, ..Default::default() }));
+/// Receipt::new().paragraph(terms).render(&mut builder).unwrap();
+///
+/// let mut emulator = Emulator::new();
+/// emulator.print_job(&builder.build());
+/// assert_eq!(
+///     emulator.render(),
+///     "Refunds  are  sub-\n\
+///      ject to the condi-\n\
+///      tions of sale\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paragraph {
+    /// Text of the paragraph
+    pub text: String,
+    /// Spread the words of the lines to both edges of the paper, but the last line of each paragraph
+    pub justify: bool,
+    /// Hyphenate the words between their syllables, beyond the soft hyphens and hyphens of the text
+    pub hyphenate: bool,
+}
+
+impl Paragraph {
+    /// A paragraph of `text` wrapped rag-right, hyphenated at its soft hyphens and hyphens only.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            justify: false,
+            hyphenate: false,
+        }
+    }
+
+    /// The paragraph justified if `justify`, else wrapped rag-right.
+    pub fn justify(self, justify: bool) -> Self {
+        Self { justify, ..self }
+    }
+
+    /// The paragraph hyphenating its words between their syllables if `hyphenate`.
+    pub fn hyphenate(self, hyphenate: bool) -> Self {
+        Self { hyphenate, ..self }
+    }
+
+    /// Render the paragraph on `builder` for a line of `columns` characters.
+    pub(crate) fn render<B: CommandBuilder + ?Sized>(&self, builder: &mut B, columns: usize) {
+        print_lines(builder, self.lines(columns));
+    }
+
+    /// The lines of the paragraph in `columns` characters.
+    fn lines(&self, columns: usize) -> Vec<String> {
+        let columns = columns.max(1);
+        let mut lines = Vec::new();
+
+        for paragraph in self.text.split('\n') {
+            let mut line: Vec<String> = Vec::new();
+            let mut len = 0;
+            for word in paragraph.split_whitespace() {
+                let (mut chars, mut soft) = parse_word(word);
+                loop {
+                    let room = match len {
+                        0 => columns,
+                        _ => columns.saturating_sub(len + 1),
+                    };
+                    if chars.len() <= room {
+                        len += usize::from(len > 0) + chars.len();
+                        line.push(chars.into_iter().collect());
+                        break;
+                    }
+
+                    let split = match self.break_at(&chars, &soft, room) {
+                        Some(split) => Some(split),
+                        // a word longer than a line is split where it overflows
+                        None if len == 0 => Some((columns, false)),
+                        None => None,
+                    };
+                    if let Some((at, hyphen)) = split {
+                        let mut head: String = chars.drain(..at).collect();
+                        if hyphen {
+                            head.push('-');
+                        }
+                        line.push(head);
+                        soft.drain(..at);
+                    }
+                    lines.push(self.join(&core::mem::take(&mut line), columns, true));
+                    len = 0;
+                }
+            }
+            lines.push(self.join(&line, columns, false));
+        }
+
+        lines
+    }
+
+    /// The position the `chars` of a word can be broken at the latest, leaving the head and a hyphen, if needed, in
+    /// `room` characters, `soft` telling if there is a soft hyphen before each character.
+    fn break_at(&self, chars: &[char], soft: &[bool], room: usize) -> Option<(usize, bool)> {
+        (1..chars.len()).rev().find_map(|at| {
+            let hyphen = chars[at - 1] != '-';
+            let allowed = !hyphen || soft[at] || (self.hyphenate && syllable_break(chars, at));
+
+            (allowed && at + usize::from(hyphen) <= room).then_some((at, hyphen))
+        })
+    }
+
+    /// Join the `words` of a line of `columns` characters, spreading them to both edges if the paragraph is
+    /// justified and the line `wrapped`.
+    fn join(&self, words: &[String], columns: usize, wrapped: bool) -> String {
+        if !(self.justify && wrapped && words.len() > 1) {
+            return words.join(" ");
+        }
+
+        let gaps = words.len() - 1;
+        let len: usize = words.iter().map(|word| word.chars().count()).sum();
+        let spaces = columns.saturating_sub(len).max(gaps);
+        let mut line = String::new();
+        for (i, word) in words.iter().enumerate() {
+            if i > 0 {
+                // the leftmost gaps take the spaces left over
+                let gap = spaces / gaps + usize::from(i <= spaces % gaps);
+                line.extend((0..gap).map(|_| ' '));
+            }
+            line.push_str(word);
+        }
+
+        line
+    }
+}
+
+/// The characters of `word` without its soft hyphens, and if there is a soft hyphen before each character.
+fn parse_word(word: &str) -> (Vec<char>, Vec<bool>) {
+    let mut chars = Vec::new();
+    let mut soft = Vec::new();
+    let mut before = false;
+    for c in word.chars() {
+        if c == SOFT_HYPHEN {
+            before = true;
+        } else {
+            chars.push(c);
+            soft.push(core::mem::take(&mut before));
+        }
+    }
+
+    (chars, soft)
+}
+
+/// Whether a word can be hyphenated before `chars[at]` between two syllables, i.e. before a consonant followed by
+/// a vowel, either after a vowel or between two consonants after a vowel, e.g. `re-fun-dable`.
+fn syllable_break(chars: &[char], at: usize) -> bool {
+    let vowel = |c: char| matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let letter = |i: usize| chars.get(i).is_some_and(|c| c.is_alphabetic());
+
+    if at < MIN_HEAD || chars.len() - at < MIN_TAIL || !(at - 2..at + 2).all(letter) {
+        return false;
+    }
+
+    let (before, after) = (chars[at - 1], chars[at]);
+    !vowel(after) && vowel(chars[at + 1]) && (vowel(before) || vowel(chars[at - 2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let text = "Goods are non-returnable\nVoid else\u{AD}where";
+        assert_eq!(
+            Paragraph::new(text).justify(true).lines(12),
+            [
+                "Goods    are",
+                "non-",
+                "returnable",
+                "Void   else-",
+                "where"
+            ]
+        );
+        // the leftmost gaps take the spaces left over
+        assert_eq!(
+            Paragraph::new("a b c d e f g").justify(true).lines(6),
+            ["a  b c", "d  e f", "g"]
+        );
+        assert_eq!(
+            Paragraph::new("abcdefgh").hyphenate(true).lines(3),
+            ["abc", "def", "gh"]
+        );
+    }
+
+    #[test]
+    fn test_syllable_break() {
+        let breaks = |word: &str| -> Vec<usize> {
+            let chars: Vec<char> = word.chars().collect();
+            (1..chars.len())
+                .filter(|at| syllable_break(&chars, *at))
+                .collect()
+        };
+        assert_eq!(breaks("conditions"), [3, 5]);
+        assert_eq!(breaks("refundable"), [2, 5]);
+        assert_eq!(breaks("strength"), [0; 0]);
+        // a break doesn't leave a single letter or cross a digit
+        assert_eq!(breaks("area51"), [0; 0]);
+    }
+}
@@ -0,0 +1,100 @@
+//! Payment blocks of the receipts: a QR code to pay the amount due, the amount and instructions.
+
+use alloc::{format, string::String, vec::Vec};
+
+use super::{Align, Element, MoneyFormat, Span};
+use crate::{Error, QrErrorCorrection};
+
+/// Largest module size of the QR codes appended by [`Receipt::qr_code()`](super::Receipt::qr_code()) in dots, 1 mm
+/// at 203 dpi, large enough for the cameras of the phones without covering the receipt.
+pub(super) const MAX_MODULE_SIZE: u8 = 8;
+
+/// Number of modules of the quiet zone left blank on each side of a QR code.
+const QUIET_ZONE: u16 = 4;
+
+/// The size of the modules of the QR code of `len` bytes at `error_correction` on a line of `dots`: the largest
+/// up to `max` fitting the code and its quiet zone in the line.
+///
+/// Returns [`Error::InvalidParameter`] if even modules of 1 dot don't fit.
+pub(super) fn module_size(
+    len: usize,
+    error_correction: QrErrorCorrection,
+    max: u8,
+    dots: u16,
+) -> Result<u8, Error> {
+    let modules = error_correction.modules(len).ok_or_else(|| {
+        Error::InvalidParameter(format!(
+            "QR code of {len} bytes is larger than version 40 at {error_correction:?} error correction"
+        ))
+    })?;
+    let fitting = dots / (modules + 2 * QUIET_ZONE);
+    if fitting == 0 {
+        return Err(Error::InvalidParameter(format!(
+            "QR code of {modules} modules is wider than {dots} dots"
+        )));
+    }
+
+    Ok(fitting.min(max.max(1) as u16) as u8)
+}
+
+/// The elements of a payment block of `payload`: its QR code, `amount` formatted by `money` and `instructions`,
+/// all centered.
+pub(super) fn payment_elements(
+    payload: Vec<u8>,
+    amount: i64,
+    instructions: String,
+    money: &MoneyFormat,
+) -> Vec<Element> {
+    let mut elements = Vec::from([
+        Element::QrCode {
+            data: payload,
+            error_correction: QrErrorCorrection::Medium,
+            module_size: MAX_MODULE_SIZE,
+        },
+        Element::Heading {
+            level: 2,
+            text: money.format(amount),
+            align: Align::Center,
+        },
+    ]);
+    if !instructions.is_empty() {
+        elements.push(Element::Text {
+            spans: Vec::from([Span::plain(instructions)]),
+            indent: 0,
+            align: Align::Center,
+        });
+    }
+
+    elements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_size() {
+        // version 3 of 29 modules, and its quiet zone of 8 modules
+        assert!(matches!(
+            module_size(40, QrErrorCorrection::Medium, 8, 576),
+            Ok(8)
+        ));
+        assert!(matches!(
+            module_size(40, QrErrorCorrection::Medium, 8, 256),
+            Ok(6)
+        ));
+        // version 33 of 149 modules
+        assert!(matches!(
+            module_size(2000, QrErrorCorrection::Low, 8, 576),
+            Ok(3)
+        ));
+        assert!(matches!(
+            module_size(2000, QrErrorCorrection::High, 8, 576),
+            Err(Error::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            module_size(2000, QrErrorCorrection::Low, 8, 150),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+}
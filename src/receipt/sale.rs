@@ -0,0 +1,333 @@
+//! Widgets of the sales of the receipts: itemized sales, totals and tax summaries.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{Align, Column, Element, HeaderStyle, MoneyFormat, Table};
+
+/// An item of an itemized sale, see [`Receipt::sale_items()`](super::Receipt::sale_items()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaleItem {
+    /// Quantity sold
+    pub quantity: u32,
+    /// Name of the item
+    pub name: String,
+    /// Price of a unit in minor units of the currency, e.g. cents
+    pub unit: i64,
+}
+
+impl SaleItem {
+    /// `quantity` units of the item `name` at the price of `unit` minor units.
+    pub fn new(quantity: u32, name: impl Into<String>, unit: i64) -> Self {
+        Self {
+            quantity,
+            name: name.into(),
+            unit,
+        }
+    }
+
+    /// Amount of the item in minor units, the quantity times the unit price.
+    pub fn amount(&self) -> i64 {
+        self.unit.saturating_mul(self.quantity as i64)
+    }
+}
+
+/// The totals of a sale in minor units of the currency, see [`Receipt::totals()`](super::Receipt::totals()).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Totals {
+    /// Sum of the amounts of the items
+    pub subtotal: i64,
+    /// Discount off the subtotal, left out if 0
+    pub discount: i64,
+    /// Tax of the sale
+    pub tax: i64,
+    /// Amount due
+    pub total: i64,
+}
+
+impl Totals {
+    /// The totals of a sale of `subtotal`, `tax` and `total`, without discount.
+    pub fn new(subtotal: i64, tax: i64, total: i64) -> Self {
+        Self {
+            subtotal,
+            discount: 0,
+            tax,
+            total,
+        }
+    }
+
+    /// The totals with a discount of `discount` minor units off the subtotal.
+    pub fn discount(self, discount: i64) -> Self {
+        Self { discount, ..self }
+    }
+}
+
+/// The taxes of a sale at a rate, a row of a tax summary, see
+/// [`Receipt::tax_summary()`](super::Receipt::tax_summary()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaxLine {
+    /// Tax rate in hundredths of a percent, e.g. 1900 for 19 %
+    pub rate: u16,
+    /// Amount before taxes in minor units of the currency
+    pub net: i64,
+    /// Tax at the rate in minor units of the currency
+    pub tax: i64,
+}
+
+impl TaxLine {
+    /// The `tax` at `rate` hundredths of a percent on `net` minor units.
+    pub fn new(rate: u16, net: i64, tax: i64) -> Self {
+        Self { rate, net, tax }
+    }
+
+    /// Amount including taxes in minor units, the net amount plus the tax.
+    pub fn gross(&self) -> i64 {
+        self.net.saturating_add(self.tax)
+    }
+}
+
+/// Labels of the sale widgets, in English by default, e.g. to translate them along the
+/// [`MoneyFormat`] of the receipt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaleLabels {
+    /// Header of the quantities of the items
+    pub quantity: String,
+    /// Header of the names of the items
+    pub item: String,
+    /// Header of the unit prices of the items
+    pub unit: String,
+    /// Header of the amounts of the items
+    pub amount: String,
+    /// Label of [`Totals::subtotal`]
+    pub subtotal: String,
+    /// Label of [`Totals::discount`]
+    pub discount: String,
+    /// Label of [`Totals::tax`], and header of the taxes of the tax summary
+    pub tax: String,
+    /// Label of [`Totals::total`]
+    pub total: String,
+    /// Header of the rates of the tax summary
+    pub rate: String,
+    /// Header of the net amounts of the tax summary
+    pub net: String,
+    /// Header of the gross amounts of the tax summary
+    pub gross: String,
+}
+
+impl Default for SaleLabels {
+    fn default() -> Self {
+        Self {
+            quantity: "Qty".to_string(),
+            item: "Item".to_string(),
+            unit: "Unit".to_string(),
+            amount: "Amount".to_string(),
+            subtotal: "Subtotal".to_string(),
+            discount: "Discount".to_string(),
+            tax: "Tax".to_string(),
+            total: "TOTAL".to_string(),
+            rate: "Rate".to_string(),
+            net: "Net".to_string(),
+            gross: "Gross".to_string(),
+        }
+    }
+}
+
+/// Style of the headers of the tables of the widgets.
+const HEADER_STYLE: HeaderStyle = HeaderStyle {
+    bold: true,
+    underline: false,
+    rule: true,
+};
+
+/// Number of characters of the longest of `cells`, as the width of their column.
+fn chars<'a>(cells: impl IntoIterator<Item = &'a String>) -> u16 {
+    cells
+        .into_iter()
+        .map(|cell| cell.chars().count())
+        .max()
+        .unwrap_or(0) as u16
+}
+
+/// The table of the itemized sale of `items`, the amounts formatted by `money`.
+pub(super) fn items_table(items: &[SaleItem], money: &MoneyFormat, labels: &SaleLabels) -> Table {
+    let rows: Vec<[String; 4]> = items
+        .iter()
+        .map(|item| {
+            [
+                item.quantity.to_string(),
+                item.name.clone(),
+                money.format_number(item.unit),
+                money.format(item.amount()),
+            ]
+        })
+        .collect();
+    let header = [
+        labels.quantity.clone(),
+        labels.item.clone(),
+        labels.unit.clone(),
+        labels.amount.clone(),
+    ];
+    let width = |i: usize| chars(rows.iter().map(|row| &row[i]).chain([&header[i]]));
+
+    let mut table = Table::new([
+        Column::chars(width(0)).align(Align::Right),
+        Column::weight(1),
+        Column::chars(width(2)).align(Align::Right),
+        Column::chars(width(3)).align(Align::Right),
+    ])
+    .header(header)
+    .header_style(HEADER_STYLE);
+    for row in rows {
+        table = table.row(row);
+    }
+
+    table
+}
+
+/// The elements of `totals`, the amounts formatted by `money` and the total emphasized in bold.
+pub(super) fn totals_elements(
+    totals: &Totals,
+    money: &MoneyFormat,
+    labels: &SaleLabels,
+) -> Vec<Element> {
+    let line = |label: &String, minor: i64| Element::KeyValue {
+        label: label.clone(),
+        value: money.format(minor),
+        fill: '.',
+    };
+
+    let mut elements = Vec::from([line(&labels.subtotal, totals.subtotal)]);
+    if totals.discount != 0 {
+        elements.push(line(&labels.discount, -totals.discount));
+    }
+    elements.push(line(&labels.tax, totals.tax));
+
+    // the total is the bold header of a table, so it's aligned with the amounts above it
+    let total = money.format(totals.total);
+    elements.push(Element::Table(
+        Table::new([
+            Column::weight(1),
+            Column::chars(chars([&total])).align(Align::Right),
+        ])
+        .header([labels.total.clone(), total])
+        .header_style(HeaderStyle {
+            bold: true,
+            ..Default::default()
+        }),
+    ));
+
+    elements
+}
+
+/// The table of the tax summary of `lines`, the amounts formatted by `money`.
+pub(super) fn tax_table(lines: &[TaxLine], money: &MoneyFormat, labels: &SaleLabels) -> Table {
+    let rows: Vec<[String; 4]> = lines
+        .iter()
+        .map(|line| {
+            [
+                percent(line.rate),
+                money.format_number(line.net),
+                money.format_number(line.tax),
+                money.format_number(line.gross()),
+            ]
+        })
+        .collect();
+    let header = [
+        labels.rate.clone(),
+        labels.net.clone(),
+        labels.tax.clone(),
+        labels.gross.clone(),
+    ];
+    let width = |i: usize| chars(rows.iter().map(|row| &row[i]).chain([&header[i]]));
+
+    let mut table = Table::new([
+        Column::weight(1),
+        Column::chars(width(1)).align(Align::Right),
+        Column::chars(width(2)).align(Align::Right),
+        Column::chars(width(3)).align(Align::Right),
+    ])
+    .header(header)
+    .header_style(HEADER_STYLE);
+    for row in rows {
+        table = table.row(row);
+    }
+
+    table
+}
+
+/// A rate of `hundredths` of a percent, without the trailing zeros of its decimals, e.g. `19%` or `7.5%`.
+fn percent(hundredths: u16) -> String {
+    let (units, fraction) = (hundredths / 100, hundredths % 100);
+    match fraction {
+        0 => format!("{units}%"),
+        _ if fraction % 10 == 0 => format!("{units}.{}%", fraction / 10),
+        _ => format!("{units}.{fraction:02}%"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent() {
+        assert_eq!(percent(1900), "19%");
+        assert_eq!(percent(750), "7.5%");
+        assert_eq!(percent(525), "5.25%");
+        assert_eq!(percent(5), "0.05%");
+    }
+
+    #[test]
+    fn test_items_table() {
+        let items = [
+            SaleItem::new(2, "Coffee", 250),
+            SaleItem::new(12, "Croissant", 180),
+        ];
+        assert_eq!(items[1].amount(), 2160);
+        let table = items_table(&items, &MoneyFormat::en_us(), &SaleLabels::default());
+        // the columns are as wide as their longest cell
+        assert_eq!(
+            table.columns,
+            [
+                Column::chars(3).align(Align::Right),
+                Column::weight(1),
+                Column::chars(4).align(Align::Right),
+                Column::chars(6).align(Align::Right),
+            ]
+        );
+        assert_eq!(table.rows[1], ["12", "Croissant", "1.80", "$21.60"]);
+    }
+
+    #[test]
+    fn test_totals_elements() {
+        let line = |label: &str, value: &str| Element::KeyValue {
+            label: label.into(),
+            value: value.into(),
+            fill: '.',
+        };
+        let money = MoneyFormat::default();
+        let labels = SaleLabels::default();
+
+        let elements = totals_elements(&Totals::new(1000, 80, 1080), &money, &labels);
+        assert_eq!(
+            elements[..2],
+            [line("Subtotal", "10.00"), line("Tax", "0.80")]
+        );
+        let elements = totals_elements(&Totals::new(1000, 72, 972).discount(100), &money, &labels);
+        // the discount is subtracted
+        assert_eq!(elements[1], line("Discount", "-1.00"));
+        match &elements[3] {
+            Element::Table(table) => {
+                assert_eq!(
+                    table.header.as_deref(),
+                    Some(&["TOTAL".into(), "9.72".into()][..])
+                );
+                assert!(table.header_style.bold);
+            }
+            element => panic!("unexpected {element:?}"),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Separators of the receipts.
+
+use alloc::string::String;
+
+use super::{columns, FONT_A_WIDTH};
+use crate::{raster::Raster, BitImageMode, CommandBuilder, Error};
+
+/// Length of the dashes of a [`SeparatorStyle::Dashed`] line and of the space after them in dots.
+const DASH: usize = 8;
+const DASH_GAP: usize = 4;
+
+/// Style of the line of a [`Separator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeparatorStyle {
+    /// A solid line drawn as a bit image
+    Solid,
+    /// A dashed line drawn as a bit image
+    Dashed,
+    /// Two solid lines drawn as a bit image, separated by their thickness
+    Double,
+    /// A line of text repeating a character, e.g. `'='`
+    Char(char),
+}
+
+/// A line across the paper separating the parts of a receipt, e.g. the items from the totals.
+///
+/// The lines of the [`SeparatorStyle::Solid`], [`Dashed`](SeparatorStyle::Dashed) and
+/// [`Double`](SeparatorStyle::Double) styles are drawn dot by dot across the printable line, and
+/// [`SeparatorStyle::Char`] lines are text in the characters of font A fitting in it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Emulator, JobBuilder, PrinterConfig, Receipt, Separator, SeparatorStyle};
+/// let receipt = Receipt::new()
+///     .separator(Separator::new(SeparatorStyle::Char('=')).inset(2))
+///     .separator(Separator::new(SeparatorStyle::Solid).thickness(1));
+///
+/// let mut builder = JobBuilder::new();
+/// builder.set_config(Some(PrinterConfig { dots_per_line: 96, ..Default::default() }));
+/// receipt.render(&mut builder).unwrap();
+///
+/// let mut emulator = Emulator::new();
+/// emulator.print_job(&builder.build());
+/// let paper = emulator.render();
+/// let lines: Vec<_> = paper.lines().collect();
+/// assert_eq!(lines[0], "  ====");
+/// assert_eq!(lines[1], "#".repeat(96));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Separator {
+    /// Style of the line
+    pub style: SeparatorStyle,
+    /// Thickness of the line in dots, or the number of lines of text of a [`SeparatorStyle::Char`], at least 1
+    pub thickness: u8,
+    /// Number of characters of font A left blank at each end of the line
+    pub inset: u16,
+}
+
+impl Separator {
+    /// A separator of `style` across the whole line, 2 dots or a line of text thick.
+    pub const fn new(style: SeparatorStyle) -> Self {
+        let thickness = match style {
+            SeparatorStyle::Char(_) => 1,
+            _ => 2,
+        };
+
+        Self {
+            style,
+            thickness,
+            inset: 0,
+        }
+    }
+
+    /// The separator `thickness` dots or lines of text thick.
+    pub const fn thickness(self, thickness: u8) -> Self {
+        Self { thickness, ..self }
+    }
+
+    /// The separator leaving `inset` characters blank at each end of the line.
+    pub const fn inset(self, inset: u16) -> Self {
+        Self { inset, ..self }
+    }
+
+    /// Render the separator on `builder` for a line of `dots`.
+    pub(crate) fn render<B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &mut B,
+        dots: u16,
+    ) -> Result<(), Error> {
+        let thickness = self.thickness.max(1) as usize;
+        let inset = self.inset as usize;
+
+        if let SeparatorStyle::Char(c) = self.style {
+            // at least a character is left between the insets
+            let columns = columns(dots);
+            let inset = inset.min((columns - 1) / 2);
+            let line = " ".repeat(inset) + &String::from(c).repeat(columns - 2 * inset);
+            for _ in 0..thickness {
+                builder.raw(line.as_bytes()).print();
+            }
+            return Ok(());
+        }
+
+        // the bit image can't be wider than the line
+        let width = dots as usize / 8 * 8;
+        let inset = (inset * FONT_A_WIDTH as usize).min(width / 2);
+        let length = width - 2 * inset;
+        let height = match self.style {
+            SeparatorStyle::Double => thickness * 3,
+            _ => thickness,
+        };
+
+        let mut raster = Raster::new(width, height);
+        match self.style {
+            SeparatorStyle::Dashed => {
+                for x in (0..length).step_by(DASH + DASH_GAP) {
+                    raster.fill(inset + x, 0, DASH.min(length - x), thickness);
+                }
+            }
+            SeparatorStyle::Double => {
+                raster.fill(inset, 0, length, thickness);
+                raster.fill(inset, thickness * 2, length, thickness);
+            }
+            _ => raster.fill(inset, 0, length, thickness),
+        }
+        let mode = if height <= 8 {
+            BitImageMode::Dots8DoubleDensity
+        } else {
+            BitImageMode::Dots24DoubleDensity
+        };
+        builder
+            .bitmap(raster.width(), raster.height(), raster.data(), mode)?
+            .print();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Emulator, PrinterConfig};
+    use alloc::format;
+
+    #[test]
+    fn test_render() {
+        let render = |separator: Separator, dots: u16| {
+            let mut emulator = Emulator::new();
+            separator.render(&mut emulator, dots).unwrap();
+            emulator.render()
+        };
+
+        assert_eq!(
+            render(Separator::new(SeparatorStyle::Dashed).thickness(1), 32),
+            "########    ########    ########\n\n\n\n\n\n\n\n"
+        );
+        assert_eq!(
+            render(
+                Separator::new(SeparatorStyle::Double).thickness(1).inset(1),
+                40
+            ),
+            format!(
+                "{0}\n\n{0}\n\n\n\n\n\n",
+                format!("{}{}", " ".repeat(12), "#".repeat(16))
+            )
+        );
+        // insets leave at least a character
+        assert_eq!(
+            render(
+                Separator::new(SeparatorStyle::Char('*'))
+                    .thickness(2)
+                    .inset(9),
+                60
+            ),
+            "  *\n  *\n"
+        );
+
+        let config = PrinterConfig {
+            dots_per_line: 100,
+            ..Default::default()
+        };
+        let mut emulator = Emulator::new();
+        Separator::new(SeparatorStyle::Solid)
+            .thickness(12)
+            .render(&mut emulator, config.printable_dots())
+            .unwrap();
+        assert_eq!(
+            emulator
+                .render()
+                .lines()
+                .filter(|line| *line == "#".repeat(96))
+                .count(),
+            12
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Styles rendering the same receipt for different readers, e.g. the customer and the kitchen.
+
+use crate::{Command, CommandBuilder, CutType, Error};
+
+/// Duration of each beep of [`RenderStyle::beeps`] in milliseconds.
+const BEEP_MS: u16 = 200;
+
+/// A style of [`Receipt::render_styled()`](super::Receipt::render_styled()), so the same order can be printed as a
+/// customer receipt or as a kitchen ticket.
+///
+/// The regions of the canvases and layouts are pixel-precise designs, so they are rendered in their own style, like
+/// the contents of the watermarks composed with them in page mode.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Command, CutType, JobBuilder, List, ListStyle, Receipt, RenderStyle};
+/// let order = Receipt::new()
+///     .heading(1, "Order 42")
+///     .list(List::new(ListStyle::Numbered(1)).nested("Burger", List::new(ListStyle::Bullet('-')).item("no onions")));
+///
+/// let mut builder = JobBuilder::new();
+/// order.render_styled(&mut builder, &RenderStyle::kitchen_ticket()).unwrap();
+/// let commands = builder.build().commands().to_vec();
+/// // double height text, cut partially and the cook called
+/// assert_eq!(commands[0], Command::CharacterSize { width: 1, height: 2 });
+/// assert!(commands.ends_with(&[
+///     Command::FeedAndCutPaper { cut_type: CutType::PartialCut, amount: 0 },
+///     Command::Beep { count: 2, duration_ms: 200 },
+/// ]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderStyle {
+    /// Height of the characters, as a multiple of the normal height up to 8, the width of the line in characters
+    /// being kept
+    pub text_height: u8,
+    /// Whether the items of the nested lists are printed in bold, e.g. the modifiers of the items of an order
+    pub bold_modifiers: bool,
+    /// Whether the bitmaps and images are printed, e.g. the logo
+    pub images: bool,
+    /// Number of beeps of the buzzer after the receipt, up to 9, see [`CommandBuilder::beep()`]
+    pub beeps: u8,
+    /// Type of the cuts, replacing the type of the [`Element::Cut`](super::Element::Cut)s and appended after the
+    /// receipt if it doesn't end with a cut, or `None` to keep the cuts of the receipt
+    pub cut: Option<CutType>,
+}
+
+impl RenderStyle {
+    /// The style of the customer receipts, rendering the elements as they are.
+    pub fn customer_receipt() -> Self {
+        Self {
+            text_height: 1,
+            bold_modifiers: false,
+            images: true,
+            beeps: 0,
+            cut: None,
+        }
+    }
+
+    /// The style of the kitchen tickets, read from afar: double height text, bold modifiers, no images, 2 beeps
+    /// and a partial cut.
+    pub fn kitchen_ticket() -> Self {
+        Self {
+            text_height: 2,
+            bold_modifiers: true,
+            images: false,
+            beeps: 2,
+            cut: Some(CutType::PartialCut),
+        }
+    }
+
+    /// Height of the characters, between 1 and 8.
+    pub(super) fn height(&self) -> u8 {
+        self.text_height.clamp(1, 8)
+    }
+
+    /// Set the height of the characters of the style on `builder`, before the receipt or after a style of its own.
+    pub(super) fn begin<B: CommandBuilder + ?Sized>(&self, builder: &mut B) {
+        if self.height() != 1 {
+            builder.character_size(1, self.height());
+        }
+    }
+
+    /// Reset the height of the characters on `builder`, after the receipt or before a style of its own.
+    pub(super) fn end<B: CommandBuilder + ?Sized>(&self, builder: &mut B) {
+        if self.height() != 1 {
+            builder.character_size(1, 1);
+        }
+    }
+
+    /// Cut the paper if the receipt didn't end with a cut, then sound the buzzer on `builder`.
+    pub(super) fn finish<B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &mut B,
+        cut: bool,
+    ) -> Result<(), Error> {
+        if let (Some(cut_type), false) = (self.cut, cut) {
+            builder.try_push(Command::FeedAndCutPaper {
+                cut_type,
+                amount: 0,
+            })?;
+        }
+        if self.beeps > 0 {
+            builder.try_push(Command::Beep {
+                count: self.beeps.min(9),
+                duration_ms: BEEP_MS,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        Self::customer_receipt()
+    }
+}
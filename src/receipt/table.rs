@@ -0,0 +1,525 @@
+//! Tables of the receipts.
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use super::{columns, pad, wrap, Align, FONT_A_WIDTH};
+use crate::{
+    raster::{Raster, CELL_HEIGHT, CELL_WIDTH},
+    BitImageMode, CommandBuilder, Error,
+};
+
+/// Space between the columns in dots, a character of font A.
+const GAP: u16 = FONT_A_WIDTH;
+/// Height of the rule below the header of a table printed as a bit image, in dots.
+const RULE_HEIGHT: usize = 8;
+
+/// Width of a [`Column`] of a [`Table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnWidth {
+    /// Number of characters of font A
+    Chars(u16),
+    /// Number of dots
+    Dots(u16),
+    /// Share of the width left by the other columns, in proportion to the weights of the weighted columns
+    Weight(u16),
+}
+
+/// A column of a [`Table`], its cells being aligned by `align`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    /// Width of the column
+    pub width: ColumnWidth,
+    /// Alignment of the cells in the column
+    pub align: Align,
+}
+
+impl Column {
+    /// A left aligned column of `chars` characters.
+    pub const fn chars(chars: u16) -> Self {
+        Self::new(ColumnWidth::Chars(chars))
+    }
+
+    /// A left aligned column of `dots` dots.
+    pub const fn dots(dots: u16) -> Self {
+        Self::new(ColumnWidth::Dots(dots))
+    }
+
+    /// A left aligned column sharing the width left by the other columns by `weight`.
+    pub const fn weight(weight: u16) -> Self {
+        Self::new(ColumnWidth::Weight(weight))
+    }
+
+    /// The column aligning its cells by `align`.
+    pub const fn align(self, align: Align) -> Self {
+        Self { align, ..self }
+    }
+
+    const fn new(width: ColumnWidth) -> Self {
+        Self {
+            width,
+            align: Align::Left,
+        }
+    }
+}
+
+/// Style of the header row of a [`Table`], plain by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeaderStyle {
+    /// Print the header in bold
+    pub bold: bool,
+    /// Underline the text of the header
+    pub underline: bool,
+    /// Draw a rule across the table below the header
+    pub rule: bool,
+}
+
+/// How a [`Table`] is printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TableMode {
+    /// Text in the characters of font A, each column being a whole number of characters
+    #[default]
+    Text,
+    /// A bit image of the text drawn by the built-in font, each column starting at its exact dot, e.g. for columns
+    /// of dots which aren't a whole number of characters
+    BitImage,
+}
+
+/// A table of text cells in [`Column`]s, the cells too long for their column being wrapped on several lines.
+///
+/// The columns are separated by the width of a character, and the widths in dots of the
+/// [`ColumnWidth::Weight`] columns are computed from the width of the line by [`widths()`](Table::widths()).
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Align, Column, Emulator, HeaderStyle, JobBuilder, PrinterConfig, Receipt, Table};
+/// let table = Table::new([Column::chars(3), Column::weight(1), Column::chars(6).align(Align::Right)])
+///     .header(["Qty", "Item", "Amount"])
+///     .header_style(HeaderStyle { rule: true, ..Default::default() })
+///     .row(["2", "Coffee", "5.00"])
+///     .row(["1", "Pain au chocolat", "2.10"]);
+///
+/// let mut builder = JobBuilder::new();
+/// builder.set_config(Some(PrinterConfig { dots_per_line: 288, ..Default::default() }));
+/// Receipt::new().table(table).render(&mut builder).unwrap();
+///
+/// let mut emulator = Emulator::new();
+/// emulator.print_job(&builder.build());
+/// assert_eq!(
+///     emulator.render(),
+///     "Qty Item          Amount\n\
+///      ------------------------\n\
+///      2   Coffee          5.00\n\
+///      1   Pain au         2.10\n\
+///      \x20   chocolat\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub(super) columns: Vec<Column>,
+    pub(super) header: Option<Vec<String>>,
+    pub(super) rows: Vec<Vec<String>>,
+    pub(super) header_style: HeaderStyle,
+    pub(super) mode: TableMode,
+}
+
+impl Table {
+    /// Create a table of `columns` without any row, printed as [`TableMode::Text`].
+    pub fn new(columns: impl Into<Vec<Column>>) -> Self {
+        Self {
+            columns: columns.into(),
+            header: None,
+            rows: Vec::new(),
+            header_style: HeaderStyle::default(),
+            mode: TableMode::default(),
+        }
+    }
+
+    /// Set the header row of the table to `cells`, printed in the [`header_style()`](Table::header_style()).
+    pub fn header<S: Into<String>>(mut self, cells: impl IntoIterator<Item = S>) -> Self {
+        self.header = Some(cells.into_iter().map(Into::into).collect());
+
+        self
+    }
+
+    /// Append a row of `cells`, the missing cells being blank and the extra ones left out.
+    pub fn row<S: Into<String>>(mut self, cells: impl IntoIterator<Item = S>) -> Self {
+        self.rows.push(cells.into_iter().map(Into::into).collect());
+
+        self
+    }
+
+    /// Set the style of the header row.
+    pub fn header_style(mut self, style: HeaderStyle) -> Self {
+        self.header_style = style;
+
+        self
+    }
+
+    /// Set how the table is printed.
+    pub fn mode(mut self, mode: TableMode) -> Self {
+        self.mode = mode;
+
+        self
+    }
+
+    /// Widths of the columns in dots in a line of `dots`.
+    ///
+    /// The [`ColumnWidth::Weight`] columns share the width left by the other columns and the space between them,
+    /// the last one taking the rounding remainder, or equally if all their weights are 0. [`Error::InvalidParameter`]
+    /// is returned if the other columns and the space between them are wider than the line.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use custom_printer::{Column, Table};
+    /// let table = Table::new([Column::chars(4), Column::weight(1), Column::weight(2), Column::dots(50)]);
+    /// assert_eq!(table.widths(384).unwrap(), [48, 83, 167, 50]);
+    /// assert!(table.widths(100).is_err());
+    /// ```
+    pub fn widths(&self, dots: u16) -> Result<Vec<u16>, Error> {
+        let fixed = self
+            .columns
+            .iter()
+            .map(|column| match column.width {
+                ColumnWidth::Chars(chars) => chars as u32 * FONT_A_WIDTH as u32,
+                ColumnWidth::Dots(dots) => dots as u32,
+                ColumnWidth::Weight(_) => 0,
+            })
+            .sum::<u32>()
+            + self.columns.len().saturating_sub(1) as u32 * GAP as u32;
+        if fixed > dots as u32 {
+            return Err(Error::InvalidParameter(format!(
+                "table columns of {fixed} dots are wider than {dots} dots"
+            )));
+        }
+
+        let left = dots as u32 - fixed;
+        // columns all of weight 0 share the width equally
+        let equal = self
+            .columns
+            .iter()
+            .all(|column| !matches!(column.width, ColumnWidth::Weight(1..)));
+        let weight = |weight: u16| if equal { 1 } else { weight as u32 };
+        let weights: u32 = self
+            .columns
+            .iter()
+            .map(|column| match column.width {
+                ColumnWidth::Weight(w) => weight(w),
+                _ => 0,
+            })
+            .sum();
+        let last = self
+            .columns
+            .iter()
+            .rposition(|column| matches!(column.width, ColumnWidth::Weight(_)));
+        let mut shared = 0;
+
+        Ok(self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| match column.width {
+                ColumnWidth::Chars(chars) => chars.saturating_mul(FONT_A_WIDTH),
+                ColumnWidth::Dots(dots) => dots,
+                ColumnWidth::Weight(_) if Some(i) == last => (left - shared) as u16,
+                ColumnWidth::Weight(w) => {
+                    let width = left * weight(w) / weights;
+                    shared += width;
+                    width as u16
+                }
+            })
+            .collect())
+    }
+
+    /// Render the table on `builder` for a line of `dots`.
+    pub(crate) fn render<B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &mut B,
+        dots: u16,
+    ) -> Result<(), Error> {
+        let widths = self.widths(dots)?;
+        match self.mode {
+            TableMode::Text => self.render_text(builder, &widths),
+            TableMode::BitImage => {
+                let raster = self.rasterize(&widths, dots);
+                builder
+                    .bitmap(
+                        raster.width(),
+                        raster.height(),
+                        raster.data(),
+                        BitImageMode::Dots24DoubleDensity,
+                    )?
+                    .print();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn render_text<B: CommandBuilder + ?Sized>(&self, builder: &mut B, widths: &[u16]) {
+        let chars: Vec<usize> = widths.iter().map(|width| columns(*width)).collect();
+
+        if let Some(header) = &self.header {
+            let style = self.header_style;
+            if style.bold {
+                builder.bold(true);
+            }
+            if style.underline {
+                builder.underline(true);
+            }
+            self.print_row(builder, header, &chars);
+            if style.underline {
+                builder.underline(false);
+            }
+            if style.bold {
+                builder.bold(false);
+            }
+            if style.rule {
+                let width = chars.iter().sum::<usize>() + chars.len().saturating_sub(1);
+                builder.raw("-".repeat(width).as_bytes()).print();
+            }
+        }
+        for row in &self.rows {
+            self.print_row(builder, row, &chars);
+        }
+    }
+
+    fn print_row<B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &mut B,
+        cells: &[String],
+        chars: &[usize],
+    ) {
+        let lines = self.cell_lines(cells, chars);
+        let height = lines.iter().map(Vec::len).max().unwrap_or(0);
+
+        for i in 0..height {
+            let line = self
+                .columns
+                .iter()
+                .zip(chars)
+                .zip(&lines)
+                .map(|((column, chars), cell)| {
+                    pad(cell.get(i).map_or("", String::as_str), *chars, column.align)
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            builder.raw(line.trim_end().as_bytes()).print();
+        }
+    }
+
+    /// The lines of each cell of `cells` wrapped in the `chars` of its column.
+    fn cell_lines(&self, cells: &[String], chars: &[usize]) -> Vec<Vec<String>> {
+        chars
+            .iter()
+            .enumerate()
+            .map(|(i, chars)| match cells.get(i) {
+                Some(cell) => wrap(cell, *chars),
+                None => Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Draw the table in a raster of `dots`, the columns being of `widths`.
+    fn rasterize(&self, widths: &[u16], dots: u16) -> Raster {
+        let chars: Vec<usize> = widths.iter().map(|width| columns(*width)).collect();
+        let rows: Vec<(bool, Vec<Vec<String>>)> = self
+            .header
+            .iter()
+            .map(|header| (true, self.cell_lines(header, &chars)))
+            .chain(
+                self.rows
+                    .iter()
+                    .map(|row| (false, self.cell_lines(row, &chars))),
+            )
+            .collect();
+        let height = |lines: &[Vec<String>]| lines.iter().map(Vec::len).max().unwrap_or(0);
+        let rule = if self.header.is_some() && self.header_style.rule {
+            RULE_HEIGHT
+        } else {
+            0
+        };
+
+        let mut raster = Raster::new(
+            dots as usize / 8 * 8,
+            rows.iter().map(|(_, lines)| height(lines)).sum::<usize>() * CELL_HEIGHT + rule,
+        );
+        let mut lefts = vec![0];
+        for width in widths {
+            lefts.push(lefts[lefts.len() - 1] + (width + GAP) as usize);
+        }
+
+        let mut top = 0;
+        for (header, lines) in &rows {
+            let bold = *header && self.header_style.bold;
+            let underline = *header && self.header_style.underline;
+            for (((column, cell), width), left) in
+                self.columns.iter().zip(lines).zip(widths).zip(&lefts)
+            {
+                for (i, line) in cell.iter().enumerate() {
+                    let len = line.chars().count() * CELL_WIDTH;
+                    let x = left
+                        + match column.align {
+                            Align::Left => 0,
+                            Align::Center => (*width as usize).saturating_sub(len) / 2,
+                            Align::Right => (*width as usize).saturating_sub(len),
+                        };
+                    let y = top + i * CELL_HEIGHT;
+                    raster.text(x, y, line, bold);
+                    if underline {
+                        raster.fill(x, y + CELL_HEIGHT - 2, len, 2);
+                    }
+                }
+            }
+            top += height(lines) * CELL_HEIGHT;
+            if *header && rule > 0 {
+                raster.fill(0, top + RULE_HEIGHT / 2 - 1, dots as usize, 2);
+                top += rule;
+            }
+        }
+
+        raster
+    }
+}
+
+/// Two independent blocks of text flowing side by side, e.g. the languages of a bilingual receipt or terms and
+/// conditions in two columns.
+///
+/// Each block is wrapped in its own [`Column`], a `\n` starting a new line of the block, and the blocks are
+/// separated like the columns of a [`Table`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Column, Emulator, JobBuilder, PrinterConfig, Receipt, TwoColumns};
+/// let columns = TwoColumns::new("Thank you for your visit", "Grazie per la visita")
+///     .columns(Column::weight(1), Column::weight(1));
+///
+/// let mut builder = JobBuilder::new();
+/// builder.set_config(Some(PrinterConfig { dots_per_line: 288, ..Default::default() }));
+/// Receipt::new().two_columns(columns).render(&mut builder).unwrap();
+///
+/// let mut emulator = Emulator::new();
+/// emulator.print_job(&builder.build());
+/// assert_eq!(
+///     emulator.render(),
+///     "Thank you   Grazie per\n\
+///      for your    la visita\n\
+///      visit\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwoColumns {
+    /// Text of the left column
+    pub left: String,
+    /// Text of the right column
+    pub right: String,
+    /// Left and right columns, sharing the line equally by default
+    pub columns: [Column; 2],
+}
+
+impl TwoColumns {
+    /// Create the blocks of `left` and `right` text, sharing the line equally.
+    pub fn new(left: impl Into<String>, right: impl Into<String>) -> Self {
+        Self {
+            left: left.into(),
+            right: right.into(),
+            columns: [Column::weight(1), Column::weight(1)],
+        }
+    }
+
+    /// The blocks wrapped in the `left` and `right` columns, e.g. of different widths or alignments.
+    pub fn columns(mut self, left: Column, right: Column) -> Self {
+        self.columns = [left, right];
+
+        self
+    }
+
+    /// Render the blocks on `builder` for a line of `dots`.
+    pub(crate) fn render<B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &mut B,
+        dots: u16,
+    ) -> Result<(), Error> {
+        Table::new(self.columns)
+            .row([self.left.as_str(), self.right.as_str()])
+            .render(builder, dots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, Emulator, JobBuilder};
+
+    #[test]
+    fn test_table() {
+        let table = Table::new([Column::dots(30), Column::weight(1).align(Align::Center)])
+            .header(["A", "B"])
+            .header_style(HeaderStyle {
+                bold: true,
+                underline: true,
+                rule: false,
+            })
+            .row(["long", "x"])
+            .row(["y"]);
+
+        let mut builder = JobBuilder::new();
+        table.render(&mut builder, 100).unwrap();
+        let commands: Vec<_> = builder.commands().cloned().collect();
+        assert_eq!(
+            &commands[..6],
+            &[
+                Command::Bold(true),
+                Command::Underline(true),
+                Command::Raw(b"A   B".to_vec()),
+                Command::Print,
+                Command::Underline(false),
+                Command::Bold(false),
+            ]
+        );
+        let mut emulator = Emulator::new();
+        emulator.print_job(&builder.build());
+        assert_eq!(emulator.render(), "A   B\nlo  x\nng\ny\n");
+
+        // the second column starts exactly at 30 + 12 dots
+        let raster = table.mode(TableMode::BitImage).rasterize(&[30, 58], 100);
+        assert_eq!((raster.width(), raster.height()), (96, 4 * CELL_HEIGHT));
+        // the centered B is at 42 + (58 - 12) / 2 and underlined
+        assert!(raster.get(65 + 1, 1) && !raster.get(64, 1));
+        assert!(raster.get(65, CELL_HEIGHT - 1));
+
+        assert!(matches!(
+            Table::new([Column::chars(5), Column::chars(5)]).widths(120),
+            Err(Error::InvalidParameter(_))
+        ));
+        assert_eq!(
+            Table::new([Column::weight(0), Column::chars(2), Column::weight(0)])
+                .widths(384)
+                .unwrap(),
+            [168, 24, 168]
+        );
+    }
+
+    #[test]
+    fn test_two_columns() {
+        let columns = TwoColumns::new("Conditions:\nno refunds", "Condizioni: nessun rimborso")
+            .columns(Column::chars(6), Column::weight(1).align(Align::Right));
+        let mut emulator = Emulator::new();
+        columns.render(&mut emulator, 240).unwrap();
+        assert_eq!(
+            emulator.render(),
+            "Condit   Condizioni:\n\
+             ions:         nessun\n\
+             no          rimborso\n\
+             refund\n\
+             s\n"
+        );
+
+        assert!(columns
+            .columns(Column::chars(30), Column::weight(1))
+            .render(&mut emulator, 240)
+            .is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! Styled text of the receipts.
+
+use alloc::{string::String, vec::Vec};
+
+use super::Align;
+use crate::CommandBuilder;
+
+/// Style of a [`Span`] of text, plain by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    /// Printed in bold
+    pub bold: bool,
+    /// Underlined
+    pub underline: bool,
+}
+
+/// A run of text in the same [`TextStyle`], e.g. a bold word in a sentence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// Text of the run
+    pub text: String,
+    /// Style of the text
+    pub style: TextStyle,
+}
+
+impl Span {
+    /// A run of `text` in `style`.
+    pub fn new(text: impl Into<String>, style: TextStyle) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+
+    /// A run of plain `text`.
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self::new(text, TextStyle::default())
+    }
+
+    /// A run of bold `text`.
+    pub fn bold(text: impl Into<String>) -> Self {
+        Self::new(
+            text,
+            TextStyle {
+                bold: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// A run of underlined `text`.
+    pub fn underline(text: impl Into<String>) -> Self {
+        Self::new(
+            text,
+            TextStyle {
+                underline: true,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// Lines of `spans` wrapped at the spaces in `columns` characters, splitting the words longer than a line.
+///
+/// The spaces at the start of the first line are kept, and the following lines are indented by `indent` spaces.
+/// The spaces between two words are in the style the words share.
+pub(crate) fn wrap_spans(spans: &[Span], columns: usize, indent: usize) -> Vec<Vec<Span>> {
+    let chars: Vec<(char, TextStyle)> = spans
+        .iter()
+        .flat_map(|span| span.text.chars().map(move |c| (c, span.style)))
+        .collect();
+    let lead = chars.iter().take_while(|(c, _)| *c == ' ').count();
+    let words = chars[lead..]
+        .split(|(c, _)| c.is_whitespace())
+        .filter(|word| !word.is_empty());
+
+    let plain = TextStyle::default();
+    let mut lines = Vec::new();
+    let mut line: Vec<(char, TextStyle)> =
+        Vec::from_iter((0..lead.min(columns)).map(|_| (' ', plain)));
+    let mut has_words = false;
+    for word in words {
+        let mut word = word;
+        if has_words {
+            let (_, before) = line[line.len() - 1];
+            let after = word[0].1;
+            if line.len() + 1 + word.len() <= columns {
+                let space = TextStyle {
+                    bold: before.bold && after.bold,
+                    underline: before.underline && after.underline,
+                };
+                line.push((' ', space));
+            } else {
+                lines.push(core::mem::take(&mut line));
+                line.extend((0..indent).map(|_| (' ', plain)));
+            }
+        }
+        // a line always takes a character, even if the indent leaves no room
+        while word.len() > columns.saturating_sub(line.len()).max(1) {
+            let (head, rest) = word.split_at(columns.saturating_sub(line.len()).max(1));
+            line.extend_from_slice(head);
+            lines.push(core::mem::take(&mut line));
+            line.extend((0..indent).map(|_| (' ', plain)));
+            word = rest;
+        }
+        line.extend_from_slice(word);
+        has_words = true;
+    }
+    if has_words || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+        .into_iter()
+        .map(|line| {
+            let mut spans: Vec<Span> = Vec::new();
+            for (c, style) in line {
+                match spans.last_mut() {
+                    Some(span) if span.style == style => span.text.push(c),
+                    _ => spans.push(Span::new(String::from(c), style)),
+                }
+            }
+            spans
+        })
+        .collect()
+}
+
+/// Align each of the styled `lines` in `columns` characters by `align`, padding them on the left by plain spaces.
+pub(crate) fn align_spans(lines: &mut [Vec<Span>], columns: usize, align: Align) {
+    for line in lines {
+        let len: usize = line.iter().map(|span| span.text.chars().count()).sum();
+        let padding = columns.saturating_sub(len);
+        let left = match align {
+            Align::Left => 0,
+            Align::Center => padding / 2,
+            Align::Right => padding,
+        };
+        if left > 0 {
+            line.insert(0, Span::plain(" ".repeat(left)));
+        }
+    }
+}
+
+/// Print the styled `lines`, switching the style between the spans and back to plain at the end.
+pub(crate) fn print_spans<B: CommandBuilder + ?Sized>(builder: &mut B, lines: &[Vec<Span>]) {
+    let mut current = TextStyle::default();
+    let mut switch = |builder: &mut B, style: TextStyle| {
+        if style.bold != current.bold {
+            builder.bold(style.bold);
+        }
+        if style.underline != current.underline {
+            builder.underline(style.underline);
+        }
+        current = style;
+    };
+
+    for line in lines {
+        for span in line {
+            switch(builder, span.style);
+            builder.raw(span.text.as_bytes());
+        }
+        builder.print();
+    }
+    switch(builder, TextStyle::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, JobBuilder};
+    use alloc::vec;
+
+    #[test]
+    fn test_wrap_spans() {
+        let spans = [
+            Span::plain("  - a "),
+            Span::bold("bold"),
+            Span::plain(" word and "),
+            Span::underline("under it"),
+        ];
+        assert_eq!(
+            wrap_spans(&spans, 12, 4),
+            [
+                vec![Span::plain("  - a "), Span::bold("bold")],
+                vec![Span::plain("    word and")],
+                vec![Span::plain("    "), Span::underline("under it")],
+            ]
+        );
+        assert_eq!(wrap_spans(&[], 12, 4), [vec![]]);
+        assert_eq!(
+            wrap_spans(&[Span::plain("abcdefgh")], 5, 2),
+            [vec![Span::plain("abcde")], vec![Span::plain("  fgh")]]
+        );
+
+        let mut lines = wrap_spans(&spans[1..2], 12, 0);
+        align_spans(&mut lines, 12, Align::Center);
+        assert_eq!(lines, [vec![Span::plain("    "), Span::bold("bold")]]);
+
+        let mut builder = JobBuilder::new();
+        print_spans(&mut builder, &wrap_spans(&spans[1..2], 12, 0));
+        assert_eq!(
+            builder.build().commands(),
+            [
+                Command::Bold(true),
+                Command::Raw(b"bold".to_vec()),
+                Command::Print,
+                Command::Bold(false)
+            ]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Bordered text boxes of the receipts.
+
+use alloc::{string::String, vec::Vec};
+
+use super::{columns, pad, wrap, Align};
+use crate::{
+    raster::{Raster, CELL_HEIGHT, CELL_WIDTH},
+    BitImageMode, CommandBuilder, Error,
+};
+
+/// Thickness of the border of a [`BorderStyle::Line`] box and the space inside it, in dots.
+const BORDER: usize = 2;
+const INNER_GAP: usize = 2;
+
+/// Border of a [`TextBox`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BorderStyle {
+    /// Thin rules drawn with the text as a bit image
+    #[default]
+    Line,
+    /// ASCII characters of font A, `+` at the corners, `-` and `|` along the sides
+    Ascii,
+}
+
+/// Text framed by a border across the paper, e.g. a warranty note or a legal disclaimer.
+///
+/// The text is wrapped inside the border and its padding, a `\n` starting a new line.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Align, BorderStyle, Emulator, JobBuilder, PrinterConfig, Receipt, TextBox};
+/// let note = TextBox::new("Keep this receipt for the warranty")
+///     .border(BorderStyle::Ascii)
+///     .align(Align::Center);
+///
+/// let mut builder = JobBuilder::new();
+/// builder.set_config(Some(PrinterConfig { dots_per_line: 240, ..Default::default() }));
+/// Receipt::new().text_box(note).render(&mut builder).unwrap();
+///
+/// let mut emulator = Emulator::new();
+/// emulator.print_job(&builder.build());
+/// assert_eq!(
+///     emulator.render(),
+///     "+------------------+\n\
+///      |    Keep this     |\n\
+///      | receipt for the  |\n\
+///      |     warranty     |\n\
+///      +------------------+\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextBox {
+    /// Text inside the box
+    pub text: String,
+    /// Style of the border
+    pub border: BorderStyle,
+    /// Number of blank characters between the border and the text on the left and right
+    pub padding: u16,
+    /// Number of blank lines between the border and the text at the top and bottom
+    pub vertical_padding: u8,
+    /// Alignment of the lines of text
+    pub align: Align,
+}
+
+impl TextBox {
+    /// A box of `text` left aligned inside a [`BorderStyle::Line`], padded by a character on the left and right.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            border: BorderStyle::Line,
+            padding: 1,
+            vertical_padding: 0,
+            align: Align::Left,
+        }
+    }
+
+    /// The box framed by `border`.
+    pub fn border(self, border: BorderStyle) -> Self {
+        Self { border, ..self }
+    }
+
+    /// The box padded by `padding` characters on the left and right, and `vertical_padding` lines at the top and
+    /// bottom.
+    pub fn padding(self, padding: u16, vertical_padding: u8) -> Self {
+        Self {
+            padding,
+            vertical_padding,
+            ..self
+        }
+    }
+
+    /// The box aligning its lines of text by `align`.
+    pub fn align(self, align: Align) -> Self {
+        Self { align, ..self }
+    }
+
+    /// Render the box on `builder` for a line of `dots`.
+    pub(crate) fn render<B: CommandBuilder + ?Sized>(
+        &self,
+        builder: &mut B,
+        dots: u16,
+    ) -> Result<(), Error> {
+        match self.border {
+            BorderStyle::Ascii => {
+                for line in self.ascii_lines(columns(dots)) {
+                    builder.raw(line.as_bytes()).print();
+                }
+            }
+            BorderStyle::Line => {
+                let raster = self.rasterize(dots);
+                builder
+                    .bitmap(
+                        raster.width(),
+                        raster.height(),
+                        raster.data(),
+                        BitImageMode::Dots24DoubleDensity,
+                    )?
+                    .print();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The lines of text of a [`BorderStyle::Ascii`] box in `columns` characters, at least a character of text
+    /// being left inside the border.
+    fn ascii_lines(&self, columns: usize) -> Vec<String> {
+        let padding = (self.padding as usize).min(columns.saturating_sub(3) / 2);
+        let inner = columns.saturating_sub(2 + 2 * padding).max(1);
+        let blank = " ".repeat(padding);
+        let row = |text: &str| ["|", &blank, &pad(text, inner, self.align), &blank, "|"].concat();
+        let rule = ["+", &"-".repeat(inner + 2 * padding), "+"].concat();
+
+        let mut lines = Vec::from([rule.clone()]);
+        let padding_lines = (0..self.vertical_padding).map(|_| row(""));
+        lines.extend(padding_lines.clone());
+        lines.extend(wrap(&self.text, inner).iter().map(|line| row(line)));
+        lines.extend(padding_lines);
+        lines.push(rule);
+
+        lines
+    }
+
+    /// Draw a [`BorderStyle::Line`] box in a raster of `dots`.
+    fn rasterize(&self, dots: u16) -> Raster {
+        let width = dots as usize / 8 * 8;
+        let margin = BORDER + INNER_GAP;
+        let chars = (width.saturating_sub(2 * margin) / CELL_WIDTH).max(1);
+        let padding = (self.padding as usize).min(chars.saturating_sub(1) / 2);
+        let inner = chars - 2 * padding;
+        let lines = wrap(&self.text, inner);
+        let top = margin + self.vertical_padding as usize * CELL_HEIGHT;
+        let height = 2 * top + lines.len() * CELL_HEIGHT;
+
+        let mut raster = Raster::new(width, height);
+        raster.fill(0, 0, width, BORDER);
+        raster.fill(0, height - BORDER, width, BORDER);
+        raster.fill(0, 0, BORDER, height);
+        raster.fill(width - BORDER, 0, BORDER, height);
+        for (i, line) in lines.iter().enumerate() {
+            let len = line.chars().count();
+            let offset = match self.align {
+                Align::Left => 0,
+                Align::Center => inner.saturating_sub(len) / 2,
+                Align::Right => inner.saturating_sub(len),
+            };
+            let x = margin + (padding + offset) * CELL_WIDTH;
+            raster.text(x, top + i * CELL_HEIGHT, line, false);
+        }
+
+        raster
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_lines() {
+        let text_box = TextBox::new("No refunds\nafter 30 days")
+            .border(BorderStyle::Ascii)
+            .padding(2, 1)
+            .align(Align::Right);
+        assert_eq!(
+            text_box.ascii_lines(16),
+            [
+                "+--------------+",
+                "|              |",
+                "|  No refunds  |",
+                "|    after 30  |",
+                "|        days  |",
+                "|              |",
+                "+--------------+",
+            ]
+        );
+        // the padding leaves a character of text
+        assert_eq!(
+            TextBox::new("ab")
+                .border(BorderStyle::Ascii)
+                .padding(9, 0)
+                .ascii_lines(5),
+            ["+---+", "| a |", "| b |", "+---+"]
+        );
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let raster = TextBox::new("I").padding(1, 1).rasterize(100);
+        // a line of padding above and below the text, inside the borders
+        assert_eq!((raster.width(), raster.height()), (96, 2 * (4 + 24) + 24));
+        assert!(raster.get(0, 0) && raster.get(95, 1) && raster.get(1, 50) && raster.get(94, 79));
+        assert!(!raster.get(2, 2) && !raster.get(93, 77));
+        // the bar of I is in the middle of the cell after the padding
+        assert!(raster.get(4 + 12 + 5, 28 + 10));
+        assert!(!raster.get(4 + 5, 28 + 10));
+    }
+}
@@ -0,0 +1,262 @@
+//! Watermarks of the receipts, faint marks printed behind their text, e.g. on the duplicates.
+
+use alloc::{format, string::String, vec::Vec};
+
+use super::{layout::nested_page, Canvas, Receipt, Region, RenderStyle};
+use crate::{
+    builder, preview,
+    raster::{Raster, CELL_HEIGHT, CELL_WIDTH},
+    BitImageMode, CommandBuilder, Error, JobBuilder,
+};
+
+/// Thresholds of the ordered dithering of the watermarks, in sixteenths.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Image of a [`Watermark`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatermarkImage {
+    /// Text enlarged to about 3/4 of the line, as tall as the content allows, e.g. `COPY` or `VOID`
+    Text(String),
+    /// A 1 bpp bitmap, e.g. a logo, its rows padded to whole bytes, cropped to the line
+    Bitmap {
+        /// Width in pixels
+        width: usize,
+        /// Height in pixels
+        height: usize,
+        /// Rows of pixels, each padded to whole bytes
+        bitmap: Vec<u8>,
+    },
+}
+
+/// A faint mark centered behind the content of a [`Receipt::watermarked()`](super::Receipt::watermarked()), e.g.
+/// `COPY` on a duplicate receipt or a light logo.
+///
+/// The mark is made faint by ordered dithering, printing only `shade` sixteenths of its black dots, and composed
+/// with the content in page mode, so the content can't contain a [`Element::Canvas`](super::Element::Canvas), a
+/// [`Element::Layout`](super::Element::Layout), a watermark or a [`Element::Cut`](super::Element::Cut).
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{mnemonics, JobBuilder, Receipt, Watermark};
+/// let duplicate = Receipt::new().item("Coffee", "2.50").item("Croissant", "1.80");
+/// let receipt = Receipt::new().watermarked(Watermark::text("COPY").shade(3), duplicate);
+///
+/// let mut builder = JobBuilder::new();
+/// receipt.render(&mut builder).unwrap();
+/// // the mark and the content in the same area of the page
+/// let mnemonics = mnemonics(&builder.build().to_bytes());
+/// let areas: Vec<_> = mnemonics.iter().filter(|m| m.starts_with("ESC W")).collect();
+/// assert_eq!(areas, ["ESC W x=0 y=0 w=384 h=68"; 2]);
+/// // 384 dots wide, the 2 lines of 34 dots high
+/// # #[cfg(feature = "image")]
+/// # {
+/// let png = receipt.to_png(&Default::default()).unwrap();
+/// assert_eq!(png[16..24], [0, 0, 1, 128, 0, 0, 0, 68]);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watermark {
+    /// Image of the mark
+    pub image: WatermarkImage,
+    /// Sixteenths of the black dots of the image printed, from 0 to 16, 4 by default
+    pub shade: u8,
+}
+
+impl Watermark {
+    /// A watermark of `text`.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            image: WatermarkImage::Text(text.into()),
+            shade: 4,
+        }
+    }
+
+    /// A watermark of the 1 bpp `bitmap` of `width` x `height`.
+    pub fn bitmap(width: usize, height: usize, bitmap: impl Into<Vec<u8>>) -> Self {
+        Self {
+            image: WatermarkImage::Bitmap {
+                width,
+                height,
+                bitmap: bitmap.into(),
+            },
+            shade: 4,
+        }
+    }
+
+    /// The watermark printing `shade` sixteenths of the dots of its image.
+    pub fn shade(self, shade: u8) -> Self {
+        Self { shade, ..self }
+    }
+
+    /// The raster of the image, at most `dots` wide and scaled for a content `height` dots high.
+    ///
+    /// A bitmap must have been checked by [`builder::check_bitmap_size()`].
+    fn raster(&self, dots: usize, height: usize) -> Raster {
+        match &self.image {
+            WatermarkImage::Text(text) => {
+                let chars = text.chars().count().max(1);
+                let scale = (dots * 3 / 4 / (chars * CELL_WIDTH))
+                    .min(height / CELL_HEIGHT)
+                    .max(1);
+                let mut glyphs = Raster::new(chars * CELL_WIDTH, CELL_HEIGHT);
+                glyphs.text(0, 0, text, true);
+                let mut raster = Raster::new(chars * CELL_WIDTH * scale, CELL_HEIGHT * scale);
+                for y in 0..CELL_HEIGHT {
+                    for x in (0..chars * CELL_WIDTH).filter(|x| glyphs.get(*x, y)) {
+                        raster.fill(x * scale, y * scale, scale, scale);
+                    }
+                }
+                raster
+            }
+            WatermarkImage::Bitmap {
+                width,
+                height,
+                bitmap,
+            } => {
+                let stride = width.div_ceil(8);
+                let mut raster = Raster::new((*width).min(dots), *height);
+                for y in 0..*height {
+                    for x in 0..raster.width() {
+                        if x < *width && bitmap[y * stride + x / 8] & (0x80 >> (x % 8)) != 0 {
+                            raster.set(x, y);
+                        }
+                    }
+                }
+                raster
+            }
+        }
+    }
+}
+
+/// The `raster` dithered to `shade` sixteenths of its dots, centered on a page of `width` x `height`.
+fn faint(raster: &Raster, shade: u8, width: usize, height: usize) -> Raster {
+    let mut page = Raster::new(width, height);
+    let (left, top) = (
+        width.saturating_sub(raster.width()) / 2,
+        height.saturating_sub(raster.height()) / 2,
+    );
+    for y in 0..raster.height() {
+        for x in (0..raster.width()).filter(|x| raster.get(*x, y)) {
+            let (x, y) = (left + x, top + y);
+            if BAYER[y % 4][x % 4] < shade {
+                page.set(x, y);
+            }
+        }
+    }
+
+    page
+}
+
+/// Render `content` on `builder` for a line of `dots` with `watermark` behind it, in page mode.
+///
+/// Returns [`Error::InvalidParameter`] if the content contains a canvas, a layout, a watermark or a cut, and
+/// [`Error::UnsupportedImage`] if the bitmap of the watermark is empty or shorter than its size, as
+/// [`Element::Bitmap`](super::Element::Bitmap) does.
+pub(super) fn render<B: CommandBuilder + ?Sized>(
+    watermark: &Watermark,
+    content: &Receipt,
+    builder: &mut B,
+    dots: u16,
+) -> Result<(), Error> {
+    if let Some(kind) = nested_page(content) {
+        return Err(Error::InvalidParameter(format!(
+            "watermarked content contains a {kind}"
+        )));
+    }
+    if let WatermarkImage::Bitmap {
+        width,
+        height,
+        bitmap,
+    } = &watermark.image
+    {
+        builder::check_bitmap_size(*width, *height, bitmap.len())?;
+    }
+
+    // the page is as tall as the content printed on the paper
+    let mut paper = JobBuilder::new();
+    content.render_in(&mut paper, dots, &RenderStyle::default())?;
+    let height = preview::rasterize(paper.build().commands(), dots).height();
+    let raster = watermark.raster(dots as usize, height);
+    let height = height.max(raster.height());
+    if height == 0 {
+        return Ok(());
+    }
+    let mark = faint(&raster, watermark.shade, dots as usize, height);
+
+    let (width, height) = (mark.width(), mark.height());
+    Canvas::new()
+        .region(Region::new(
+            0,
+            0,
+            dots,
+            height as u16,
+            Receipt::new().bitmap(
+                width,
+                height,
+                mark.data(),
+                BitImageMode::Dots24DoubleDensity,
+            ),
+        ))
+        .region(Region::new(0, 0, dots, height as u16, content.clone()))
+        .render(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faint() {
+        let mut raster = Raster::new(8, 4);
+        raster.fill(0, 0, 8, 4);
+        // a quarter of the dots, centered
+        let page = faint(&raster, 4, 16, 8);
+        let dots = (0..16)
+            .flat_map(|x| (0..8).map(move |y| (x, y)))
+            .filter(|(x, y)| page.get(*x, *y))
+            .count();
+        assert_eq!(dots, 8);
+        assert!(!page.get(0, 0) && !page.get(16, 1));
+        assert_eq!(faint(&raster, 0, 8, 4), Raster::new(8, 4));
+        assert_eq!(faint(&raster, 16, 8, 4), raster);
+    }
+
+    #[test]
+    fn test_raster() {
+        // 4 characters scaled 6 times in 3/4 of 384 dots, unless the content is shorter
+        let copy = Watermark::text("COPY");
+        assert_eq!(copy.raster(384, 500).width(), 4 * CELL_WIDTH * 6);
+        assert_eq!(copy.raster(384, 50).height(), 2 * CELL_HEIGHT);
+        // the bitmap is cropped to the line
+        let logo = Watermark::bitmap(16, 2, [0xFF, 0x01, 0x80, 0x00]).raster(8, 100);
+        assert_eq!(logo.width(), 8);
+        assert!(logo.get(7, 0) && logo.get(0, 1) && !logo.get(1, 1));
+    }
+
+    #[test]
+    fn test_render() {
+        let content = Receipt::new().line("VOID");
+        let mut builder = JobBuilder::new();
+        render(&Watermark::text("VOID"), &content, &mut builder, 384).unwrap();
+        let page = preview::rasterize(builder.build().commands(), 384);
+        // the text over the faint mark of a single line
+        assert_eq!(page.height(), CELL_HEIGHT.max(34));
+
+        let cut = Receipt::new().cut(crate::CutType::TotalCut);
+        assert!(matches!(
+            render(&Watermark::text("VOID"), &cut, &mut JobBuilder::new(), 384),
+            Err(Error::InvalidParameter(_))
+        ));
+        // a bitmap shorter than its 2 lines
+        assert!(matches!(
+            render(
+                &Watermark::bitmap(16, 2, [0xFF, 0x01]),
+                &content,
+                &mut JobBuilder::new(),
+                384
+            ),
+            Err(Error::UnsupportedImage(_))
+        ));
+    }
+}
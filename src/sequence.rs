@@ -0,0 +1,201 @@
+//! Sequences of ticket and queue numbers stamped into the receipts.
+
+#[cfg(feature = "std")]
+use alloc::{format, string::ToString};
+#[cfg(feature = "std")]
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::Error;
+
+/// A provider of monotonically increasing numbers, e.g. the ticket numbers of
+/// [`Receipt::ticket_number()`](crate::Receipt::ticket_number()).
+///
+/// It's implemented by [`FileSequence`], persisting the numbers in a file, and by the closures returning the next
+/// number, e.g. from the database of a point of sale.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Error, Sequence};
+/// let mut last = 41;
+/// let mut sequence = || -> Result<u64, Error> {
+///     last += 1;
+///     Ok(last)
+/// };
+/// assert_eq!(sequence.next().unwrap(), 42);
+/// ```
+pub trait Sequence {
+    /// Take the next number of the sequence, greater than all the numbers taken before.
+    fn next(&mut self) -> Result<u64, Error>;
+}
+
+impl<F: FnMut() -> Result<u64, Error>> Sequence for F {
+    fn next(&mut self) -> Result<u64, Error> {
+        self()
+    }
+}
+
+/// A [`Sequence`] persisted in a file, so the numbers keep increasing across restarts of the process.
+///
+/// The file holds the last number taken in decimal. Each number is taken while holding an exclusive lock on a
+/// `.lock` file next to it, so the processes sharing the file never take the same number, and the file is
+/// replaced atomically, so a crash never leaves it truncated.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{FileSequence, Sequence};
+/// let path = std::env::temp_dir().join("tickets.seq");
+/// # let _ = std::fs::remove_file(&path);
+/// let mut sequence = FileSequence::new(&path).start(100);
+/// assert_eq!(sequence.next().unwrap(), 100);
+/// assert_eq!(sequence.next().unwrap(), 101);
+///
+/// // after a restart
+/// let mut sequence = FileSequence::new(&path).start(100);
+/// assert_eq!(sequence.next().unwrap(), 102);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSequence {
+    path: PathBuf,
+    start: u64,
+}
+
+#[cfg(feature = "std")]
+impl FileSequence {
+    /// A sequence persisted in the file `path`, starting from 1 if the file doesn't exist yet.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            start: 1,
+        }
+    }
+
+    /// The sequence starting from `start` if the file doesn't exist yet.
+    pub fn start(self, start: u64) -> Self {
+        Self { start, ..self }
+    }
+
+    /// Path of the file of the sequence.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The last number taken, or `None` if no number was taken yet.
+    ///
+    /// Returns [`Error::SequenceFile`] if the file can't be read, or [`Error::InvalidFormat`] if it doesn't hold a
+    /// number.
+    pub fn last(&self) -> Result<Option<u64>, Error> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(self.error(source)),
+        };
+
+        text.trim().parse().map(Some).map_err(|_| {
+            Error::InvalidFormat(format!(
+                "sequence file {} doesn't hold a number",
+                self.path.display()
+            ))
+        })
+    }
+
+    /// The path of the file next to the file of the sequence with `extension` appended, e.g. `tickets.seq.lock`.
+    fn sibling(&self, extension: &str) -> PathBuf {
+        let mut name = OsString::from(self.path.as_os_str());
+        name.push(extension);
+
+        name.into()
+    }
+
+    fn error(&self, source: io::Error) -> Error {
+        Error::SequenceFile {
+            path: self.path.clone(),
+            source,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Sequence for FileSequence {
+    /// Take the next number, returning [`Error::SequenceFile`] if the file can't be read or written, or
+    /// [`Error::InvalidFormat`] if it doesn't hold a number.
+    fn next(&mut self) -> Result<u64, Error> {
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.sibling(".lock"))
+            .map_err(|source| self.error(source))?;
+        // the lock is released when the file is closed
+        lock.lock().map_err(|source| self.error(source))?;
+
+        let next = match self.last()? {
+            Some(last) => last.checked_add(1).ok_or_else(|| {
+                Error::InvalidParameter(format!("sequence {} is exhausted", self.path.display()))
+            })?,
+            None => self.start,
+        };
+
+        let temporary = self.sibling(".tmp");
+        let mut file = File::create(&temporary).map_err(|source| self.error(source))?;
+        file.write_all((next.to_string() + "\n").as_bytes())
+            .and_then(|()| file.sync_all())
+            .and_then(|()| fs::rename(&temporary, &self.path))
+            .map_err(|source| self.error(source))?;
+
+        Ok(next)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_file_sequence() {
+        let path = std::env::temp_dir().join(format!("custom-printer-{}.seq", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let sequence = FileSequence::new(&path);
+        assert_eq!(sequence.last().unwrap(), None);
+
+        // the processes sharing the file never take the same number
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let mut sequence = sequence.clone();
+                std::thread::spawn(move || {
+                    (0..25)
+                        .map(|_| sequence.next().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let mut numbers: Vec<u64> = threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect();
+        numbers.sort();
+        assert_eq!(numbers, (1..=100).collect::<Vec<_>>());
+        assert_eq!(sequence.last().unwrap(), Some(100));
+
+        fs::write(&path, "ticket").unwrap();
+        assert!(matches!(
+            sequence.clone().next(),
+            Err(Error::InvalidFormat(_))
+        ));
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(sequence.sibling(".lock")).unwrap();
+
+        assert!(matches!(
+            FileSequence::new("/nonexistent/tickets.seq").next(),
+            Err(Error::SequenceFile { .. })
+        ));
+    }
+}
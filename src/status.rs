@@ -0,0 +1,447 @@
+//! Types describing the responses of the printer to status and information queries.
+// The parsers of the responses are only used by the printer with the `std` feature
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::Model;
+
+/// Header byte of a text block transmitted by the printer (e.g. model name or firmware version).
+const BLOCK_HEADER: u8 = 0x5F;
+/// Terminator byte of a text block transmitted by the printer.
+pub(crate) const BLOCK_TERMINATOR: u8 = 0x00;
+
+/// Identification of the printer returned by [`CustomPrinter::printer_info()`](crate::CustomPrinter::printer_info()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrinterInfo {
+    /// Model name, e.g. `TG2480H`
+    pub model: String,
+    /// Firmware version
+    pub firmware: String,
+    /// Features reported by the printer
+    pub features: Features,
+}
+
+impl PrinterInfo {
+    /// The supported [`Model`] matching the reported model name, if any.
+    pub fn supported_model(&self) -> Option<Model> {
+        Model::from_name(&self.model)
+    }
+}
+
+/// Feature bits reported by the printer in its type ID.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Features(u8);
+
+impl Features {
+    /// Create [`Features`] from the raw type ID byte.
+    pub fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    /// Raw type ID byte.
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Whether multi-byte character codes are supported.
+    pub fn multi_byte_chars(&self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    /// Whether an autocutter is equipped.
+    pub fn autocutter(&self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    /// Whether a black mark sensor is equipped.
+    pub fn black_mark_sensor(&self) -> bool {
+        self.0 & 0x04 != 0
+    }
+}
+
+/// Full status of the printer returned by [`CustomPrinter::status()`](crate::CustomPrinter::status()).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Status {
+    paper: u8,
+    user: u8,
+    recoverable: u8,
+    unrecoverable: u8,
+}
+
+impl Status {
+    /// Length of the full status response
+    pub(crate) const LEN: usize = 6;
+
+    /// Parse the full status response in the form of `0x10 0x0F <paper> <user> <recoverable> <unrecoverable>`.
+    pub(crate) fn parse(response: &[u8]) -> Option<Self> {
+        match response {
+            [0x10, 0x0F, paper, user, recoverable, unrecoverable] => Some(Self {
+                paper: *paper,
+                user: *user,
+                recoverable: *recoverable,
+                unrecoverable: *unrecoverable,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether the paper has run out.
+    pub fn paper_end(&self) -> bool {
+        self.paper & 0x01 != 0
+    }
+
+    /// Whether the paper is near its end.
+    pub fn paper_near_end(&self) -> bool {
+        self.paper & 0x04 != 0
+    }
+
+    /// Whether a printed ticket is still present in the output.
+    pub fn ticket_present(&self) -> bool {
+        self.paper & 0x20 != 0
+    }
+
+    /// Whether the print head is up, i.e. the cover is open.
+    pub fn cover_open(&self) -> bool {
+        self.user & 0x01 != 0
+    }
+
+    /// Whether the printer still has data in its receive buffer.
+    pub fn spooling(&self) -> bool {
+        self.user & 0x04 != 0
+    }
+
+    /// Whether the paper drag motor is running, i.e. the printer is printing or feeding.
+    pub fn motor_on(&self) -> bool {
+        self.user & 0x08 != 0
+    }
+
+    /// Whether the print head is overheated, in which case printing is suspended until it cools down.
+    pub fn head_overheated(&self) -> bool {
+        self.recoverable & 0x01 != 0
+    }
+
+    /// Whether the autocutter is jammed or failed to return to its home position.
+    pub fn cutter_error(&self) -> bool {
+        self.unrecoverable & 0x01 != 0
+    }
+
+    /// Whether the cover of the autocutter is open.
+    pub fn cutter_cover_open(&self) -> bool {
+        self.unrecoverable & 0x02 != 0
+    }
+
+    /// List of faults reported in the status, which is empty if the printer is ready to print.
+    pub fn faults(&self) -> Vec<PrinterFault> {
+        [
+            (self.paper, 0x01, PrinterFault::PaperEnd),
+            (self.user, 0x01, PrinterFault::CoverOpen),
+            (self.recoverable, 0x01, PrinterFault::HeadOverheat),
+            (self.recoverable, 0x02, PrinterFault::CommunicationError),
+            (self.recoverable, 0x08, PrinterFault::VoltageError),
+            (self.recoverable, 0x20, PrinterFault::UnknownCommand),
+            (self.recoverable, 0x40, PrinterFault::PaperJam),
+            (self.unrecoverable, 0x01, PrinterFault::CutterLocked),
+            (self.unrecoverable, 0x02, PrinterFault::CutterCoverOpen),
+            (self.unrecoverable, 0x04, PrinterFault::RamError),
+            (self.unrecoverable, 0x08, PrinterFault::EepromError),
+            (self.unrecoverable, 0x40, PrinterFault::FlashError),
+        ]
+        .into_iter()
+        .filter(|(byte, mask, _)| byte & mask != 0)
+        .map(|(_, _, fault)| fault)
+        .collect()
+    }
+
+    /// Return the first fault reported in the status, if any.
+    pub fn check(&self) -> Result<(), PrinterFault> {
+        match self.faults().first() {
+            Some(fault) => Err(*fault),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the receive buffer is empty and printing is complete.
+    pub fn is_idle(&self) -> bool {
+        !self.spooling() && !self.motor_on()
+    }
+}
+
+/// Faults of the printer derived from its [`Status`], returned as [`Error::Fault`](crate::Error::Fault).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PrinterFault {
+    /// The paper has run out
+    #[error("paper end")]
+    PaperEnd,
+    /// The cover is open
+    #[error("cover open")]
+    CoverOpen,
+    /// The print head is overheated
+    #[error("print head overheated")]
+    HeadOverheat,
+    /// An error occurred on the communication port
+    #[error("communication error")]
+    CommunicationError,
+    /// The power supply voltage is out of range
+    #[error("power supply voltage error")]
+    VoltageError,
+    /// A command wasn't acknowledged by the printer
+    #[error("command not acknowledged")]
+    UnknownCommand,
+    /// The paper is jammed
+    #[error("paper jam")]
+    PaperJam,
+    /// The autocutter is locked or jammed
+    #[error("cutter locked")]
+    CutterLocked,
+    /// The cover of the autocutter is open
+    #[error("cutter cover open")]
+    CutterCoverOpen,
+    /// RAM error
+    #[error("RAM error")]
+    RamError,
+    /// EEPROM error
+    #[error("EEPROM error")]
+    EepromError,
+    /// Flash memory error
+    #[error("flash memory error")]
+    FlashError,
+}
+
+impl PrinterFault {
+    /// Whether the printer can recover from the fault without being power-cycled.
+    pub fn is_recoverable(&self) -> bool {
+        !matches!(self, Self::RamError | Self::EepromError | Self::FlashError)
+    }
+}
+
+/// Causes of the printer being offline returned by [`CustomPrinter::offline_causes()`](crate::CustomPrinter::offline_causes()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfflineCause {
+    /// The cover is open
+    CoverOpen,
+    /// The paper is being fed by the feed button
+    FeedButton,
+    /// Printing stopped because the paper has run out
+    PaperEnd,
+    /// An error occurred
+    Error,
+}
+
+impl OfflineCause {
+    /// Parse the offline cause byte into the list of causes, which is empty if the printer is online.
+    pub(crate) fn parse(byte: u8) -> Option<Vec<Self>> {
+        // bit 1 and 4 are fixed to 1, bit 0 and 7 are fixed to 0
+        if byte & 0x93 != 0x12 {
+            return None;
+        }
+
+        Some(
+            [
+                (0x04, Self::CoverOpen),
+                (0x08, Self::FeedButton),
+                (0x20, Self::PaperEnd),
+                (0x40, Self::Error),
+            ]
+            .into_iter()
+            .filter(|(mask, _)| byte & mask != 0)
+            .map(|(_, cause)| cause)
+            .collect(),
+        )
+    }
+}
+
+/// Maintenance counters of the printer returned by [`CustomPrinter::maintenance_counters()`](crate::CustomPrinter::maintenance_counters()).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MaintenanceCounters {
+    /// Number of dot lines printed by the print head
+    pub printed_dot_lines: u64,
+    /// Number of cuts performed by the autocutter
+    pub cuts: u64,
+    /// Number of hours the printer has been powered on
+    pub power_on_hours: u64,
+}
+
+/// Usage of the NV graphics memory returned by [`CustomPrinter::nv_memory()`](crate::CustomPrinter::nv_memory()).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NvMemory {
+    /// Total capacity in bytes
+    pub capacity: usize,
+    /// Remaining capacity in bytes
+    pub remaining: usize,
+}
+
+impl NvMemory {
+    /// Used capacity in bytes.
+    pub fn used(&self) -> usize {
+        self.capacity.saturating_sub(self.remaining)
+    }
+
+    /// Check whether an asset of `size` bytes fits in the remaining capacity before downloading it.
+    pub fn ensure_fits(&self, size: usize) -> Result<(), InsufficientMemory> {
+        if size > self.remaining {
+            return Err(InsufficientMemory {
+                required: size,
+                available: self.remaining,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Error returned when an asset doesn't fit in the remaining NV memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("insufficient NV memory: {required} bytes required, {available} bytes available")]
+pub struct InsufficientMemory {
+    /// Size of the asset in bytes
+    pub required: usize,
+    /// Remaining capacity in bytes
+    pub available: usize,
+}
+
+/// Parse a number block in the form of `<header> <decimal digits> 0x00` transmitted by the printer.
+pub(crate) fn parse_number_block(block: &[u8], header: &[u8]) -> Option<usize> {
+    let digits = block
+        .strip_prefix(header)?
+        .strip_suffix(&[BLOCK_TERMINATOR])?;
+    core::str::from_utf8(digits).ok()?.parse().ok()
+}
+
+/// Parse a text block in the form of `0x5F <text> 0x00` transmitted by the printer.
+pub(crate) fn parse_text_block(block: &[u8]) -> Option<String> {
+    let text = block
+        .strip_prefix(&[BLOCK_HEADER])?
+        .strip_suffix(&[BLOCK_TERMINATOR])?;
+    String::from_utf8(text.to_vec())
+        .ok()
+        .map(|text| text.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_parse_text_block() {
+        assert_eq!(
+            parse_text_block(b"\x5FTG2480H\x00"),
+            Some("TG2480H".to_string())
+        );
+        assert_eq!(parse_text_block(b"\x5F1.01 \x00"), Some("1.01".to_string()));
+        assert_eq!(parse_text_block(b"TG2480H\x00"), None);
+        assert_eq!(parse_text_block(b"\x5FTG2480H"), None);
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let status = Status::parse(&[0x10, 0x0F, 0x05, 0x0C, 0x00, 0x00]).unwrap();
+        assert!(status.paper_end());
+        assert!(status.paper_near_end());
+        assert!(!status.ticket_present());
+        assert!(!status.cover_open());
+        assert!(status.spooling());
+        assert!(status.motor_on());
+        assert!(!status.is_idle());
+
+        assert!(!status.cutter_error());
+        assert!(!status.cutter_cover_open());
+        assert!(!status.head_overheated());
+
+        let status = Status::parse(&[0x10, 0x0F, 0x00, 0x00, 0x00, 0x01]).unwrap();
+        assert!(status.cutter_error());
+        assert!(!status.cutter_cover_open());
+        assert!(status.is_idle());
+
+        assert_eq!(Status::parse(&[0x10, 0x0F, 0x00, 0x00, 0x00]), None);
+        assert_eq!(Status::parse(&[0x00, 0x0F, 0x00, 0x00, 0x00, 0x00]), None);
+    }
+
+    #[test]
+    fn test_faults() {
+        let status = Status::parse(&[0x10, 0x0F, 0x00, 0x00, 0x00, 0x00]).unwrap();
+        assert_eq!(status.faults(), vec![]);
+        assert_eq!(status.check(), Ok(()));
+
+        let status = Status::parse(&[0x10, 0x0F, 0x01, 0x00, 0x41, 0x01]).unwrap();
+        assert_eq!(
+            status.faults(),
+            vec![
+                PrinterFault::PaperEnd,
+                PrinterFault::HeadOverheat,
+                PrinterFault::PaperJam,
+                PrinterFault::CutterLocked
+            ]
+        );
+        assert_eq!(status.check(), Err(PrinterFault::PaperEnd));
+        assert!(status.head_overheated());
+    }
+
+    #[test]
+    fn test_parse_offline_cause() {
+        assert_eq!(OfflineCause::parse(0x12), Some(vec![]));
+        assert_eq!(
+            OfflineCause::parse(0x32),
+            Some(vec![OfflineCause::PaperEnd])
+        );
+        assert_eq!(
+            OfflineCause::parse(0x7E),
+            Some(vec![
+                OfflineCause::CoverOpen,
+                OfflineCause::FeedButton,
+                OfflineCause::PaperEnd,
+                OfflineCause::Error
+            ])
+        );
+        assert_eq!(OfflineCause::parse(0x00), None);
+        assert_eq!(OfflineCause::parse(0x92), None);
+    }
+
+    #[test]
+    fn test_parse_counter() {
+        assert_eq!(
+            parse_text_block(b"\x5F123456\x00").and_then(|text| text.parse::<u64>().ok()),
+            Some(123456)
+        );
+    }
+
+    #[test]
+    fn test_nv_memory() {
+        assert_eq!(
+            parse_number_block(b"\x37\x30262144\x00", &[0x37, 0x30]),
+            Some(262144)
+        );
+        assert_eq!(
+            parse_number_block(b"\x37\x31262144\x00", &[0x37, 0x30]),
+            None
+        );
+        assert_eq!(parse_number_block(b"\x37\x30abc\x00", &[0x37, 0x30]), None);
+
+        let memory = NvMemory {
+            capacity: 1024,
+            remaining: 256,
+        };
+        assert_eq!(memory.used(), 768);
+        assert_eq!(memory.ensure_fits(256), Ok(()));
+        assert_eq!(
+            memory.ensure_fits(257),
+            Err(InsufficientMemory {
+                required: 257,
+                available: 256
+            })
+        );
+    }
+
+    #[test]
+    fn test_features() {
+        let features = Features::from_bits(0x03);
+        assert!(features.multi_byte_chars());
+        assert!(features.autocutter());
+        assert!(!features.black_mark_sensor());
+        assert_eq!(features.bits(), 0x03);
+    }
+}
@@ -0,0 +1,225 @@
+//! Streaming of the commands to the printer on a writer thread.
+
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    command_set::Line, Command, CommandBuilder, CustomPrinter, Error, Model, PrinterConfig, Profile,
+};
+
+/// Printer streaming the constructed commands to the device on a writer thread as they are constructed, instead of
+/// sending them all at once by [`CustomPrinter::run()`].
+///
+/// The commands are sent over a channel to the thread owning the [`CustomPrinter`], so e.g. converting the next
+/// bit image overlaps with transmitting the previous commands, lowering the latency of long jobs.
+///
+/// **NOTE:** As the commands are sent one by one, they are only checked against the [`Profile`] of the printer, and
+/// neither the automatic cut nor the limit of [`CustomPrinter::set_max_size()`] is applied. After the first
+/// error, the following commands are dropped and the error is returned by
+/// [`finish()`](StreamingPrinter::finish()). If the [`Profile::raster_images`] of the printer is enabled, each bit
+/// image is held until the next command, so a bit image followed by a print is sent as raster images like by
+/// [`CustomPrinter::run()`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{BitImageMode, CommandBuilder, CustomPrinter, CutType, StreamingPrinter};
+/// let printer = CustomPrinter::new("/dev/null").unwrap();
+/// let mut streaming = StreamingPrinter::spawn(printer);
+/// for _ in 0..3 {
+///     // the previous bit image is being sent while this one is converted
+///     streaming
+///         .bitmap(8, 24, &[0xFF; 24], BitImageMode::Dots24DoubleDensity)
+///         .unwrap()
+///         .print();
+/// }
+/// streaming.cut_paper(CutType::TotalCut);
+/// let printer = streaming.finish().unwrap();
+/// ```
+pub struct StreamingPrinter {
+    sender: Option<Sender<Command>>,
+    model: Option<Model>,
+    profile: Option<Profile>,
+    config: PrinterConfig,
+    handle: Option<JoinHandle<Result<CustomPrinter, Error>>>,
+}
+
+impl StreamingPrinter {
+    /// Spawn a writer thread streaming the commands to `printer`, after running its pending commands.
+    pub fn spawn(printer: CustomPrinter) -> Self {
+        let model = printer.model();
+        let profile = printer.profile();
+        let config = printer.config();
+        let (sender, commands) = mpsc::channel();
+        let handle = thread::spawn(move || Self::write(printer, &commands));
+
+        Self {
+            sender: Some(sender),
+            model,
+            profile,
+            config,
+            handle: Some(handle),
+        }
+    }
+
+    fn write(
+        mut printer: CustomPrinter,
+        commands: &Receiver<Command>,
+    ) -> Result<CustomPrinter, Error> {
+        printer.run()?;
+        // a bit image is held until the next command, so it's sent as raster images with the following print
+        let raster = printer.raster_images();
+        let mut line = Line::default();
+        let mut image = None;
+        for command in commands {
+            if let Some(image) = image.take() {
+                if command == Command::Print {
+                    printer.send(&[image, command], &mut line)?;
+                    continue;
+                }
+                printer.send(&[image], &mut line)?;
+            }
+            match command {
+                Command::BitImage { .. } if raster => image = Some(command),
+                command => printer.send(&[command], &mut line)?,
+            }
+        }
+        if let Some(image) = image {
+            printer.send(&[image], &mut line)?;
+        }
+
+        Ok(printer)
+    }
+
+    fn join(&mut self) -> Option<Result<CustomPrinter, Error>> {
+        // closing the channel ends the writer thread after the remaining commands are sent
+        self.sender = None;
+        let handle = self.handle.take()?;
+        Some(
+            handle
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e)),
+        )
+    }
+
+    /// Wait until all the constructed commands have been sent and give back the [`CustomPrinter`], or return
+    /// the first error of sending them.
+    pub fn finish(mut self) -> Result<CustomPrinter, Error> {
+        self.join().unwrap_or(Err(Error::NoDevice))
+    }
+}
+
+impl Drop for StreamingPrinter {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+impl CommandBuilder for StreamingPrinter {
+    fn push(&mut self, command: Command) -> &mut Self {
+        if let Some(sender) = &self.sender {
+            // the writer thread has stopped on an error, returned by finish()
+            let _ = sender.send(command);
+        }
+
+        self
+    }
+
+    fn model(&self) -> Option<Model> {
+        self.model
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.profile.clone()
+    }
+
+    fn config(&self) -> PrinterConfig {
+        self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitImageMode, CutType, TraceEvent};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_streaming() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut printer = CustomPrinter::new("/dev/null").unwrap();
+        printer.set_trace(Some(Box::new({
+            let sent = sent.clone();
+            move |event: &TraceEvent<'_>| {
+                if let TraceEvent::Transmitted { bytes, .. } = event {
+                    sent.lock().unwrap().push(*bytes);
+                }
+            }
+        })));
+        printer.print();
+
+        let mut streaming = StreamingPrinter::spawn(printer);
+        streaming.print().cut_paper(CutType::TotalCut);
+        streaming.finish().unwrap();
+        // the pending commands are run first, then every command is sent on its own
+        assert_eq!(*sent.lock().unwrap(), [1, 1, 2]);
+    }
+
+    #[test]
+    fn test_streaming_raster_images() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut printer = CustomPrinter::new("/dev/null").unwrap();
+        printer.set_profile(Some(Profile {
+            raster_images: true,
+            ..Profile::for_model(Model::Tg2480h)
+        }));
+        printer.set_trace(Some(Box::new({
+            let sent = sent.clone();
+            move |event: &TraceEvent<'_>| {
+                if let TraceEvent::Transmitted { bytes, .. } = event {
+                    sent.lock().unwrap().push(*bytes);
+                }
+            }
+        })));
+        printer.print();
+
+        let mut streaming = StreamingPrinter::spawn(printer);
+        streaming
+            .bitmap(16, 24, &[0xFF; 48], BitImageMode::Dots24DoubleDensity)
+            .unwrap()
+            .print()
+            .bitmap(16, 24, &[0xFF; 48], BitImageMode::Dots24DoubleDensity)
+            .unwrap()
+            .cut_paper(CutType::TotalCut);
+        streaming.finish().unwrap();
+        // a raster image of 2 x 24 bytes replacing the bit image and the print, while the bit image which isn't
+        // printed on its own is kept
+        assert_eq!(*sent.lock().unwrap(), [1, 8 + 48, 5 + 48, 2]);
+    }
+
+    #[test]
+    fn test_streaming_error() {
+        let mut printer = CustomPrinter::new("/dev/null").unwrap();
+        printer.set_model(Some(Model::Tg2480h));
+
+        let mut streaming = StreamingPrinter::spawn(printer);
+        streaming
+            .print()
+            .cut_paper(CutType::PartialCut)
+            .cut_paper(CutType::TotalCut);
+        assert!(matches!(
+            streaming.finish(),
+            Err(Error::Unsupported {
+                command: "cut_paper",
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            StreamingPrinter::spawn(CustomPrinter::detached()).finish(),
+            Err(Error::NoDevice)
+        ));
+    }
+}
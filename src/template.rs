@@ -0,0 +1,454 @@
+//! Receipt templates instantiated from the variables of each transaction.
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{borrow::Borrow, fmt::Display};
+
+use crate::{
+    command::json::Named,
+    json::{self, Value},
+    BarcodeSystem, CutType, Error, Receipt,
+};
+
+/// Variables substituted in a [`Template`], looked up by their dotted paths, e.g. `order.total`.
+///
+/// The elements of a list are looked up by their index, e.g. `order.items.0.name`.
+pub trait Variables {
+    /// The value of the variable at `path`, if any.
+    fn value(&self, path: &str) -> Option<String>;
+
+    /// Number of elements of the list at `path` iterated by `each`, if it's a list, none by default.
+    fn count(&self, path: &str) -> Option<usize> {
+        let _ = path;
+        None
+    }
+}
+
+/// Variables of a map from their paths to their values, without any list.
+impl<K: Borrow<str> + Ord, V: Display> Variables for BTreeMap<K, V> {
+    fn value(&self, path: &str) -> Option<String> {
+        self.get(path).map(ToString::to_string)
+    }
+}
+
+/// Variables of a JSON document, e.g. serialized from the data structure of a transaction by `serde_json`.
+///
+/// Strings are substituted as they are, numbers by their shortest representation, booleans by `true` or `false`
+/// and `null` by nothing. Arrays are the lists iterated by `each`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonVariables(Value);
+
+impl JsonVariables {
+    /// Parse the variables from the JSON `text`.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        json::parse(text).map(Self).map_err(Error::InvalidTemplate)
+    }
+
+    fn lookup(&self, path: &str) -> Option<&Value> {
+        path.split('.').try_fold(&self.0, |value, key| match value {
+            Value::Array(values) => values.get(key.parse::<usize>().ok()?),
+            _ => value.get(key),
+        })
+    }
+}
+
+impl Variables for JsonVariables {
+    fn value(&self, path: &str) -> Option<String> {
+        match self.lookup(path)? {
+            Value::Null => Some(String::new()),
+            Value::Bool(value) => Some(value.to_string()),
+            Value::Number(value) => Some(value.to_string()),
+            Value::String(value) => Some(value.clone()),
+            Value::Array(_) | Value::Object(_) => None,
+        }
+    }
+
+    fn count(&self, path: &str) -> Option<usize> {
+        self.lookup(path)?.as_array().map(<[Value]>::len)
+    }
+}
+
+/// A receipt layout defined once, with placeholders like `{{order.total}}`, and instantiated into a [`Receipt`] for
+/// each transaction by [`render()`](Template::render()), so the layout can be changed without changing any code.
+///
+/// A template has a directive a line, adding an element to the receipt:
+///
+/// * `header text`, `line text` and `footer text`: an [`Element::Header`](crate::Element::Header),
+///   [`Element::Line`](crate::Element::Line) or [`Element::Footer`](crate::Element::Footer)
+/// * `item name | amount`: an [`Element::Item`](crate::Element::Item)
+/// * `kv label | value`: an [`Element::KeyValue`](crate::Element::KeyValue) filled by dots
+/// * `barcode system data`: an [`Element::Barcode`](crate::Element::Barcode), `system` being one of `upc_a`,
+///   `upc_e`, `ean13`, `ean8`, `code39`, `itf`, `codabar`, `code93` or `code128`
+/// * `feed lines`: an [`Element::Feed`](crate::Element::Feed)
+/// * `cut` or `cut partial`: an [`Element::Cut`](crate::Element::Cut)
+/// * `each list as name` to `end`: the directives in between for each element of `list`, the placeholders
+///   starting with `name.` looking up the variables of the element
+///
+/// Blank lines and lines starting with `#` are ignored. Every `{{path}}` in the text is replaced by the value of the
+/// variable at `path`, see [`Variables`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{Emulator, JsonVariables, Template};
+/// let template = Template::parse(
+///     "header {{store}}
+///      each items as item
+///        item {{item.name}} | {{item.amount}}
+///      end
+///      kv Total | {{total}}
+///      cut",
+/// )
+/// .unwrap();
+///
+/// let order = JsonVariables::parse(
+///     r#"{"store":"CAFE","items":[{"name":"Coffee","amount":"2.50"}],"total":"2.50"}"#,
+/// )
+/// .unwrap();
+/// let mut emulator = Emulator::new();
+/// template.render(&order).unwrap().render(&mut emulator).unwrap();
+/// assert!(emulator.render().contains("Coffee"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Element {
+        line: usize,
+        kind: Kind,
+        texts: Vec<String>,
+    },
+    Each {
+        line: usize,
+        path: String,
+        name: String,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Header,
+    Line,
+    Footer,
+    Item,
+    KeyValue,
+    Barcode(BarcodeSystem),
+    Feed(u8),
+    Cut(CutType),
+}
+
+impl Template {
+    /// Parse the template from `text`.
+    ///
+    /// [`Error::InvalidTemplate`] is returned for an unknown directive, an unterminated placeholder or `each`
+    /// block, or invalid arguments.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()));
+        let nodes = parse_nodes(&mut lines, None)?;
+
+        Ok(Self { nodes })
+    }
+
+    /// Instantiate the template into a [`Receipt`] from `variables`.
+    ///
+    /// [`Error::InvalidTemplate`] is returned for a missing variable, or an `each` of something else than a list.
+    pub fn render<V: Variables + ?Sized>(&self, variables: &V) -> Result<Receipt, Error> {
+        render_nodes(&self.nodes, variables, &mut Vec::new(), Receipt::new())
+    }
+}
+
+fn parse_nodes<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    each: Option<usize>,
+) -> Result<Vec<Node>, Error> {
+    let mut nodes = Vec::new();
+
+    while let Some((line, text)) = lines.next() {
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let invalid = |reason: &str| Error::InvalidTemplate(format!("line {line}: {reason}"));
+
+        let (directive, rest) = text.split_once(' ').unwrap_or((text, ""));
+        let rest = rest.trim();
+        let (kind, texts) = match directive {
+            "end" if each.is_some() => return Ok(nodes),
+            "each" => {
+                let Some((path, name)) = rest.split_once(" as ") else {
+                    return Err(invalid("each isn't `each list as name`"));
+                };
+                let body = parse_nodes(lines, Some(line))?;
+                nodes.push(Node::Each {
+                    line,
+                    path: path.trim().into(),
+                    name: name.trim().into(),
+                    body,
+                });
+                continue;
+            }
+            "header" => (Kind::Header, Vec::from([rest])),
+            "line" => (Kind::Line, Vec::from([rest])),
+            "footer" => (Kind::Footer, Vec::from([rest])),
+            "item" | "kv" => {
+                let Some((left, right)) = rest.split_once('|') else {
+                    return Err(invalid("missing | between the columns"));
+                };
+                let kind = if directive == "item" {
+                    Kind::Item
+                } else {
+                    Kind::KeyValue
+                };
+                (kind, Vec::from([left.trim(), right.trim()]))
+            }
+            "barcode" => {
+                let (system, data) = rest.split_once(' ').unwrap_or((rest, ""));
+                let system = BarcodeSystem::from_name(system)
+                    .ok_or_else(|| invalid("unknown barcode system"))?;
+                (Kind::Barcode(system), Vec::from([data.trim()]))
+            }
+            "feed" => {
+                let lines = rest
+                    .parse()
+                    .map_err(|_| invalid("invalid number of lines"))?;
+                (Kind::Feed(lines), Vec::new())
+            }
+            "cut" => match rest {
+                "" => (Kind::Cut(CutType::TotalCut), Vec::new()),
+                "partial" => (Kind::Cut(CutType::PartialCut), Vec::new()),
+                _ => return Err(invalid("unknown cut type")),
+            },
+            _ => return Err(invalid(&format!("unknown directive {directive}"))),
+        };
+        for text in &texts {
+            if placeholders(text).any(|placeholder| placeholder.is_none()) {
+                return Err(invalid("unterminated placeholder"));
+            }
+        }
+
+        nodes.push(Node::Element {
+            line,
+            kind,
+            texts: texts.into_iter().map(Into::into).collect(),
+        });
+    }
+
+    match each {
+        Some(line) => Err(Error::InvalidTemplate(format!(
+            "line {line}: each isn't terminated by end"
+        ))),
+        None => Ok(nodes),
+    }
+}
+
+/// The placeholders of `text` as the ranges of their paths, `None` for an unterminated one.
+fn placeholders(text: &str) -> impl Iterator<Item = Option<(usize, usize)>> + '_ {
+    let mut from = 0;
+
+    core::iter::from_fn(move || {
+        let start = from + text[from..].find("{{")?;
+        let Some(len) = text[start..].find("}}") else {
+            from = text.len();
+            return Some(None);
+        };
+        from = start + len + 2;
+
+        Some(Some((start + 2, start + len)))
+    })
+}
+
+fn render_nodes<V: Variables + ?Sized>(
+    nodes: &[Node],
+    variables: &V,
+    scopes: &mut Vec<(String, String)>,
+    mut receipt: Receipt,
+) -> Result<Receipt, Error> {
+    for node in nodes {
+        match node {
+            Node::Element { line, kind, texts } => {
+                let texts = texts
+                    .iter()
+                    .map(|text| substitute(text, variables, scopes, *line))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut texts = texts.into_iter();
+                let mut text = || texts.next().unwrap_or_default();
+
+                receipt = match *kind {
+                    Kind::Header => receipt.header(text()),
+                    Kind::Line => receipt.line(text()),
+                    Kind::Footer => receipt.footer(text()),
+                    Kind::Item => receipt.item(text(), text()),
+                    Kind::KeyValue => receipt.kv_line(text(), text()),
+                    Kind::Barcode(system) => receipt.barcode(system, text()),
+                    Kind::Feed(lines) => receipt.feed(lines),
+                    Kind::Cut(cut_type) => receipt.cut(cut_type),
+                };
+            }
+            Node::Each {
+                line,
+                path,
+                name,
+                body,
+            } => {
+                let path = resolve(path, scopes);
+                let count = variables.count(&path).ok_or_else(|| {
+                    Error::InvalidTemplate(format!("line {line}: {path} isn't a list"))
+                })?;
+                for i in 0..count {
+                    scopes.push((name.clone(), format!("{path}.{i}")));
+                    let result = render_nodes(body, variables, scopes, receipt);
+                    scopes.pop();
+                    receipt = result?;
+                }
+            }
+        }
+    }
+
+    Ok(receipt)
+}
+
+/// `path` with its first key replaced by the path of the innermost `each` element of the same name, if any.
+fn resolve(path: &str, scopes: &[(String, String)]) -> String {
+    let (first, rest) = match path.split_once('.') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (path, None),
+    };
+
+    match scopes.iter().rev().find(|(name, _)| name == first) {
+        Some((_, prefix)) => match rest {
+            Some(rest) => format!("{prefix}.{rest}"),
+            None => prefix.clone(),
+        },
+        None => path.into(),
+    }
+}
+
+/// `text` with its placeholders replaced by the values of their variables.
+fn substitute<V: Variables + ?Sized>(
+    text: &str,
+    variables: &V,
+    scopes: &[(String, String)],
+    line: usize,
+) -> Result<String, Error> {
+    let mut out = String::new();
+    let mut from = 0;
+
+    // the placeholders were checked by Template::parse()
+    for (start, end) in placeholders(text).flatten() {
+        let path = resolve(text[start..end].trim(), scopes);
+        let value = variables.value(&path).ok_or_else(|| {
+            Error::InvalidTemplate(format!("line {line}: missing variable {path}"))
+        })?;
+        out.push_str(&text[from..start - 2]);
+        out.push_str(&value);
+        from = end + 2;
+    }
+    out.push_str(&text[from..]);
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Element;
+
+    const TEMPLATE: &str = "
+        # customer receipt
+        header {{store.name}}
+        each order.items as item
+          item {{ item.qty }}x {{item.name}} | {{item.amount}}
+          each item.options as option
+            line   + {{option}}
+          end
+        end
+        kv Total | {{order.total}}
+        barcode ean8 {{order.code}}
+        feed 2
+        cut partial
+    ";
+
+    #[test]
+    fn test_render() {
+        let variables = JsonVariables::parse(
+            r#"{
+                "store": {"name": "CAFE"},
+                "order": {
+                    "items": [
+                        {"qty": 2, "name": "Coffee", "amount": "5.00", "options": ["oat milk"]},
+                        {"qty": 1, "name": "Tea", "amount": "1.80", "options": []}
+                    ],
+                    "total": 6.8,
+                    "code": "1234567"
+                }
+            }"#,
+        )
+        .unwrap();
+        let receipt = Template::parse(TEMPLATE)
+            .unwrap()
+            .render(&variables)
+            .unwrap();
+        assert_eq!(
+            receipt.elements(),
+            Receipt::new()
+                .header("CAFE")
+                .item("2x Coffee", "5.00")
+                .line("+ oat milk")
+                .item("1x Tea", "1.80")
+                .kv_line("Total", "6.8")
+                .barcode(BarcodeSystem::Ean8, "1234567")
+                .feed(2)
+                .cut(CutType::PartialCut)
+                .elements()
+        );
+
+        let mut variables = BTreeMap::new();
+        variables.insert("name", "CAFE");
+        let template = Template::parse("header {{name}}\nfooter {{missing}}").unwrap();
+        assert!(matches!(
+            template.render(&variables),
+            Err(Error::InvalidTemplate(reason)) if reason == "line 2: missing variable missing"
+        ));
+        assert_eq!(
+            Template::parse("line {{name}}!")
+                .unwrap()
+                .render(&variables)
+                .unwrap()
+                .elements(),
+            [Element::Line("CAFE!".into())]
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        for (template, reason) in [
+            ("line a\nbold b", "line 2: unknown directive bold"),
+            ("line {{a", "line 1: unterminated placeholder"),
+            (
+                "each a as b\nline c",
+                "line 1: each isn't terminated by end",
+            ),
+            ("item a", "line 1: missing | between the columns"),
+            ("barcode qr a", "line 1: unknown barcode system"),
+            ("end", "line 1: unknown directive end"),
+        ] {
+            assert!(
+                matches!(
+                    Template::parse(template),
+                    Err(Error::InvalidTemplate(ref r)) if r == reason
+                ),
+                "{template}"
+            );
+        }
+    }
+}
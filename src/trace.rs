@@ -0,0 +1,47 @@
+//! Events of the communication with the printer, reported to the hook set by
+//! [`CustomPrinter::set_trace()`](crate::CustomPrinter::set_trace()).
+
+use std::time::Duration;
+
+use crate::Error;
+
+/// An event of the communication with the printer, e.g. to be forwarded to `tracing` or `log` so printer problems
+/// can be correlated with the logs of the application.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TraceEvent<'a> {
+    /// Bytes were written to the printer
+    Transmitted {
+        /// Number of the bytes written
+        bytes: usize,
+        /// Time spent writing the bytes
+        duration: Duration,
+    },
+    /// A response was received from the printer, e.g. to a status query
+    Received(&'a [u8]),
+    /// Writing to or reading from the printer failed
+    Failed(&'a Error),
+}
+
+impl TraceEvent<'_> {
+    /// Emit the event to `tracing`, in the span of the job being printed if any.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn emit(&self) {
+        match self {
+            Self::Transmitted { bytes, duration } => {
+                tracing::debug!(bytes, ?duration, "transmitted to the printer");
+            }
+            Self::Received(response) => {
+                tracing::debug!(
+                    bytes = response.len(),
+                    ?response,
+                    "received from the printer"
+                );
+            }
+            Self::Failed(error) => tracing::warn!(%error, "communication with the printer failed"),
+        }
+    }
+}
+
+/// Hook receiving the [`TraceEvent`]s of a printer, see [`CustomPrinter::set_trace()`](crate::CustomPrinter::set_trace()).
+pub type TraceHook = Box<dyn FnMut(&TraceEvent<'_>) + Send>;
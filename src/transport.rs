@@ -0,0 +1,81 @@
+//! Transmission of jobs over any byte-oriented link to the printer.
+
+/// Byte-oriented link to the printer, e.g. a UART of a microcontroller driving a printer mechanism.
+///
+/// With the `std` feature, it's implemented by every [`std::io::Write`], e.g. a [`File`](std::fs::File)
+/// or a [`TcpStream`](std::net::TcpStream). Without it, it's implemented on top of the serial driver
+/// of the platform, e.g. by `Serial` for the serial ports of `embedded-hal` with the `embedded-hal` feature,
+/// or the blocking write of `embedded-io`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{CommandBuilder, JobBuilder, Transport};
+/// struct Uart;
+///
+/// impl Transport for Uart {
+///     type Error = ();
+///
+///     fn transmit(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+///         // write `bytes` to the UART, e.g. `embedded_io::Write::write_all(&mut self.0, bytes)`
+///         Ok(())
+///     }
+/// }
+///
+/// let job = JobBuilder::new().print().build();
+/// job.transmit(&mut Uart).unwrap();
+/// ```
+pub trait Transport {
+    /// Error of the link
+    type Error;
+
+    /// Transmit all of `bytes` to the printer.
+    fn transmit(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Transport for W {
+    type Error = std::io::Error;
+
+    fn transmit(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.write_all(bytes)
+    }
+}
+
+/// [`Transport`] over a serial port of `embedded-hal`, e.g. the UART of a microcontroller driving a printer
+/// mechanism, writing each byte as soon as the port accepts it and flushing the port after `bytes`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use custom_printer::{CommandBuilder, JobBuilder, Serial};
+/// # use embedded_hal_nb::serial::{ErrorKind, ErrorType, Write};
+/// # struct Uart;
+/// # impl ErrorType for Uart {
+/// #     type Error = ErrorKind;
+/// # }
+/// # impl Write for Uart {
+/// #     fn write(&mut self, _: u8) -> embedded_hal_nb::nb::Result<(), ErrorKind> { Ok(()) }
+/// #     fn flush(&mut self) -> embedded_hal_nb::nb::Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// // `Uart` is the serial port of the HAL of the microcontroller
+/// let mut serial = Serial(Uart);
+/// JobBuilder::new().print().build().transmit(&mut serial).unwrap();
+/// ```
+#[cfg(feature = "embedded-hal")]
+#[derive(Debug)]
+pub struct Serial<S>(pub S);
+
+#[cfg(feature = "embedded-hal")]
+impl<S: embedded_hal_nb::serial::Write> Transport for Serial<S> {
+    type Error = S::Error;
+
+    fn transmit(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        use embedded_hal_nb::nb::block;
+
+        for &byte in bytes {
+            block!(self.0.write(byte))?;
+        }
+        block!(self.0.flush())
+    }
+}
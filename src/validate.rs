@@ -0,0 +1,255 @@
+//! Validation of the commands without any device.
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{command, BitImageMode, Command, Error, PrinterConfig, Profile};
+
+/// Problem of a command found by [`Job::validate()`](crate::Job::validate()),
+/// [`JobBuilder::validate()`](crate::JobBuilder::validate()) or
+/// [`CustomPrinter::validate()`](crate::CustomPrinter::validate()).
+#[derive(Debug)]
+pub struct Problem {
+    /// Index of the command in the commands
+    pub index: usize,
+    /// What's wrong with the command, e.g. [`Error::Unsupported`], [`Error::UnprintedData`] or
+    /// [`Error::InvalidParameter`]
+    pub error: Error,
+}
+
+/// Check every command of `commands` against `profile`, if known, and the printer of `config`, returning all the
+/// problems in the order of the commands.
+pub(crate) fn validate(
+    commands: &[Command],
+    profile: Option<&Profile>,
+    config: &PrinterConfig,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for (index, command) in commands.iter().enumerate() {
+        if let Some(profile) = profile.filter(|profile| !profile.supports(command)) {
+            problems.push(Problem {
+                index,
+                error: Error::Unsupported {
+                    command: command.kind(),
+                    printer: profile.name.clone(),
+                },
+            });
+        }
+        if let Some(reason) = profile.and_then(|profile| profile.exceeded_limit(command)) {
+            problems.push(Problem {
+                index,
+                error: Error::InvalidParameter(reason),
+            });
+        }
+        if let Some(reason) = invalid_parameter(command, config) {
+            problems.push(Problem {
+                index,
+                error: Error::InvalidParameter(reason),
+            });
+        }
+    }
+    if let Err(error @ Error::UnprintedData { index }) = command::check_printed(commands) {
+        problems.push(Problem { index, error });
+    }
+
+    problems
+}
+
+/// Why a parameter of `command` is out of the range of the command or the printer of `config`, if it is.
+fn invalid_parameter(command: &Command, config: &PrinterConfig) -> Option<String> {
+    let dots = config.dots_per_line;
+    match *command {
+        Command::BitImage {
+            mode,
+            width,
+            ref data,
+        } => {
+            let (dots_per_column, bytes) = match mode {
+                BitImageMode::Dots8SingleDensity => (2, 1),
+                BitImageMode::Dots8DoubleDensity => (1, 1),
+                BitImageMode::Dots24SingleDensity => (2, 3),
+                BitImageMode::Dots24DoubleDensity => (1, 3),
+            };
+            if width as usize * dots_per_column > dots as usize {
+                Some(format!(
+                    "bit image of {width} columns is wider than {dots} dots"
+                ))
+            } else if width == 0 || !data.len().is_multiple_of(width as usize * bytes) {
+                Some(format!(
+                    "bit image of {} bytes doesn't fill its {width} columns",
+                    data.len()
+                ))
+            } else {
+                None
+            }
+        }
+        Command::Barcode { system, ref data } => system.invalid_data(data),
+        Command::BarcodeStyle { module_width, .. } if !(1..=6).contains(&module_width) => Some(
+            format!("barcode module width {module_width} isn't 1 to 6 dots"),
+        ),
+        Command::QrCode {
+            ref data,
+            module_size,
+            ..
+        } => invalid_qr_code(data, module_size).or_else(|| {
+            // the smallest QR codes are 21 modules wide
+            (21 * module_size as u32 > dots as u32)
+                .then(|| format!("QR code of {module_size} dots modules is wider than {dots} dots"))
+        }),
+        Command::CharacterSize { width, height }
+            if !(1..=8).contains(&width) || !(1..=8).contains(&height) =>
+        {
+            Some(format!(
+                "character size {width} x {height} isn't 1 to 8 times"
+            ))
+        }
+        Command::PageArea { x, width, .. } if x as u32 + width as u32 > dots as u32 => Some(
+            format!("page area from {x} of {width} dots is wider than {dots} dots"),
+        ),
+        Command::PrintArea { left, width } if left as u32 + width as u32 > dots as u32 => Some(
+            format!("print area from {left} of {width} dots is wider than {dots} dots"),
+        ),
+        Command::Position { x, .. } if x >= dots => {
+            Some(format!("position {x} is beyond {dots} dots"))
+        }
+        Command::Beep { count, .. } if count > 9 => {
+            Some(format!("buzzer count {count} is above 9"))
+        }
+        Command::Beep { duration_ms, .. } if duration_ms > 900 => {
+            Some(format!("buzzer duration {duration_ms} ms is above 900 ms"))
+        }
+        Command::OpenDrawer { on_ms, off_ms, .. } if on_ms > 510 || off_ms > 510 => Some(format!(
+            "drawer pulse {on_ms} / {off_ms} ms is above 510 ms"
+        )),
+        Command::SleepTimeout(Some(0)) => Some("sleep timeout of 0 minutes".into()),
+        Command::RetractOnTimeout(Some(0)) => Some("retract timeout of 0 seconds".into()),
+        _ => None,
+    }
+}
+
+/// Describe why `data` can't be printed as a QR code of modules of `module_size` dots, if it can't.
+pub(crate) fn invalid_qr_code(data: &[u8], module_size: u8) -> Option<String> {
+    if data.is_empty() || data.len() > command::QR_MAX_BYTES {
+        Some(format!(
+            "QR code of {} bytes isn't 1 to {} bytes long",
+            data.len(),
+            command::QR_MAX_BYTES
+        ))
+    } else if !(1..=16).contains(&module_size) {
+        Some(format!(
+            "QR code module size {module_size} isn't 1 to 16 dots"
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BarcodeSystem, CommandBuilder, CutType, HriFont, HriPosition, JobBuilder, Model,
+        QrErrorCorrection,
+    };
+    use alloc::vec;
+
+    #[test]
+    fn test_validate() {
+        let mut builder = JobBuilder::new();
+        builder.set_model(Some(Model::Tg2480h));
+        builder
+            .print()
+            .cut_paper(CutType::PartialCut)
+            .push(Command::Beep {
+                count: 10,
+                duration_ms: 100,
+            })
+            .push(Command::BitImage {
+                mode: BitImageMode::Dots8SingleDensity,
+                width: 300,
+                data: vec![0; 300],
+            });
+        let problems = builder.validate();
+        assert_eq!(problems.len(), 5);
+        assert!(matches!(
+            &problems[0],
+            Problem {
+                index: 1,
+                error: Error::Unsupported {
+                    command: "cut_paper",
+                    printer
+                }
+            } if printer == "TG2480H"
+        ));
+        // TG2480H has no buzzer
+        assert!(matches!(
+            &problems[1],
+            Problem {
+                index: 2,
+                error: Error::Unsupported {
+                    command: "beep",
+                    ..
+                }
+            }
+        ));
+        assert!(matches!(
+            &problems[2],
+            Problem { index: 2, error: Error::InvalidParameter(reason) } if reason.contains("count")
+        ));
+        assert!(matches!(
+            &problems[3],
+            Problem { index: 3, error: Error::InvalidParameter(reason) } if reason.contains("wider")
+        ));
+        assert!(matches!(
+            problems[4],
+            Problem {
+                index: 3,
+                error: Error::UnprintedData { index: 3 }
+            }
+        ));
+
+        assert!(JobBuilder::new()
+            .page_area(0, 0, 384, 800)
+            .position(383, 0)
+            .validate()
+            .is_empty());
+
+        let problems = JobBuilder::new()
+            .push(Command::Barcode {
+                system: BarcodeSystem::Code128,
+                data: b"{B0123".to_vec(),
+            })
+            .push(Command::Barcode {
+                system: BarcodeSystem::Codabar,
+                data: b"A123".to_vec(),
+            })
+            .push(Command::BarcodeStyle {
+                module_width: 7,
+                hri: HriPosition::Below,
+                hri_font: HriFont::A,
+            })
+            .validate();
+        assert!(matches!(
+            &problems[..],
+            [
+                Problem { index: 1, error: Error::InvalidParameter(reason) },
+                Problem { index: 2, error: Error::InvalidParameter(width) },
+            ] if reason.contains("Codabar") && width.contains("module width")
+        ));
+
+        let qr_code = |data: &[u8], module_size| Command::QrCode {
+            data: data.to_vec(),
+            module_size,
+            error_correction: QrErrorCorrection::Medium,
+        };
+        let problems = JobBuilder::new()
+            .push(qr_code(b"https://example.com", 8))
+            .push(qr_code(b"", 8))
+            .push(qr_code(b"https://example.com", 17))
+            .validate();
+        assert_eq!(problems.len(), 2);
+        assert!(matches!(
+            &problems[1],
+            Problem { index: 2, error: Error::InvalidParameter(reason) } if reason.contains("module size")
+        ));
+    }
+}